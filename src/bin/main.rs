@@ -3,6 +3,7 @@ use markdown2pdf::validation;
 #[cfg(feature = "fetch")]
 use reqwest::blocking::Client;
 use std::fs;
+use std::io::{IsTerminal, Read};
 use std::path::PathBuf;
 use std::process;
 
@@ -101,6 +102,67 @@ fn toml_string(v: &str) -> String {
     format!("\"{}\"", v.replace('\\', "\\\\").replace('"', "\\\""))
 }
 
+/// Quote `v` as a JSON string literal.
+fn json_string(v: &str) -> String {
+    let mut out = String::with_capacity(v.len() + 2);
+    out.push('"');
+    for c in v.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// `--format json`: the validation warnings as a JSON array of
+/// `{"kind", "message", "suggestion"}` objects.
+fn json_warnings(warnings: &[validation::ValidationWarning]) -> String {
+    let items: Vec<String> = warnings
+        .iter()
+        .map(|w| {
+            let kind = match w.kind {
+                validation::WarningKind::MissingFont => "missing_font",
+                validation::WarningKind::MissingImage => "missing_image",
+                validation::WarningKind::MissingConfig => "missing_config",
+                validation::WarningKind::UnicodeWithoutFont => "unicode_without_font",
+                validation::WarningKind::LargeDocument => "large_document",
+                validation::WarningKind::SyntaxWarning => "syntax_warning",
+                validation::WarningKind::FontVariantMissing => "font_variant_missing",
+                validation::WarningKind::BrokenLink => "broken_link",
+            };
+            format!(
+                "{{\"kind\":{},\"message\":{},\"suggestion\":{}}}",
+                json_string(kind),
+                json_string(&w.message),
+                json_string(&w.suggestion)
+            )
+        })
+        .collect();
+    format!("[{}]", items.join(","))
+}
+
+/// `--format json` success line. `output` and `pages` are `null` when
+/// no PDF was written (`--check`, `--dry-run`).
+fn json_ok(
+    output: Option<&str>,
+    pages: Option<usize>,
+    warnings: &[validation::ValidationWarning],
+) -> String {
+    format!(
+        "{{\"status\":\"ok\",\"output\":{},\"pages\":{},\"warnings\":{}}}",
+        output.map_or_else(|| "null".to_string(), json_string),
+        pages.map_or_else(|| "null".to_string(), |n| n.to_string()),
+        json_warnings(warnings)
+    )
+}
+
 /// Build a single TOML fragment from the override flags. Returns
 /// `None` when no override flag was supplied. The fragment is parsed
 /// and validated by the library against the real config schema.
@@ -135,6 +197,10 @@ fn build_overrides(m: &clap::ArgMatches) -> Result<Option<String>, AppError> {
             toml_string("{page} / {total_pages}")
         ));
     }
+    if let Some(flags) = m.get_many::<String>("flag") {
+        let flags: Vec<String> = flags.map(|f| toml_string(f)).collect();
+        lines.push(format!("document.flags = [{}]", flags.join(", ")));
+    }
     if let Some(vars) = m.get_many::<String>("var") {
         for kv in vars {
             let (key, value) = kv.split_once('=').ok_or_else(|| {
@@ -160,6 +226,8 @@ enum AppError {
     FileRead(std::io::Error),
     Conversion(String),
     Path(String),
+    /// `--check` found these validation warnings.
+    Check(Vec<validation::ValidationWarning>),
     #[cfg(feature = "fetch")]
     Network(String),
 }
@@ -172,9 +240,51 @@ enum Verbosity {
     Verbose, // Detailed output
 }
 
-fn get_markdown_input(matches: &clap::ArgMatches) -> Result<String, AppError> {
+fn read_stdin() -> Result<Vec<u8>, AppError> {
+    let mut bytes = Vec::new();
+    std::io::stdin()
+        .read_to_end(&mut bytes)
+        .map_err(AppError::FileRead)?;
+    Ok(bytes)
+}
+
+/// Decode input bytes, honoring `--encoding`. `name` is how the
+/// warning refers to the input.
+fn decode_input(
+    bytes: &[u8],
+    name: &str,
+    matches: &clap::ArgMatches,
+    verbosity: Verbosity,
+) -> Result<String, AppError> {
+    let label = matches.get_one::<String>("encoding").map(|s| s.as_str());
+    let decoded = markdown2pdf::encoding::decode_markdown(bytes, label)
+        .map_err(|e| AppError::Conversion(e.to_string()))?;
+    // Only auto-detection is worth a warning; a forced codec is
+    // what the user asked for.
+    if decoded.transcoded && label.is_none() && verbosity != Verbosity::Quiet {
+        eprintln!(
+            "Warning: {} is not valid UTF-8; decoded it as {}",
+            name, decoded.encoding
+        );
+        eprintln!("   Suggestion: Pass --encoding <LABEL> if the detected codec is wrong");
+    }
+    Ok(decoded.text)
+}
+
+/// Reads the markdown from the one input the user named: `--path`
+/// (`-` meaning stdin), `--url`, `--string` or `--stdin`. With none of
+/// them, piped stdin is the input, so an explicit flag always wins
+/// over whatever is piped in.
+fn get_markdown_input(
+    matches: &clap::ArgMatches,
+    verbosity: Verbosity,
+) -> Result<String, AppError> {
     if let Some(file_path) = matches.get_one::<String>("path") {
-        return fs::read_to_string(file_path).map_err(AppError::FileRead);
+        if file_path == "-" {
+            return decode_input(&read_stdin()?, "stdin", matches, verbosity);
+        }
+        let bytes = fs::read(file_path).map_err(AppError::FileRead)?;
+        return decode_input(&bytes, file_path, matches, verbosity);
     }
 
     // The `url` argument is only registered when the `fetch` feature
@@ -189,17 +299,21 @@ fn get_markdown_input(matches: &clap::ArgMatches) -> Result<String, AppError> {
     // workflow.
     #[cfg(feature = "fetch")]
     if let Some(url) = matches.get_one::<String>("url") {
-        // 15s, vs. the library's own 5s for a document-triggered image
-        // fetch (`src/lib/render/net_guard.rs`): that fetch is
-        // triggered by an untrusted markdown document and bounded
-        // tightly on purpose, while this one is a URL the operator
-        // typed directly on the command line, so it's worth tolerating
-        // a slower, deliberately-chosen endpoint over strict CLI
-        // responsiveness.
-        const TIMEOUT_SECS: u64 = 15;
+        // 30s by default, vs. the library's own 5s for a
+        // document-triggered image fetch (`src/lib/render/net_guard.rs`):
+        // that fetch is triggered by an untrusted markdown document and
+        // bounded tightly on purpose, while this one is a URL the
+        // operator typed directly on the command line, so it's worth
+        // tolerating a slower, deliberately-chosen endpoint over strict
+        // CLI responsiveness. `--url-timeout` overrides it.
+        const MAX_REDIRECTS: usize = 5;
+        let timeout_secs = *matches.get_one::<u64>("url-timeout").unwrap_or(&30);
+        let timeout = std::time::Duration::from_secs(timeout_secs);
 
         let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(TIMEOUT_SECS))
+            .connect_timeout(timeout)
+            .timeout(timeout)
+            .redirect(reqwest::redirect::Policy::limited(MAX_REDIRECTS))
             .build()
             .map_err(|e| AppError::Network(e.to_string()))?;
         let resp = client
@@ -215,7 +329,7 @@ fn get_markdown_input(matches: &clap::ArgMatches) -> Result<String, AppError> {
         // own `.timeout()` doesn't already bound this. Reads one byte
         // past the cap so an over-size body is detectable without
         // ever buffering the whole thing.
-        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(TIMEOUT_SECS);
+        let deadline = std::time::Instant::now() + timeout;
         let buf = net_read::read_capped_with_deadline(resp, deadline).map_err(AppError::Network)?;
         if buf.len() as u64 > net_read::MAX_FETCH_BYTES {
             return Err(AppError::Network(format!(
@@ -225,6 +339,15 @@ fn get_markdown_input(matches: &clap::ArgMatches) -> Result<String, AppError> {
             )));
         }
 
+        // A NUL byte never occurs in text; an image or archive served
+        // by mistake almost always has one early on.
+        if buf.contains(&0) {
+            return Err(AppError::Network(format!(
+                "response from {} looks like binary data, not markdown",
+                url
+            )));
+        }
+
         // A non-UTF-8 response is not markdown; don't silently
         // lossy-convert it into something that looks plausible.
         let body = String::from_utf8(buf)
@@ -233,19 +356,47 @@ fn get_markdown_input(matches: &clap::ArgMatches) -> Result<String, AppError> {
     }
 
     if let Some(markdown_string) = matches.get_one::<String>("string") {
-        Ok(markdown_string.to_string())
-    } else {
-        Err(AppError::Conversion("No input provided".to_string()))
+        return Ok(markdown_string.to_string());
     }
+
+    let forced = matches.get_flag("stdin");
+    if !forced && std::io::stdin().is_terminal() {
+        return Err(AppError::Conversion("No input provided".to_string()));
+    }
+    let bytes = read_stdin()?;
+    // Without `--stdin`, empty stdin (`< /dev/null`, a closed pipe)
+    // means nothing was piped in rather than an empty document.
+    if !forced && bytes.is_empty() {
+        return Err(AppError::Conversion(
+            "No input provided (stdin was empty; pass --stdin to convert it anyway)".to_string(),
+        ));
+    }
+    decode_input(&bytes, "stdin", matches, verbosity)
 }
 
+/// `-o` when given. Otherwise a `--path` input names the output after
+/// itself (`notes.md` → `./notes.pdf`), and `--string`, `--url` and
+/// stdin write `./output.pdf`. Relative paths resolve against the
+/// working directory.
 fn get_output_path(matches: &clap::ArgMatches) -> Result<PathBuf, AppError> {
     let current_dir = std::env::current_dir().map_err(|e| AppError::Path(e.to_string()))?;
 
     Ok(matches
         .get_one::<String>("output")
         .map(|p| current_dir.join(p))
-        .unwrap_or_else(|| current_dir.join("output.pdf")))
+        .unwrap_or_else(|| {
+            let path = matches.get_one::<String>("path");
+            current_dir.join(default_output_name(path.filter(|p| *p != "-")))
+        }))
+}
+
+/// File name of the default output: the input's stem with a `.pdf`
+/// extension, or `output.pdf` without a named input file.
+fn default_output_name(input: Option<&String>) -> PathBuf {
+    input
+        .and_then(|p| std::path::Path::new(p).file_stem())
+        .map(|stem| PathBuf::from(stem).with_extension("pdf"))
+        .unwrap_or_else(|| PathBuf::from("output.pdf"))
 }
 
 /// Locate a config file when `-c` was not given, in precedence order:
@@ -278,7 +429,10 @@ fn user_config_file() -> Option<PathBuf> {
 }
 
 fn run(matches: clap::ArgMatches) -> Result<(), AppError> {
-    let verbosity = if matches.get_flag("quiet") {
+    // `--format json` replaces every human-readable line with a single
+    // JSON object on stdout, so it runs otherwise quiet.
+    let json = is_json(&matches);
+    let verbosity = if json || matches.get_flag("quiet") {
         Verbosity::Quiet
     } else if matches.get_flag("verbose") {
         Verbosity::Verbose
@@ -287,6 +441,7 @@ fn run(matches: clap::ArgMatches) -> Result<(), AppError> {
     };
 
     let dry_run = matches.get_flag("dry-run");
+    let check = matches.get_flag("check");
 
     // Per-parameter CLI overrides (highest priority in the cascade).
     let overrides = build_overrides(&matches)?;
@@ -324,33 +479,30 @@ fn run(matches: clap::ArgMatches) -> Result<(), AppError> {
         return Ok(());
     }
 
-    let markdown = get_markdown_input(&matches)?;
+    let markdown = get_markdown_input(&matches, verbosity)?;
+    let markdown = if matches.get_flag("sanitize") {
+        markdown2pdf::validation::sanitize_markdown(&markdown)
+    } else {
+        markdown
+    };
     let output_path = get_output_path(&matches)?;
     let output_path_str = output_path
         .to_str()
         .ok_or_else(|| AppError::Path("Invalid output path".to_string()))?;
 
-    let font_config = if matches.contains_id("default-font") || matches.contains_id("code-font") {
-        let default_font = matches
-            .get_one::<String>("default-font")
-            .map(|s| s.to_string());
-
-        let code_font = matches
-            .get_one::<String>("code-font")
-            .map(|s| s.to_string());
-
-        Some(markdown2pdf::fonts::FontConfig {
-            default_font,
-            code_font,
-            enable_subsetting: true,
-            default_font_source: None,
-            code_font_source: None,
-            fallback_fonts: Vec::new(),
-            fallback_font_sources: Vec::new(),
-        })
-    } else {
-        None
-    };
+    // Fonts come from the config file's `[fonts]` section, with
+    // --default-font / --code-font winning over it name by name.
+    let mut font_config = markdown2pdf::config::load_font_config(config_source.clone());
+    if let Some(name) = matches.get_one::<String>("default-font") {
+        font_config
+            .get_or_insert_with(markdown2pdf::fonts::FontConfig::new)
+            .default_font = Some(name.to_string());
+    }
+    if let Some(name) = matches.get_one::<String>("code-font") {
+        font_config
+            .get_or_insert_with(markdown2pdf::fonts::FontConfig::new)
+            .code_font = Some(name.to_string());
+    }
 
     // Load the resolved style up front so validation can see any
     // `[defaults].fallback_fonts` configured — without that, the
@@ -364,35 +516,84 @@ fn run(matches: clap::ArgMatches) -> Result<(), AppError> {
     )
     .map_err(|e| AppError::Conversion(e.to_string()))?;
 
-    // With no font on the CLI, fall back to the fonts named in the
-    // resolved style ([defaults].font_family / [code_block]). This
-    // lets a config file select an embeddable system font without
-    // the caller also passing --default-font.
+    // With no font on the CLI or in `[fonts]`, fall back to the
+    // fonts named in the resolved style ([defaults].font_family /
+    // [code_block]). This lets a config file select an embeddable
+    // system font without the caller also passing --default-font.
     let font_config = font_config.or_else(|| {
         let default_font = resolved_style.paragraph.font_family.clone();
         let code_font = resolved_style.code_block.font_family.clone();
         if default_font.is_none() && code_font.is_none() {
             return None;
         }
-        Some(markdown2pdf::fonts::FontConfig {
-            default_font,
-            code_font,
-            enable_subsetting: true,
-            default_font_source: None,
-            code_font_source: None,
-            fallback_fonts: Vec::new(),
-            fallback_font_sources: Vec::new(),
-        })
+        let mut config = markdown2pdf::fonts::FontConfig::new();
+        config.default_font = default_font;
+        config.code_font = code_font;
+        Some(config)
     });
 
-    if verbosity != Verbosity::Quiet {
-        let warnings = validation::validate_conversion(
+    // Relative links are checked against the input file's directory;
+    // stdin and inline strings have none to check against.
+    let validation_config = match matches.get_one::<String>("path") {
+        Some(p) if p != "-" => {
+            let dir = std::path::Path::new(p)
+                .parent()
+                .unwrap_or(std::path::Path::new(""));
+            let dir = if dir.as_os_str().is_empty() {
+                std::path::Path::new(".")
+            } else {
+                dir
+            };
+            validation::ValidationConfig::default().with_base_dir(dir)
+        }
+        _ => validation::ValidationConfig::default(),
+    };
+
+    // `--check` is the strict lint: every warning is printed whatever
+    // the verbosity, and any warning fails the run. The output path is
+    // only checked when one was given, since nothing is written.
+    if check {
+        let warnings = validation::validate_conversion_with(
             &markdown,
             font_config.as_ref(),
             &resolved_style.fallback_fonts,
-            Some(output_path_str),
+            matches.get_one::<String>("output").map(|s| s.as_str()),
+            &validation_config,
         );
+        if !json {
+            for warning in &warnings {
+                eprintln!("{}", warning);
+            }
+        }
+        if !warnings.is_empty() {
+            return Err(AppError::Check(warnings));
+        }
+        if json {
+            println!("{}", json_ok(None, None, &warnings));
+        } else if verbosity != Verbosity::Quiet {
+            println!("No issues detected.");
+        }
+        return Ok(());
+    }
+
+    let warnings = if verbosity != Verbosity::Quiet || dry_run || json {
+        validation::validate_conversion_with(
+            &markdown,
+            font_config.as_ref(),
+            &resolved_style.fallback_fonts,
+            Some(output_path_str),
+            &validation_config,
+        )
+    } else {
+        Vec::new()
+    };
 
+    if json && dry_run {
+        println!("{}", json_ok(None, None, &warnings));
+        return Ok(());
+    }
+
+    if verbosity != Verbosity::Quiet {
         if !warnings.is_empty() {
             if verbosity == Verbosity::Verbose {
                 eprintln!("\nPre-flight validation:");
@@ -418,12 +619,6 @@ fn run(matches: clap::ArgMatches) -> Result<(), AppError> {
             return Ok(());
         }
     } else if dry_run {
-        let warnings = validation::validate_conversion(
-            &markdown,
-            font_config.as_ref(),
-            &resolved_style.fallback_fonts,
-            Some(output_path_str),
-        );
         if warnings.is_empty() {
             return Ok(());
         } else {
@@ -454,6 +649,15 @@ fn run(matches: clap::ArgMatches) -> Result<(), AppError> {
     )
     .map_err(|e| AppError::Conversion(e.to_string()))?;
 
+    if json {
+        let pages = fs::read(output_path_str)
+            .ok()
+            .and_then(|bytes| lopdf::Document::load_mem(&bytes).ok())
+            .map(|doc| doc.get_pages().len());
+        println!("{}", json_ok(Some(output_path_str), pages, &warnings));
+        return Ok(());
+    }
+
     if verbosity != Verbosity::Quiet {
         println!("Successfully saved PDF to {}", output_path_str);
 
@@ -506,8 +710,15 @@ fn main() {
                 .short('p')
                 .long("path")
                 .value_name("FILE_PATH")
-                .help("Path to the markdown file")
+                .help("Path to the markdown file, or - to read stdin")
                 .conflicts_with("string"),
+        )
+        .arg(
+            Arg::new("encoding")
+                .long("encoding")
+                .value_name("LABEL")
+                .requires("path")
+                .help("Character encoding of the input file (e.g. utf-8, windows-1252, latin1); detected when omitted"),
         );
 
     let cmd = cmd.arg(
@@ -519,10 +730,22 @@ fn main() {
             .conflicts_with("path"),
     );
 
+    let cmd = cmd.arg(
+        Arg::new("stdin")
+            .long("stdin")
+            .action(ArgAction::SetTrue)
+            .help(
+                "Read markdown from stdin, even from a terminal. Without any input flag, \
+                 piped stdin is read automatically",
+            )
+            .conflicts_with_all(["path", "string"]),
+    );
+
     #[cfg(feature = "fetch")]
     let cmd = cmd
         .mut_arg("path", |a| a.conflicts_with("url"))
         .mut_arg("string", |a| a.conflicts_with("url"))
+        .mut_arg("stdin", |a| a.conflicts_with("url"))
         .arg(
             Arg::new("url")
                 .short('u')
@@ -530,6 +753,14 @@ fn main() {
                 .value_name("URL")
                 .help("URL to fetch markdown content from (requires 'fetch' feature)")
                 .conflicts_with_all(["string", "path"]),
+        )
+        .arg(
+            Arg::new("url-timeout")
+                .long("url-timeout")
+                .value_name("SECS")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .requires("url")
+                .help("Connect and read timeout for --url in seconds (default 30)"),
         );
 
     let mut cmd = cmd
@@ -538,7 +769,10 @@ fn main() {
                 .short('o')
                 .long("output")
                 .value_name("OUTPUT_PATH")
-                .help("Path to the output PDF file (defaults to ./output.pdf)"),
+                .help(
+                    "Path to the output PDF file (defaults to the input file's name with \
+                     .pdf, or ./output.pdf for --string, --url and stdin)",
+                ),
         )
         .arg(
             Arg::new("default-font")
@@ -568,12 +802,33 @@ fn main() {
                 .action(clap::ArgAction::SetTrue)
                 .conflicts_with("verbose"),
         )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .value_parser(["human", "json"])
+                .default_value("human")
+                .help("How results and errors are reported: human | json (one object on stdout)"),
+        )
         .arg(
             Arg::new("dry-run")
                 .long("dry-run")
                 .help("Validate input without generating PDF")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .help("Validate input, print every warning, and exit non-zero if there are any")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("dry-run"),
+        )
+        .arg(
+            Arg::new("sanitize")
+                .long("sanitize")
+                .help("Strip stray trailing whitespace and expand tabs in list indentation before converting")
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("config-path")
                 .short('c')
@@ -593,6 +848,27 @@ fn main() {
                 .help("Print the fully-resolved style as TOML and exit")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("init-config")
+                .long("init-config")
+                .value_name("PATH")
+                .num_args(0..=1)
+                .default_missing_value("markdown2pdf.toml")
+                .help("Write a commented starter config (default ./markdown2pdf.toml) and exit"),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .requires("init-config")
+                .help("Let --init-config overwrite an existing file")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("list-fonts")
+                .long("list-fonts")
+                .help("List font names usable with --default-font / --code-font and exit")
+                .action(clap::ArgAction::SetTrue),
+        )
         .next_help_heading("Config overrides (win over config file & --theme)")
         .arg(
             Arg::new("title")
@@ -636,6 +912,13 @@ fn main() {
                 .help("Add `page / total` to the footer center")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("flag")
+                .long("flag")
+                .value_name("NAME")
+                .action(ArgAction::Append)
+                .help("Keep `<!-- if: NAME -->` regions, repeatable"),
+        )
         .arg(
             Arg::new("var")
                 .short('V')
@@ -655,29 +938,99 @@ fn main() {
     #[cfg(not(feature = "fetch"))]
     let has_url = false;
 
+    if matches.get_flag("list-fonts") {
+        for name in markdown2pdf::fonts::list_available_fonts() {
+            println!("{}", name);
+        }
+        return;
+    }
+
+    if let Some(path) = matches.get_one::<String>("init-config") {
+        if let Err(e) = init_config(path, matches.get_flag("force")) {
+            report(e);
+            process::exit(1);
+        }
+        return;
+    }
+
     let only_printing_config = matches.get_flag("print-effective-config");
+    // Piped stdin is an input too; only a terminal with no input
+    // flag at all gets the help text.
     if !only_printing_config
         && !matches.contains_id("path")
         && !matches.contains_id("string")
         && !has_url
+        && !matches.get_flag("stdin")
+        && std::io::stdin().is_terminal()
     {
         cmd.print_help().unwrap();
         println!();
         process::exit(1);
     }
 
+    let json = is_json(&matches);
     if let Err(e) = run(matches) {
-        match e {
-            AppError::FileRead(e) => eprintln!("[X] Error reading file: {}", e),
-            AppError::Conversion(e) => eprintln!("[X] Conversion error: {}", e),
-            AppError::Path(e) => eprintln!("[X] Path error: {}", e),
-            #[cfg(feature = "fetch")]
-            AppError::Network(e) => eprintln!("[X] Network error: {}", e),
+        if json {
+            report_json(e)
+        } else {
+            report(e)
         }
         process::exit(1);
     }
 }
 
+fn is_json(matches: &clap::ArgMatches) -> bool {
+    matches
+        .get_one::<String>("format")
+        .is_some_and(|f| f == "json")
+}
+
+fn report(e: AppError) {
+    match e {
+        AppError::FileRead(e) => eprintln!("[X] Error reading file: {}", e),
+        AppError::Conversion(e) => eprintln!("[X] Conversion error: {}", e),
+        AppError::Path(e) => eprintln!("[X] Path error: {}", e),
+        AppError::Check(w) => eprintln!("[X] Check failed: {} warning(s)", w.len()),
+        #[cfg(feature = "fetch")]
+        AppError::Network(e) => eprintln!("[X] Network error: {}", e),
+    }
+}
+
+/// `--format json` counterpart of [`report`]: one
+/// `{"status":"error","error":KIND,"message":…}` object on stdout,
+/// with the warnings attached when `--check` failed.
+fn report_json(e: AppError) {
+    let (kind, message, warnings) = match e {
+        AppError::FileRead(e) => ("file_read", e.to_string(), Vec::new()),
+        AppError::Conversion(e) => ("conversion", e, Vec::new()),
+        AppError::Path(e) => ("path", e, Vec::new()),
+        AppError::Check(w) => ("check", format!("{} warning(s)", w.len()), w),
+        #[cfg(feature = "fetch")]
+        AppError::Network(e) => ("network", e, Vec::new()),
+    };
+    println!(
+        "{{\"status\":\"error\",\"error\":{},\"message\":{},\"warnings\":{}}}",
+        json_string(kind),
+        json_string(&message),
+        json_warnings(&warnings)
+    );
+}
+
+/// `--init-config`: write the starter config to `path`, leaving an
+/// existing file alone unless `force` is set.
+fn init_config(path: &str, force: bool) -> Result<(), AppError> {
+    if !force && std::path::Path::new(path).exists() {
+        return Err(AppError::Path(format!(
+            "{} already exists; pass --force to overwrite it",
+            path
+        )));
+    }
+    fs::write(path, markdown2pdf::config::default_config_toml())
+        .map_err(|e| AppError::Path(format!("could not write {}: {}", path, e)))?;
+    println!("Wrote {}", path);
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,164 @@
+//! Conditional regions: drop or keep spans of the source depending on
+//! which flags are active, so one document can produce several
+//! variants (draft and release, internal and public, …).
+//!
+//! A region opens with `<!-- if: NAME -->` and closes with
+//! `<!-- endif -->`. Everything between is kept when `NAME` is one of
+//! the active flags and removed otherwise, before the markdown is
+//! lexed. Markers inside fenced code blocks are left as written, so a
+//! document can show the syntax. A marker alone on its line takes the
+//! line with it.
+//!
+//! Regions may nest; an inner region is kept only when every region
+//! around it is. Nesting, a stray `endif` and a region left open at
+//! the end of the input each log a warning. A region left open drops
+//! the rest of the document when its flag is off.
+
+/// One `<!-- ... -->` marker recognised by [`filter`].
+enum Marker<'a> {
+    If(&'a str),
+    EndIf,
+}
+
+fn parse_marker(inner: &str) -> Option<Marker<'_>> {
+    let inner = inner.trim();
+    if inner == "endif" {
+        return Some(Marker::EndIf);
+    }
+    let name = inner.strip_prefix("if:")?.trim();
+    (!name.is_empty()).then_some(Marker::If(name))
+}
+
+/// Return `markdown` with the regions whose flag is not in `flags`
+/// removed and every region marker stripped. Input without markers
+/// comes back unchanged.
+pub fn filter(markdown: &str, flags: &[&str]) -> String {
+    if !markdown.contains("<!--") {
+        return markdown.to_string();
+    }
+    let mut out = String::with_capacity(markdown.len());
+    // One entry per open region: whether its flag is active.
+    let mut open: Vec<bool> = Vec::new();
+    let mut fence: Option<(char, usize)> = None;
+
+    for line in markdown.split_inclusive('\n') {
+        let keep = open.iter().all(|&on| on);
+        if let Some(f) = fence_of(line) {
+            fence = match fence {
+                None => Some(f),
+                Some((ch, len)) if f.0 == ch && f.1 >= len => None,
+                current => current,
+            };
+            if keep {
+                out.push_str(line);
+            }
+            continue;
+        }
+        if fence.is_some() {
+            if keep {
+                out.push_str(line);
+            }
+            continue;
+        }
+
+        let mut kept = String::new();
+        let mut saw_marker = false;
+        let mut rest = line;
+        while let Some(start) = rest.find("<!--") {
+            let Some(len) = rest[start + 4..].find("-->") else {
+                break;
+            };
+            let end = start + 4 + len + 3;
+            let Some(marker) = parse_marker(&rest[start + 4..start + 4 + len]) else {
+                if open.iter().all(|&on| on) {
+                    kept.push_str(&rest[..end]);
+                }
+                rest = &rest[end..];
+                continue;
+            };
+            if open.iter().all(|&on| on) {
+                kept.push_str(&rest[..start]);
+            }
+            saw_marker = true;
+            match marker {
+                Marker::If(name) => {
+                    if !open.is_empty() {
+                        log::warn!("conditional region `{}` is nested in another", name);
+                    }
+                    open.push(flags.contains(&name));
+                }
+                Marker::EndIf => {
+                    if open.pop().is_none() {
+                        log::warn!("`<!-- endif -->` without a matching `<!-- if: ... -->`");
+                    }
+                }
+            }
+            rest = &rest[end..];
+        }
+        if open.iter().all(|&on| on) {
+            kept.push_str(rest);
+        }
+        if saw_marker && kept.trim().is_empty() {
+            continue;
+        }
+        out.push_str(&kept);
+    }
+    if !open.is_empty() {
+        log::warn!(
+            "{} conditional region(s) not closed with `<!-- endif -->`",
+            open.len()
+        );
+    }
+    out
+}
+
+/// The fence character and run length when `line` opens or closes a
+/// fenced code block.
+fn fence_of(line: &str) -> Option<(char, usize)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let ch = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.chars().take_while(|c| *c == ch).count();
+    (len >= 3).then_some((ch, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn region_follows_its_flag() {
+        let md = "Intro.\n\n<!-- if: draft -->\nDraft note.\n<!-- endif -->\n\nOutro.\n";
+        assert_eq!(filter(md, &[]), "Intro.\n\n\nOutro.\n");
+        assert_eq!(filter(md, &["draft"]), "Intro.\n\nDraft note.\n\nOutro.\n");
+    }
+
+    #[test]
+    fn inline_region_keeps_surrounding_text() {
+        let md = "Ships <!-- if: beta -->soon<!-- endif -->today.\n";
+        assert_eq!(filter(md, &[]), "Ships today.\n");
+        assert_eq!(filter(md, &["beta"]), "Ships soontoday.\n");
+    }
+
+    #[test]
+    fn nested_region_needs_every_flag() {
+        let md = "<!-- if: a -->\nA\n<!-- if: b -->\nB\n<!-- endif -->\n<!-- endif -->\n";
+        assert_eq!(filter(md, &["a"]), "A\n");
+        assert_eq!(filter(md, &["b"]), "");
+        assert_eq!(filter(md, &["a", "b"]), "A\nB\n");
+    }
+
+    #[test]
+    fn markers_in_code_fences_and_other_comments_stay() {
+        let md = "<!-- note -->\n```\n<!-- if: x -->\n```\n";
+        assert_eq!(filter(md, &[]), md);
+    }
+
+    #[test]
+    fn unmatched_markers_do_not_panic() {
+        assert_eq!(filter("a\n<!-- endif -->\nb\n", &[]), "a\nb\n");
+        assert_eq!(filter("a\n<!-- if: x -->\nb\n", &[]), "a\n");
+    }
+}
@@ -5,9 +5,17 @@
 //! lower to `ResolvedStyle`. Errors surface through
 //! [`styling::ResolveError`].
 
-use crate::styling::{DocumentConfig, ResolveError, ResolvedStyle, merge::resolve_with_overrides};
+use crate::fonts::{FontConfig, FontSource, find_system_font_in, resolve_font_source};
+use crate::styling::{
+    DocumentConfig, PaletteConfig, ResolveError, ResolvedStyle, load_theme_preset,
+    merge::resolve_with_overrides, merge_documents,
+};
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
 
 /// Where the styling configuration comes from.
 #[derive(Debug, Clone)]
@@ -25,6 +33,15 @@ pub enum ConfigSource<'a> {
     File(&'a str),
     /// Treat `s` as the body of a TOML config (no I/O).
     Embedded(&'a str),
+    /// Load each source in order and merge them, later layers winning
+    /// field by field over earlier ones (a built-in theme, then a
+    /// project file, then inline overrides, say). A theme named by
+    /// any layer but the first is expanded in place, so it overrides
+    /// the layers before it; a theme in the first layer stays the
+    /// base, and a `theme_override` still replaces it. A layer that
+    /// fails to load is skipped with a warning instead of aborting
+    /// the whole stack.
+    Layered(Vec<ConfigSource<'a>>),
 }
 
 /// Load the styling configuration and resolve it to a concrete
@@ -53,33 +70,43 @@ pub fn load_config_strict_with_overrides(
     theme_override: Option<&str>,
     overrides_toml: Option<&str>,
 ) -> Result<ResolvedStyle, ResolveError> {
+    let user = load_document(source)?;
+
     // Parse the override fragment once, reusing the config-file error
-    // mapping (unknown key → BadToml + suggestion).
+    // mapping (unknown key → BadToml + suggestion). Palette names in it
+    // resolve against the user config's palette.
     let overrides: Option<DocumentConfig> = match overrides_toml {
-        Some(text) if !text.trim().is_empty() => {
-            let parsed = toml::from_str(text).map_err(|source| {
-                let suggestion = crate::styling::error::unknown_field_suggestion(source.message());
-                ResolveError::BadToml {
-                    source: Box::new(source),
-                    input: text.to_string(),
-                    file: None,
-                    suggestion,
-                }
-            })?;
-            Some(parsed)
-        }
+        Some(text) if !text.trim().is_empty() => Some(parse_document(
+            text,
+            None,
+            palette_in_scope(&user).as_ref(),
+        )?),
         _ => None,
     };
 
+    resolve_with_overrides(user, theme_override, overrides)
+}
+
+/// Read one `ConfigSource` into an unresolved `DocumentConfig`. A
+/// `Theme` source becomes a config whose only field is `theme`, so
+/// the caller's `theme_override` still wins over it at resolve time.
+fn load_document(source: ConfigSource) -> Result<DocumentConfig, ResolveError> {
+    load_document_in(source, None)
+}
+
+/// [`load_document`] with `inherited`, the palette of the layers
+/// beneath this one, available to its colour fields.
+fn load_document_in(
+    source: ConfigSource,
+    inherited: Option<&PaletteConfig>,
+) -> Result<DocumentConfig, ResolveError> {
     let (toml_text, file_for_errors) = match source {
-        ConfigSource::Default => {
-            return resolve_with_overrides(DocumentConfig::default(), theme_override, overrides);
-        }
+        ConfigSource::Default => return Ok(DocumentConfig::default()),
         ConfigSource::Theme(name) => {
-            // CLI `--theme` semantics: caller-supplied theme_override
-            // still wins so a user can layer overrides on top.
-            let theme = theme_override.or(Some(name));
-            return resolve_with_overrides(DocumentConfig::default(), theme, overrides);
+            return Ok(DocumentConfig {
+                theme: Some(name.to_string()),
+                ..DocumentConfig::default()
+            });
         }
         ConfigSource::File(path) => {
             let p = Path::new(path).to_path_buf();
@@ -90,19 +117,289 @@ pub fn load_config_strict_with_overrides(
             (text, Some(p))
         }
         ConfigSource::Embedded(s) => (s.to_string(), None),
+        ConfigSource::Layered(layers) => {
+            let merged = layers.into_iter().enumerate().fold(
+                DocumentConfig::default(),
+                |acc, (idx, layer)| {
+                    // A layer naming an unknown theme would only fail at
+                    // resolve time, taking the whole stack down with it;
+                    // check it here so it's skipped like any other
+                    // broken layer. Past the first layer the preset is
+                    // merged in right here: applied only as the base at
+                    // resolve time, it would sit under the earlier
+                    // layers instead of over them.
+                    let in_scope = merge_palettes(inherited.cloned(), palette_in_scope(&acc));
+                    let loaded = load_document_in(layer, in_scope.as_ref()).and_then(|doc| {
+                        match &doc.theme {
+                            Some(name) if idx > 0 => {
+                                load_theme_preset(name).map(|preset| merge_documents(preset, doc))
+                            }
+                            Some(name) => load_theme_preset(name).map(|_| doc),
+                            None => Ok(doc),
+                        }
+                    });
+                    match loaded {
+                        Ok(doc) => merge_documents(acc, doc),
+                        Err(e) => {
+                            log::warn!("skipping config layer {}: {}", idx, e);
+                            acc
+                        }
+                    }
+                },
+            );
+            return Ok(merged);
+        }
+    };
+
+    parse_document(&toml_text, file_for_errors, inherited)
+}
+
+/// The palette a document's colour fields can name: its theme
+/// preset's `[palette]` with its own `[palette]` over it.
+fn palette_in_scope(doc: &DocumentConfig) -> Option<PaletteConfig> {
+    let preset = doc
+        .theme
+        .as_deref()
+        .and_then(|name| load_theme_preset(name).ok())
+        .and_then(|preset| preset.palette);
+    merge_palettes(preset, doc.palette.clone())
+}
+
+/// `overlay`'s palette entries over `base`'s.
+fn merge_palettes(
+    base: Option<PaletteConfig>,
+    overlay: Option<PaletteConfig>,
+) -> Option<PaletteConfig> {
+    let only = |palette| DocumentConfig {
+        palette,
+        ..DocumentConfig::default()
     };
+    merge_documents(only(base), only(overlay)).palette
+}
 
-    let user: DocumentConfig = toml::from_str(&toml_text).map_err(|source| {
+/// `palette` as TOML, each colour written as `"#RRGGBB"`.
+fn palette_table(palette: &PaletteConfig) -> toml::Table {
+    [
+        ("primary", palette.primary),
+        ("text", palette.text),
+        ("muted", palette.muted),
+        ("accent", palette.accent),
+        ("background", palette.background),
+    ]
+    .into_iter()
+    .filter_map(|(name, color)| {
+        let c = color?;
+        let hex = format!("#{:02X}{:02X}{:02X}", c.r, c.g, c.b);
+        Some((name.to_string(), toml::Value::String(hex)))
+    })
+    .collect()
+}
+
+/// Parse one TOML config body, first replacing colour fields that name
+/// a palette entry (`text_color = "accent"`) with that entry. The
+/// names in scope are `inherited` (the layers beneath), then the
+/// palette of the theme the body names, then the body's own
+/// `[palette]`, later ones winning. With no palette in scope the body
+/// goes straight through serde so type errors keep their line /
+/// column; with one, the substituted table is deserialized instead.
+fn parse_document(
+    text: &str,
+    file: Option<PathBuf>,
+    inherited: Option<&PaletteConfig>,
+) -> Result<DocumentConfig, ResolveError> {
+    let bad_toml = |source: toml::de::Error| {
         let suggestion = crate::styling::error::unknown_field_suggestion(source.message());
         ResolveError::BadToml {
             source: Box::new(source),
-            input: toml_text.clone(),
-            file: file_for_errors,
+            input: text.to_string(),
+            file: file.clone(),
             suggestion,
         }
-    })?;
+    };
+    let mut table: toml::Table = toml::from_str(text).map_err(bad_toml)?;
+    let theme_palette = table
+        .get("theme")
+        .and_then(|t| t.as_str())
+        .and_then(|name| load_theme_preset(name).ok())
+        .and_then(|preset| preset.palette);
+    let mut palette = toml::Table::new();
+    for scope in [inherited, theme_palette.as_ref()].into_iter().flatten() {
+        palette.extend(palette_table(scope));
+    }
+    if let Some(own) = table.get("palette").and_then(|p| p.as_table()) {
+        palette.extend(own.clone());
+    }
+    if palette.is_empty() {
+        return toml::from_str(text).map_err(bad_toml);
+    }
+    for (key, value) in table.iter_mut() {
+        if key != "palette" {
+            substitute_palette(key, value, &palette)?;
+        }
+    }
+    toml::Value::Table(table).try_into().map_err(bad_toml)
+}
 
-    resolve_with_overrides(user, theme_override, overrides)
+/// Walk `value` (stored under `key`) and swap every colour field whose
+/// value is a bare name for the matching `[palette]` entry. Literal
+/// colours (`"#RRGGBB"`, tables, arrays) are left alone.
+fn substitute_palette(
+    key: &str,
+    value: &mut toml::Value,
+    palette: &toml::Table,
+) -> Result<(), ResolveError> {
+    if let toml::Value::Table(t) = value {
+        for (k, v) in t.iter_mut() {
+            substitute_palette(k, v, palette)?;
+        }
+        return Ok(());
+    }
+    let is_color_key =
+        key == "color" || key.ends_with("_color") || key == "alternating_row_background";
+    let Some(name) = value
+        .as_str()
+        .map(str::trim)
+        .filter(|s| is_color_key && !s.starts_with('#'))
+        .map(str::to_string)
+    else {
+        return Ok(());
+    };
+    match palette.get(&name) {
+        Some(color) => *value = color.clone(),
+        None => {
+            let suggestion =
+                crate::styling::error::closest_match(&name, palette.keys().map(String::as_str), 3)
+                    .map(str::to_string);
+            return Err(ResolveError::UnknownPaletteColor { name, suggestion });
+        }
+    }
+    Ok(())
+}
+
+/// Resolve a bundled theme preset by name, or `None` if no preset has
+/// that name. A convenience over [`ConfigSource::Theme`] for callers
+/// that just want the style; use [`load_config_strict`] to get the
+/// typed [`ResolveError::UnknownTheme`] with a closest-match hint.
+pub fn theme(name: &str) -> Option<ResolvedStyle> {
+    load_config_strict(ConfigSource::Theme(name), None).ok()
+}
+
+/// A complete starter config to copy and edit: every field of the
+/// bundled `default` theme with a comment saying what it does, so
+/// loading it unchanged gives exactly [`ResolvedStyle::default`]. The
+/// CLI's `--init-config` writes this.
+pub fn default_config_toml() -> &'static str {
+    include_str!("starter_config.toml")
+}
+
+/// Read the `[fonts]` section of `source` into a [`FontConfig`], so
+/// one config file can pick fonts as well as styling. `None` when the
+/// source has no `[fonts]` section or fails to load; the styling
+/// loaders report the same load error, so it isn't repeated here.
+///
+/// `[fonts.map]` picks a font per element; its `text` and `code`
+/// entries fill `default_font` and `code_font`.
+///
+/// A name found in one of `custom_paths` becomes that file's path;
+/// other names are resolved at render time as usual. Relative
+/// `custom_paths` in a [`ConfigSource::File`] are taken from the
+/// file's directory.
+pub fn load_font_config(source: ConfigSource) -> Option<FontConfig> {
+    let base_dir = match &source {
+        ConfigSource::File(path) => Path::new(path).parent().map(Path::to_path_buf),
+        _ => None,
+    };
+    let fonts = load_document(source).ok()?.fonts?;
+    let dirs: Vec<PathBuf> = fonts
+        .custom_paths
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| match &base_dir {
+            Some(base) if p.is_relative() => base.join(p),
+            _ => p,
+        })
+        .collect();
+    let locate = |name: String| -> String {
+        if dirs.is_empty() || !matches!(resolve_font_source(&name), FontSource::System(_)) {
+            return name;
+        }
+        match find_system_font_in(&name, &dirs) {
+            Some(path) => path.to_string_lossy().into_owned(),
+            None => name,
+        }
+    };
+
+    // `[fonts.map]` entries win over the flat `default_font` /
+    // `code_font` keys they stand in for.
+    let map = fonts.map.unwrap_or_default();
+    let mut config = FontConfig::new();
+    config.default_font = map.text.or(fonts.default_font).map(&locate);
+    config.code_font = map.code.or(fonts.code_font).map(&locate);
+    config.heading_font = map.heading.map(&locate);
+    config.blockquote_font = map.blockquote.map(&locate);
+    config.fallback_fonts = fonts
+        .fallback_fonts
+        .unwrap_or_default()
+        .into_iter()
+        .map(&locate)
+        .collect();
+    if let Some(enabled) = fonts.enable_subsetting {
+        config.enable_subsetting = enabled;
+    }
+    config.auto_emoji = fonts.auto_emoji.unwrap_or(false);
+    Some(config)
+}
+
+/// Process-wide cache behind [`load_config_cached`]: canonical path →
+/// (modification time at parse, resolved style).
+type StyleCache = Mutex<HashMap<PathBuf, (SystemTime, Arc<ResolvedStyle>)>>;
+
+fn style_cache() -> &'static StyleCache {
+    static CACHE: OnceLock<StyleCache> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Strict load of a config file, memoised per path for batch and
+/// server use. The file is only reparsed when its modification time
+/// changes; otherwise the previously resolved style is shared. Errors
+/// are never cached, so a fixed file is picked up on the next call.
+pub fn load_config_cached(path: impl AsRef<Path>) -> Result<Arc<ResolvedStyle>, ResolveError> {
+    let path = path.as_ref();
+    let io_err = |source| ResolveError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let key = fs::canonicalize(path).map_err(io_err)?;
+    let mtime = fs::metadata(&key)
+        .and_then(|m| m.modified())
+        .map_err(io_err)?;
+
+    let mut cache = style_cache().lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((cached_mtime, style)) = cache.get(&key)
+        && *cached_mtime == mtime
+    {
+        return Ok(Arc::clone(style));
+    }
+    let path_text = path.to_string_lossy();
+    let style = Arc::new(load_config_strict(ConfigSource::File(&path_text), None)?);
+    cache.insert(key, (mtime, Arc::clone(&style)));
+    Ok(style)
+}
+
+/// Strict load of a config read from any [`Read`] source: an archive
+/// entry, a network body, an embedded resource. Reads to the end and
+/// parses like [`ConfigSource::Embedded`], so TOML errors keep their
+/// line / column and typo hints. A failed read (or non-UTF-8 input)
+/// is a [`ResolveError::Io`] with the placeholder path `<reader>`.
+pub fn load_config_from_reader<R: Read>(mut reader: R) -> Result<ResolvedStyle, ResolveError> {
+    let mut text = String::new();
+    reader
+        .read_to_string(&mut text)
+        .map_err(|source| ResolveError::Io {
+            path: PathBuf::from("<reader>"),
+            source,
+        })?;
+    load_config_strict(ConfigSource::Embedded(&text), None)
 }
 
 /// Soft-fail version of [`load_config_strict`]. On any error logs a
@@ -328,4 +625,300 @@ mod tests {
         .unwrap();
         assert_eq!(style.paragraph.font_size_pt, 7.5);
     }
+
+    #[test]
+    fn font_family_is_owned_per_load() {
+        // Font names are plain owned `String`s on the resolved style,
+        // so each load's copy is freed with it; nothing is leaked to
+        // get a `'static` name, however often a server reloads.
+        let cfg = "[defaults]\nfont_family = \"Georgia\"\n";
+        let styles: Vec<ResolvedStyle> = (0..3)
+            .map(|_| load_config_strict(ConfigSource::Embedded(cfg), None).unwrap())
+            .collect();
+        for style in &styles {
+            assert_eq!(style.paragraph.font_family.as_deref(), Some("Georgia"));
+        }
+        let a = styles[0].paragraph.font_family.as_ref().unwrap();
+        let b = styles[1].paragraph.font_family.as_ref().unwrap();
+        assert_ne!(a.as_ptr(), b.as_ptr(), "each load owns its own copy");
+    }
+
+    // --- Layered sources ---------------------------------------------
+
+    #[test]
+    fn layered_override_wins_only_for_its_fields() {
+        let base = load_config_strict(ConfigSource::Default, None).unwrap();
+        let style = load_config_strict(
+            ConfigSource::Layered(vec![
+                ConfigSource::Default,
+                ConfigSource::Embedded("[paragraph]\nfont_size_pt = 12.5\n"),
+            ]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(style.paragraph.font_size_pt, 12.5);
+        assert_eq!(style.paragraph.text_color, base.paragraph.text_color);
+        assert_eq!(
+            style.headings[0].font_size_pt,
+            base.headings[0].font_size_pt
+        );
+    }
+
+    #[test]
+    fn layered_later_layer_beats_earlier() {
+        let style = load_config_strict(
+            ConfigSource::Layered(vec![
+                ConfigSource::Theme("github"),
+                ConfigSource::Embedded("[paragraph]\nfont_size_pt = 9.0\n"),
+                ConfigSource::Embedded("[paragraph]\nfont_size_pt = 11.0\n"),
+            ]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(style.paragraph.font_size_pt, 11.0);
+    }
+
+    #[test]
+    fn layered_later_theme_overrides_earlier_layers() {
+        let github = load_config_strict(ConfigSource::Theme("github"), None).unwrap();
+        let style = load_config_strict(
+            ConfigSource::Layered(vec![
+                ConfigSource::Embedded("[paragraph]\nfont_size_pt = 21.0\n"),
+                ConfigSource::Theme("github"),
+            ]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(style.paragraph.font_size_pt, github.paragraph.font_size_pt);
+    }
+
+    #[test]
+    fn layered_palette_names_resolve_against_earlier_layers() {
+        let style = load_config_strict(
+            ConfigSource::Layered(vec![
+                ConfigSource::Embedded("[palette]\naccent = \"#0A64C8\"\n"),
+                ConfigSource::Embedded("[paragraph]\ntext_color = \"accent\"\n"),
+            ]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            style.paragraph.text_color,
+            crate::styling::Color::rgb(0x0A, 0x64, 0xC8)
+        );
+
+        let style = load_config_strict_with_overrides(
+            ConfigSource::Embedded("[palette]\nmuted = \"#777777\"\n"),
+            None,
+            Some("[blockquote]\ntext_color = \"muted\"\n"),
+        )
+        .unwrap();
+        assert_eq!(
+            style.blockquote.text_color,
+            crate::styling::Color::rgb(0x77, 0x77, 0x77)
+        );
+    }
+
+    #[test]
+    fn layered_skips_broken_layers() {
+        let style = load_config_strict(
+            ConfigSource::Layered(vec![
+                ConfigSource::Embedded("[paragraph]\nfont_size_pt = 12.0\n"),
+                ConfigSource::File("nonexistent.toml"),
+                ConfigSource::Embedded("not valid toml {{{"),
+                ConfigSource::Theme("doesnotexist"),
+            ]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(style.paragraph.font_size_pt, 12.0);
+    }
+
+    // --- Cached loader -----------------------------------------------
+
+    #[test]
+    fn cached_loader_reuses_until_file_changes() {
+        let path =
+            std::env::temp_dir().join(format!("m2p_cached_config_{}.toml", std::process::id()));
+        fs::write(&path, "[paragraph]\nfont_size_pt = 10.0\n").unwrap();
+
+        let a = load_config_cached(&path).unwrap();
+        let b = load_config_cached(&path).unwrap();
+        assert!(Arc::ptr_eq(&a, &b), "unchanged file should hit the cache");
+        assert_eq!(a.paragraph.font_size_pt, 10.0);
+
+        fs::write(&path, "[paragraph]\nfont_size_pt = 12.0\n").unwrap();
+        // Bump the mtime explicitly; coarse filesystem timestamps can
+        // otherwise make the rewrite look unchanged.
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+
+        let c = load_config_cached(&path).unwrap();
+        assert!(!Arc::ptr_eq(&a, &c), "modified file should reparse");
+        assert_eq!(c.paragraph.font_size_pt, 12.0);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn reader_source_parses_like_embedded() {
+        let toml = b"theme = \"github\"\n[paragraph]\nfont_size_pt = 13.0\n";
+        let style = load_config_from_reader(std::io::Cursor::new(&toml[..])).unwrap();
+        assert_eq!(style.paragraph.font_size_pt, 13.0);
+        let github = theme("github").unwrap();
+        assert_eq!(
+            style.headings[0].font_size_pt,
+            github.headings[0].font_size_pt
+        );
+
+        let typo = load_config_from_reader(&b"[paragraph]\nfont_sise_pt = 1.0\n"[..]);
+        assert!(matches!(typo, Err(ResolveError::BadToml { .. })));
+        let binary = load_config_from_reader(&[0xff, 0xfe, 0x00][..]);
+        assert!(matches!(binary, Err(ResolveError::Io { .. })));
+    }
+
+    #[test]
+    fn cached_loader_missing_file_is_io_error() {
+        let err = load_config_cached("nonexistent-cached.toml");
+        assert!(matches!(err, Err(ResolveError::Io { .. })));
+    }
+
+    // --- Font section ------------------------------------------------
+
+    #[test]
+    fn fonts_section_parses_into_font_config() {
+        let dir = std::env::temp_dir().join(format!("m2p_fonts_section_{}", std::process::id()));
+        let font_dir = dir.join("fonts");
+        fs::create_dir_all(&font_dir).unwrap();
+        fs::write(font_dir.join("House Serif.ttf"), b"").unwrap();
+        let path = dir.join("config.toml");
+        fs::write(
+            &path,
+            "[fonts]\n\
+             default_font = \"House Serif\"\n\
+             code_font = \"Courier\"\n\
+             custom_paths = [\"fonts\"]\n\
+             fallback_fonts = [\"Noto Sans CJK\", \"House Serif\"]\n\
+             enable_subsetting = false\n\
+             auto_emoji = true\n\
+             [paragraph]\n\
+             font_size_pt = 10.0\n",
+        )
+        .unwrap();
+        let path_text = path.to_string_lossy();
+
+        let fonts = load_font_config(ConfigSource::File(&path_text)).unwrap();
+        let house = font_dir
+            .join("House Serif.ttf")
+            .to_string_lossy()
+            .into_owned();
+        // Found in the relative custom path: resolved to the file.
+        assert_eq!(fonts.default_font.as_deref(), Some(house.as_str()));
+        assert_eq!(fonts.code_font.as_deref(), Some("Courier"));
+        assert_eq!(fonts.fallback_fonts, ["Noto Sans CJK".to_string(), house]);
+        assert!(!fonts.enable_subsetting);
+        assert!(fonts.auto_emoji);
+        // The styling loader accepts the same file.
+        let style = load_config_strict(ConfigSource::File(&path_text), None).unwrap();
+        assert_eq!(style.paragraph.font_size_pt, 10.0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fonts_map_sets_a_font_per_element() {
+        let fonts = load_font_config(ConfigSource::Embedded(
+            "[fonts]\n\
+             default_font = \"Helvetica\"\n\
+             [fonts.map]\n\
+             heading = \"Georgia\"\n\
+             text = \"Times\"\n\
+             code = \"Courier\"\n\
+             blockquote = \"Palatino\"\n",
+        ))
+        .unwrap();
+        assert_eq!(fonts.heading_font.as_deref(), Some("Georgia"));
+        // The map's `text` wins over the flat `default_font`.
+        assert_eq!(fonts.default_font.as_deref(), Some("Times"));
+        assert_eq!(fonts.code_font.as_deref(), Some("Courier"));
+        assert_eq!(fonts.blockquote_font.as_deref(), Some("Palatino"));
+        assert!(
+            load_config_strict(
+                ConfigSource::Embedded("[fonts.map]\nfooter = \"Georgia\"\n"),
+                None
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn missing_fonts_section_is_none() {
+        assert!(
+            load_font_config(ConfigSource::Embedded("[paragraph]\nfont_size_pt = 9.0\n")).is_none()
+        );
+        assert!(load_font_config(ConfigSource::Default).is_none());
+        let fonts = FontConfig::from_toml("[fonts]\ndefault_font = \"Georgia\"\n").unwrap();
+        assert_eq!(fonts.default_font.as_deref(), Some("Georgia"));
+        assert!(fonts.enable_subsetting);
+    }
+
+    #[test]
+    fn default_config_toml_reproduces_the_default_style() {
+        let style =
+            load_config_strict(ConfigSource::Embedded(default_config_toml()), None).unwrap();
+        assert_eq!(style, ResolvedStyle::default());
+        assert!(default_config_toml().starts_with("# markdown2pdf config."));
+        // Each field carries its own explanation.
+        let mut previous = "";
+        for line in default_config_toml().lines() {
+            if line.contains(" = ") && !line.starts_with('#') {
+                assert!(previous.starts_with('#'), "no comment above `{line}`");
+            }
+            previous = line;
+        }
+    }
+
+    // --- Named presets -----------------------------------------------
+
+    #[test]
+    fn every_bundled_theme_resolves_by_name() {
+        for name in crate::styling::available_theme_names() {
+            assert!(theme(name).is_some(), "preset `{name}` failed to resolve");
+        }
+        assert!(theme("doesnotexist").is_none());
+    }
+
+    #[test]
+    fn non_default_themes_differ_from_default() {
+        let default = theme("default").unwrap();
+        assert_eq!(default, ResolvedStyle::default());
+        for name in crate::styling::available_theme_names() {
+            if *name == "default" {
+                continue;
+            }
+            assert_ne!(
+                theme(name).unwrap(),
+                default,
+                "preset `{name}` is indistinguishable from default"
+            );
+        }
+    }
+
+    #[test]
+    fn themes_set_expected_distinguishing_fields() {
+        let default = theme("default").unwrap();
+        let github = theme("github").unwrap();
+        assert_eq!(github.paragraph.font_size_pt, 10.0);
+        assert_ne!(github.link.text_color, default.link.text_color);
+        let academic = theme("academic").unwrap();
+        assert_ne!(
+            academic.paragraph.font_family,
+            default.paragraph.font_family
+        );
+        let compact = theme("compact").unwrap();
+        assert!(compact.page.margins_mm.top < default.page.margins_mm.top);
+    }
 }
@@ -33,10 +33,13 @@ impl Token {
         let inner_indent = "  ".repeat(indent_level + 1);
 
         match self {
-            Token::Heading(content, level) => {
+            Token::Heading(content, level, id) => {
                 let mut result = format!("{}{{\n", indent);
                 result.push_str(&format!("{}\"type\": \"Heading\",\n", inner_indent));
                 result.push_str(&format!("{}\"level\": {},\n", inner_indent, level));
+                if let Some(id) = id {
+                    result.push_str(&format!("{}\"id\": \"{}\",\n", inner_indent, id));
+                }
                 result.push_str(&format!("{}\"content\": [\n", inner_indent));
 
                 for (i, token) in content.iter().enumerate() {
@@ -93,6 +96,7 @@ impl Token {
                 language,
                 content,
                 block,
+                ..
             } => {
                 format!(
                     "{}{{\n{}\"type\": \"Code\",\n{}\"block\": {},\n{}\"language\": \"{}\",\n{}\"content\": \"{}\"\n{}}}",
@@ -582,9 +586,12 @@ impl Token {
             format!("[{}]", inner.join(", "))
         }
         match self {
-            Token::Heading(content, level) => {
+            Token::Heading(content, level, None) => {
                 format!("Heading({}, {})", level, list(content))
             }
+            Token::Heading(content, level, Some(id)) => {
+                format!("Heading({}, {}, #{})", level, list(content), id)
+            }
             Token::Emphasis { level, content } => {
                 format!("Emphasis({}, {})", level, list(content))
             }
@@ -593,6 +600,7 @@ impl Token {
                 language,
                 content,
                 block,
+                ..
             } => {
                 let kind = if *block { "CodeBlock" } else { "CodeSpan" };
                 format!("{}({}, {})", kind, quote(language), quote(content))
@@ -779,6 +787,7 @@ mod compact_tests {
                 },
             ],
             2,
+            None,
         );
         assert_eq!(
             t.to_compact(),
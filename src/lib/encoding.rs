@@ -0,0 +1,128 @@
+//! Decoding Markdown source bytes into UTF-8 text.
+//!
+//! The lexer works on `String`s, but Markdown files written by older
+//! Windows editors are often Windows-1252 rather than UTF-8. Reading
+//! those with `fs::read_to_string` fails outright. [`decode_markdown`]
+//! tries the bytes as UTF-8 first and falls back to a legacy codec,
+//! reporting which one it used so the caller can warn about it.
+//!
+//! Detection order without an explicit codec:
+//!
+//! 1. A byte-order mark (UTF-8, UTF-16LE, UTF-16BE) wins outright.
+//! 2. Bytes that are valid UTF-8 are taken as UTF-8.
+//! 3. Anything else is decoded as Windows-1252. That covers ASCII and
+//!    ISO-8859-1 too, since the WHATWG Encoding Standard maps both
+//!    labels to it, and it decodes every byte.
+
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+
+use crate::MdpError;
+
+/// Text produced by [`decode_markdown`], plus the codec it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedText {
+    pub text: String,
+    /// Canonical WHATWG name of the codec used, e.g. `"UTF-8"` or
+    /// `"windows-1252"`.
+    pub encoding: &'static str,
+    /// True when the input was not UTF-8 and had to be transcoded.
+    pub transcoded: bool,
+}
+
+/// Decode `bytes` to UTF-8 text.
+///
+/// `label` forces a codec by any WHATWG label (`"latin1"`,
+/// `"cp1252"`, `"utf-16le"`, `"shift_jis"`, ...); a matching BOM is
+/// still stripped. Without it the codec is detected as described in
+/// the module docs. Bytes the codec cannot map become U+FFFD.
+///
+/// # Errors
+/// `MdpError::ConfigError` if `label` names no known encoding.
+pub fn decode_markdown(bytes: &[u8], label: Option<&str>) -> Result<DecodedText, MdpError> {
+    let encoding = match label {
+        Some(label) => {
+            Encoding::for_label(label.trim().as_bytes()).ok_or_else(|| MdpError::ConfigError {
+                message: format!("Unknown input encoding '{}'", label),
+                suggestion: "Use a standard label such as utf-8, windows-1252, latin1 or utf-16le"
+                    .to_string(),
+                source: None,
+            })?
+        }
+        None => detect(bytes),
+    };
+    let (text, _) = encoding.decode_with_bom_removal(bytes);
+    Ok(DecodedText {
+        text: text.into_owned(),
+        encoding: encoding.name(),
+        transcoded: encoding != UTF_8,
+    })
+}
+
+fn detect(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        UTF_8
+    } else {
+        WINDOWS_1252
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf8_passes_through_untouched() {
+        let d = decode_markdown("# Café\n".as_bytes(), None).unwrap();
+        assert_eq!(d.text, "# Café\n");
+        assert_eq!(d.encoding, "UTF-8");
+        assert!(!d.transcoded);
+    }
+
+    #[test]
+    fn windows_1252_is_transcoded() {
+        // "Café – “quoted”" in Windows-1252: é = 0xE9, en dash = 0x96,
+        // curly quotes = 0x93 / 0x94. None of these is valid UTF-8.
+        let bytes = b"Caf\xe9 \x96 \x93quoted\x94";
+        assert!(std::str::from_utf8(bytes).is_err());
+        let d = decode_markdown(bytes, None).unwrap();
+        assert_eq!(d.text, "Café – “quoted”");
+        assert_eq!(d.encoding, "windows-1252");
+        assert!(d.transcoded);
+    }
+
+    #[test]
+    fn bom_selects_utf16() {
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in "Hé".encode_utf16() {
+            bytes.extend(unit.to_le_bytes());
+        }
+        let d = decode_markdown(&bytes, None).unwrap();
+        assert_eq!(d.text, "Hé");
+        assert_eq!(d.encoding, "UTF-16LE");
+    }
+
+    #[test]
+    fn utf8_bom_is_stripped() {
+        let d = decode_markdown(b"\xEF\xBB\xBF# Title", None).unwrap();
+        assert_eq!(d.text, "# Title");
+        assert!(!d.transcoded);
+    }
+
+    #[test]
+    fn forced_label_overrides_detection() {
+        // Valid UTF-8 ("é" = C3 A9), read as Latin-1 on purpose.
+        let d = decode_markdown("é".as_bytes(), Some("latin1")).unwrap();
+        assert_eq!(d.text, "Ã©");
+        assert_eq!(d.encoding, "windows-1252");
+    }
+
+    #[test]
+    fn unknown_label_is_a_config_error() {
+        let err = decode_markdown(b"x", Some("klingon")).unwrap_err();
+        assert!(matches!(err, MdpError::ConfigError { .. }));
+        assert!(err.to_string().contains("klingon"));
+    }
+}
@@ -11,6 +11,7 @@
 //! doesn't ripple into the public configuration API.
 
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 /// Specifies where to load a font from.
 #[derive(Debug, Clone)]
@@ -48,7 +49,12 @@ impl FontSource {
 /// Both `default_font` and `code_font` accept friendly names ("Georgia",
 /// "Helvetica", "/path/to/font.ttf") and are resolved at render time.
 /// Explicit `*_source` fields take priority when set.
+///
+/// Build one with [`FontConfig::new`] and the `with_*` methods; the
+/// struct is `#[non_exhaustive]` so new options can be added without
+/// breaking callers.
 #[derive(Debug, Clone, Default)]
+#[non_exhaustive]
 pub struct FontConfig {
     /// Default font name for body text.
     pub default_font: Option<String>,
@@ -58,6 +64,11 @@ pub struct FontConfig {
     pub default_font_source: Option<FontSource>,
     /// Font source for code blocks. Takes priority over `code_font` if set.
     pub code_font_source: Option<FontSource>,
+    /// Font name for headings. Unset, headings use the body font.
+    pub heading_font: Option<String>,
+    /// Font name for blockquote text. Unset, blockquotes use the body
+    /// font.
+    pub blockquote_font: Option<String>,
     /// Ordered list of fallback font *names* (system / path / built-in
     /// alias). Resolved the same way as `default_font` at render time.
     /// Composed with `fallback_font_sources` (sources first, then names)
@@ -70,6 +81,10 @@ pub struct FontConfig {
     pub fallback_font_sources: Vec<FontSource>,
     /// Enable font subsetting for smaller PDFs.
     pub enable_subsetting: bool,
+    /// Append the platform emoji font ([`default_emoji_font`]) to the
+    /// fallback chain when the document contains emoji and no
+    /// configured fallback already names an emoji font.
+    pub auto_emoji: bool,
 }
 
 impl FontConfig {
@@ -80,9 +95,12 @@ impl FontConfig {
             code_font: None,
             default_font_source: None,
             code_font_source: None,
+            heading_font: None,
+            blockquote_font: None,
             fallback_fonts: Vec::new(),
             fallback_font_sources: Vec::new(),
             enable_subsetting: true,
+            auto_emoji: false,
         }
     }
 
@@ -98,6 +116,18 @@ impl FontConfig {
         self
     }
 
+    /// Set the heading font.
+    pub fn with_heading_font(mut self, font: impl Into<String>) -> Self {
+        self.heading_font = Some(font.into());
+        self
+    }
+
+    /// Set the blockquote font.
+    pub fn with_blockquote_font(mut self, font: impl Into<String>) -> Self {
+        self.blockquote_font = Some(font.into());
+        self
+    }
+
     /// Set the font source for body text directly.
     pub fn with_default_font_source(mut self, source: FontSource) -> Self {
         self.default_font_source = Some(source);
@@ -116,6 +146,13 @@ impl FontConfig {
         self
     }
 
+    /// Enable or disable the automatic emoji fallback. See
+    /// [`FontConfig::auto_emoji`].
+    pub fn with_auto_emoji(mut self, enabled: bool) -> Self {
+        self.auto_emoji = enabled;
+        self
+    }
+
     /// Replace the fallback-font name list. See [`FontConfig::fallback_fonts`].
     pub fn with_fallback_fonts<I, S>(mut self, names: I) -> Self
     where
@@ -137,6 +174,13 @@ impl FontConfig {
         self.fallback_font_sources.push(source);
         self
     }
+
+    /// Build from the `[fonts]` section of a TOML config body. `None`
+    /// when the body has no `[fonts]` section or doesn't parse. See
+    /// [`crate::config::load_font_config`].
+    pub fn from_toml(text: &str) -> Option<Self> {
+        crate::config::load_font_config(crate::config::ConfigSource::Embedded(text))
+    }
 }
 
 /// Names recognized as PDF Type 1 built-ins. The renderer's font module
@@ -177,9 +221,14 @@ pub fn resolve_font_source(name: &str) -> FontSource {
     FontSource::System(name.to_string())
 }
 
-/// Returns known font directories for the current platform.
+/// Returns known font directories for the current platform. Empty
+/// without the `system-fonts` feature, which leaves every name lookup
+/// ([`find_system_font`], [`default_body_source`],
+/// [`list_available_fonts`]) to the built-in fonts.
 pub fn system_font_dirs() -> Vec<&'static str> {
-    if cfg!(target_os = "macos") {
+    if !cfg!(feature = "system-fonts") {
+        vec![]
+    } else if cfg!(target_os = "macos") {
         vec![
             "/System/Library/Fonts",
             "/System/Library/Fonts/Supplemental",
@@ -247,9 +296,179 @@ pub fn default_body_source() -> Option<FontSource> {
     None
 }
 
+/// The color emoji font each platform ships, used by the automatic
+/// emoji fallback ([`FontConfig::auto_emoji`]).
+pub fn default_emoji_font() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "Apple Color Emoji"
+    } else if cfg!(target_os = "windows") {
+        "Segoe UI Emoji"
+    } else {
+        "Noto Color Emoji"
+    }
+}
+
+/// True for codepoints with the Unicode `Emoji_Presentation`
+/// property, i.e. those that render as color emoji by default.
+/// Symbols that are text by default (check marks, arrows, stars,
+/// most dingbats) are left out; they only become emoji when followed
+/// by U+FE0F, which [`with_emoji_fallback`] checks for separately.
+pub fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x231A..=0x231B
+            | 0x23E9..=0x23EC
+            | 0x23F0
+            | 0x23F3
+            | 0x25FD..=0x25FE
+            | 0x2614..=0x2615
+            | 0x2648..=0x2653
+            | 0x267F
+            | 0x2693
+            | 0x26A1
+            | 0x26AA..=0x26AB
+            | 0x26BD..=0x26BE
+            | 0x26C4..=0x26C5
+            | 0x26CE
+            | 0x26D4
+            | 0x26EA
+            | 0x26F2..=0x26F3
+            | 0x26F5
+            | 0x26FA
+            | 0x26FD
+            | 0x2705
+            | 0x270A..=0x270B
+            | 0x2728
+            | 0x274C
+            | 0x274E
+            | 0x2753..=0x2755
+            | 0x2757
+            | 0x2795..=0x2797
+            | 0x27B0
+            | 0x27BF
+            | 0x2B1B..=0x2B1C
+            | 0x2B50
+            | 0x2B55
+            | 0x1F004
+            | 0x1F0CF
+            | 0x1F18E
+            | 0x1F191..=0x1F19A
+            | 0x1F1E6..=0x1F1FF
+            | 0x1F201
+            | 0x1F21A
+            | 0x1F22F
+            | 0x1F232..=0x1F236
+            | 0x1F238..=0x1F23A
+            | 0x1F250..=0x1F251
+            | 0x1F300..=0x1F320
+            | 0x1F32D..=0x1F335
+            | 0x1F337..=0x1F37C
+            | 0x1F37E..=0x1F393
+            | 0x1F3A0..=0x1F3CA
+            | 0x1F3CF..=0x1F3D3
+            | 0x1F3E0..=0x1F3F0
+            | 0x1F3F4
+            | 0x1F3F8..=0x1F43E
+            | 0x1F440
+            | 0x1F442..=0x1F4FC
+            | 0x1F4FF..=0x1F53D
+            | 0x1F54B..=0x1F54E
+            | 0x1F550..=0x1F567
+            | 0x1F57A
+            | 0x1F595..=0x1F596
+            | 0x1F5A4
+            | 0x1F5FB..=0x1F64F
+            | 0x1F680..=0x1F6C5
+            | 0x1F6CC
+            | 0x1F6D0..=0x1F6D2
+            | 0x1F6D5..=0x1F6D7
+            | 0x1F6DC..=0x1F6DF
+            | 0x1F6EB..=0x1F6EC
+            | 0x1F6F4..=0x1F6FC
+            | 0x1F7E0..=0x1F7EB
+            | 0x1F7F0
+            | 0x1F90C..=0x1F93A
+            | 0x1F93C..=0x1F945
+            | 0x1F947..=0x1F9FF
+            | 0x1FA70..=0x1FA7C
+            | 0x1FA80..=0x1FA88
+            | 0x1FA90..=0x1FABD
+            | 0x1FABF..=0x1FAC5
+            | 0x1FACE..=0x1FADB
+            | 0x1FAE0..=0x1FAE8
+            | 0x1FAF0..=0x1FAF8
+    )
+}
+
+/// U+FE0F VARIATION SELECTOR-16, which asks for emoji presentation of
+/// the preceding text-default symbol (e.g. "❤️", "✔️").
+const EMOJI_VARIATION_SELECTOR: char = '\u{FE0F}';
+
+/// `names` with [`default_emoji_font`] appended when `used` contains
+/// an emoji (or the emoji variation selector) and no name in the chain
+/// already looks like an emoji font (contains "emoji",
+/// case-insensitively). Order is otherwise kept, so a user-listed
+/// emoji font stays where it was put.
+pub fn with_emoji_fallback(names: &[String], used: &[char]) -> Vec<String> {
+    let mut out = names.to_vec();
+    let has_emoji_font = names.iter().any(|n| n.to_lowercase().contains("emoji"));
+    let wants_emoji = used
+        .iter()
+        .any(|&c| is_emoji(c) || c == EMOJI_VARIATION_SELECTOR);
+    if !has_emoji_font && wants_emoji {
+        out.push(default_emoji_font().to_string());
+    }
+    out
+}
+
+/// The built-in PDF Type 1 families, always available without any
+/// font file.
+const BUILTIN_FAMILIES: &[&str] = &["Courier", "Helvetica", "Times"];
+
+/// Font names usable as `default_font` / `code_font`: the built-in
+/// families plus every `.ttf` / `.otf` in [`system_font_dirs`], named
+/// by file stem since that is what [`find_system_font`] matches.
+/// Sorted case-insensitively and deduplicated. The directory scan runs
+/// once per process.
+pub fn list_available_fonts() -> Vec<String> {
+    static SYSTEM_FONTS: OnceLock<Vec<String>> = OnceLock::new();
+    let system = SYSTEM_FONTS.get_or_init(|| list_fonts_in(&system_font_dirs()));
+    let mut names: Vec<String> = BUILTIN_FAMILIES
+        .iter()
+        .map(|n| n.to_string())
+        .chain(system.iter().cloned())
+        .collect();
+    names.sort_by_cached_key(|n| n.to_lowercase());
+    names.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    names
+}
+
+/// File stems of the loadable (`.ttf` / `.otf`) fonts directly inside
+/// `dirs`. `.ttc` collections are skipped, matching
+/// [`find_system_font`].
+fn list_fonts_in(dirs: &[&str]) -> Vec<String> {
+    let mut names = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let loadable = path
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .is_some_and(|e| e == "ttf" || e == "otf");
+            if loadable && let Some(stem) = path.file_stem() {
+                names.push(stem.to_string_lossy().into_owned());
+            }
+        }
+    }
+    names
+}
+
 /// `find_system_font` with the search directories injected, so the
 /// matching logic can be exercised against a controlled directory.
-fn find_system_font_in(name: &str, dirs: &[&str]) -> Option<PathBuf> {
+pub(crate) fn find_system_font_in<P: AsRef<Path>>(name: &str, dirs: &[P]) -> Option<PathBuf> {
     let name_lower = name.to_lowercase();
     let patterns: Vec<String> = [
         format!("{}.ttf", name),
@@ -268,7 +487,7 @@ fn find_system_font_in(name: &str, dirs: &[&str]) -> Option<PathBuf> {
     // than their `X Bold` / `X Italic` siblings).
     let mut prefix_match: Option<PathBuf> = None;
     for dir in dirs {
-        let dir_path = Path::new(dir);
+        let dir_path = dir.as_ref();
         if !dir_path.exists() {
             continue;
         }
@@ -405,4 +624,53 @@ mod tests {
             assert!(find_system_font_in("Helvetica Neue", &[dir]).is_none());
         });
     }
+
+    #[test]
+    fn emoji_in_document_appends_emoji_fallback() {
+        let names = vec!["Noto Sans CJK SC".to_string()];
+        let chain = with_emoji_fallback(&names, &['a', '🎉']);
+        assert_eq!(
+            chain,
+            vec![
+                "Noto Sans CJK SC".to_string(),
+                default_emoji_font().to_string()
+            ]
+        );
+        // No emoji in the document: the chain is untouched.
+        assert_eq!(with_emoji_fallback(&names, &['a', '日']), names);
+        // A listed emoji font keeps its place and isn't duplicated.
+        let listed = vec!["Twemoji".to_string(), "Noto Sans".to_string()];
+        assert_eq!(with_emoji_fallback(&listed, &['🎉']), listed);
+    }
+
+    #[test]
+    fn list_available_fonts_always_includes_builtins() {
+        let names = list_available_fonts();
+        for builtin in ["Helvetica", "Times", "Courier"] {
+            assert!(
+                names.iter().any(|n| n == builtin),
+                "{builtin} missing from {names:?}"
+            );
+        }
+        let mut sorted = names.clone();
+        sorted.sort_by_cached_key(|n| n.to_lowercase());
+        assert_eq!(names, sorted, "list must be sorted");
+    }
+
+    #[test]
+    fn list_fonts_in_names_loadable_files_by_stem() {
+        with_font_dir(
+            &[
+                "Georgia.ttf",
+                "Inter.OTF",
+                "Helvetica Neue.ttc",
+                "README.txt",
+            ],
+            |dir| {
+                let mut names = list_fonts_in(&[dir]);
+                names.sort();
+                assert_eq!(names, ["Georgia", "Inter"]);
+            },
+        );
+    }
 }
@@ -22,14 +22,19 @@
 //!
 //! The TOML side delegates to the `toml` crate.
 
-use crate::styling::ResolvedMetadata;
+use crate::markdown::{TableAlignment, TableCell, Token};
+use crate::styling::{MetadataKey, ResolvedMetadata, ResolvedTitlePage};
 
 /// Parsed frontmatter values, ready to be merged onto a resolved
 /// style's metadata.
 #[derive(Debug, Default, Clone)]
 pub struct Frontmatter {
     pub title: Option<String>,
+    /// Only used by the title page.
+    pub subtitle: Option<String>,
     pub author: Option<String>,
+    /// Free-form; printed as written on the title page.
+    pub date: Option<String>,
     pub subject: Option<String>,
     pub creator: Option<String>,
     pub keywords: Vec<String>,
@@ -56,6 +61,65 @@ impl Frontmatter {
             metadata.keywords = self.keywords;
         }
     }
+
+    /// Fill the title page's text from the frontmatter. Like
+    /// [`apply`](Self::apply), frontmatter wins for any field it
+    /// specifies.
+    pub fn apply_title_page(&self, title_page: &mut ResolvedTitlePage) {
+        if let Some(v) = &self.title {
+            title_page.title = v.clone();
+        }
+        if let Some(v) = &self.subtitle {
+            title_page.subtitle = Some(v.clone());
+        }
+        if let Some(v) = &self.author {
+            title_page.author = Some(v.clone());
+        }
+        if let Some(v) = &self.date {
+            title_page.date = Some(v.clone());
+        }
+    }
+
+    /// The value written for `key`, if any. Keywords are joined with
+    /// commas.
+    pub fn value(&self, key: MetadataKey) -> Option<String> {
+        match key {
+            MetadataKey::Title => self.title.clone(),
+            MetadataKey::Subtitle => self.subtitle.clone(),
+            MetadataKey::Author => self.author.clone(),
+            MetadataKey::Date => self.date.clone(),
+            MetadataKey::Subject => self.subject.clone(),
+            MetadataKey::Creator => self.creator.clone(),
+            MetadataKey::Keywords => (!self.keywords.is_empty()).then(|| self.keywords.join(", ")),
+        }
+    }
+
+    /// A `Field | Value` table with one row per key in `keys` that the
+    /// frontmatter sets, for `[metadata] render_table`. `None` when
+    /// none of them is set.
+    pub fn table(&self, keys: &[MetadataKey]) -> Option<Token> {
+        let rows: Vec<Vec<TableCell<Token>>> = keys
+            .iter()
+            .filter_map(|&key| {
+                let value = self.value(key).filter(|v| !v.trim().is_empty())?;
+                Some(vec![
+                    TableCell::new(vec![Token::Text(key.label().to_string())]),
+                    TableCell::new(vec![Token::Text(value)]),
+                ])
+            })
+            .collect();
+        if rows.is_empty() {
+            return None;
+        }
+        Some(Token::Table {
+            headers: vec![
+                TableCell::new(vec![Token::Text("Field".to_string())]),
+                TableCell::new(vec![Token::Text("Value".to_string())]),
+            ],
+            aligns: vec![TableAlignment::Left, TableAlignment::Left],
+            rows,
+        })
+    }
 }
 
 /// Look for a frontmatter block at the start of `input`. On success
@@ -174,7 +238,9 @@ fn parse_toml(body: &str) -> Frontmatter {
     #[serde(default)]
     struct Raw {
         title: Option<String>,
+        subtitle: Option<String>,
         author: Option<String>,
+        date: Option<toml::Value>,
         subject: Option<String>,
         creator: Option<String>,
         keywords: Option<Vec<String>>,
@@ -182,7 +248,13 @@ fn parse_toml(body: &str) -> Frontmatter {
     let raw: Raw = toml::from_str(body).unwrap_or_default();
     Frontmatter {
         title: raw.title,
+        subtitle: raw.subtitle,
         author: raw.author,
+        // TOML has a native datetime type; keep whatever was written.
+        date: raw.date.map(|v| match v {
+            toml::Value::String(s) => s,
+            other => other.to_string(),
+        }),
         subject: raw.subject,
         creator: raw.creator,
         keywords: raw.keywords.unwrap_or_default(),
@@ -197,6 +269,8 @@ enum YamlValue {
 fn assign(fm: &mut Frontmatter, key: &str, value: YamlValue) {
     match (key.to_ascii_lowercase().as_str(), value) {
         ("title", YamlValue::Scalar(s)) => fm.title = Some(s),
+        ("subtitle", YamlValue::Scalar(s)) => fm.subtitle = Some(s),
+        ("date", YamlValue::Scalar(s)) => fm.date = Some(s),
         ("author" | "authors", YamlValue::Scalar(s)) => fm.author = Some(s),
         ("author" | "authors", YamlValue::List(v)) => fm.author = Some(v.join(", ")),
         ("subject" | "description", YamlValue::Scalar(s)) => fm.subject = Some(s),
@@ -316,6 +390,17 @@ mod tests {
         assert_eq!(&src[off..], "body");
     }
 
+    #[test]
+    fn title_page_keys() {
+        let (fm, _) = extract("---\nsubtitle: A study\ndate: 2026-03-04\n---\n").unwrap();
+        assert_eq!(fm.subtitle.as_deref(), Some("A study"));
+        assert_eq!(fm.date.as_deref(), Some("2026-03-04"));
+
+        // A bare TOML date is a datetime value, not a string.
+        let (fm, _) = extract("+++\ndate = 2026-03-04\n+++\n").unwrap();
+        assert_eq!(fm.date.as_deref(), Some("2026-03-04"));
+    }
+
     #[test]
     fn missing_close_returns_none() {
         let src = "---\ntitle: Foo\n\nstill in frontmatter";
@@ -339,6 +424,8 @@ mod tests {
             creator: Some("CLI".to_string()),
             keywords: vec!["existing".to_string()],
             language: None,
+            render_table: false,
+            table_keys: Vec::new(),
         };
         let fm = Frontmatter {
             title: Some("New".to_string()),
@@ -354,6 +441,34 @@ mod tests {
         assert_eq!(meta.keywords, vec!["fresh"]);
     }
 
+    #[test]
+    fn table_lists_the_configured_keys_in_order() {
+        let src = "---\ntitle: Report\nauthor: Jane\ndate: 2024-05-01\n---\nBody";
+        let (fm, _) = extract(src).unwrap();
+        let keys = [MetadataKey::Date, MetadataKey::Subject, MetadataKey::Title];
+        let Some(Token::Table { headers, rows, .. }) = fm.table(&keys) else {
+            panic!("expected a table");
+        };
+        assert_eq!(headers.len(), 2);
+        let cells: Vec<(String, String)> = rows
+            .iter()
+            .map(|r| {
+                (
+                    Token::plain_text_of(&r[0].content),
+                    Token::plain_text_of(&r[1].content),
+                )
+            })
+            .collect();
+        assert_eq!(
+            cells,
+            vec![
+                ("Date".to_string(), "2024-05-01".to_string()),
+                ("Title".to_string(), "Report".to_string()),
+            ]
+        );
+        assert!(fm.table(&[MetadataKey::Creator]).is_none());
+    }
+
     #[test]
     fn crlf_line_endings_supported() {
         let src = "---\r\ntitle: Foo\r\n---\r\nbody";
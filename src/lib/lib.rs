@@ -106,8 +106,10 @@
 //! +---------------+     +------------------+     +--------------+
 //! ```
 
+pub mod conditional;
 pub mod config;
 mod debug;
+pub mod encoding;
 pub mod fonts;
 pub mod frontmatter;
 pub mod markdown;
@@ -119,8 +121,16 @@ use markdown::*;
 use std::error::Error;
 use std::fmt;
 
+/// Boxed underlying error carried by [`MdpError`] variants and returned
+/// from [`Error::source`].
+pub type ErrorSource = Box<dyn Error + Send + Sync + 'static>;
+
 /// Represents errors that can occur during the markdown-to-pdf conversion process.
 /// This includes both parsing failures and PDF generation issues.
+///
+/// Variants that wrap a lower-level failure (lexer, I/O, TOML) keep it
+/// in `source`, reachable through [`Error::source`]; it is not part of
+/// the `Display` output.
 #[derive(Debug)]
 pub enum MdpError {
     /// Indicates an error occurred while parsing the Markdown content.
@@ -131,12 +141,14 @@ pub enum MdpError {
         line: Option<usize>,
         column: Option<usize>,
         suggestion: Option<String>,
+        source: Option<ErrorSource>,
     },
     /// Indicates an error occurred during PDF file generation
     PdfError {
         message: String,
         path: Option<String>,
         suggestion: Option<String>,
+        source: Option<ErrorSource>,
     },
     /// Indicates a font loading error
     FontError {
@@ -145,16 +157,44 @@ pub enum MdpError {
         suggestion: String,
     },
     /// Indicates an invalid configuration
-    ConfigError { message: String, suggestion: String },
+    ConfigError {
+        message: String,
+        suggestion: String,
+        source: Option<ErrorSource>,
+    },
     /// Indicates an I/O error
     IoError {
         message: String,
         path: String,
         suggestion: String,
+        source: Option<ErrorSource>,
     },
 }
 
-impl Error for MdpError {}
+impl Error for MdpError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MdpError::ParseError { source, .. }
+            | MdpError::PdfError { source, .. }
+            | MdpError::ConfigError { source, .. }
+            | MdpError::IoError { source, .. } => source.as_deref().map(|e| e as _),
+            MdpError::FontError { .. } => None,
+        }
+    }
+}
+
+impl From<styling::ResolveError> for MdpError {
+    /// A config that failed to load or resolve. The `Display` of the
+    /// resolve error already carries the file, position and hint.
+    fn from(e: styling::ResolveError) -> Self {
+        MdpError::ConfigError {
+            message: e.to_string(),
+            suggestion: "Fix the configuration or run with --print-effective-config to inspect it"
+                .to_string(),
+            source: Some(Box::new(e)),
+        }
+    }
+}
 impl fmt::Display for MdpError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -163,6 +203,7 @@ impl fmt::Display for MdpError {
                 line,
                 column,
                 suggestion,
+                ..
             } => {
                 write!(f, "Markdown Parsing Error: {}", message)?;
                 if let (Some(l), Some(c)) = (line, column) {
@@ -179,6 +220,7 @@ impl fmt::Display for MdpError {
                 message,
                 path,
                 suggestion,
+                ..
             } => {
                 write!(f, "PDF Generation Error: {}", message)?;
                 if let Some(p) = path {
@@ -202,6 +244,7 @@ impl fmt::Display for MdpError {
             MdpError::ConfigError {
                 message,
                 suggestion,
+                ..
             } => {
                 write!(f, "Configuration Error: {}", message)?;
                 write!(f, "\nSuggestion: {}", suggestion)?;
@@ -211,6 +254,7 @@ impl fmt::Display for MdpError {
                 message,
                 path,
                 suggestion,
+                ..
             } => {
                 write!(f, "File Error: {}", message)?;
                 write!(f, "\nPath: {}", path)?;
@@ -232,6 +276,7 @@ impl MdpError {
                 "Check your Markdown syntax for unclosed brackets, quotes, or code blocks"
                     .to_string(),
             ),
+            source: None,
         }
     }
 
@@ -243,6 +288,7 @@ impl MdpError {
             suggestion: Some(
                 "Check that the output directory exists and you have write permissions".to_string(),
             ),
+            source: None,
         }
     }
 }
@@ -266,6 +312,7 @@ impl MdpError {
 /// # Errors
 /// * `MdpError::IoError` if the output directory does not exist
 /// * `MdpError::ParseError` if the Markdown itself fails to lex
+/// * `MdpError::ConfigError` if the input is over `[limits] max_input_bytes`
 /// * `MdpError::PdfError` (or another `MdpError` variant) if PDF rendering fails
 ///
 /// # Example
@@ -300,15 +347,15 @@ pub fn parse_into_file_with_style(
             message: "Output directory does not exist".to_string(),
             path: parent.display().to_string(),
             suggestion: format!("Create the directory first: mkdir -p {}", parent.display()),
+            source: None,
         });
     }
 
-    let (body, fm) = split_frontmatter(markdown);
-    let tokens = parse_markdown(body)?;
+    check_input_size(&markdown, &style)?;
+    let (body, fm) = split_frontmatter(markdown, &style.flags);
+    let mut tokens = parse_markdown(body)?;
     let mut style = style;
-    if let Some(fm) = fm {
-        fm.apply(&mut style.metadata);
-    }
+    apply_frontmatter(&mut style, fm, &mut tokens);
     render::render_to_file(tokens, style, font_config, path)
 }
 
@@ -325,7 +372,8 @@ pub fn parse_into_file_with_style(
 /// * `path` - The output file path for the generated PDF
 /// * `config` - Configuration source: `Default` (built-in theme), `File(path)` (load a
 ///   `markdown2pdfrc.toml`-style file from disk), or `Embedded(toml_str)` (an in-memory TOML string)
-/// * `font_config` - Font overrides; pass `None` to auto-detect a system Unicode font
+/// * `font_config` - Font overrides; pass `None` to use the config's `[fonts]` section, or
+///   auto-detect a system Unicode font without one
 ///
 /// # Returns
 /// * `Ok(())` on successful PDF generation and save
@@ -334,6 +382,7 @@ pub fn parse_into_file_with_style(
 /// # Errors
 /// * `MdpError::IoError` if the output directory does not exist
 /// * `MdpError::ParseError` if the Markdown itself fails to lex
+/// * `MdpError::ConfigError` if the input is over `[limits] max_input_bytes`
 /// * `MdpError::PdfError` (or another `MdpError` variant) if PDF rendering fails
 ///
 /// # Example
@@ -370,7 +419,107 @@ pub fn parse_into_file(
     config: config::ConfigSource,
     font_config: Option<&fonts::FontConfig>,
 ) -> Result<(), MdpError> {
-    let path = path.as_ref();
+    parse_into_file_with_progress(markdown, path, config, font_config, |_| {})
+}
+
+/// [`parse_into_file`] that reports each step of the conversion to
+/// `progress`, for driving a progress bar on long documents.
+///
+/// Events arrive as [`render::ProgressEvent::StylingLoaded`],
+/// [`Lexing`](render::ProgressEvent::Lexing), one
+/// [`RenderingBlock`](render::ProgressEvent::RenderingBlock) per
+/// top-level block, then [`Writing`](render::ProgressEvent::Writing).
+/// A conversion that fails stops sending events at the failing step.
+///
+/// # Errors
+/// The same as [`parse_into_file`].
+///
+/// # Example
+/// ```rust,no_run
+/// use markdown2pdf::config::ConfigSource;
+/// use markdown2pdf::render::ProgressEvent;
+///
+/// markdown2pdf::parse_into_file_with_progress(
+///     "# Report\n\nBody.".to_string(),
+///     "report.pdf",
+///     ConfigSource::Default,
+///     None,
+///     |event| {
+///         if let ProgressEvent::RenderingBlock(i, total) = event {
+///             eprint!("\rblock {}/{}", i + 1, total);
+///         }
+///     },
+/// )
+/// .unwrap();
+/// ```
+pub fn parse_into_file_with_progress(
+    markdown: String,
+    path: impl AsRef<std::path::Path>,
+    config: config::ConfigSource,
+    font_config: Option<&fonts::FontConfig>,
+    mut progress: impl FnMut(render::ProgressEvent),
+) -> Result<(), MdpError> {
+    convert_into_file(
+        markdown,
+        path.as_ref(),
+        config,
+        font_config,
+        &mut progress,
+        &mut |bytes| Ok(bytes),
+    )
+}
+
+/// [`parse_into_file`] with a hook that transforms the finished PDF
+/// bytes before they are written: signing, encryption, a watermark
+/// pass, or anything else that works on a complete PDF. Whatever the
+/// hook returns is what lands at `path`.
+///
+/// # Errors
+/// The same as [`parse_into_file`], plus any error the hook returns.
+/// When the hook fails nothing is written.
+///
+/// # Example
+/// ```rust,no_run
+/// use markdown2pdf::config::ConfigSource;
+///
+/// markdown2pdf::parse_into_file_with_post_process(
+///     "# Report\n\nBody.".to_string(),
+///     "report.pdf",
+///     ConfigSource::Default,
+///     None,
+///     |mut bytes| {
+///         bytes.extend_from_slice(b"% reviewed\n");
+///         Ok(bytes)
+///     },
+/// )
+/// .unwrap();
+/// ```
+pub fn parse_into_file_with_post_process(
+    markdown: String,
+    path: impl AsRef<std::path::Path>,
+    config: config::ConfigSource,
+    font_config: Option<&fonts::FontConfig>,
+    mut post_process: impl FnMut(Vec<u8>) -> Result<Vec<u8>, MdpError>,
+) -> Result<(), MdpError> {
+    convert_into_file(
+        markdown,
+        path.as_ref(),
+        config,
+        font_config,
+        &mut |_| {},
+        &mut post_process,
+    )
+}
+
+/// Shared body of the `ConfigSource` file entry points.
+fn convert_into_file(
+    markdown: String,
+    path: &std::path::Path,
+    config: config::ConfigSource,
+    font_config: Option<&fonts::FontConfig>,
+    progress: &mut dyn FnMut(render::ProgressEvent),
+    post_process: &mut dyn FnMut(Vec<u8>) -> Result<Vec<u8>, MdpError>,
+) -> Result<(), MdpError> {
     if let Some(parent) = path.parent()
         && !parent.as_os_str().is_empty()
         && !parent.exists()
@@ -379,27 +528,100 @@ pub fn parse_into_file(
             message: "Output directory does not exist".to_string(),
             path: parent.display().to_string(),
             suggestion: format!("Create the directory first: mkdir -p {}", parent.display()),
+            source: None,
         });
     }
 
-    let (body, fm) = split_frontmatter(markdown);
-    let tokens = parse_markdown(body)?;
+    let config_fonts = fonts_from_config(font_config, &config);
+    let font_config = font_config.or(config_fonts.as_ref());
     let mut style = config::load_config_from_source(config);
-    if let Some(fm) = fm {
-        fm.apply(&mut style.metadata);
+    check_input_size(&markdown, &style)?;
+    progress(render::ProgressEvent::StylingLoaded);
+    let (body, fm) = split_frontmatter(markdown, &style.flags);
+    progress(render::ProgressEvent::Lexing);
+    let mut tokens = parse_markdown(body)?;
+    apply_frontmatter(&mut style, fm, &mut tokens);
+    render::render_to_file_with_progress(tokens, style, font_config, path, progress, post_process)
+}
+
+/// The `[fonts]` section of `config`, loaded only when the caller
+/// passed no `font_config` of their own to use instead.
+fn fonts_from_config(
+    font_config: Option<&fonts::FontConfig>,
+    config: &config::ConfigSource,
+) -> Option<fonts::FontConfig> {
+    match font_config {
+        Some(_) => None,
+        None => config::load_font_config(config.clone()),
+    }
+}
+
+/// Refuse input over `[limits].max_input_bytes` before it is lexed.
+fn check_input_size(markdown: &str, style: &styling::ResolvedStyle) -> Result<(), MdpError> {
+    match style.limits.max_input_bytes {
+        Some(max) if markdown.len() > max => Err(MdpError::ConfigError {
+            message: format!(
+                "Input is {} bytes, over the configured limit of {} bytes",
+                markdown.len(),
+                max
+            ),
+            suggestion:
+                "Split the document or raise `max_input_bytes` under [limits] in the config"
+                    .to_string(),
+            source: None,
+        }),
+        _ => Ok(()),
     }
-    render::render_to_file(tokens, style, font_config, path)
 }
 
 /// Pull the YAML/TOML frontmatter (if any) off the input. Returns
 /// `(body, frontmatter)` where `body` is the markdown stripped of the
-/// frontmatter block.
-fn split_frontmatter(markdown: String) -> (String, Option<frontmatter::Frontmatter>) {
-    if let Some((fm, body_start)) = frontmatter::extract(&markdown) {
-        let body = markdown[body_start..].to_string();
-        (body, Some(fm))
-    } else {
-        (markdown, None)
+/// frontmatter block, with the conditional regions `flags` leaves off
+/// removed.
+fn split_frontmatter(
+    markdown: String,
+    flags: &[String],
+) -> (String, Option<frontmatter::Frontmatter>) {
+    let (body, fm) = match frontmatter::extract(&markdown) {
+        Some((fm, body_start)) => (&markdown[body_start..], Some(fm)),
+        None => (markdown.as_str(), None),
+    };
+    let flags: Vec<&str> = flags.iter().map(String::as_str).collect();
+    (conditional::filter(body, &flags), fm)
+}
+
+/// Layer the frontmatter onto the resolved style: document metadata
+/// and, when `[title_page]` is on, the title page's text. A title page
+/// that still has no title falls back to `[metadata].title`, and is
+/// dropped if that is unset too. With `[metadata] render_table` the
+/// frontmatter values are also put in front of `tokens` as a table.
+fn apply_frontmatter(
+    style: &mut styling::ResolvedStyle,
+    fm: Option<frontmatter::Frontmatter>,
+    tokens: &mut Vec<markdown::Token>,
+) {
+    if let Some(fm) = fm {
+        if style.metadata.render_table
+            && let Some(table) = fm.table(&style.metadata.table_keys)
+        {
+            tokens.insert(0, table);
+        }
+        if let Some(tp) = style.title_page.as_mut() {
+            fm.apply_title_page(tp);
+        }
+        fm.apply(&mut style.metadata);
+    }
+    if let Some(tp) = style.title_page.as_mut()
+        && tp.title.is_empty()
+    {
+        tp.title = style.metadata.title.clone().unwrap_or_default();
+    }
+    if style
+        .title_page
+        .as_ref()
+        .is_some_and(|tp| tp.title.is_empty())
+    {
+        style.title_page = None;
     }
 }
 
@@ -425,6 +647,7 @@ fn parse_markdown(markdown: String) -> Result<Vec<markdown::Token>, MdpError> {
             line: Some(line),
             column: Some(column),
             suggestion: Some(suggestion),
+            source: Some(Box::new(e)),
         }
     })
 }
@@ -447,6 +670,7 @@ fn parse_markdown(markdown: String) -> Result<Vec<markdown::Token>, MdpError> {
 ///
 /// # Errors
 /// * `MdpError::ParseError` if the Markdown itself fails to lex
+/// * `MdpError::ConfigError` if the input is over `[limits] max_input_bytes`
 /// * `MdpError::PdfError` (or another `MdpError` variant) if PDF rendering fails
 ///
 /// # Example
@@ -477,13 +701,9 @@ pub fn parse_into_bytes(
     config: config::ConfigSource,
     font_config: Option<&fonts::FontConfig>,
 ) -> Result<Vec<u8>, MdpError> {
-    let (body, fm) = split_frontmatter(markdown);
-    let tokens = parse_markdown(body)?;
-    let mut style = config::load_config_from_source(config);
-    if let Some(fm) = fm {
-        fm.apply(&mut style.metadata);
-    }
-    render::render_to_bytes(tokens, style, font_config)
+    let mut options = ConversionOptions::new(config).with_validation(false);
+    options.font_config = font_config.cloned();
+    convert(ConversionInput::String(markdown), options).map(|out| out.bytes)
 }
 
 /// Variant of [`parse_into_bytes`] that takes a pre-resolved style
@@ -502,21 +722,404 @@ pub fn parse_into_bytes(
 ///
 /// # Errors
 /// * `MdpError::ParseError` if the Markdown itself fails to lex
+/// * `MdpError::ConfigError` if the input is over `[limits] max_input_bytes`
 /// * `MdpError::PdfError` (or another `MdpError` variant) if PDF rendering fails
 pub fn parse_into_bytes_with_style(
     markdown: String,
     style: styling::ResolvedStyle,
     font_config: Option<&fonts::FontConfig>,
 ) -> Result<Vec<u8>, MdpError> {
-    let (body, fm) = split_frontmatter(markdown);
-    let tokens = parse_markdown(body)?;
+    check_input_size(&markdown, &style)?;
+    let (body, fm) = split_frontmatter(markdown, &style.flags);
+    let mut tokens = parse_markdown(body)?;
     let mut style = style;
-    if let Some(fm) = fm {
-        fm.apply(&mut style.metadata);
-    }
+    apply_frontmatter(&mut style, fm, &mut tokens);
     render::render_to_bytes(tokens, style, font_config)
 }
 
+/// Variant of [`parse_into_bytes`] that tries to keep the PDF under
+/// `max_bytes`. The document is rendered once as configured; while the
+/// output is over budget it is re-rendered with progressively tighter
+/// settings:
+///
+/// 1. `[image] max_dpi` lowered to 150, then 96, then 72 (an existing,
+///    lower cap is kept).
+/// 2. `[output] embed_fonts = false`, swapping embedded faces for the
+///    builtin PDF fonts.
+///
+/// Embedded fonts are always subset already, so there is no subsetting
+/// step to tighten. The first attempt that fits is returned; when none
+/// does, the smallest one is returned with a warning logged.
+///
+/// # Errors
+/// Same as [`parse_into_bytes`].
+///
+/// # Example
+/// ```rust,no_run
+/// use markdown2pdf::config::ConfigSource;
+///
+/// let markdown = "# Photos\n\n![site](site.jpg)".to_string();
+/// let pdf = markdown2pdf::parse_into_bytes_budget(markdown, ConfigSource::Default, None, 2_000_000)
+///     .unwrap();
+/// ```
+pub fn parse_into_bytes_budget(
+    markdown: String,
+    config: config::ConfigSource,
+    font_config: Option<&fonts::FontConfig>,
+    max_bytes: usize,
+) -> Result<Vec<u8>, MdpError> {
+    let config_fonts = fonts_from_config(font_config, &config);
+    let font_config = font_config.or(config_fonts.as_ref());
+    let mut style = config::load_config_from_source(config);
+    check_input_size(&markdown, &style)?;
+    let (body, fm) = split_frontmatter(markdown, &style.flags);
+    let mut tokens = parse_markdown(body)?;
+    apply_frontmatter(&mut style, fm, &mut tokens);
+
+    let mut best = render::render_to_bytes(tokens.clone(), style.clone(), font_config)?;
+    if best.len() <= max_bytes {
+        return Ok(best);
+    }
+
+    let mut attempts = Vec::new();
+    for dpi in [150.0, 96.0, 72.0] {
+        if style.image.max_dpi.is_none_or(|current| current > dpi) {
+            let mut tighter = style.clone();
+            tighter.image.max_dpi = Some(dpi);
+            attempts.push(tighter);
+        }
+    }
+    let mut no_embed = attempts.last().unwrap_or(&style).clone();
+    no_embed.output.embed_fonts = Some(false);
+    attempts.push(no_embed);
+
+    for attempt in attempts {
+        let bytes = render::render_to_bytes(tokens.clone(), attempt, font_config)?;
+        if bytes.len() <= max_bytes {
+            return Ok(bytes);
+        }
+        if bytes.len() < best.len() {
+            best = bytes;
+        }
+    }
+    log::warn!(
+        "could not fit the PDF in {} bytes; smallest attempt is {} bytes",
+        max_bytes,
+        best.len()
+    );
+    Ok(best)
+}
+
+/// Where [`convert`] reads the document from.
+#[derive(Debug, Clone)]
+pub enum ConversionInput {
+    /// Markdown source held in memory.
+    String(String),
+    /// A markdown file on disk. Text that isn't UTF-8 is decoded the
+    /// way [`encoding::decode_markdown`] detects it.
+    File(std::path::PathBuf),
+    /// Markdown fetched over HTTP(S), with a 30 second timeout and the
+    /// same 10 MB cap as remote images.
+    #[cfg(feature = "fetch")]
+    Url(String),
+    /// Tokens already lexed, or built by hand. Frontmatter and the
+    /// markdown checks of [`validation`] need source text, so neither
+    /// applies.
+    Tokens(Vec<Token>),
+}
+
+/// Everything besides the input that [`convert`] needs.
+///
+/// # Example
+/// ```rust
+/// use markdown2pdf::ConversionOptions;
+/// use markdown2pdf::config::ConfigSource;
+/// use markdown2pdf::fonts::FontConfig;
+///
+/// let options = ConversionOptions::new(ConfigSource::Theme("github"))
+///     .with_font_config(FontConfig::new().with_default_font("Georgia"))
+///     .with_strict_config(true);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConversionOptions<'a> {
+    /// The styling config to resolve.
+    pub config: config::ConfigSource<'a>,
+    /// Font overrides; `None` uses the config's `[fonts]` section, or
+    /// auto-detects a system Unicode font without one.
+    pub font_config: Option<fonts::FontConfig>,
+    /// Run the pre-flight checks of [`validation`] and return what
+    /// they find. Default `true`.
+    pub validate: bool,
+    /// Fail with [`MdpError::ConfigError`] when the config can't be
+    /// loaded, instead of logging it and using the bundled default
+    /// theme as the other entry points do. Default `false`.
+    pub strict_config: bool,
+}
+
+impl<'a> ConversionOptions<'a> {
+    /// Options for `config` with the defaults for everything else.
+    pub fn new(config: config::ConfigSource<'a>) -> Self {
+        Self {
+            config,
+            font_config: None,
+            validate: true,
+            strict_config: false,
+        }
+    }
+
+    /// Set the font overrides.
+    pub fn with_font_config(mut self, font_config: fonts::FontConfig) -> Self {
+        self.font_config = Some(font_config);
+        self
+    }
+
+    /// Turn the pre-flight checks on or off.
+    pub fn with_validation(mut self, enabled: bool) -> Self {
+        self.validate = enabled;
+        self
+    }
+
+    /// Turn strict config loading on or off.
+    pub fn with_strict_config(mut self, enabled: bool) -> Self {
+        self.strict_config = enabled;
+        self
+    }
+}
+
+impl Default for ConversionOptions<'_> {
+    fn default() -> Self {
+        Self::new(config::ConfigSource::Default)
+    }
+}
+
+/// What [`convert`] produces.
+#[derive(Debug, Clone)]
+pub struct ConversionOutput {
+    /// The finished PDF.
+    pub bytes: Vec<u8>,
+    /// Non-fatal issues found in the input. Empty when
+    /// [`ConversionOptions::validate`] is off.
+    pub warnings: Vec<validation::ValidationWarning>,
+    /// Page, block and word counts for the rendered document.
+    pub stats: render::RenderStats,
+}
+
+/// Convert a document to PDF bytes, returning the pre-flight warnings
+/// and render statistics along with them. This is the recommended
+/// entry point: the `parse_into_*` functions cover the same pipeline
+/// one piece at a time.
+///
+/// # Errors
+/// * `MdpError::IoError` if a [`ConversionInput::File`] can't be read
+///   or a `ConversionInput::Url` can't be fetched
+/// * `MdpError::ParseError` if the Markdown itself fails to lex
+/// * `MdpError::ConfigError` if the input is over `[limits]
+///   max_input_bytes`, or with [`ConversionOptions::strict_config`] if
+///   the config fails to load
+/// * `MdpError::PdfError` (or another `MdpError` variant) if PDF rendering fails
+///
+/// # Example
+/// ```rust
+/// use markdown2pdf::{ConversionInput, ConversionOptions};
+///
+/// let output = markdown2pdf::convert(
+///     ConversionInput::String("# Report\n\nBody text.".to_string()),
+///     ConversionOptions::default(),
+/// )
+/// .unwrap();
+/// assert!(output.bytes.starts_with(b"%PDF-"));
+/// assert_eq!(output.stats.pages, 1);
+/// for warning in &output.warnings {
+///     eprintln!("{}", warning);
+/// }
+/// ```
+pub fn convert(
+    input: ConversionInput,
+    mut options: ConversionOptions,
+) -> Result<ConversionOutput, MdpError> {
+    if options.font_config.is_none() {
+        options.font_config = config::load_font_config(options.config.clone());
+    }
+    let mut style = if options.strict_config {
+        config::load_config_strict(options.config, None)?
+    } else {
+        config::load_config_from_source(options.config)
+    };
+    let font_config = options.font_config.as_ref();
+
+    // Relative links resolve against the input file's directory, so
+    // only file input gets the broken-link check.
+    let mut validation_config = validation::ValidationConfig::default();
+    let markdown = match input {
+        ConversionInput::Tokens(tokens) => {
+            let warnings = if options.validate {
+                validation::validate_conversion("", font_config, &style.fallback_fonts, None)
+            } else {
+                Vec::new()
+            };
+            return render_conversion(tokens, style, font_config, warnings);
+        }
+        ConversionInput::String(markdown) => markdown,
+        ConversionInput::File(path) => {
+            let bytes = std::fs::read(&path).map_err(|e| MdpError::IoError {
+                message: format!("Could not read the input file: {}", e),
+                path: path.display().to_string(),
+                suggestion: "Check that the file exists and is readable".to_string(),
+                source: Some(Box::new(e)),
+            })?;
+            let dir = path.parent().filter(|d| !d.as_os_str().is_empty());
+            validation_config =
+                validation_config.with_base_dir(dir.unwrap_or(std::path::Path::new(".")));
+            encoding::decode_markdown(&bytes, None)?.text
+        }
+        #[cfg(feature = "fetch")]
+        ConversionInput::Url(url) => fetch_markdown(&url)?,
+    };
+
+    check_input_size(&markdown, &style)?;
+    let warnings = if options.validate {
+        validation::validate_conversion_with(
+            &markdown,
+            font_config,
+            &style.fallback_fonts,
+            None,
+            &validation_config,
+        )
+    } else {
+        Vec::new()
+    };
+    let (body, fm) = split_frontmatter(markdown, &style.flags);
+    let mut tokens = parse_markdown(body)?;
+    apply_frontmatter(&mut style, fm, &mut tokens);
+    render_conversion(tokens, style, font_config, warnings)
+}
+
+/// Final step of [`convert`].
+fn render_conversion(
+    tokens: Vec<Token>,
+    style: styling::ResolvedStyle,
+    font_config: Option<&fonts::FontConfig>,
+    warnings: Vec<validation::ValidationWarning>,
+) -> Result<ConversionOutput, MdpError> {
+    let (bytes, stats) = render::render_with_stats(
+        tokens,
+        style,
+        font_config,
+        &render::FontCache::default(),
+        &mut |_| {},
+    )?;
+    Ok(ConversionOutput {
+        bytes,
+        warnings,
+        stats,
+    })
+}
+
+/// Fetch the markdown behind a [`ConversionInput::Url`]. The URL comes
+/// from the caller rather than from a document, so unlike remote
+/// images it may point at a private host.
+#[cfg(feature = "fetch")]
+fn fetch_markdown(url: &str) -> Result<String, MdpError> {
+    use render::net_read;
+    const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+    let network_error = |message: String| MdpError::IoError {
+        message,
+        path: url.to_string(),
+        suggestion: "Check the URL and your network connection".to_string(),
+        source: None,
+    };
+    let client = reqwest::blocking::Client::builder()
+        .connect_timeout(TIMEOUT)
+        .timeout(TIMEOUT)
+        .redirect(reqwest::redirect::Policy::limited(5))
+        .build()
+        .map_err(|e| network_error(e.to_string()))?;
+    let resp = client
+        .get(url)
+        .send()
+        .map_err(|e| network_error(e.to_string()))?;
+    if !resp.status().is_success() {
+        return Err(network_error(format!("HTTP {}", resp.status())));
+    }
+    let deadline = std::time::Instant::now() + TIMEOUT;
+    let bytes = net_read::read_capped_with_deadline(resp, deadline).map_err(network_error)?;
+    if bytes.len() as u64 > net_read::MAX_FETCH_BYTES {
+        return Err(network_error(format!(
+            "Response exceeds the {} byte cap",
+            net_read::MAX_FETCH_BYTES
+        )));
+    }
+    Ok(encoding::decode_markdown(&bytes, None)?.text)
+}
+
+/// A converter that resolves its config once and keeps font data
+/// between documents, for callers rendering many documents in a row
+/// (a server answering requests, a batch job).
+///
+/// Every [`render`](Self::render) reuses the resolved style and the
+/// font files and system font lookups of earlier calls, so only the
+/// first one reads fonts from disk or scans the font directories.
+/// Each document still subsets and embeds its own fonts. A `Renderer`
+/// is `Sync` and can be shared across threads.
+///
+/// # Example
+/// ```rust
+/// use markdown2pdf::{Renderer, config::ConfigSource};
+///
+/// let renderer = Renderer::new(ConfigSource::Default, None);
+/// for name in ["Ada", "Grace"] {
+///     let pdf = renderer.render(&format!("# Invoice\n\nBilled to {}.", name)).unwrap();
+///     assert!(pdf.starts_with(b"%PDF-"));
+/// }
+/// ```
+pub struct Renderer {
+    style: styling::ResolvedStyle,
+    font_config: Option<fonts::FontConfig>,
+    fonts: render::FontCache,
+}
+
+impl Renderer {
+    /// Load and resolve `config` now; the same fallbacks as
+    /// [`parse_into_bytes`] apply to a config that fails to load.
+    /// Without `font_config`, the config's `[fonts]` section is used.
+    pub fn new(config: config::ConfigSource, font_config: Option<fonts::FontConfig>) -> Self {
+        let font_config = font_config.or_else(|| config::load_font_config(config.clone()));
+        Self::with_style(config::load_config_from_source(config), font_config)
+    }
+
+    /// Use an already resolved style, as [`parse_into_bytes_with_style`]
+    /// does.
+    pub fn with_style(
+        style: styling::ResolvedStyle,
+        font_config: Option<fonts::FontConfig>,
+    ) -> Self {
+        Self {
+            style,
+            font_config,
+            fonts: render::FontCache::default(),
+        }
+    }
+
+    /// Convert `markdown` to PDF bytes.
+    ///
+    /// # Errors
+    /// Same as [`parse_into_bytes`].
+    pub fn render(&self, markdown: &str) -> Result<Vec<u8>, MdpError> {
+        check_input_size(markdown, &self.style)?;
+        let (body, fm) = split_frontmatter(markdown.to_string(), &self.style.flags);
+        let mut tokens = parse_markdown(body)?;
+        let mut style = self.style.clone();
+        apply_frontmatter(&mut style, fm, &mut tokens);
+        render::render_to_bytes_with_progress(
+            tokens,
+            style,
+            self.font_config.as_ref(),
+            &self.fonts,
+            &mut |_| {},
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -558,6 +1161,86 @@ mod tests {
         let _ = std::fs::remove_file("error_output.pdf");
     }
 
+    #[test]
+    fn test_bytes_errors_carry_the_real_cause() {
+        let bytes =
+            parse_into_bytes("# Ok\n".to_string(), config::ConfigSource::Default, None).unwrap();
+        assert!(bytes.starts_with(b"%PDF-"));
+
+        // A render that fails reports why, not a generic hint.
+        let long = "paragraph\n\n".repeat(400);
+        let err = parse_into_bytes(
+            long,
+            config::ConfigSource::Embedded("[limits]\nmax_pages = 1\n"),
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(err, MdpError::PdfError { .. }));
+        let text = err.to_string();
+        assert!(text.contains("limit of 1 pages"), "{text}");
+        assert!(!text.to_lowercase().contains("memory"), "{text}");
+    }
+
+    #[test]
+    fn test_progress_events_follow_the_pipeline() {
+        use render::ProgressEvent::*;
+        let path = std::env::temp_dir().join(format!("m2p_progress_{}.pdf", std::process::id()));
+        let mut events = Vec::new();
+        parse_into_file_with_progress(
+            "# Title\n\nFirst.\n\nSecond.".to_string(),
+            &path,
+            config::ConfigSource::Default,
+            None,
+            |e| events.push(e),
+        )
+        .unwrap();
+        assert!(path.exists());
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                StylingLoaded,
+                Lexing,
+                RenderingBlock(0, 3),
+                RenderingBlock(1, 3),
+                RenderingBlock(2, 3),
+                Writing,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_post_process_hook_output_is_what_gets_written() {
+        let path = std::env::temp_dir().join(format!("m2p_post_{}.pdf", std::process::id()));
+        parse_into_file_with_post_process(
+            "# Signed\n".to_string(),
+            &path,
+            config::ConfigSource::Default,
+            None,
+            |mut bytes| {
+                bytes.extend_from_slice(b"% post-processed\n");
+                Ok(bytes)
+            },
+        )
+        .unwrap();
+        let written = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(written.starts_with(b"%PDF-"));
+        assert!(written.ends_with(b"% post-processed\n"));
+
+        // A failing hook surfaces its own error and writes nothing.
+        let err = parse_into_file_with_post_process(
+            "# Refused\n".to_string(),
+            &path,
+            config::ConfigSource::Default,
+            None,
+            |_| Err(MdpError::pdf_error("signing key unavailable")),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("signing key unavailable"), "{err}");
+        assert!(!path.exists());
+    }
+
     #[test]
     fn test_invalid_output_path() {
         let markdown = "# Test".to_string();
@@ -591,6 +1274,74 @@ mod tests {
         assert!(bytes.starts_with(b"%PDF-"));
     }
 
+    #[test]
+    fn renderer_converts_many_documents() {
+        let renderer = Renderer::new(config::ConfigSource::Default, None);
+        for i in 0..10 {
+            let markdown = format!("# Document {}\n\nBody of document {}.\n", i, i);
+            let bytes = renderer.render(&markdown).expect("render");
+            let doc = lopdf::Document::load_mem(&bytes).expect("valid PDF");
+            assert_eq!(doc.get_pages().len(), 1, "document {}", i);
+        }
+    }
+
+    #[test]
+    fn convert_returns_bytes_warnings_and_stats() {
+        let markdown = "# Report\n\nThree short words.\n\n```\nunclosed fence\n".to_string();
+        let out = convert(
+            ConversionInput::String(markdown),
+            ConversionOptions::default(),
+        )
+        .expect("convert");
+        assert!(out.bytes.starts_with(b"%PDF-"));
+        assert!(
+            out.warnings
+                .iter()
+                .any(|w| w.kind == validation::WarningKind::SyntaxWarning),
+            "{:?}",
+            out.warnings
+        );
+        let doc = lopdf::Document::load_mem(&out.bytes).expect("valid PDF");
+        assert_eq!(out.stats.pages, doc.get_pages().len());
+        assert_eq!(out.stats.blocks, 3);
+        assert_eq!(out.stats.words, 4);
+    }
+
+    #[test]
+    fn convert_without_validation_returns_no_warnings() {
+        let out = convert(
+            ConversionInput::String("```\nunclosed\n".to_string()),
+            ConversionOptions::default().with_validation(false),
+        )
+        .expect("convert");
+        assert!(out.warnings.is_empty());
+        assert_eq!(out.stats.pages, 1);
+    }
+
+    #[test]
+    fn convert_with_strict_config_reports_a_bad_config() {
+        let err = convert(
+            ConversionInput::String("# Title".to_string()),
+            ConversionOptions::new(config::ConfigSource::Embedded("[page\nsize = 1"))
+                .with_strict_config(true),
+        )
+        .unwrap_err();
+        assert!(matches!(err, MdpError::ConfigError { .. }), "{err}");
+    }
+
+    #[test]
+    fn convert_accepts_tokens() {
+        let tokens = vec![Token::Heading(vec![Token::Text("Title".into())], 1, None)];
+        let out = convert(
+            ConversionInput::Tokens(tokens),
+            ConversionOptions::default(),
+        )
+        .expect("convert");
+        assert!(out.bytes.starts_with(b"%PDF-"));
+        assert_eq!(out.stats.blocks, 1);
+        assert_eq!(out.stats.words, 1);
+    }
+
     #[test]
     fn parse_error_display_includes_line_and_column_when_present() {
         let err = MdpError::ParseError {
@@ -598,6 +1349,7 @@ mod tests {
             line: Some(7),
             column: Some(3),
             suggestion: None,
+            source: None,
         };
         let s = format!("{}", err);
         assert!(
@@ -614,11 +1366,53 @@ mod tests {
             line: None,
             column: None,
             suggestion: None,
+            source: None,
         };
         let s = format!("{}", err);
         assert!(!s.contains("line"), "unexpected position in display: {}", s);
     }
 
+    #[test]
+    fn write_failure_exposes_io_error_as_source() {
+        // The output path is an existing directory, so the final write
+        // fails with an OS error rather than the up-front parent check.
+        let dir = std::env::temp_dir();
+        let err = parse_into_file(
+            "# Hi".to_string(),
+            &dir,
+            config::ConfigSource::Default,
+            None,
+        )
+        .expect_err("writing to a directory must fail");
+        let source = err.source().expect("I/O failure should be chained");
+        assert!(source.downcast_ref::<std::io::Error>().is_some());
+        assert!(
+            format!("{}", err).starts_with("PDF Generation Error: "),
+            "display unchanged: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn resolve_error_converts_to_config_error_with_source() {
+        let resolve_err = config::load_config_strict(
+            config::ConfigSource::Embedded("[paragraph]\nfont_size_pt = \"big\"\n"),
+            None,
+        )
+        .expect_err("bad TOML must fail");
+        let err = MdpError::from(resolve_err);
+        assert!(matches!(err, MdpError::ConfigError { .. }));
+        let source = err.source().expect("resolve error should be chained");
+        assert!(source.downcast_ref::<styling::ResolveError>().is_some());
+        assert!(source.source().is_some(), "TOML error reachable below it");
+    }
+
+    #[test]
+    fn errors_without_a_cause_have_no_source() {
+        assert!(MdpError::pdf_error("boom").source().is_none());
+        assert!(MdpError::parse_error("boom").source().is_none());
+    }
+
     #[test]
     fn parse_into_file_accepts_pathbuf_and_str() {
         let markdown = "# Hi".to_string();
@@ -12,8 +12,8 @@
 //! use markdown2pdf::markdown::Token;
 //!
 //! // Heading token with nested content (level 1-6 is valid)
-//! let heading = Token::Heading(vec![Token::Text("Title".to_string())], 1);
-//! assert!(matches!(heading, Token::Heading(_, 1)));
+//! let heading = Token::Heading(vec![Token::Text("Title".to_string())], 1, None);
+//! assert!(matches!(heading, Token::Heading(_, 1, _)));
 //!
 //! // Emphasis token with nested content (level 1-3 is valid)
 //! let emphasis = Token::Emphasis {
@@ -44,6 +44,7 @@
 //!         └── title: Option<String>
 
 use std::collections::HashMap;
+use std::ops::Range;
 
 /// Column alignment for a GFM table.
 ///
@@ -290,6 +291,25 @@ pub(crate) fn extract_gfm_alert_marker(
     Some((kind, raw_label, stripped))
 }
 
+/// Attributes from the `{...}` block of a fenced code info string,
+/// e.g. ```` ```rust {caption="Example 1" highlight="3-5"} ````.
+/// Unknown keys are ignored.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct CodeAttrs {
+    /// `caption`: a line of text drawn above the block.
+    pub caption: Option<String>,
+    /// `highlight`: 1-based, inclusive line ranges to emphasize, from
+    /// a comma-separated list like `"2,4-6"`.
+    pub highlight: Vec<(usize, usize)>,
+}
+
+impl CodeAttrs {
+    /// Whether the 1-based line `n` falls in a `highlight` range.
+    pub fn highlights(&self, n: usize) -> bool {
+        self.highlight.iter().any(|&(a, b)| (a..=b).contains(&n))
+    }
+}
+
 /// Internal parsing-state context — which tokens are valid where.
 /// Not exposed: consumers use [`Lexer::parse`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -306,20 +326,31 @@ pub(crate) enum ParseContext {
 /// needed to properly render the element.
 #[derive(Debug, PartialEq, Clone)]
 pub enum Token {
-    /// A heading with nested content and level (e.g., # h1, ## h2)
-    Heading(Vec<Token>, usize),
-    /// Emphasized text with configurable level (1-3) for * or _ delimiters
+    /// A heading with nested content, level (e.g., # h1, ## h2), and
+    /// the explicit anchor id from a trailing `{#id}`, if any. Headings
+    /// without one are anchored by the slug of their text; see
+    /// [`Token::heading_id`].
+    Heading(Vec<Token>, usize, Option<String>),
+    /// Emphasis from `*`/`_` delimiter runs. `level` is the number of
+    /// delimiters the pair consumed: 1 is emphasis (italic), 2 is strong
+    /// (bold). The lexer never emits more than 2 — `***x***` becomes a
+    /// level-1 node wrapping a level-2 one — but a hand-built level of 3
+    /// or more renders bold italic.
     Emphasis { level: usize, content: Vec<Token> },
-    /// Strong emphasis (bold) text using ** or __ delimiters
+    /// Strong emphasis (bold). The lexer reports `**x**` / `__x__` as
+    /// `Emphasis { level: 2 }` and never emits this variant; it is kept
+    /// for hand-built token streams and renders exactly like level 2.
     StrongEmphasis(Vec<Token>),
     /// Code construct. `block: true` for indented or fenced code blocks
     /// (rendered as `<pre><code>…</code></pre>`); `block: false` for inline
     /// code spans (`<code>…</code>`). `language` is the info-string first
     /// word for fenced blocks; empty for inline spans and indented blocks.
+    /// `attrs` holds a fenced block's `{...}` attributes.
     Code {
         language: String,
         content: String,
         block: bool,
+        attrs: CodeAttrs,
     },
     /// Block quote whose body is itself a sequence of tokens (so emphasis,
     /// links, code, etc. inside `> …` lines are properly parsed).
@@ -480,6 +511,37 @@ pub struct DefinitionListEntry {
 }
 
 impl Token {
+    /// The anchor id of a heading: the explicit `{#id}` when given,
+    /// otherwise the GitHub-style slug of its text (`section` when the
+    /// text slugs to nothing). `None` for non-heading tokens. The
+    /// renderer suffixes repeated auto slugs with `-2`, `-3`, … so
+    /// duplicates stay addressable; explicit ids are used verbatim.
+    ///
+    /// # Example
+    /// ```
+    /// use markdown2pdf::markdown::Lexer;
+    ///
+    /// let tokens = Lexer::new("# Getting Started {#start}\n## Next Steps".to_string())
+    ///     .parse()
+    ///     .unwrap();
+    /// let ids: Vec<String> = tokens.iter().filter_map(|t| t.heading_id()).collect();
+    /// assert_eq!(ids, ["start", "next-steps"]);
+    /// ```
+    pub fn heading_id(&self) -> Option<String> {
+        let Token::Heading(content, _, id) = self else {
+            return None;
+        };
+        if let Some(id) = id {
+            return Some(id.clone());
+        }
+        let slug = slugify(Token::plain_text_of(content).trim());
+        Some(if slug.is_empty() {
+            "section".to_string()
+        } else {
+            slug
+        })
+    }
+
     /// Recursively extracts all text content from a token and its nested tokens.
     /// This is useful for collecting all characters used in a document for font subsetting.
     ///
@@ -491,7 +553,7 @@ impl Token {
     /// use markdown2pdf::markdown::Token;
     ///
     /// let tokens = vec![
-    ///     Token::Heading(vec![Token::Text("Title".to_string())], 1),
+    ///     Token::Heading(vec![Token::Text("Title".to_string())], 1, None),
     ///     Token::Text("Body text with ăâîșț".to_string()),
     /// ];
     ///
@@ -515,7 +577,7 @@ impl Token {
                     result.push(*ch);
                 }
             }
-            Token::Heading(nested, _) => {
+            Token::Heading(nested, _, _) => {
                 for token in nested {
                     token.collect_text_recursive(result);
                 }
@@ -622,6 +684,110 @@ impl Token {
             }
         }
     }
+
+    /// The text a reader sees in this token, with all markup gone.
+    /// Unlike [`Token::collect_all_text`] it leaves out raw HTML,
+    /// comments and footnote markers, and separates block-level
+    /// content and line breaks with a space. Trailing whitespace is
+    /// trimmed. Heading anchors slug this
+    /// text, and the renderer uses it for the TOC and bookmark titles.
+    ///
+    /// # Example
+    /// ```
+    /// use markdown2pdf::markdown::Lexer;
+    ///
+    /// let tokens = Lexer::new("# **Bold** `code` [link](https://example.com)".to_string())
+    ///     .parse()
+    ///     .unwrap();
+    /// assert_eq!(tokens[0].plain_text(), "Bold code link");
+    /// ```
+    pub fn plain_text(&self) -> String {
+        Token::plain_text_of(std::slice::from_ref(self))
+    }
+
+    /// [`Token::plain_text`] of a token sequence.
+    pub fn plain_text_of(tokens: &[Token]) -> String {
+        let mut out = String::new();
+        for token in tokens {
+            token.push_plain_text(&mut out);
+        }
+        out.truncate(out.trim_end().len());
+        out
+    }
+
+    fn push_plain_text(&self, out: &mut String) {
+        // Block content and breaks must not glue words together.
+        fn separate(out: &mut String) {
+            if !out.is_empty() && !out.ends_with(char::is_whitespace) {
+                out.push(' ');
+            }
+        }
+        fn push_all(tokens: &[Token], out: &mut String) {
+            for token in tokens {
+                token.push_plain_text(out);
+            }
+        }
+        match self {
+            Token::Text(s) | Token::Unknown(s) => out.push_str(s),
+            Token::DelimRun { ch, count } => out.extend(std::iter::repeat_n(*ch, *count)),
+            Token::Code {
+                content,
+                block: false,
+                ..
+            } => out.push_str(content),
+            Token::Math {
+                content,
+                inline: true,
+            } => out.push_str(content),
+            Token::Emphasis { content, .. }
+            | Token::StrongEmphasis(content)
+            | Token::Strikethrough(content)
+            | Token::Highlight(content)
+            | Token::Link { content, .. }
+            | Token::Image { alt: content, .. } => push_all(content, out),
+            Token::Newline | Token::HardBreak => separate(out),
+            Token::Code { content, .. } | Token::Math { content, .. } => {
+                separate(out);
+                out.push_str(content);
+            }
+            Token::Heading(content, _, _)
+            | Token::BlockQuote(content)
+            | Token::ListItem { content, .. }
+            | Token::FootnoteDefinition { content, .. } => {
+                separate(out);
+                push_all(content, out);
+            }
+            Token::Admonition { title, body, .. } => {
+                separate(out);
+                if let Some(title) = title {
+                    push_all(title, out);
+                    separate(out);
+                }
+                push_all(body, out);
+            }
+            Token::DefinitionList { entries } => {
+                for entry in entries {
+                    for part in entry.terms.iter().chain(&entry.definitions) {
+                        separate(out);
+                        push_all(part, out);
+                    }
+                }
+            }
+            Token::Table { headers, rows, .. } => {
+                for cell in headers.iter().chain(rows.iter().flatten()) {
+                    separate(out);
+                    push_all(&cell.content, out);
+                }
+            }
+            Token::FootnoteReference(_)
+            | Token::InlineFootnote { .. }
+            | Token::HtmlComment(_)
+            | Token::HtmlInline(_)
+            | Token::HtmlBlock(_)
+            | Token::HorizontalRule
+            | Token::TableAlignment(_) => {}
+        }
+    }
 }
 
 /// Tries to decode an HTML/CommonMark entity reference starting at
@@ -739,6 +905,27 @@ fn strip_leading_cols(chars: &[char], from: usize, to: usize, strip_cols: usize)
     out
 }
 
+/// Splits a trailing Pandoc-style `{#id}` attribute off heading text.
+/// The attribute must be the whole text or follow whitespace, and the
+/// id must be non-empty `[A-Za-z0-9_:.-]`; anything else is left in
+/// the text as written.
+fn split_heading_id(text: &str) -> (&str, Option<String>) {
+    let trimmed = text.trim_end_matches([' ', '\t']);
+    let Some(open) = trimmed.rfind("{#").filter(|_| trimmed.ends_with('}')) else {
+        return (text, None);
+    };
+    let id = &trimmed[open + 2..trimmed.len() - 1];
+    let before = &trimmed[..open];
+    let valid_id = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'));
+    if !valid_id || !(before.is_empty() || before.ends_with([' ', '\t'])) {
+        return (text, None);
+    }
+    (before.trim_end_matches([' ', '\t']), Some(id.to_string()))
+}
+
 /// Strips the optional closing `#` sequence from an ATX heading line per
 /// CommonMark §4.2. The trailing run of unescaped `#` chars is removed, plus
 /// any whitespace that immediately preceded it. An odd-length run of `\`
@@ -786,6 +973,89 @@ fn strip_atx_trailing_hashes(line: &str) -> String {
     chars[..new_end].iter().collect()
 }
 
+/// Split a fenced code info string into its language word and the
+/// attributes of a trailing `{...}` block. The block may follow the
+/// language after a space or directly (`rust{caption=x}`); an info
+/// string that doesn't end in `}` has no attributes, and neither does
+/// one whose block isn't attribute syntax (R Markdown's `{r}`).
+fn parse_info_string(info: &str) -> (String, CodeAttrs) {
+    let info = info.trim();
+    let (head, attrs) = match info.find('{') {
+        Some(open) if info.ends_with('}') && is_attr_block(&info[open + 1..info.len() - 1]) => (
+            &info[..open],
+            parse_code_attrs(&info[open + 1..info.len() - 1]),
+        ),
+        _ => (info, CodeAttrs::default()),
+    };
+    let language = decode_escapes_and_entities(head.split_whitespace().next().unwrap_or(""));
+    (language, attrs)
+}
+
+/// Whether the inside of a `{...}` block reads as attributes: it holds
+/// a `key=value` or a `.class`, and doesn't open with a bare word the
+/// way an R Markdown / Quarto chunk names its engine (`{r echo=FALSE}`).
+fn is_attr_block(src: &str) -> bool {
+    let attr_like = |w: &str| w.contains('=') || w.starts_with('.');
+    src.split_whitespace()
+        .next()
+        .is_some_and(|first| attr_like(first) || first.starts_with('#'))
+        && src.split_whitespace().any(attr_like)
+}
+
+/// `key=value` pairs separated by whitespace. Values may be quoted
+/// with `"` or `'`; bare words (`.class`, `#id`, flags) are skipped.
+fn parse_code_attrs(src: &str) -> CodeAttrs {
+    let mut attrs = CodeAttrs::default();
+    let mut rest = src.trim_start();
+    while !rest.is_empty() {
+        let key_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = &rest[..key_end];
+        rest = &rest[key_end..];
+        if !rest.starts_with('=') {
+            rest = rest.trim_start();
+            continue;
+        }
+        rest = &rest[1..];
+        let value = match rest.chars().next() {
+            Some(q @ ('"' | '\'')) => {
+                let end = rest[1..].find(q).map_or(rest.len(), |i| i + 1);
+                let value = &rest[1..end];
+                rest = rest.get(end + 1..).unwrap_or("");
+                value
+            }
+            _ => {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let value = &rest[..end];
+                rest = &rest[end..];
+                value
+            }
+        };
+        match key {
+            "caption" => attrs.caption = Some(decode_escapes_and_entities(value)),
+            "highlight" => attrs.highlight = parse_line_ranges(value),
+            _ => {}
+        }
+        rest = rest.trim_start();
+    }
+    attrs
+}
+
+/// `"2,4-6"` → `[(2, 2), (4, 6)]`. Malformed or zero entries are
+/// dropped; a reversed range is put in order.
+fn parse_line_ranges(src: &str) -> Vec<(usize, usize)> {
+    src.split(',')
+        .filter_map(|part| {
+            let part = part.trim();
+            let (a, b) = part.split_once('-').unwrap_or((part, part));
+            let a: usize = a.trim().parse().ok()?;
+            let b: usize = b.trim().parse().ok()?;
+            (a > 0 && b > 0).then(|| (a.min(b), a.max(b)))
+        })
+        .collect()
+}
+
 fn decode_escapes_and_entities(s: &str) -> String {
     let chars: Vec<char> = s.chars().collect();
     let mut out = String::with_capacity(s.len());
@@ -810,6 +1080,38 @@ fn decode_escapes_and_entities(s: &str) -> String {
     out
 }
 
+/// `true` for the `//` label of the `[//]: # (comment)` idiom. Such a
+/// definition is a comment: it is never registered, so `[//]` in text
+/// stays literal.
+fn is_comment_label(label: &str) -> bool {
+    label.trim() == "//"
+}
+
+/// End of a `[//]: # (comment)` line starting at `chars[start]`, past
+/// its newline. The definition must fit on that one line; anything
+/// else, including a `[//]:` that isn't a valid definition, is `None`
+/// and stays in the text.
+fn comment_definition_end(chars: &[char], start: usize) -> Option<usize> {
+    let (label, _, _, end) = try_parse_definition(chars, start)?;
+    let line_end = chars[start..]
+        .iter()
+        .position(|&c| c == '\n')
+        .map_or(chars.len(), |p| start + p + 1);
+    (is_comment_label(&label) && end <= line_end).then_some(end)
+}
+
+/// Marker char and run length of a code fence (three or more backticks
+/// or tildes after up to three spaces) opening the line at `start`.
+fn fence_line(chars: &[char], start: usize) -> Option<(char, usize)> {
+    let mut p = start;
+    while p < chars.len() && chars[p] == ' ' && p - start < 3 {
+        p += 1;
+    }
+    let marker = *chars.get(p).filter(|c| matches!(c, '`' | '~'))?;
+    let run = chars[p..].iter().take_while(|&&c| c == marker).count();
+    (run >= 3).then_some((marker, run))
+}
+
 /// Tries to parse a single line as a CommonMark link reference definition:
 /// `(spaces 0-3)[label]:(spaces)url(spaces title)?(spaces)?`.
 /// Returns `(label, url, optional_title)` if the whole line matches.
@@ -1398,6 +1700,188 @@ struct EmDelim {
     can_close: bool,
 }
 
+/// GFM extended `www.` autolinks: split each `Text` run so a bare
+/// `www.example.com` becomes a `Link` to `http://www.example.com`.
+/// Runs after emphasis is resolved, so it sees the final text and can
+/// descend into emphasis and the block containers (see
+/// [`www_autolink_children`]). Link text and image alt are left alone,
+/// since links don't nest.
+fn link_www_autolinks(tokens: &mut Vec<Token>) {
+    link_www_autolinks_spanned(tokens, None);
+}
+
+/// [`link_www_autolinks`], keeping `spans` (one per token) in step.
+fn link_www_autolinks_spanned(tokens: &mut Vec<Token>, mut spans: Option<&mut Vec<Range<usize>>>) {
+    if !tokens.iter_mut().any(may_hold_www_autolink) {
+        return;
+    }
+    let old_spans = spans.as_deref_mut().map(std::mem::take);
+    let mut out = Vec::with_capacity(tokens.len());
+    let mut out_spans = Vec::new();
+    for (i, mut token) in std::mem::take(tokens).into_iter().enumerate() {
+        let span = old_spans.as_ref().map(|s| s[i].clone());
+        if let Token::Text(text) = &token {
+            if let Some(parts) = split_www_autolinks(text) {
+                if let Some(span) = span {
+                    out_spans.extend(split_span(span, &parts));
+                }
+                out.extend(parts);
+                continue;
+            }
+        } else {
+            for content in www_autolink_children(&mut token) {
+                link_www_autolinks(content);
+            }
+        }
+        out_spans.extend(span);
+        out.push(token);
+    }
+    *tokens = out;
+    if let Some(spans) = spans {
+        *spans = out_spans;
+    }
+}
+
+/// Share a text token's `span` among the `parts` it was split into,
+/// by their length in bytes, the unit spans count in. When the lengths don't add up to the span
+/// (escapes and entities shorten the text), every part gets the whole
+/// span.
+fn split_span(span: Range<usize>, parts: &[Token]) -> Vec<Range<usize>> {
+    let lens: Vec<usize> = parts
+        .iter()
+        .map(|part| match part {
+            Token::Text(text) => text.len(),
+            Token::Link { content, .. } => Token::plain_text_of(content).len(),
+            _ => 0,
+        })
+        .collect();
+    if lens.iter().sum::<usize>() != span.len() {
+        return vec![span; parts.len()];
+    }
+    let mut at = span.start;
+    lens.iter()
+        .map(|len| {
+            at += len;
+            at - len..at
+        })
+        .collect()
+}
+
+/// Cheap pre-check so text without `www.` is never rebuilt.
+fn may_hold_www_autolink(token: &mut Token) -> bool {
+    match token {
+        Token::Text(text) => text.contains("www."),
+        _ => www_autolink_children(token)
+            .into_iter()
+            .any(|content| content.iter_mut().any(may_hold_www_autolink)),
+    }
+}
+
+/// The token lists nested in `token` that `www.` autolinks reach:
+/// headings and emphasis, and the bodies of lists, quotes, tables,
+/// footnotes, admonitions and definition lists, which their own
+/// lexers have already finished.
+fn www_autolink_children(token: &mut Token) -> Vec<&mut Vec<Token>> {
+    match token {
+        Token::Heading(content, _, _)
+        | Token::Emphasis { content, .. }
+        | Token::StrongEmphasis(content)
+        | Token::Strikethrough(content)
+        | Token::Highlight(content)
+        | Token::BlockQuote(content)
+        | Token::ListItem { content, .. }
+        | Token::FootnoteDefinition { content, .. }
+        | Token::InlineFootnote { content, .. } => vec![content],
+        Token::Admonition { title, body, .. } => {
+            title.iter_mut().chain(std::iter::once(body)).collect()
+        }
+        Token::DefinitionList { entries } => entries
+            .iter_mut()
+            .flat_map(|e| e.terms.iter_mut().chain(e.definitions.iter_mut()))
+            .collect(),
+        Token::Table { headers, rows, .. } => headers
+            .iter_mut()
+            .chain(rows.iter_mut().flatten())
+            .map(|cell| &mut cell.content)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// `text` cut into `Text` and `Link` tokens around its `www.`
+/// autolinks, or `None` when it holds none. An autolink starts the
+/// text or follows whitespace, `*`, `_`, `~` or `(`.
+fn split_www_autolinks(text: &str) -> Option<Vec<Token>> {
+    let mut out = Vec::new();
+    let mut last = 0;
+    let mut search = 0;
+    while let Some(offset) = text[search..].find("www.") {
+        let start = search + offset;
+        let at_boundary = text[..start]
+            .chars()
+            .next_back()
+            .is_none_or(|c| c.is_whitespace() || matches!(c, '*' | '_' | '~' | '('));
+        let Some(len) = at_boundary
+            .then(|| www_autolink_len(&text[start..]))
+            .flatten()
+        else {
+            search = start + 4;
+            continue;
+        };
+        if start > last {
+            out.push(Token::Text(text[last..start].to_string()));
+        }
+        let body = &text[start..start + len];
+        out.push(Token::Link {
+            content: vec![Token::Text(body.to_string())],
+            url: format!("http://{}", body),
+            title: None,
+        });
+        last = start + len;
+        search = last;
+    }
+    if out.is_empty() {
+        return None;
+    }
+    if last < text.len() {
+        out.push(Token::Text(text[last..].to_string()));
+    }
+    Some(out)
+}
+
+/// Byte length of the `www.` autolink at the start of `s`, if its
+/// domain is valid. The link runs to the next whitespace or `<`, minus
+/// trailing punctuation (`?!.,:*_~'"`) and any `)` without a matching
+/// `(` inside the link, following GFM.
+fn www_autolink_len(s: &str) -> Option<usize> {
+    let mut link = &s[..s
+        .find(|c: char| c.is_whitespace() || c == '<')
+        .unwrap_or(s.len())];
+    loop {
+        let Some(last) = link.chars().next_back() else {
+            break;
+        };
+        let unbalanced_paren = last == ')' && link.matches(')').count() > link.matches('(').count();
+        if matches!(
+            last,
+            '?' | '!' | '.' | ',' | ':' | '*' | '_' | '~' | '\'' | '"'
+        ) || unbalanced_paren
+        {
+            link = &link[..link.len() - 1];
+        } else {
+            break;
+        }
+    }
+    let domain_end = link
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '.' | '-' | '_')))
+        .unwrap_or(link.len());
+    let labels: Vec<&str> = link[..domain_end].split('.').collect();
+    let valid = labels.len() >= 2
+        && labels.iter().all(|l| !l.is_empty())
+        && labels.iter().rev().take(2).all(|l| !l.contains('_'));
+    valid.then_some(link.len())
+}
+
 /// CommonMark emphasis algorithm. Scans `tokens` for delimiter-run text
 /// tokens (`Text("***")`, `Text("___")`, etc.), computes left/right
 /// flanking using neighboring tokens, and matches opener/closer pairs
@@ -1407,6 +1891,11 @@ struct EmDelim {
 /// literal `Text`. Paragraph boundaries (consecutive `Newline` tokens)
 /// split the scan so emphasis can't cross a blank line.
 fn resolve_emphasis(tokens: &mut Vec<Token>) {
+    resolve_emphasis_spanned(tokens, None);
+}
+
+/// [`resolve_emphasis`], keeping `spans` (one per token) in step.
+fn resolve_emphasis_spanned(tokens: &mut Vec<Token>, mut spans: Option<&mut Vec<Range<usize>>>) {
     // Split tokens into paragraph-bounded chunks at any pair of consecutive
     // `Newline` tokens (CommonMark: the delimiter stack resets across blank
     // lines). Build the result Vec by extending it chunk-by-chunk; doing
@@ -1415,6 +1904,10 @@ fn resolve_emphasis(tokens: &mut Vec<Token>) {
     let mut result: Vec<Token> = Vec::with_capacity(tokens.len());
     let mut chunk: Vec<Token> = Vec::new();
     let original = std::mem::take(tokens);
+    let tracked = spans.is_some();
+    let original_spans = spans.as_deref_mut().map(std::mem::take).unwrap_or_default();
+    let mut result_spans: Vec<Range<usize>> = Vec::new();
+    let mut chunk_spans: Vec<Range<usize>> = Vec::new();
     let mut i = 0;
     while i < original.len() {
         if i + 1 < original.len()
@@ -1422,27 +1915,34 @@ fn resolve_emphasis(tokens: &mut Vec<Token>) {
             && matches!(original[i + 1], Token::Newline)
         {
             if !chunk.is_empty() {
-                resolve_emphasis_chunk(&mut chunk);
+                resolve_emphasis_chunk(&mut chunk, tracked.then_some(&mut chunk_spans));
                 result.append(&mut chunk);
+                result_spans.append(&mut chunk_spans);
             }
             // Run of newlines passes through verbatim.
             while i < original.len() && matches!(original[i], Token::Newline) {
                 result.push(Token::Newline);
+                result_spans.extend(original_spans.get(i).cloned());
                 i += 1;
             }
             continue;
         }
         chunk.push(original[i].clone());
+        chunk_spans.extend(original_spans.get(i).cloned());
         i += 1;
     }
     if !chunk.is_empty() {
-        resolve_emphasis_chunk(&mut chunk);
+        resolve_emphasis_chunk(&mut chunk, tracked.then_some(&mut chunk_spans));
         result.append(&mut chunk);
+        result_spans.append(&mut chunk_spans);
     }
     *tokens = result;
+    if let Some(spans) = spans {
+        *spans = result_spans;
+    }
 }
 
-fn resolve_emphasis_chunk(tokens: &mut Vec<Token>) {
+fn resolve_emphasis_chunk(tokens: &mut Vec<Token>, mut spans: Option<&mut Vec<Range<usize>>>) {
     // Canonical CommonMark emphasis algorithm (cmark's `process_emphasis`):
     // walk closers left-to-right ONCE, maintain `openers_bottom` per
     // (delim-char, count%3, can-open-too) to short-circuit Rule 9/10
@@ -1514,6 +2014,7 @@ fn resolve_emphasis_chunk(tokens: &mut Vec<Token>) {
             }
             wrap_emphasis_pair(
                 tokens,
+                spans.as_deref_mut(),
                 opener.pos,
                 closer.pos,
                 opener.count,
@@ -1657,7 +2158,7 @@ fn last_meaningful_char(tok: &Token) -> Option<char> {
         Token::Highlight(content) => last_meaningful_in_slice(content),
         Token::Link { content, .. } => last_meaningful_in_slice(content),
         Token::Image { alt, .. } => last_meaningful_in_slice(alt),
-        Token::Heading(content, _) => last_meaningful_in_slice(content),
+        Token::Heading(content, _, _) => last_meaningful_in_slice(content),
         Token::Newline | Token::HardBreak => Some(' '),
         Token::Unknown(s) => s.chars().last(),
         _ => None,
@@ -1677,7 +2178,7 @@ fn first_meaningful_char(tok: &Token) -> Option<char> {
         Token::Highlight(content) => first_meaningful_in_slice(content),
         Token::Link { content, .. } => first_meaningful_in_slice(content),
         Token::Image { alt, .. } => first_meaningful_in_slice(alt),
-        Token::Heading(content, _) => first_meaningful_in_slice(content),
+        Token::Heading(content, _, _) => first_meaningful_in_slice(content),
         Token::Newline | Token::HardBreak => Some(' '),
         Token::Unknown(s) => s.chars().next(),
         _ => None,
@@ -1704,6 +2205,7 @@ fn first_meaningful_in_slice(slice: &[Token]) -> Option<char> {
 
 fn wrap_emphasis_pair(
     tokens: &mut Vec<Token>,
+    spans: Option<&mut Vec<Range<usize>>>,
     opener_pos: usize,
     closer_pos: usize,
     opener_count: usize,
@@ -1725,7 +2227,7 @@ fn wrap_emphasis_pair(
     // been wrapped (`**a*b*c**` keeps `*b*` as a nested em). Recurse so
     // any leftover `DelimRun` becomes either `Emphasis` or `Text`, never
     // escaping into a final `Emphasis.content` slot.
-    resolve_emphasis_chunk(&mut inside);
+    resolve_emphasis_chunk(&mut inside, None);
     let emph = Token::Emphasis {
         level: n,
         content: inside,
@@ -1745,6 +2247,23 @@ fn wrap_emphasis_pair(
         });
     }
     tokens.splice(opener_pos..closer_pos + 1, replacement);
+    if let Some(spans) = spans {
+        // The opener gives up its innermost (rightmost) `n` delimiters
+        // and the closer its leftmost `n`.
+        let opener = spans[opener_pos].clone();
+        let closer = spans[closer_pos].clone();
+        let open_split = (opener.start + opener_remaining).min(opener.end);
+        let close_split = (closer.start + n).min(closer.end);
+        let mut replacement = Vec::new();
+        if opener_remaining > 0 {
+            replacement.push(opener.start..open_split);
+        }
+        replacement.push(open_split..close_split);
+        if closer_remaining > 0 {
+            replacement.push(close_split..closer.end);
+        }
+        spans.splice(opener_pos..closer_pos + 1, replacement);
+    }
 }
 
 /// True for the 32 ASCII punctuation characters that allows
@@ -1940,6 +2459,126 @@ pub struct Lexer {
     /// unique across nested content — an inline footnote inside a list
     /// item or table cell must not collide with one in the body.
     inline_footnote_seq: std::rc::Rc<std::cell::Cell<usize>>,
+    /// Byte offset in the string given to [`Lexer::new`] of each input
+    /// char, plus one for the end. Only built when normalization (a
+    /// stripped BOM, CRLF, NUL) moved chars off their source offsets;
+    /// read by [`Lexer::parse_with_spans`].
+    source_offsets: Option<Vec<usize>>,
+}
+
+/// Iterator over a document's top-level tokens, returned by
+/// [`Lexer::tokens`]. Holds at most one paragraph-sized chunk of
+/// tokens at a time.
+pub struct Tokens<'a> {
+    lexer: &'a mut Lexer,
+    /// Tokens of the chunk still being lexed.
+    chunk: Vec<Token>,
+    /// Finished tokens waiting to be yielded.
+    ready: std::collections::VecDeque<Token>,
+    last_was_newline: bool,
+    done: bool,
+    /// Input spans parallel to `chunk` and `ready`, in chars. Only
+    /// kept for [`Lexer::parse_with_spans`].
+    spans: Option<SpanTrack>,
+}
+
+/// Span bookkeeping for [`Tokens`].
+#[derive(Default)]
+struct SpanTrack {
+    chunk: Vec<Range<usize>>,
+    ready: std::collections::VecDeque<Range<usize>>,
+    /// Span of the last construct lexed. Tokens a parser queued on the
+    /// side while lexing it share it.
+    last: Range<usize>,
+}
+
+/// Byte offset in `source` of each char [`Lexer::new`] keeps after
+/// normalizing it, plus the end: a leading BOM is skipped and CRLF
+/// becomes one newline at the CR's offset.
+fn normalized_offsets(source: &str) -> Vec<usize> {
+    let mut out = Vec::with_capacity(source.len() + 1);
+    let mut chars = source.char_indices().peekable();
+    if source.starts_with('\u{FEFF}') {
+        chars.next();
+    }
+    while let Some((at, c)) = chars.next() {
+        if c == '\r' {
+            chars.next_if(|&(_, next)| next == '\n');
+        }
+        out.push(at);
+    }
+    out.push(source.len());
+    out
+}
+
+impl Tokens<'_> {
+    /// Run the whole-chunk passes and queue the chunk for yielding.
+    fn finish_chunk(&mut self) {
+        let mut spans = self.spans.as_mut().map(|s| &mut s.chunk);
+        resolve_emphasis_spanned(&mut self.chunk, spans.as_deref_mut());
+        link_www_autolinks_spanned(&mut self.chunk, spans.as_deref_mut());
+        propagate_loose_tight(&mut self.chunk);
+        self.ready.extend(self.chunk.drain(..));
+        if let Some(s) = &mut self.spans {
+            s.ready.extend(s.chunk.drain(..));
+        }
+    }
+
+    /// The next token with its span in input chars (empty unless
+    /// spans are tracked).
+    fn next_spanned(&mut self) -> Option<Result<(Token, Range<usize>), LexerError>> {
+        loop {
+            if let Some(token) = self.ready.pop_front() {
+                let span = self.spans.as_mut().and_then(|s| s.ready.pop_front());
+                return Some(Ok((token, span.unwrap_or_default())));
+            }
+            if self.done {
+                return None;
+            }
+            if !self.lexer.has_more_input() {
+                self.done = true;
+                self.finish_chunk();
+                continue;
+            }
+            let queued = !self.lexer.pending.is_empty();
+            let start = self.lexer.position;
+            match self.lexer.next_token(ParseContext::Root) {
+                Err(e) => {
+                    self.done = true;
+                    self.chunk.clear();
+                    return Some(Err(e));
+                }
+                Ok(None) => {}
+                Ok(Some(token)) => {
+                    self.lexer.note_emitted(&token, self.last_was_newline);
+                    self.last_was_newline = matches!(token, Token::Newline);
+                    let after_blank_line = self.chunk.len() >= 2
+                        && self.chunk[self.chunk.len() - 2..]
+                            .iter()
+                            .all(|t| matches!(t, Token::Newline));
+                    if after_blank_line && !matches!(token, Token::Newline | Token::ListItem { .. })
+                    {
+                        self.finish_chunk();
+                    }
+                    if let Some(s) = &mut self.spans {
+                        if !queued {
+                            s.last = start..self.lexer.position;
+                        }
+                        s.chunk.push(s.last.clone());
+                    }
+                    self.chunk.push(token);
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = Result<Token, LexerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_spanned().map(|item| item.map(|(token, _)| token))
+    }
 }
 
 impl Lexer {
@@ -1949,6 +2588,8 @@ impl Lexer {
     /// normalized to LF up-front so the rest of the lexer only
     /// needs to reason about `\n`.
     pub fn new(input: String) -> Self {
+        let source_offsets = (input.starts_with('\u{FEFF}') || input.contains(['\r', '\u{0}']))
+            .then(|| normalized_offsets(&input));
         // Strip a single leading BOM. Without this, the BOM character
         // sits at position 0 and `is_at_line_start` reports false for
         // any non-BOM block marker that follows, so a doc starting with
@@ -1978,6 +2619,7 @@ impl Lexer {
             pending: std::collections::VecDeque::new(),
             depth: 0,
             inline_footnote_seq: std::rc::Rc::new(std::cell::Cell::new(0)),
+            source_offsets,
         }
     }
 
@@ -2005,21 +2647,126 @@ impl Lexer {
 
     /// Parses the entire input string into a sequence of tokens.
     /// Returns a Result containing either a Vec of parsed tokens or a LexerError.
+    /// Collects [`Lexer::tokens`].
     pub fn parse(&mut self) -> Result<Vec<Token>, LexerError> {
+        self.tokens().collect()
+    }
+
+    /// Lexes the input lazily, yielding top-level tokens as each
+    /// paragraph-sized chunk completes instead of building the whole
+    /// `Vec<Token>` first. A chunk ends at a blank line followed by
+    /// anything but a list item: emphasis never pairs across a blank
+    /// line and a list's loose/tight state is settled by then, so the
+    /// sequence matches [`Lexer::parse`] token for token.
+    ///
+    /// An error is yielded once and ends the iteration; tokens of the
+    /// chunk it occurred in are dropped.
+    ///
+    /// # Example
+    /// ```
+    /// use markdown2pdf::markdown::{Lexer, Token};
+    ///
+    /// let mut lexer = Lexer::new("# Title\n\nBody.\n".to_string());
+    /// let first = lexer.tokens().next().unwrap().unwrap();
+    /// assert!(matches!(first, Token::Heading(_, 1, _)));
+    /// ```
+    pub fn tokens(&mut self) -> Tokens<'_> {
         // Pre-pass: collect reference-link definitions and strip those lines
         // so the main lexer doesn't see them as paragraph text.
-        self.extract_definitions();
-        let mut tokens = self.parse_with_context(ParseContext::Root)?;
-        propagate_loose_tight(&mut tokens);
-        Ok(tokens)
+        self.extract_definitions(None);
+        Tokens {
+            lexer: self,
+            chunk: Vec::new(),
+            ready: std::collections::VecDeque::new(),
+            last_was_newline: false,
+            done: false,
+            spans: None,
+        }
+    }
+
+    /// Like [`Lexer::parse`], but pairs every top-level token with the
+    /// byte range of the source (the string given to [`Lexer::new`])
+    /// it was lexed from, for mapping output back to the document.
+    /// [`Lexer::parse`] returns the same tokens.
+    ///
+    /// A span covers the whole construct: a heading's runs from its
+    /// `#` to the end of its text, a list item or block quote takes
+    /// all its lines. Emphasis spans its delimiters. Nested tokens
+    /// carry no spans of their own.
+    ///
+    /// # Example
+    /// ```
+    /// use markdown2pdf::markdown::{Lexer, Token};
+    ///
+    /// let src = "Intro.\n\n## Setup\n";
+    /// let tokens = Lexer::new(src.to_string()).parse_with_spans().unwrap();
+    /// let (_, span) = tokens
+    ///     .iter()
+    ///     .find(|(t, _)| matches!(t, Token::Heading(..)))
+    ///     .unwrap();
+    /// assert_eq!(&src[span.clone()], "## Setup");
+    /// ```
+    pub fn parse_with_spans(&mut self) -> Result<Vec<(Token, Range<usize>)>, LexerError> {
+        let offsets = self.source_offsets.take().unwrap_or_else(|| {
+            let mut at = 0;
+            let mut offsets: Vec<usize> = self
+                .input
+                .iter()
+                .map(|c| {
+                    at += c.len_utf8();
+                    at - c.len_utf8()
+                })
+                .collect();
+            offsets.push(at);
+            offsets
+        });
+        // Definition lines come out of the input; `origin` maps each
+        // char left back to its index before that.
+        let mut origin = Vec::with_capacity(self.input.len() + 1);
+        self.extract_definitions(Some(&mut origin));
+        let byte = |i: usize| offsets[origin[i.min(origin.len() - 1)].min(offsets.len() - 1)];
+        let to_source = |span: Range<usize>| {
+            let start = byte(span.start);
+            // The end is measured from the span's last char, so lines
+            // removed right after it don't count toward it.
+            let end = match span.end.checked_sub(1) {
+                Some(last) if span.end > span.start => {
+                    offsets[(origin[last.min(origin.len() - 1)] + 1).min(offsets.len() - 1)]
+                }
+                _ => start,
+            };
+            start..end.max(start)
+        };
+        let mut tokens = Tokens {
+            lexer: self,
+            chunk: Vec::new(),
+            ready: std::collections::VecDeque::new(),
+            last_was_newline: false,
+            done: false,
+            spans: Some(SpanTrack::default()),
+        };
+        let mut out = Vec::new();
+        while let Some(item) = tokens.next_spanned() {
+            let (token, span) = item?;
+            out.push((token, to_source(span)));
+        }
+        Ok(out)
     }
 
     /// Pre-pass: scans the input line-by-line for `[label]: url "title"`
     /// definitions, removes those lines from `self.input`, and stores the
     /// result in `self.definitions` for later resolution by `parse_link` /
     /// `parse_image`. Idempotent: safe to call multiple times.
-    fn extract_definitions(&mut self) {
+    /// With `origin`, also records for every char kept its index in the
+    /// input before extraction, then one past the end.
+    fn extract_definitions(&mut self, mut origin: Option<&mut Vec<usize>>) {
         let chars = self.input.clone();
+        let mut keep = |kept: &mut Vec<char>, c: char, at: usize| {
+            kept.push(c);
+            if let Some(origin) = origin.as_deref_mut() {
+                origin.push(at);
+            }
+        };
         let mut definitions = HashMap::new();
         let mut kept: Vec<char> = Vec::with_capacity(chars.len());
         let mut i = 0usize;
@@ -2030,6 +2777,8 @@ impl Lexer {
         // BOF.
         let mut may_start_def = true;
         let mut line_start = 0usize;
+        // Marker char and length of the code fence the scan is inside.
+        let mut open_fence: Option<(char, usize)> = None;
         while i < chars.len() {
             let at_line_start = i == 0 || chars[i - 1] == '\n';
             if at_line_start {
@@ -2047,16 +2796,34 @@ impl Lexer {
                     p + 1 < chars.len() && chars[p] == '[' && chars[p + 1] == '^'
                 };
                 if footnote_skip {
-                    kept.push(chars[i]);
+                    keep(&mut kept, chars[i], i);
                     i += 1;
                     may_start_def = false;
                     continue;
                 }
+                if let Some(fence) = fence_line(&chars, i) {
+                    open_fence = match open_fence {
+                        Some((c, n)) if fence.0 == c && fence.1 >= n => None,
+                        Some(open) => Some(open),
+                        None => Some(fence),
+                    };
+                }
+                // `[//]: # (comment)` is dropped wherever a line starts
+                // with it, even inside a paragraph, and leaves the
+                // paragraph state as it was. Fenced code keeps it.
+                if open_fence.is_none()
+                    && let Some(end) = comment_definition_end(&chars, i)
+                {
+                    i = end;
+                    continue;
+                }
                 if may_start_def {
                     if let Some((label, url, title, end)) = try_parse_definition(&chars, i) {
-                        definitions
-                            .entry(normalize_label(&label))
-                            .or_insert((url, title));
+                        if !is_comment_label(&label) {
+                            definitions
+                                .entry(normalize_label(&label))
+                                .or_insert((url, title));
+                        }
                         i = end;
                         may_start_def = true;
                         continue;
@@ -2092,19 +2859,22 @@ impl Lexer {
                             let single_line = def_end <= line_end
                                 || (def_end == line_end + 1 && line_end < chars.len());
                             if single_line {
-                                definitions
-                                    .entry(normalize_label(&label))
-                                    .or_insert((url, title));
-                                for c in &chars[i..prefix_start] {
-                                    kept.push(*c);
+                                if !is_comment_label(&label) {
+                                    definitions
+                                        .entry(normalize_label(&label))
+                                        .or_insert((url, title));
                                 }
-                                for c in &chars[prefix_start..peel] {
+                                for (j, c) in chars.iter().enumerate().take(prefix_start).skip(i) {
+                                    keep(&mut kept, *c, j);
+                                }
+                                for (j, c) in chars.iter().enumerate().take(peel).skip(prefix_start)
+                                {
                                     if *c == '>' {
-                                        kept.push('>');
+                                        keep(&mut kept, '>', j);
                                     }
                                 }
                                 i = if line_end < chars.len() {
-                                    kept.push('\n');
+                                    keep(&mut kept, '\n', line_end);
                                     line_end + 1
                                 } else {
                                     line_end
@@ -2119,9 +2889,12 @@ impl Lexer {
             if chars[i] == '\n' {
                 may_start_def = is_paragraph_breaking_line_chars(&chars, line_start, i);
             }
-            kept.push(chars[i]);
+            keep(&mut kept, chars[i], i);
             i += 1;
         }
+        if let Some(origin) = origin {
+            origin.push(chars.len());
+        }
         self.input = kept;
         self.position = 0;
         self.definitions = definitions;
@@ -2139,47 +2912,10 @@ impl Lexer {
         }
         let mut tokens = Vec::new();
 
-        while self.position < self.input.len() || !self.pending.is_empty() {
+        while self.has_more_input() {
             if let Some(token) = self.next_token(ctx)? {
-                // Track whether the most recent top-level emission was a
-                // ListItem (Newline doesn't reset it) and whether it was
-                // inline paragraph text (a blank line resets both).
-                let mut newlines_in_a_row = 0;
-                match &token {
-                    Token::ListItem { .. } => {
-                        self.last_emitted_list_item = true;
-                        self.last_emitted_was_paragraph_text = false;
-                    }
-                    Token::Newline => {
-                        // Count consecutive Newlines so a blank line (≥2)
-                        // can reset paragraph state.
-                        let mut n = tokens.len();
-                        while n > 0 && matches!(tokens[n - 1], Token::Newline) {
-                            newlines_in_a_row += 1;
-                            n -= 1;
-                        }
-                        if newlines_in_a_row >= 1 {
-                            // The Newline we're about to push makes ≥2.
-                            self.last_emitted_was_paragraph_text = false;
-                        }
-                    }
-                    Token::Heading(_, _)
-                    | Token::HorizontalRule
-                    | Token::BlockQuote(_)
-                    | Token::Table { .. }
-                    | Token::HtmlComment(_) => {
-                        self.last_emitted_list_item = false;
-                        self.last_emitted_was_paragraph_text = false;
-                    }
-                    Token::Code { block: true, .. } => {
-                        self.last_emitted_list_item = false;
-                        self.last_emitted_was_paragraph_text = false;
-                    }
-                    _ => {
-                        self.last_emitted_list_item = false;
-                        self.last_emitted_was_paragraph_text = true;
-                    }
-                }
+                let after_newline = matches!(tokens.last(), Some(Token::Newline));
+                self.note_emitted(&token, after_newline);
                 tokens.push(token);
             }
         }
@@ -2188,6 +2924,46 @@ impl Lexer {
         Ok(tokens)
     }
 
+    /// True while input or queued tokens remain.
+    fn has_more_input(&self) -> bool {
+        self.position < self.input.len() || !self.pending.is_empty()
+    }
+
+    /// Track whether the most recent top-level emission was a ListItem
+    /// (Newline doesn't reset it) and whether it was inline paragraph
+    /// text (a blank line resets both). `after_newline` is whether the
+    /// previous emission was a Newline.
+    fn note_emitted(&mut self, token: &Token, after_newline: bool) {
+        match token {
+            Token::ListItem { .. } => {
+                self.last_emitted_list_item = true;
+                self.last_emitted_was_paragraph_text = false;
+            }
+            Token::Newline => {
+                if after_newline {
+                    // This Newline makes a blank line.
+                    self.last_emitted_was_paragraph_text = false;
+                }
+            }
+            Token::Heading(_, _, _)
+            | Token::HorizontalRule
+            | Token::BlockQuote(_)
+            | Token::Table { .. }
+            | Token::HtmlComment(_) => {
+                self.last_emitted_list_item = false;
+                self.last_emitted_was_paragraph_text = false;
+            }
+            Token::Code { block: true, .. } => {
+                self.last_emitted_list_item = false;
+                self.last_emitted_was_paragraph_text = false;
+            }
+            _ => {
+                self.last_emitted_list_item = false;
+                self.last_emitted_was_paragraph_text = true;
+            }
+        }
+    }
+
     /// Parse nested content until a delimiter is encountered (emphasis,
     /// link/image labels, headings, list items). This is the same-lexer
     /// recursion driver — `[`/`*` nesting re-enters here per level — so
@@ -2377,6 +3153,19 @@ impl Lexer {
             return Ok(Some(tok));
         }
 
+        // GFM tables whose rows omit the outer pipes (`a | b`). Rows
+        // that do start with `|` are dispatched from the `'|'` arm below.
+        // A line opening another block (heading, quote, fence, list
+        // item) keeps its meaning even if it holds a `|`.
+        if is_block_start
+            && allow_block_tokens(ctx)
+            && !matches!(current_char, '|' | '#' | '>' | '`' | '~')
+            && !self.line_starts_with_list_marker(self.position)
+            && self.is_table_start()
+        {
+            return Ok(Some(self.parse_table()?));
+        }
+
         let token = match current_char {
             '#' if is_block_start && allow_block_tokens(ctx) && self.is_atx_heading_start() => {
                 self.parse_heading()?
@@ -2573,11 +3362,12 @@ impl Lexer {
         }
         let raw_line: String = self.input[line_start..self.position].iter().collect();
         let stripped = strip_atx_trailing_hashes(&raw_line);
-        let mut sub = self.sub_lexer(stripped);
+        let (text, id) = split_heading_id(&stripped);
+        let mut sub = self.sub_lexer(text.to_string());
         sub.in_heading = true;
         sub.definitions = self.definitions.clone();
         let content = sub.parse_with_context(ParseContext::Inline)?;
-        Ok(Token::Heading(content, level))
+        Ok(Token::Heading(content, level, id))
     }
 
     /// Emits a `*`/`_` delimiter run as a `Token::DelimRun`. The matching
@@ -2633,12 +3423,12 @@ impl Lexer {
         }
 
         // Fenced code block. Info string spans to end of line; the
-        // *language* is the first whitespace-delimited word, with any
-        // remaining metadata discarded (we have no consumer for it).
+        // *language* is the first whitespace-delimited word, and a
+        // trailing `{...}` block carries the attributes. Any other
+        // metadata is discarded.
         self.skip_whitespace();
         let info_string = self.read_until_newline();
-        let language =
-            decode_escapes_and_entities(info_string.split_whitespace().next().unwrap_or(""));
+        let (language, attrs) = parse_info_string(&info_string);
         // Consume the opener line's newline.
         if self.position < self.input.len() && self.current_char() == '\n' {
             self.advance();
@@ -2691,6 +3481,7 @@ impl Lexer {
                             language,
                             content: body,
                             block: true,
+                            attrs,
                         });
                     }
                 }
@@ -2719,6 +3510,7 @@ impl Lexer {
             language,
             content: body,
             block: true,
+            attrs,
         })
     }
 
@@ -2791,6 +3583,7 @@ impl Lexer {
                         language: String::new(),
                         content: strip_code_span_outer_space(content),
                         block: false,
+                        attrs: CodeAttrs::default(),
                     };
                 }
                 for _ in 0..close_count {
@@ -2859,7 +3652,9 @@ impl Lexer {
     /// `parse_strikethrough`: opens with two-or-more `=`, always closes
     /// with two, and falls back to literal text when the closer is
     /// missing so an unterminated `==` (or a stray `===` line that
-    /// wasn't a Setext underline) degrades cleanly.
+    /// wasn't a Setext underline) degrades cleanly. The span must hug
+    /// its text — no whitespace just inside either delimiter — so
+    /// spaced operators like `a == b == c` stay literal.
     fn parse_highlight(&mut self) -> Result<Token, LexerError> {
         let mut level = 0;
         while self.current_char() == '=' {
@@ -2869,12 +3664,18 @@ impl Lexer {
         let after_opener = self.position;
 
         let close_level = 2;
-        let content = self.parse_nested_content(|c| c == '=', ParseContext::Inline)?;
-
         let mut found = 0usize;
-        while found < close_level && self.current_char() == '=' {
-            self.advance();
-            found += 1;
+        let mut content = Vec::new();
+        let opens = after_opener < self.input.len() && !self.input[after_opener].is_whitespace();
+        if opens {
+            content = self.parse_nested_content(|c| c == '=', ParseContext::Inline)?;
+            let close_start = self.position;
+            let hugs_closer =
+                close_start > after_opener && !self.input[close_start - 1].is_whitespace();
+            while hugs_closer && found < close_level && self.current_char() == '=' {
+                self.advance();
+                found += 1;
+            }
         }
 
         if found < close_level {
@@ -2887,7 +3688,6 @@ impl Lexer {
             return Ok(Token::Text(run));
         }
 
-        let mut content = content;
         resolve_emphasis(&mut content);
         Ok(Token::Highlight(content))
     }
@@ -3021,8 +3821,7 @@ impl Lexer {
         // the language is still the first whitespace-delimited word.
         self.skip_whitespace();
         let info_string = self.read_until_newline();
-        let language =
-            decode_escapes_and_entities(info_string.split_whitespace().next().unwrap_or(""));
+        let (language, attrs) = parse_info_string(&info_string);
         if self.position < self.input.len() && self.current_char() == '\n' {
             self.advance();
         }
@@ -3066,6 +3865,7 @@ impl Lexer {
                         language,
                         content: content_lines.join("\n"),
                         block: true,
+                        attrs,
                     });
                 }
             }
@@ -3091,6 +3891,7 @@ impl Lexer {
             language,
             content: content_lines.join("\n"),
             block: true,
+            attrs,
         })
     }
 
@@ -6005,11 +6806,12 @@ impl Lexer {
             }
         }
         let joined = content_lines.join("\n");
-        let mut sub = self.sub_lexer(joined.trim().to_string());
+        let (text, id) = split_heading_id(joined.trim());
+        let mut sub = self.sub_lexer(text.to_string());
         sub.in_heading = true;
         sub.definitions = self.definitions.clone();
         let content = sub.parse_with_context(ParseContext::Inline)?;
-        Ok(Token::Heading(content, level))
+        Ok(Token::Heading(content, level, id))
     }
 
     /// Checks if current position starts an ordered list marker (e.g.
@@ -6378,7 +7180,7 @@ impl Lexer {
             && !content.is_empty()
             && content
                 .iter()
-                .all(|t| !matches!(t, Token::HorizontalRule | Token::Heading(_, _)))
+                .all(|t| !matches!(t, Token::HorizontalRule | Token::Heading(_, _, _)))
         {
             let next_line_start = self.position;
             let mut p = next_line_start;
@@ -6407,7 +7209,7 @@ impl Lexer {
                 if run_len >= 1 && ends_line {
                     let level = if underline_char == '=' { 1 } else { 2 };
                     let inner = std::mem::take(&mut content);
-                    content.push(Token::Heading(inner, level));
+                    content.push(Token::Heading(inner, level, None));
                     self.position = if tail < self.input.len() {
                         tail + 1
                     } else {
@@ -6687,11 +7489,14 @@ impl Lexer {
     }
 
     /// Checks if the current position is the start of a table: the
-    /// line after the current one must contain a `-` (the delimiter
-    /// row). Scans indices directly — collecting the remaining input
-    /// into a `String` here was O(n) per call and, since the
-    /// dispatcher calls this for every line-leading `|`, O(n²) on
-    /// pipe-heavy input.
+    /// current line must contain a `|` and the next line must be a
+    /// GFM delimiter row (see [`Self::is_delimiter_row`]). Outer pipes
+    /// are optional, but a header without a leading `|` must have as
+    /// many cells as the delimiter row, so prose that happens to hold
+    /// a stray `|` isn't taken for a table. Only the two candidate
+    /// lines are scanned — collecting the remaining input here was
+    /// O(n) per call and, since the dispatcher calls this at every
+    /// block start, O(n²) on long input.
     fn is_table_start(&self) -> bool {
         let n = self.input.len();
         let mut i = self.position;
@@ -6701,14 +7506,37 @@ impl Lexer {
         if i >= n {
             return false; // no newline ⇒ no delimiter row
         }
+        let header = &self.input[self.position..i];
+        if !header.contains(&'|') {
+            return false;
+        }
         let mut j = i + 1;
         while j < n && self.input[j] != '\n' {
-            if self.input[j] == '-' {
-                return true;
-            }
             j += 1;
         }
-        false
+        let delim: String = self.input[i + 1..j].iter().collect();
+        let Some(columns) = Self::is_delimiter_row(&delim) else {
+            return false;
+        };
+        let header: String = header.iter().collect();
+        header.trim_start().starts_with('|') || Self::split_table_line(&header).len() == columns
+    }
+
+    /// Returns the column count if `line` is a GFM delimiter row: one
+    /// or more `|`-separated cells, each a run of `-` with an optional
+    /// `:` on either side, and at least one `|` somewhere (a bare `---`
+    /// is a thematic break or setext underline, never a table).
+    fn is_delimiter_row(line: &str) -> Option<usize> {
+        if !line.contains('|') {
+            return None;
+        }
+        let cells = Self::split_table_line(line);
+        let valid = cells.iter().all(|cell| {
+            let inner = cell.strip_prefix(':').unwrap_or(cell);
+            let inner = inner.strip_suffix(':').unwrap_or(inner);
+            !inner.is_empty() && inner.chars().all(|c| c == '-')
+        });
+        valid.then_some(cells.len())
     }
 
     /// Parses a table, handling column alignment
@@ -7114,6 +7942,7 @@ impl Lexer {
             language: String::new(),
             content: content.trim_matches('\n').to_string(),
             block: true,
+            attrs: CodeAttrs::default(),
         }
     }
 
@@ -0,0 +1,238 @@
+//! Diagram rendering for fenced `mermaid` / `dot` blocks (`diagrams`
+//! feature).
+//!
+//! Runs after lowering: every [`Block::Code`] whose language names a
+//! diagram dialect is handed to the matching command-line tool —
+//! `mmdc` (mermaid-cli) or Graphviz `dot` — and replaced with a
+//! [`Block::Diagram`] carrying the PNG it produced. The layout engine
+//! embeds that through the same path as a local image.
+//!
+//! Nothing here is fatal. A missing tool, a non-zero exit, a timeout
+//! or an unreadable output file logs a warning and leaves the block as
+//! ordinary code, so the source text still reaches the PDF.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use super::ir::Block;
+
+/// How long one tool invocation may run before it is killed. `mmdc`
+/// boots a headless browser, so this is generous.
+const TOOL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Executables used to render each dialect. Resolved through `PATH`
+/// unless given as a path.
+#[derive(Debug, Clone)]
+pub(crate) struct DiagramTools {
+    pub mmdc: PathBuf,
+    pub dot: PathBuf,
+}
+
+impl Default for DiagramTools {
+    fn default() -> Self {
+        Self {
+            mmdc: PathBuf::from("mmdc"),
+            dot: PathBuf::from("dot"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dialect {
+    Mermaid,
+    Dot,
+}
+
+impl Dialect {
+    fn of(language: &str) -> Option<Self> {
+        match language.trim().to_ascii_lowercase().as_str() {
+            "mermaid" | "mmd" => Some(Dialect::Mermaid),
+            "dot" | "graphviz" => Some(Dialect::Dot),
+            _ => None,
+        }
+    }
+}
+
+/// Replace diagram code blocks in `blocks` with rendered diagrams,
+/// using the default tools from `PATH`.
+pub(crate) fn render_diagrams(blocks: &mut [Block]) {
+    render_diagrams_with(blocks, &DiagramTools::default());
+}
+
+pub(crate) fn render_diagrams_with(blocks: &mut [Block], tools: &DiagramTools) {
+    for block in blocks {
+        match block {
+            Block::Code {
+                lines, language, ..
+            } => {
+                let Some(dialect) = Dialect::of(language) else {
+                    continue;
+                };
+                let source = lines.join("\n");
+                match run_tool(dialect, &source, tools) {
+                    Ok(png) => {
+                        *block = Block::Diagram {
+                            png,
+                            lines: std::mem::take(lines),
+                        };
+                    }
+                    Err(e) => {
+                        log::warn!("{} diagram rendered as code: {}", language.trim(), e);
+                    }
+                }
+            }
+            Block::List { entries } => {
+                for entry in entries {
+                    render_diagrams_with(&mut entry.children, tools);
+                }
+            }
+            Block::Quote { body } | Block::Admonition { body, .. } => {
+                render_diagrams_with(body, tools)
+            }
+            Block::DefinitionList { entries } => {
+                for entry in entries {
+                    for def in &mut entry.definitions {
+                        render_diagrams_with(def, tools);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Write `source` to a scratch directory, run the dialect's tool on
+/// it and return the PNG bytes it wrote.
+fn run_tool(dialect: Dialect, source: &str, tools: &DiagramTools) -> Result<Vec<u8>, String> {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "markdown2pdf-diagram-{}-{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let result = run_tool_in(dialect, source, tools, &dir);
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+fn run_tool_in(
+    dialect: Dialect,
+    source: &str,
+    tools: &DiagramTools,
+    dir: &Path,
+) -> Result<Vec<u8>, String> {
+    let output = dir.join("out.png");
+    let mut command = match dialect {
+        Dialect::Mermaid => {
+            let input = dir.join("in.mmd");
+            std::fs::write(&input, source).map_err(|e| e.to_string())?;
+            let mut c = Command::new(&tools.mmdc);
+            c.arg("-i").arg(&input).arg("-o").arg(&output);
+            c
+        }
+        Dialect::Dot => {
+            let input = dir.join("in.dot");
+            std::fs::write(&input, source).map_err(|e| e.to_string())?;
+            let mut c = Command::new(&tools.dot);
+            c.arg("-Tpng").arg("-o").arg(&output).arg(&input);
+            c
+        }
+    };
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("could not run `{}`: {}", program, e))?;
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) => break status,
+            None if started.elapsed() > TOOL_TIMEOUT => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "`{}` timed out after {}s",
+                    program,
+                    TOOL_TIMEOUT.as_secs()
+                ));
+            }
+            None => std::thread::sleep(Duration::from_millis(20)),
+        }
+    };
+    if !status.success() {
+        return Err(format!("`{}` exited with {}", program, status));
+    }
+    std::fs::read(&output).map_err(|e| format!("`{}` wrote no PNG: {}", program, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(language: &str, lines: &[&str]) -> Block {
+        Block::Code {
+            lines: lines.iter().map(|s| s.to_string()).collect(),
+            language: language.to_string(),
+            attrs: crate::markdown::CodeAttrs::default(),
+        }
+    }
+
+    fn missing_tools() -> DiagramTools {
+        DiagramTools {
+            mmdc: PathBuf::from("markdown2pdf-test-no-such-mmdc"),
+            dot: PathBuf::from("markdown2pdf-test-no-such-dot"),
+        }
+    }
+
+    #[test]
+    fn dialect_matches_fence_languages() {
+        assert_eq!(Dialect::of("mermaid"), Some(Dialect::Mermaid));
+        assert_eq!(Dialect::of("Mermaid"), Some(Dialect::Mermaid));
+        assert_eq!(Dialect::of("dot"), Some(Dialect::Dot));
+        assert_eq!(Dialect::of("graphviz"), Some(Dialect::Dot));
+        assert_eq!(Dialect::of("rust"), None);
+        assert_eq!(Dialect::of(""), None);
+    }
+
+    #[test]
+    fn mermaid_without_tool_stays_code() {
+        let mut blocks = vec![code("mermaid", &["graph TD", "  A --> B"])];
+        render_diagrams_with(&mut blocks, &missing_tools());
+        let Block::Code {
+            lines, language, ..
+        } = &blocks[0]
+        else {
+            panic!("expected code fallback, got {:?}", blocks[0]);
+        };
+        assert_eq!(
+            lines,
+            &vec!["graph TD".to_string(), "  A --> B".to_string()]
+        );
+        assert_eq!(language, "mermaid");
+    }
+
+    #[test]
+    fn nested_dot_without_tool_stays_code() {
+        let mut blocks = vec![Block::Quote {
+            body: vec![code("dot", &["digraph { a -> b }"])],
+        }];
+        render_diagrams_with(&mut blocks, &missing_tools());
+        let Block::Quote { body } = &blocks[0] else {
+            panic!("expected quote");
+        };
+        assert!(matches!(body[0], Block::Code { .. }));
+    }
+
+    #[test]
+    fn other_languages_are_not_run() {
+        let mut blocks = vec![code("rust", &["fn main() {}"])];
+        render_diagrams_with(&mut blocks, &missing_tools());
+        assert!(matches!(&blocks[0], Block::Code { language, .. } if language == "rust"));
+    }
+}
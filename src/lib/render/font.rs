@@ -22,14 +22,17 @@
 //! engine asks [`FontSet::handle_for`] which font handle and which
 //! transliteration policy to use.
 
-use std::collections::BTreeMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, PoisonError};
 
 use printpdf::{BuiltinFont, FontId, PdfDocument, PdfFontHandle};
 use ttf_parser::Face;
 
 use super::ir::{RunFlags, VariantUsage};
-use crate::fonts::{FontConfig, FontSource, default_body_source, find_system_font};
+use crate::fonts::{
+    FontConfig, FontSource, default_body_source, find_system_font, with_emoji_fallback,
+};
 
 /// The set of built-in PDF fonts the renderer can fall back to when
 /// no external Unicode font is loaded. Body / emphasis runs map to a
@@ -133,7 +136,7 @@ impl VariantMetrics {
         }
     }
 
-    /// Advance width of `text` at `font_size_pt`, in points.
+    /// Design units per em of this variant's metrics.
     #[allow(dead_code)]
     pub fn units_per_em(&self) -> u16 {
         self.units_per_em
@@ -254,6 +257,16 @@ impl ExternalFont {
         self.advance_by_codepoint.contains_key(&(c as u32))
     }
 
+    /// `true` when every loaded glyph with a width has the same
+    /// advance. Zero-width glyphs (combining marks) are ignored.
+    pub(crate) fn is_monospaced(&self) -> bool {
+        let mut widths = self.advance_by_codepoint.values().filter(|w| **w > 0);
+        let Some(first) = widths.next() else {
+            return true;
+        };
+        widths.all(|w| w == first)
+    }
+
     /// The original (pre-subset) font file bytes.
     pub(crate) fn source_bytes(&self) -> &[u8] {
         &self.source_bytes
@@ -278,12 +291,62 @@ pub struct FontSet {
     /// `[code_inline].font_family` is configured; otherwise inline-code
     /// runs fall through to `external_code`, then to builtin Courier.
     pub external_code_inline: ExternalFamily,
+    /// `[fonts.map] heading` family. Loaded only when it names a font
+    /// other than the body's; otherwise heading runs use
+    /// `external_body`.
+    pub external_heading: ExternalFamily,
+    /// `[fonts.map] blockquote` family, loaded on the same terms as
+    /// `external_heading`.
+    pub external_blockquote: ExternalFamily,
     /// Ordered fallback fonts consulted when the primary body / code
     /// font does not cover a codepoint. Regular weight only — fallbacks
     /// are loaded once per family and reused for every flag combination.
     pub fallbacks: Vec<ExternalFont>,
 }
 
+/// Font files and system font lookups kept across renders. A
+/// [`crate::Renderer`] holds one for its lifetime, so each face is
+/// read from disk and each font name searched for once; a one-shot
+/// render starts from an empty cache. Subsetting and embedding still
+/// happen per document, since they depend on the characters it uses.
+#[derive(Default)]
+pub struct FontCache {
+    files: Mutex<HashMap<PathBuf, Option<Vec<u8>>>>,
+    system: Mutex<HashMap<String, Option<PathBuf>>>,
+    body_default: OnceLock<Option<FontSource>>,
+    monospace_default: OnceLock<Option<FontSource>>,
+}
+
+impl FontCache {
+    /// The bytes of the font file at `path`, read on first use.
+    fn read(&self, path: &Path) -> Option<Vec<u8>> {
+        let mut files = self.files.lock().unwrap_or_else(PoisonError::into_inner);
+        files
+            .entry(path.to_path_buf())
+            .or_insert_with(|| read_font_file(path))
+            .clone()
+    }
+
+    /// [`find_system_font`] for `name`, searched on first use.
+    fn find_system(&self, name: &str) -> Option<PathBuf> {
+        let mut system = self.system.lock().unwrap_or_else(PoisonError::into_inner);
+        system
+            .entry(name.to_string())
+            .or_insert_with(|| find_system_font(name))
+            .clone()
+    }
+
+    fn default_body_source(&self) -> Option<FontSource> {
+        self.body_default.get_or_init(default_body_source).clone()
+    }
+
+    fn default_monospace_source(&self) -> Option<FontSource> {
+        self.monospace_default
+            .get_or_init(default_monospace_source)
+            .clone()
+    }
+}
+
 /// Up to four weight slots for an external font family.
 #[derive(Default)]
 pub struct ExternalFamily {
@@ -378,11 +441,14 @@ impl FontSet {
     /// in TOML) combined with any sources/names on `FontConfig`. Each
     /// is loaded in order, regular weight only; consumed by
     /// [`FontSet::split_for_emit`] when the primary lacks a glyph.
+    ///
+    /// Font files and system font lookups go through `cache`.
     pub fn load(
         font_config: Option<&FontConfig>,
         used_codepoints: &[char],
         usage: VariantUsage,
         doc: &mut PdfDocument,
+        cache: &FontCache,
     ) -> Self {
         let builtin = FontMetricsCache::new();
         let body_variants = BodyVariantNeed {
@@ -427,17 +493,18 @@ impl FontSet {
         let user_src = font_config.and_then(default_source);
         let opted_into_builtin = matches!(&user_src, Some(FontSource::Builtin(_)));
         let external_body =
-            load_external_family(user_src, used_codepoints, body_variants, doc, true)
+            load_external_family(user_src, used_codepoints, body_variants, doc, true, cache)
                 .or_else(|| {
                     if opted_into_builtin {
                         return None;
                     }
                     load_external_family(
-                        default_body_source(),
+                        cache.default_body_source(),
                         used_codepoints,
                         body_variants,
                         doc,
                         true,
+                        cache,
                     )
                 })
                 .unwrap_or_default();
@@ -451,22 +518,62 @@ impl FontSet {
         let user_code_src = font_config.and_then(code_source);
         let code_src = match user_code_src {
             Some(src) => Some(src),
-            None if external_body.is_loaded() => default_monospace_source(),
+            None if external_body.is_loaded() => cache.default_monospace_source(),
             None => None,
         };
         let external_code =
-            load_external_family(code_src, used_codepoints, code_variants, doc, false)
+            load_external_family(code_src, used_codepoints, code_variants, doc, false, cache)
                 .unwrap_or_default();
-        let fallbacks = load_fallbacks(font_config, used_codepoints, doc);
+        // Code blocks line columns up by character count, which only
+        // holds when every glyph has the same advance.
+        if let Some(code) = &external_code.regular
+            && !code.is_monospaced()
+        {
+            log::warn!("code font is not monospaced; aligned code blocks may drift");
+        }
+        let external_heading = load_element_family(
+            font_config.and_then(|c| c.heading_font.as_deref()),
+            font_config,
+            used_codepoints,
+            body_variants,
+            doc,
+            cache,
+        );
+        let external_blockquote = load_element_family(
+            font_config.and_then(|c| c.blockquote_font.as_deref()),
+            font_config,
+            used_codepoints,
+            body_variants,
+            doc,
+            cache,
+        );
+        let fallbacks = load_fallbacks(font_config, used_codepoints, doc, cache);
         Self {
             builtin,
             external_body,
             external_code,
             external_code_inline: ExternalFamily::default(),
+            external_heading,
+            external_blockquote,
             fallbacks,
         }
     }
 
+    /// A font set with no external fonts at all: every run renders in
+    /// the base-14 Helvetica / Courier faces and nothing is embedded.
+    /// Used for `[output] embed_fonts = false`.
+    pub fn builtin_only() -> Self {
+        Self {
+            builtin: FontMetricsCache::new(),
+            external_body: ExternalFamily::default(),
+            external_code: ExternalFamily::default(),
+            external_code_inline: ExternalFamily::default(),
+            external_heading: ExternalFamily::default(),
+            external_blockquote: ExternalFamily::default(),
+            fallbacks: Vec::new(),
+        }
+    }
+
     /// Build the font set with an additional list of fallback sources
     /// (resolved from `[defaults].fallback_fonts` in the styling
     /// config) and an optional dedicated inline-code font family
@@ -482,8 +589,9 @@ impl FontSet {
         used_codepoints: &[char],
         usage: VariantUsage,
         doc: &mut PdfDocument,
+        cache: &FontCache,
     ) -> Self {
-        let mut set = Self::load(font_config, used_codepoints, usage, doc);
+        let mut set = Self::load(font_config, used_codepoints, usage, doc, cache);
         if let Some(name) = code_inline_name {
             let inline_variants = BodyVariantNeed {
                 bold: usage.inline_code_bold || usage.inline_code_bold_italic,
@@ -496,12 +604,28 @@ impl FontSet {
                 inline_variants,
                 doc,
                 false,
+                cache,
             )
             .unwrap_or_default();
         }
-        for name in style_fallback_names {
+        // The emoji check sees the whole chain so a FontConfig-listed
+        // emoji font isn't followed by a second one.
+        let mut style_names = style_fallback_names.to_vec();
+        if font_config.is_some_and(|c| c.auto_emoji) {
+            let mut chain = font_config
+                .map(|c| c.fallback_fonts.clone())
+                .unwrap_or_default();
+            chain.extend(style_names.iter().cloned());
+            if let Some(emoji) = with_emoji_fallback(&chain, used_codepoints)
+                .into_iter()
+                .nth(chain.len())
+            {
+                style_names.push(emoji);
+            }
+        }
+        for name in &style_names {
             let src = name_to_external_source(name);
-            let Some((_, bytes)) = resolve_regular(src) else {
+            let Some((_, bytes)) = resolve_regular(src, cache) else {
                 continue;
             };
             if let Some(font) = parse_and_register(bytes, "fallback", used_codepoints, doc, true) {
@@ -530,11 +654,20 @@ impl FontSet {
                     font: ext,
                 };
             }
-        } else if let Some(ext) = self.external_body.pick(flags) {
-            return FontResolution::External {
-                handle: PdfFontHandle::External(ext.font_id.clone()),
-                font: ext,
+        } else {
+            let element = if flags.heading {
+                self.external_heading.pick(flags)
+            } else if flags.blockquote {
+                self.external_blockquote.pick(flags)
+            } else {
+                None
             };
+            if let Some(ext) = element.or_else(|| self.external_body.pick(flags)) {
+                return FontResolution::External {
+                    handle: PdfFontHandle::External(ext.font_id.clone()),
+                    font: ext,
+                };
+            }
         }
         let variant = FontVariant::for_flags(flags);
         FontResolution::Builtin {
@@ -559,6 +692,15 @@ impl FontSet {
             .sum()
     }
 
+    /// Advance width of the single char `c` at `size_pt`. Measurement
+    /// is additive, so summing this over a string's chars equals
+    /// [`FontSet::measure`] on the whole string — callers growing a
+    /// prefix one char at a time can keep a running total instead of
+    /// re-measuring the prefix.
+    pub fn char_advance(&self, flags: RunFlags, c: char, size_pt: f32) -> f32 {
+        self.measure(flags, c.encode_utf8(&mut [0u8; 4]), size_pt)
+    }
+
     /// `true` if the *primary* font for `flags` is a built-in and
     /// emitted text has to pass through `to_win1252`. Note: even when
     /// this returns `true`, individual codepoints may still emit via
@@ -695,6 +837,7 @@ fn load_fallbacks(
     font_config: Option<&FontConfig>,
     used_codepoints: &[char],
     doc: &mut PdfDocument,
+    cache: &FontCache,
 ) -> Vec<ExternalFont> {
     let mut out = Vec::new();
     let Some(cfg) = font_config else {
@@ -708,7 +851,7 @@ fn load_fallbacks(
             .map(|n| name_to_external_source(n)),
     );
     for src in sources {
-        let Some((_, bytes)) = resolve_regular(src) else {
+        let Some((_, bytes)) = resolve_regular(src, cache) else {
             continue;
         };
         if let Some(font) = parse_and_register(bytes, "fallback", used_codepoints, doc, true) {
@@ -718,6 +861,41 @@ fn load_fallbacks(
     out
 }
 
+/// `font_config` with any body / code font that would fall back to a
+/// built-in face swapped for an installed system font, for
+/// `[output] embed_fonts = true`. A face that already resolves to a
+/// file is kept. When no system font is found the slot is left as is
+/// and renders built-in after all.
+pub fn force_embedded(font_config: Option<&FontConfig>) -> FontConfig {
+    let mut cfg = font_config.cloned().unwrap_or_default();
+    let body = font_config.and_then(default_source);
+    if !body.as_ref().is_some_and(resolves_to_file)
+        && let Some(src) = default_body_source()
+    {
+        cfg.default_font = None;
+        cfg.default_font_source = Some(src);
+    }
+    let code = font_config.and_then(code_source);
+    if !code.as_ref().is_some_and(resolves_to_file)
+        && let Some(src) = default_monospace_source()
+    {
+        cfg.code_font = None;
+        cfg.code_font_source = Some(src);
+    }
+    cfg
+}
+
+/// True when `src` names font bytes the external loader can read,
+/// rather than a base-14 face or a system font that isn't installed.
+fn resolves_to_file(src: &FontSource) -> bool {
+    match src {
+        FontSource::Builtin(_) => false,
+        FontSource::Bytes(_) => true,
+        FontSource::File(path) => path.is_file(),
+        FontSource::System(name) => find_system_font(name).is_some(),
+    }
+}
+
 fn default_source(c: &FontConfig) -> Option<FontSource> {
     if let Some(src) = c.default_font_source.clone() {
         return Some(src);
@@ -775,22 +953,52 @@ fn name_to_external_source(name: &str) -> FontSource {
     FontSource::System(name.to_string())
 }
 
+/// Load the family `[fonts.map]` names for one element. Empty when
+/// `name` is unset or is the body font, so that face isn't embedded a
+/// second time; the element's runs then fall through to the body
+/// family.
+fn load_element_family(
+    name: Option<&str>,
+    font_config: Option<&FontConfig>,
+    used_codepoints: &[char],
+    need: BodyVariantNeed,
+    doc: &mut PdfDocument,
+    cache: &FontCache,
+) -> ExternalFamily {
+    let Some(name) = name else {
+        return ExternalFamily::default();
+    };
+    let body = font_config.and_then(|c| c.default_font.as_deref());
+    if body.is_some_and(|b| b.eq_ignore_ascii_case(name)) {
+        return ExternalFamily::default();
+    }
+    load_external_family(
+        Some(name_to_external_source(name)),
+        used_codepoints,
+        need,
+        doc,
+        false,
+        cache,
+    )
+    .unwrap_or_default()
+}
+
 /// Resolve a `FontSource` to a regular-weight path (if any) and the
 /// font bytes. The path is what we use for sibling-variant discovery.
-fn resolve_regular(source: FontSource) -> Option<(Option<PathBuf>, Vec<u8>)> {
+fn resolve_regular(source: FontSource, cache: &FontCache) -> Option<(Option<PathBuf>, Vec<u8>)> {
     match source {
         FontSource::Builtin(_) => None,
         FontSource::Bytes(b) => Some((None, b.to_vec())),
         FontSource::File(path) => {
-            let bytes = read_font_file(&path)?;
+            let bytes = cache.read(&path)?;
             Some((Some(path), bytes))
         }
         FontSource::System(name) => {
-            let path = find_system_font(&name).or_else(|| {
+            let path = cache.find_system(&name).or_else(|| {
                 log::warn!("could not locate system font {:?}", name);
                 None
             })?;
-            let bytes = read_font_file(&path)?;
+            let bytes = cache.read(&path)?;
             Some((Some(path), bytes))
         }
     }
@@ -818,9 +1026,10 @@ fn load_external_family(
     need: BodyVariantNeed,
     doc: &mut PdfDocument,
     retain_regular: bool,
+    cache: &FontCache,
 ) -> Option<ExternalFamily> {
     let source = source?;
-    let (anchor_path, regular_bytes) = resolve_regular(source)?;
+    let (anchor_path, regular_bytes) = resolve_regular(source, cache)?;
     let regular = parse_and_register(
         regular_bytes,
         "regular",
@@ -835,21 +1044,17 @@ fn load_external_family(
     };
 
     if let Some(path) = anchor_path {
-        let candidates: &[(VariantKind, &[&str], bool)] = &[
-            (VariantKind::Bold, &["Bold"], need.bold),
-            (VariantKind::Italic, &["Italic", "Oblique"], need.italic),
-            (
-                VariantKind::BoldItalic,
-                &["Bold Italic", "BoldItalic", "Bold-Italic", "BoldOblique"],
-                need.bold_italic,
-            ),
-        ];
-        for (kind, names, wanted) in candidates {
+        for kind in VariantKind::ALL {
+            let wanted = match kind {
+                VariantKind::Bold => need.bold,
+                VariantKind::Italic => need.italic,
+                VariantKind::BoldItalic => need.bold_italic,
+            };
             if !wanted {
                 continue;
             }
-            if let Some(variant_path) = find_variant_path(&path, names)
-                && let Some(bytes) = read_font_file(&variant_path)
+            if let Some(variant_path) = find_variant_path(&path, kind.file_names())
+                && let Some(bytes) = cache.read(&variant_path)
                 && let Some(parsed) =
                     parse_and_register(bytes, kind.label(), used_codepoints, doc, false)
             {
@@ -877,6 +1082,21 @@ enum VariantKind {
 }
 
 impl VariantKind {
+    const ALL: [VariantKind; 3] = [
+        VariantKind::Bold,
+        VariantKind::Italic,
+        VariantKind::BoldItalic,
+    ];
+
+    /// Suffixes searched for after the regular file's stem.
+    fn file_names(self) -> &'static [&'static str] {
+        match self {
+            VariantKind::Bold => &["Bold"],
+            VariantKind::Italic => &["Italic", "Oblique"],
+            VariantKind::BoldItalic => &["Bold Italic", "BoldItalic", "Bold-Italic", "BoldOblique"],
+        }
+    }
+
     fn label(self) -> &'static str {
         match self {
             VariantKind::Bold => "bold",
@@ -886,6 +1106,59 @@ impl VariantKind {
     }
 }
 
+/// Each external font `font_config` names, paired with the weight
+/// variants ("bold", "italic", "bold-italic") that have no file beside
+/// it. [`FontSet::load`] sets text in those styles in the regular face
+/// instead. Fonts given as raw bytes have no siblings, so every variant
+/// is missing; built-in faces and fonts that can't be found are left
+/// out.
+pub(crate) fn missing_variants(font_config: &FontConfig) -> Vec<(String, Vec<&'static str>)> {
+    let sources = [
+        default_source(font_config),
+        code_source(font_config),
+        font_config
+            .heading_font
+            .as_deref()
+            .map(name_to_external_source),
+        font_config
+            .blockquote_font
+            .as_deref()
+            .map(name_to_external_source),
+    ];
+    let mut out: Vec<(String, Vec<&'static str>)> = Vec::new();
+    for src in sources.into_iter().flatten() {
+        let (name, anchor) = match src {
+            FontSource::Builtin(_) => continue,
+            FontSource::Bytes(_) => ("embedded font".to_string(), None),
+            FontSource::File(path) if path.is_file() => {
+                (path.to_string_lossy().into_owned(), Some(path))
+            }
+            FontSource::File(_) => continue,
+            FontSource::System(name) => match find_system_font(&name) {
+                Some(path) => (name, Some(path)),
+                None => continue,
+            },
+        };
+        if out.iter().any(|(seen, _)| *seen == name) {
+            continue;
+        }
+        let missing: Vec<&'static str> = VariantKind::ALL
+            .into_iter()
+            .filter(|kind| {
+                anchor
+                    .as_deref()
+                    .and_then(|path| find_variant_path(path, kind.file_names()))
+                    .is_none()
+            })
+            .map(VariantKind::label)
+            .collect();
+        if !missing.is_empty() {
+            out.push((name, missing));
+        }
+    }
+    out
+}
+
 /// Given the regular-weight font's path, return a sibling file
 /// matching one of the variant name patterns
 /// (`Foo Bold.ttf`, `Foo-Bold.ttf`, `FooBold.ttf`, plus `.otf`).
@@ -1204,7 +1477,13 @@ mod tests {
         // chunk; only the transliteration flag differs by path (built-in
         // sets it true so `to_win1252` runs; external leaves it false).
         let mut doc = PdfDocument::new("test");
-        let set = FontSet::load(None, &[], VariantUsage::default(), &mut doc);
+        let set = FontSet::load(
+            None,
+            &[],
+            VariantUsage::default(),
+            &mut doc,
+            &FontCache::default(),
+        );
         let chunks = set.split_for_emit(RunFlags::default(), "Hello", 12.0);
         assert_eq!(chunks.len(), 1);
         assert_eq!(chunks[0].text, "Hello");
@@ -1216,7 +1495,13 @@ mod tests {
     #[test]
     fn split_empty_text_returns_empty() {
         let mut doc = PdfDocument::new("test");
-        let set = FontSet::load(None, &[], VariantUsage::default(), &mut doc);
+        let set = FontSet::load(
+            None,
+            &[],
+            VariantUsage::default(),
+            &mut doc,
+            &FontCache::default(),
+        );
         let chunks = set.split_for_emit(RunFlags::default(), "", 12.0);
         assert!(chunks.is_empty());
     }
@@ -1231,7 +1516,13 @@ mod tests {
         // path (the only one we can construct without an external
         // font file in unit tests).
         let mut doc = PdfDocument::new("test");
-        let set = FontSet::load(None, &[], VariantUsage::default(), &mut doc);
+        let set = FontSet::load(
+            None,
+            &[],
+            VariantUsage::default(),
+            &mut doc,
+            &FontCache::default(),
+        );
         let cases = ["", "Hello", "Hello world", "ABCDE 12345 !?.,"];
         for text in cases {
             let direct = set.measure(RunFlags::default(), text, 10.0);
@@ -1250,6 +1541,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn measure_grows_with_text_and_scales_with_size() {
+        let mut doc = PdfDocument::new("test");
+        let set = FontSet::load(
+            None,
+            &[],
+            VariantUsage::default(),
+            &mut doc,
+            &FontCache::default(),
+        );
+        let flags = RunFlags::default();
+        assert!(set.measure(flags, "WWWW", 12.0) > set.measure(flags, "iiii", 12.0));
+        let small = set.measure(flags, "Hello", 10.0);
+        let large = set.measure(flags, "Hello", 20.0);
+        assert!((large - 2.0 * small).abs() < 1e-3, "{small} vs {large}");
+        // Per-char advances add up to the whole-string width, the
+        // invariant incremental prefix measuring relies on.
+        let summed: f32 = "Hello, world"
+            .chars()
+            .map(|c| set.char_advance(flags, c, 11.0))
+            .sum();
+        let direct = set.measure(flags, "Hello, world", 11.0);
+        assert!((direct - summed).abs() < 1e-3, "{direct} != {summed}");
+    }
+
     #[test]
     fn builtin_measure_matches_transliterated_emit() {
         // `to_win1252` rewrites a curated set of Win-1252 punctuation
@@ -1310,12 +1626,21 @@ mod tests {
             code_font: None,
             default_font_source: None,
             code_font_source: None,
+            heading_font: None,
+            blockquote_font: None,
             fallback_fonts: vec!["This_Font_Definitely_Does_Not_Exist_12345".to_string()],
             fallback_font_sources: Vec::new(),
+            auto_emoji: false,
             enable_subsetting: true,
         };
         let mut doc = PdfDocument::new("test");
-        let set = FontSet::load(Some(&cfg), &['日'], VariantUsage::default(), &mut doc);
+        let set = FontSet::load(
+            Some(&cfg),
+            &['日'],
+            VariantUsage::default(),
+            &mut doc,
+            &FontCache::default(),
+        );
         assert!(set.fallbacks.is_empty());
         // Uncovered codepoint must not panic — it routes through the
         // primary's degraded path. With the auto-detected body font
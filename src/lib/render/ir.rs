@@ -10,12 +10,30 @@
 /// A top-level block-level rendering unit.
 #[derive(Debug, Clone)]
 pub enum Block {
-    /// A heading. `level` is 1..=6.
-    Heading { level: u8, runs: Vec<InlineRun> },
+    /// A heading. `level` is 1..=6. `id` is the explicit `{#id}`
+    /// anchor; `None` anchors the heading by the slug of its text.
+    /// `text` is the source heading's [`Token::plain_text`], the name
+    /// the slug, TOC and bookmarks use.
+    ///
+    /// [`Token::plain_text`]: crate::markdown::Token::plain_text
+    Heading {
+        level: u8,
+        runs: Vec<InlineRun>,
+        id: Option<String>,
+        text: String,
+    },
     /// A paragraph of flowing text.
     Paragraph { runs: Vec<InlineRun> },
     /// A fenced or indented code block. One entry per source line.
-    Code { lines: Vec<String> },
+    /// `language` is the fence's info-string word (empty when none);
+    /// the `diagrams` pass reads it to pick `mermaid` / `dot` blocks.
+    /// `attrs` carries the fence's caption and highlighted lines.
+    Code {
+        lines: Vec<String>,
+        #[cfg_attr(not(feature = "diagrams"), allow(dead_code))]
+        language: String,
+        attrs: crate::markdown::CodeAttrs,
+    },
     /// A horizontal rule (`---`).
     HorizontalRule,
     /// A run of consecutive list items at the same level + marker
@@ -55,6 +73,13 @@ pub enum Block {
         alt: String,
         caption: Option<String>,
     },
+    /// A paragraph holding nothing but two or more images on one line.
+    /// With `[image] gallery` the images are laid out in a grid;
+    /// otherwise `runs`, the paragraph it would have been, is drawn.
+    Gallery {
+        images: Vec<GalleryImage>,
+        runs: Vec<InlineRun>,
+    },
     /// Verbatim block-level raw HTML. Rendered as a monospace block
     /// so the source stays visible. CommonMark §4.6 lets us choose
     /// whether to interpret HTML or pass it through; we pass through.
@@ -64,6 +89,18 @@ pub enum Block {
     /// flushes the current page and starts a fresh one with no
     /// other side effects.
     PageBreak,
+    /// A `<!-- numbering: STYLE [reset] -->` block. Page numbers from
+    /// the page it falls on are written in `style`, restarting at 1
+    /// when `reset` is set.
+    PageNumbering {
+        style: crate::styling::PageNumberStyle,
+        reset: bool,
+    },
+    /// Pages of an existing PDF spliced into the output at this point,
+    /// from a ```` ```pdf-include ```` fence or a standalone
+    /// `![](file.pdf)`. The renderer starts a new page here and the
+    /// pages are inserted once the document is saved.
+    PdfInclude { path: std::path::PathBuf },
     /// Collected GFM footnote definitions, rendered as a "Footnotes"
     /// section at the end of the document. Numbers are assigned in
     /// first-reference order by the lower pass.
@@ -76,6 +113,11 @@ pub enum Block {
     /// source verbatim; full mathematical typesetting is a separate,
     /// larger effort tracked independently.)
     Math { content: String },
+    /// A `mermaid` / `dot` fenced block rendered to PNG by an external
+    /// tool (`diagrams` feature). `lines` is the source, drawn as a
+    /// code block instead if the PNG fails to decode.
+    #[cfg_attr(not(feature = "diagrams"), allow(dead_code))]
+    Diagram { png: Vec<u8>, lines: Vec<String> },
 }
 
 #[derive(Debug, Clone)]
@@ -84,6 +126,15 @@ pub struct DefinitionEntry {
     pub definitions: Vec<Vec<Block>>,
 }
 
+/// One image of a [`Block::Gallery`], with the same fields as
+/// [`Block::Image`].
+#[derive(Debug, Clone)]
+pub struct GalleryImage {
+    pub path: std::path::PathBuf,
+    pub alt: String,
+    pub caption: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct FootnoteEntry {
     /// Original markdown label (e.g. `1` or `note-a`). Retained for
@@ -141,16 +192,20 @@ pub struct InlineRun {
     /// the string is the raw TeX, typeset by the math engine as one
     /// indivisible box on the text baseline.
     pub math: Option<String>,
+    /// If `Some`, this run is an inline image: the string is its path
+    /// or URL, drawn as one indivisible box the height of the text.
+    /// `text` is the italic `[image: …]` shown if it can't be loaded.
+    pub image: Option<String>,
 }
 
 impl InlineRun {
-    #[cfg_attr(not(test), allow(dead_code))]
     pub fn new(text: impl Into<String>) -> Self {
         Self {
             text: text.into(),
             flags: RunFlags::default(),
             link: None,
             math: None,
+            image: None,
         }
     }
 
@@ -161,6 +216,7 @@ impl InlineRun {
             flags,
             link,
             math: Some(tex.into()),
+            image: None,
         }
     }
 }
@@ -198,7 +254,7 @@ impl VariantUsage {
 
 fn walk_block(block: &Block, u: &mut VariantUsage) {
     match block {
-        Block::Heading { runs, .. } | Block::Paragraph { runs } => {
+        Block::Heading { runs, .. } | Block::Paragraph { runs } | Block::Gallery { runs, .. } => {
             for r in runs {
                 walk_run(r, u);
             }
@@ -250,7 +306,7 @@ fn walk_block(block: &Block, u: &mut VariantUsage) {
                 }
             }
         }
-        Block::Code { .. } | Block::Html { .. } => {
+        Block::Code { .. } | Block::Html { .. } | Block::Diagram { .. } => {
             u.mono_regular = true;
         }
         Block::FootnoteDefinitions { entries } => {
@@ -283,7 +339,65 @@ fn walk_block(block: &Block, u: &mut VariantUsage) {
             // Rendered as centered italic monospace.
             u.mono_italic = true;
         }
-        Block::HorizontalRule | Block::Image { .. } | Block::PageBreak => {}
+        Block::HorizontalRule
+        | Block::Image { .. }
+        | Block::PageBreak
+        | Block::PageNumbering { .. }
+        | Block::PdfInclude { .. } => {}
+    }
+}
+
+/// Call `f` on every inline run list in `blocks`, recursing into
+/// nested containers. Used by IR passes that rewrite runs in place.
+pub(crate) fn visit_runs_mut(blocks: &mut [Block], f: &mut impl FnMut(&mut Vec<InlineRun>)) {
+    for block in blocks {
+        match block {
+            Block::Heading { runs, .. }
+            | Block::Paragraph { runs }
+            | Block::Gallery { runs, .. } => f(runs),
+            Block::List { entries } => {
+                for entry in entries {
+                    f(&mut entry.runs);
+                    visit_runs_mut(&mut entry.children, f);
+                }
+            }
+            Block::Quote { body } => visit_runs_mut(body, f),
+            Block::Admonition { title, body, .. } => {
+                if let Some(runs) = title {
+                    f(runs);
+                }
+                visit_runs_mut(body, f);
+            }
+            Block::Table { headers, rows, .. } => {
+                for cell in headers.iter_mut().chain(rows.iter_mut().flatten()) {
+                    f(&mut cell.content);
+                }
+            }
+            Block::FootnoteDefinitions { entries } => {
+                for entry in entries {
+                    f(&mut entry.runs);
+                }
+            }
+            Block::DefinitionList { entries } => {
+                for entry in entries {
+                    for term in &mut entry.terms {
+                        f(term);
+                    }
+                    for def in &mut entry.definitions {
+                        visit_runs_mut(def, f);
+                    }
+                }
+            }
+            Block::Code { .. }
+            | Block::Diagram { .. }
+            | Block::Html { .. }
+            | Block::Math { .. }
+            | Block::HorizontalRule
+            | Block::Image { .. }
+            | Block::PageBreak
+            | Block::PageNumbering { .. }
+            | Block::PdfInclude { .. } => {}
+        }
     }
 }
 
@@ -324,8 +438,9 @@ pub struct RunFlags {
     /// `==text==` highlight. The renderer paints `style.mark`'s
     /// background behind the run's glyphs.
     pub highlight: bool,
-    /// Renders the glyphs at ~70% size with a raised baseline. Used
-    /// for footnote marker numbers and any `<sup>` HTML inline.
+    /// Renders the glyphs smaller on a raised baseline, as configured
+    /// by `[superscript]` (70% size by default). Used for footnote
+    /// marker numbers, `<sup>` HTML inline, and raised ordinals.
     pub superscript: bool,
     /// Renders the glyphs at ~70% size with a lowered baseline. Used
     /// for `<sub>` HTML inline (chemical formulas, indices).
@@ -344,6 +459,17 @@ pub struct RunFlags {
     /// `external_code_inline` family so inline code can use a different
     /// monospace face than block code.
     pub inline_code: bool,
+    /// Set on runs inside a heading. Routes through the
+    /// `[fonts.map] heading` family when one is loaded.
+    pub heading: bool,
+    /// Set on runs inside a blockquote. Routes through the
+    /// `[fonts.map] blockquote` family when one is loaded.
+    pub blockquote: bool,
+    /// Markdown emphasis the run sits in, as bits: 1 for `*em*`, 2 for
+    /// `**strong**`, both for `***both***` or nesting. Picks the
+    /// `[emphasis]` / `[strong_emphasis]` / `[emphasis_strong]` colour
+    /// and size; the face itself still follows `bold` / `italic`.
+    pub emphasis: u8,
 }
 
 impl RunFlags {
@@ -388,6 +514,15 @@ impl RunFlags {
         self.monospace = true;
         self
     }
+    /// Mark the run as inside emphasis of `level` (see `emphasis`).
+    pub fn with_emphasis(mut self, level: usize) -> Self {
+        self.emphasis |= match level {
+            1 => 1,
+            2 => 2,
+            _ => 3,
+        };
+        self
+    }
 
     /// OR every flag with `other`. Folds a block-level base style
     /// (e.g. a heading's bold weight) into per-run inline flags so
@@ -405,6 +540,9 @@ impl RunFlags {
             small_caps: self.small_caps || other.small_caps,
             small: self.small || other.small,
             inline_code: self.inline_code || other.inline_code,
+            heading: self.heading || other.heading,
+            blockquote: self.blockquote || other.blockquote,
+            emphasis: self.emphasis | other.emphasis,
         }
     }
 }
@@ -13,17 +13,20 @@ use printpdf::{
 use std::collections::{BTreeMap, HashMap, HashSet};
 
 use crate::styling::{
-    BorderStyle, ImageAlign, Orientation, PageSize, ResolvedBlock, ResolvedBorder,
-    ResolvedBorderSide, ResolvedList, ResolvedPage, ResolvedPageFurniture, ResolvedStyle,
-    ResolvedToc, TextAlignment,
+    BorderStyle, Digits, HeadingTransform, ImageAlign, ImageFloat, ImageMode, Orientation,
+    PageNumberStyle, PageSize, ResolvedBlock, ResolvedBorder, ResolvedBorderSide, ResolvedEmphasis,
+    ResolvedList, ResolvedPage, ResolvedPageFurniture, ResolvedStyle, ResolvedToc, TableOverflow,
+    TextAlignment,
 };
 
-use crate::markdown::{TableCell, slugify};
+use crate::markdown::{CodeAttrs, TableCell, slugify};
 
+use super::ProgressEvent;
 use super::font::FontSet;
 use super::image_policy::{ImagePathRefusal, is_http_url, resolve_image_path};
-use super::ir::{Block, InlineRun, ListBullet, ListEntry, RunFlags};
+use super::ir::{Block, GalleryImage, InlineRun, ListBullet, ListEntry, RunFlags};
 use super::math::layout::GlyphFont;
+use super::postprocess::{OutlineEntry, PdfInclude};
 
 type Color = printpdf::Color;
 
@@ -33,6 +36,22 @@ type Color = printpdf::Color;
 /// these links so they don't visually claim to be live.
 const UNRESOLVED_LINK_COLOR: (u8, u8, u8) = (192, 57, 43);
 
+/// Resolution images are placed at: one decoded pixel is
+/// `1 / IMAGE_DPI` inch on the page before any fit scaling.
+const IMAGE_DPI: f32 = 300.0;
+
+/// Horizontal gap between a floated image and the text wrapping
+/// beside it.
+const FLOAT_GAP_PT: f32 = 10.0;
+
+/// Narrowest text measure worth wrapping beside a floated image. An
+/// image leaving less than this sits on its own line instead.
+const MIN_FLOAT_TEXT_PT: f32 = 72.0;
+
+/// Smallest `[table] overflow = "scale"` shrinks a table's text to,
+/// as a fraction of its configured size. Past this, cells wrap.
+const MIN_TABLE_SCALE: f32 = 0.5;
+
 /// Resolve a `ResolvedPage` to (width_mm, height_mm). Landscape
 /// swaps the named-size dimensions; `PageSize::Custom` is taken
 /// verbatim.
@@ -67,6 +86,34 @@ pub(crate) fn page_dimensions_mm(page: &ResolvedPage) -> (f32, f32) {
     }
 }
 
+/// Left and right x (points) of the body: the page margins, drawn in
+/// evenly on both sides when `[page] max_content_width` is narrower
+/// than the space between them.
+fn body_edges_pt(page: &ResolvedPage, page_width_pt: f32) -> (f32, f32) {
+    let left = mm_to_pt(page.margins_mm.left.max(1.0));
+    let right = page_width_pt - mm_to_pt(page.margins_mm.right.max(1.0));
+    // Same 10pt floor as the column math, so a tiny cap still leaves
+    // a body that wrapping can work with.
+    match page.max_content_width_pt.map(|max| max.max(10.0)) {
+        Some(max) if right - left > max => {
+            let inset = (right - left - max) / 2.0;
+            (left + inset, right - inset)
+        }
+        _ => (left, right),
+    }
+}
+
+/// The result of [`lay_out_pages`]: the pages, plus what the save's
+/// post-passes need to finish the document.
+pub struct LaidOut {
+    pub pages: Vec<PdfPage>,
+    /// PDFs to splice in after saving (`pdf-include`).
+    pub pdf_includes: Vec<PdfInclude>,
+    /// One entry per heading in document order, for the bookmark
+    /// pane. Empty with `[document] bookmarks = false`.
+    pub outline: Vec<OutlineEntry>,
+}
+
 /// Render the IR to a vector of [`PdfPage`]s ready to hand to
 /// [`printpdf::PdfDocument::with_pages`].
 ///
@@ -79,13 +126,24 @@ pub fn lay_out_pages(
     font_set: &FontSet,
     known_heading_slugs: &HashSet<String>,
     doc: &mut PdfDocument,
-) -> Vec<PdfPage> {
+    progress: &mut dyn FnMut(ProgressEvent),
+) -> LaidOut {
     let mut engine = Engine::new(style, font_set, doc);
     engine.known_heading_slugs = known_heading_slugs.clone();
-    let mut it = blocks.iter().peekable();
-    while let Some(block) = it.next() {
-        let next = it.peek().copied();
-        engine.render_block(block, next);
+    let mut it = blocks.iter().enumerate().peekable();
+    while let Some((idx, block)) = it.next() {
+        progress(ProgressEvent::RenderingBlock(idx, blocks.len()));
+        // Past `[limits].max_pages` the render is going to be refused
+        // anyway; don't spend time laying out the rest.
+        if engine.page_limit_exceeded() {
+            break;
+        }
+        let next = it.peek().map(|&(_, b)| b);
+        if matches!(block, Block::Heading { .. }) && engine.headings_span_columns() {
+            engine.render_spanning_block(block, next);
+        } else {
+            engine.render_block(block, next);
+        }
     }
     engine.finish()
 }
@@ -160,6 +218,9 @@ struct Engine<'a> {
     /// keep the `[code_block]` colour instead of being repainted with
     /// the `[code_inline]` colour (both carry the `monospace` flag).
     in_code_block: bool,
+    /// True while rendering a blockquote's body, so its paragraph and
+    /// list text picks up the `[fonts.map] blockquote` family.
+    in_blockquote: bool,
     /// When set, paragraphs take their *text* style (font, colour,
     /// weight, slant, size, alignment, decorations) from this block
     /// instead of `[paragraph]` — so a blockquote's or admonition's
@@ -171,6 +232,19 @@ struct Engine<'a> {
     /// call. Set by `render_paragraph` from `[paragraph].indent_pt`;
     /// the call consumes it (resets to 0) so it applies once.
     first_line_indent_pt: f32,
+    /// Coarse kind of the block most recently dispatched by
+    /// `render_block`. Lets `render_paragraph` apply
+    /// `[paragraph].paragraph_spacing_pt` only between successive
+    /// paragraphs and drop the leading gap right under a heading.
+    last_block: BlockKind,
+    /// The vertical margin most recently advanced with nothing drawn
+    /// since. The next margin collapses into it, so two adjacent
+    /// blocks sit `max(after, before)` apart rather than the sum.
+    pending_margin: Option<PendingMargin>,
+    /// Image floated with `[image].float` that paragraphs are still
+    /// wrapping around. Dropped on a column / page change; any other
+    /// block first moves the cursor below it.
+    float: Option<FloatRegion>,
     /// Extra spacing (points) added after every glyph of the block
     /// currently being rendered. Set by `begin_block` from the block's
     /// `letter_spacing_pt` and restored by `end_block`; read by both
@@ -192,6 +266,9 @@ struct Engine<'a> {
     /// Inline math is measured several times per line (wrap, natural
     /// width, emit) — typesetting once and cloning keeps that O(1).
     math_inline_cache: HashMap<(String, u32), super::math::layout::Frag>,
+    /// Inline images by path or URL, each decoded and embedded once.
+    /// `None` for one that failed to load, so it isn't retried.
+    inline_images: HashMap<String, Option<InlineImage>>,
     /// One Form XObject per distinct (source font, glyph id). A glyph
     /// outline is emitted once here and invoked with a tiny `cm`/`Do`
     /// at every occurrence, instead of re-inlining its
@@ -213,6 +290,29 @@ struct Engine<'a> {
     /// Which body column the cursor is currently in (`0 .. num_columns`).
     /// Advanced by [`advance_column`]; reset to 0 by [`start_new_page`].
     current_column: u8,
+    /// Top y of the current band of columns. The page's top margin,
+    /// except below a heading that spans the columns, where the next
+    /// band starts. [`advance_column`] returns here.
+    column_top_pt: f32,
+    /// Deepest y any column of the current band has reached so far.
+    /// A spanning heading starts below it.
+    column_band_bottom_pt: f32,
+    /// Number of captioned figures drawn so far. Feeds the `{n}` in
+    /// `[image].caption_prefix`.
+    figure_count: usize,
+    /// An image decoded ahead of time by `[image] keep_with_previous`,
+    /// keyed by its path; `None` records a failed decode.
+    /// [`Self::render_image`] takes it instead of reading the file again.
+    pending_image: Option<(std::path::PathBuf, Option<image::DynamicImage>)>,
+    /// PDFs from `pdf-include` blocks, with `after_page` counting body
+    /// pages until [`Self::finish`] shifts it past the title and TOC.
+    pdf_includes: Vec<PdfInclude>,
+    /// `<!-- numbering -->` markers, with `page_idx` counting body
+    /// pages until [`Self::finish`] shifts it past the title and TOC.
+    numbering_marks: Vec<NumberingMark>,
+    /// Body pages laid out turned to landscape for a wide table
+    /// (`[table] overflow = "rotate"`), counted like `numbering_marks`.
+    landscape_pages: HashSet<usize>,
 }
 
 struct MathState<'a> {
@@ -238,6 +338,62 @@ struct SavedColumns {
     current_column: u8,
 }
 
+/// A margin advanced by [`Engine::advance_margin`], with the cursor
+/// position it left behind. It only collapses with the next margin
+/// while the cursor is still exactly there.
+#[derive(Debug, Clone, Copy)]
+struct PendingMargin {
+    pt: f32,
+    y: f32,
+    column: u8,
+    page: usize,
+}
+
+/// The strip beside the text flow a floated image occupies, down to
+/// `bottom_pt` (image, caption and after-margin). Lines starting
+/// above it lose `width_pt` (image plus gap) on the image's `side`.
+#[derive(Debug, Clone, Copy)]
+struct FloatRegion {
+    side: ImageFloat,
+    width_pt: f32,
+    bottom_pt: f32,
+}
+
+/// An image added to the document, with the scale that places it at
+/// its intended size.
+struct EmbeddedImage {
+    id: XObjectId,
+    scale_x: f32,
+    scale_y: f32,
+}
+
+/// An image drawn inside a line of text, embedded at `h_pt` tall.
+/// Other text sizes scale the same XObject.
+struct InlineImage {
+    image: EmbeddedImage,
+    w_pt: f32,
+    h_pt: f32,
+}
+
+/// What kind of block preceded the one being rendered, as far as
+/// paragraph spacing cares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockKind {
+    Heading,
+    Paragraph,
+    Other,
+}
+
+impl BlockKind {
+    fn of(block: &Block) -> Self {
+        match block {
+            Block::Heading { .. } => BlockKind::Heading,
+            Block::Paragraph { .. } | Block::Gallery { .. } => BlockKind::Paragraph,
+            _ => BlockKind::Other,
+        }
+    }
+}
+
 /// One background-bearing block that is currently open. Tracks the
 /// rect to paint *for the current page fragment*; cross-page blocks
 /// produce one rect per page they touch.
@@ -258,11 +414,11 @@ type OpenBgFrag = (usize, f32, f32, f32, (u8, u8, u8));
 
 /// Row-span bookkeeping for [`Engine::draw_row`]: which row this call
 /// starts at, the per-row heights of the whole table (needed to sum a
-/// row-spanning cell's merged region), and the shared column width.
+/// row-spanning cell's merged region), and the column edges.
 struct RowLayout<'r> {
     row_offset: usize,
     row_heights: &'r [f32],
-    col_width: f32,
+    col_x: &'r [f32],
 }
 
 /// Text styling shared by every cell in a [`Engine::draw_row`] call.
@@ -277,8 +433,7 @@ struct RowTextStyle {
 impl<'a> Engine<'a> {
     fn new(style: &'a ResolvedStyle, font_set: &'a FontSet, doc: &'a mut PdfDocument) -> Self {
         let (page_width_mm, page_height_mm) = page_dimensions_mm(&style.page);
-        let left = mm_to_pt(style.page.margins_mm.left.max(1.0));
-        let right = page_width_mm * MM_TO_PT - mm_to_pt(style.page.margins_mm.right.max(1.0));
+        let (left, right) = body_edges_pt(&style.page, page_width_mm * MM_TO_PT);
         let top = mm_to_pt(style.page.margins_mm.top.max(1.0));
         let body_width = (right - left).max(10.0);
         let num_columns = style.page.columns.clamp(1, 4);
@@ -330,21 +485,33 @@ impl<'a> Engine<'a> {
             text_section_marker: 0,
             pending_highlights: Vec::new(),
             in_code_block: false,
+            in_blockquote: false,
             text_style_override: None,
             first_line_indent_pt: 0.0,
+            last_block: BlockKind::Other,
+            pending_margin: None,
+            float: None,
             letter_spacing_pt: 0.0,
             open_bg: Vec::new(),
             math: None,
             math_inline_cache: HashMap::new(),
+            inline_images: HashMap::new(),
             math_glyph_xobjects: HashMap::new(),
             num_columns,
             column_gap_pt,
             column_width_pt,
             current_column: 0,
+            column_top_pt: top,
+            column_band_bottom_pt: top,
+            figure_count: 0,
+            pending_image: None,
+            pdf_includes: Vec::new(),
+            numbering_marks: Vec::new(),
+            landscape_pages: HashSet::new(),
         }
     }
 
-    fn finish(mut self) -> Vec<PdfPage> {
+    fn finish(mut self) -> LaidOut {
         self.close_text_section();
         self.push_current_page();
 
@@ -352,6 +519,14 @@ impl<'a> Engine<'a> {
         // raw_pages slot is empty for title-page / TOC passes.
         let content_pages: Vec<Vec<Op>> = std::mem::take(&mut self.raw_pages);
         let body_link_count = self.pending_internal_links.len();
+        let included_count: usize = self.pdf_includes.iter().map(|inc| inc.pages).sum();
+
+        // Included PDF pages take page numbers too: move each
+        // numbering mark past the ones spliced in ahead of it.
+        for i in 0..self.numbering_marks.len() {
+            let shift = self.included_pages_before(self.numbering_marks[i].page_idx);
+            self.numbering_marks[i].page_idx += shift;
+        }
 
         // Optional title page first. Currently always produces one
         // page; multi-page title support is a follow-up.
@@ -398,8 +573,18 @@ impl<'a> Engine<'a> {
         for link in &mut self.pending_internal_links[body_link_count..] {
             link.page_idx += title_offset;
         }
+        for mark in &mut self.numbering_marks {
+            mark.page_idx += prefix_offset;
+        }
+        let landscape: HashSet<usize> = self
+            .landscape_pages
+            .iter()
+            .map(|idx| idx + prefix_offset)
+            .collect();
 
-        let total = content_pages.len() + prefix_offset;
+        let total = content_pages.len() + prefix_offset + included_count;
+        let header_labels = self.page_labels(self.style.header.as_ref(), 0, total);
+        let footer_labels = self.page_labels(self.style.footer.as_ref(), 0, total);
         let base = TemplateBase {
             total_pages: total,
             title: self.style.metadata.title.clone().unwrap_or_default(),
@@ -416,6 +601,14 @@ impl<'a> Engine<'a> {
             .collect();
         let mut deferred_per_page: BTreeMap<usize, Vec<Op>> = BTreeMap::new();
         let page_height_pt = self.page_height_pt();
+        // A landscape page's height is the portrait page's width.
+        let height_of = |idx: usize| {
+            if landscape.contains(&idx) {
+                self.page_width_pt()
+            } else {
+                page_height_pt
+            }
+        };
         for pending in &self.pending_internal_links {
             let Some(dest) = anchor_index.get(pending.target_slug.as_str()) else {
                 log::warn!(
@@ -425,14 +618,14 @@ impl<'a> Engine<'a> {
                 );
                 continue;
             };
-            let y_bot_pt = page_height_pt - pending.baseline_y_pt;
+            let y_bot_pt = height_of(pending.page_idx) - pending.baseline_y_pt;
             let rect = Rect::from_xywh(
                 Pt(pending.x0_pt),
                 Pt(y_bot_pt),
                 Pt((pending.x1_pt - pending.x0_pt).max(1.0)),
                 Pt(pending.size_pt),
             );
-            let dest_top_pdf_pt = page_height_pt - dest.y_pt;
+            let dest_top_pdf_pt = height_of(dest.page_idx) - dest.y_pt;
             let annotation = LinkAnnotation::new(
                 rect,
                 Actions::go_to(Destination::Xyz {
@@ -451,18 +644,21 @@ impl<'a> Engine<'a> {
                 .push(Op::LinkAnnotation { link: annotation });
         }
 
-        // Bookmarks: every heading is registered with its shifted page
-        // number. printpdf 0.9's outline serializer is flat; we hint at
-        // hierarchy via an indent prefix per heading level.
-        for anchor in &self.heading_anchors {
-            let indent_level = anchor.level.saturating_sub(1).min(5) as usize;
-            let mut name = String::with_capacity(indent_level * 2 + anchor.text.len());
-            for _ in 0..indent_level {
-                name.push_str("  ");
-            }
-            name.push_str(&anchor.text);
-            self.doc.add_bookmark(&name, anchor.page_idx + 1);
-        }
+        // Bookmarks: every heading at its shifted page. printpdf's
+        // outline is flat, so the nested tree is written after saving.
+        let outline = if self.style.bookmarks {
+            self.heading_anchors
+                .iter()
+                .map(|anchor| OutlineEntry {
+                    title: anchor.text.clone(),
+                    level: anchor.level,
+                    page_idx: anchor.page_idx,
+                    top_pt: page_height_pt - anchor.y_pt,
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
 
         // Page assembly: title pages → TOC pages → body content. Header
         // / footer furniture applies to every page EXCEPT the title
@@ -473,17 +669,37 @@ impl<'a> Engine<'a> {
             .chain(toc_pages)
             .chain(content_pages);
         for (idx, content_ops) in combined.enumerate() {
-            let ctx = base.with_page(idx + 1);
+            let number = if idx < prefix_offset {
+                idx
+            } else {
+                self.page_number_of(idx - prefix_offset, prefix_offset)
+            };
+            let ctx = base.with_page(number + 1);
             let is_title_page = idx < title_offset;
+            // Header and footer sit along the turned page's edges.
+            let turned = landscape.contains(&idx);
+            if turned {
+                std::mem::swap(&mut self.page_width_mm, &mut self.page_height_mm);
+            }
             let header_ops = if is_title_page {
                 Vec::new()
             } else {
-                self.render_furniture(self.style.header.as_ref(), &ctx, FurniturePosition::Top)
+                self.render_furniture(
+                    self.style.header.as_ref(),
+                    &ctx,
+                    &header_labels[number],
+                    FurniturePosition::Top,
+                )
             };
             let footer_ops = if is_title_page {
                 Vec::new()
             } else {
-                self.render_furniture(self.style.footer.as_ref(), &ctx, FurniturePosition::Bottom)
+                self.render_furniture(
+                    self.style.footer.as_ref(),
+                    &ctx,
+                    &footer_labels[number],
+                    FurniturePosition::Bottom,
+                )
             };
             let internal_link_ops = deferred_per_page.remove(&idx).unwrap_or_default();
             let mut all = Vec::with_capacity(
@@ -498,8 +714,18 @@ impl<'a> Engine<'a> {
                 Mm(self.page_height_mm),
                 all,
             ));
+            if turned {
+                std::mem::swap(&mut self.page_width_mm, &mut self.page_height_mm);
+            }
+        }
+        for include in &mut self.pdf_includes {
+            include.after_page += prefix_offset;
+        }
+        LaidOut {
+            pages,
+            pdf_includes: std::mem::take(&mut self.pdf_includes),
+            outline,
         }
-        pages
     }
 
     /// Lay out the TOC into a fresh sequence of page ops. The
@@ -526,9 +752,8 @@ impl<'a> Engine<'a> {
         let saved_in_text = self.in_text_section;
         let saved_columns = self.snapshot_columns_single();
 
-        let page_width_pt = self.page_width_pt();
-        self.indent_left_pt = mm_to_pt(self.style.page.margins_mm.left.max(1.0));
-        self.indent_right_pt = page_width_pt - mm_to_pt(self.style.page.margins_mm.right.max(1.0));
+        (self.indent_left_pt, self.indent_right_pt) =
+            body_edges_pt(&self.style.page, self.page_width_pt());
         self.in_text_section = false;
 
         let base_size = tp.style.font_size_pt.max(8.0);
@@ -564,10 +789,14 @@ impl<'a> Engine<'a> {
         let cover = tp
             .cover_image_path
             .as_deref()
-            .and_then(|p| self.decode_image_file(std::path::Path::new(p)))
+            .and_then(|p| {
+                let path = std::path::Path::new(p);
+                self.decode_image_file(path)
+                    .and_then(|img| to_raw_image(img, path))
+            })
             .map(|raw| {
-                let nat_w = raw.width as f32 / 300.0 * 72.0;
-                let nat_h = raw.height as f32 / 300.0 * 72.0;
+                let nat_w = raw.width as f32 / IMAGE_DPI * 72.0;
+                let nat_h = raw.height as f32 / IMAGE_DPI * 72.0;
                 let scale = (content_w / nat_w).min((usable_h * 0.45) / nat_h).min(1.0);
                 (raw, nat_w * scale, nat_h * scale, scale)
             });
@@ -593,7 +822,7 @@ impl<'a> Engine<'a> {
                     rotate: None,
                     scale_x: Some(scale),
                     scale_y: Some(scale),
-                    dpi: Some(300.0),
+                    dpi: Some(IMAGE_DPI),
                 },
             });
             self.y_from_top_pt += cover_h + line_gap;
@@ -651,6 +880,9 @@ impl<'a> Engine<'a> {
             small: false,
             underline: false,
             inline_code: false,
+            heading: false,
+            blockquote: false,
+            emphasis: 0,
         };
         let measured = self.measure_text(flags, text, size_pt);
         let center_x = (self.page_width_pt() - measured) / 2.0;
@@ -675,10 +907,11 @@ impl<'a> Engine<'a> {
         self.advance_y(size_pt);
     }
 
-    /// expected to occupy; entries display `anchor.page_idx + 1 +
-    /// toc_offset` so the printed page numbers match what the body's
-    /// headings will sit at after concatenation. Caller iterates
-    /// until the returned page count matches the estimate.
+    /// expected to occupy; entries display the footer's label for
+    /// `anchor.page_idx + toc_offset` so the printed page numbers
+    /// match what the body's headings will sit at after
+    /// concatenation. Caller iterates until the returned page count
+    /// matches the estimate.
     fn lay_out_toc(&mut self, toc_offset_estimate: usize) -> Vec<Vec<Op>> {
         let toc = self
             .style
@@ -699,19 +932,25 @@ impl<'a> Engine<'a> {
         let saved_columns = self.snapshot_columns_single();
         // Reset to first-page top.
         self.y_from_top_pt = mm_to_pt(self.style.page.margins_mm.top.max(1.0));
-        let page_width_pt = self.page_width_pt();
-        self.indent_left_pt = mm_to_pt(self.style.page.margins_mm.left.max(1.0));
-        self.indent_right_pt = page_width_pt - mm_to_pt(self.style.page.margins_mm.right.max(1.0));
+        (self.indent_left_pt, self.indent_right_pt) =
+            body_edges_pt(&self.style.page, self.page_width_pt());
         self.in_text_section = false;
 
         self.render_toc_title(&toc);
 
         let anchors = self.heading_anchors.clone();
-        for anchor in anchors.iter() {
+        let numbers: Vec<usize> = anchors
+            .iter()
+            .map(|a| self.page_number_of(a.page_idx, toc_offset_estimate))
+            .collect();
+        let page_count = numbers.iter().map(|n| n + 1).max().unwrap_or(0);
+        let furniture = self.style.footer.as_ref().or(self.style.header.as_ref());
+        let labels = self.page_labels(furniture, toc_offset_estimate, page_count);
+        for (anchor, &number) in anchors.iter().zip(&numbers) {
             if anchor.level > toc.max_depth {
                 continue;
             }
-            let displayed = anchor.page_idx + 1 + toc_offset_estimate;
+            let displayed = &labels[number];
             self.render_toc_entry(anchor, displayed, &toc);
         }
 
@@ -741,6 +980,7 @@ impl<'a> Engine<'a> {
         let s = self.style.headings[0].clone();
         let runs = vec![InlineRun {
             math: None,
+            image: None,
             text: toc.title.clone(),
             flags: RunFlags::default(),
             link: None,
@@ -758,13 +998,16 @@ impl<'a> Engine<'a> {
             small: false,
             underline: false,
             inline_code: false,
+            heading: true,
+            blockquote: false,
+            emphasis: 0,
         };
         let ctx = self.begin_block(&s);
         self.write_wrapped_runs(&runs, s.font_size_pt, s.line_height, flags, color);
         self.end_block(ctx);
     }
 
-    fn render_toc_entry(&mut self, anchor: &HeadingAnchor, page_num: usize, toc: &ResolvedToc) {
+    fn render_toc_entry(&mut self, anchor: &HeadingAnchor, page_str: &str, toc: &ResolvedToc) {
         let style = toc.style.clone();
         let entry_indent = (anchor.level.saturating_sub(1) as f32) * 12.0;
         let flags = RunFlags::default();
@@ -799,8 +1042,7 @@ impl<'a> Engine<'a> {
         );
 
         // Page-number portion (right-aligned at row_right).
-        let page_str = page_num.to_string();
-        let num_w = self.measure_text(flags, &page_str, size_pt);
+        let num_w = self.measure_text(flags, page_str, size_pt);
         let num_x = row_right - num_w;
         self.close_text_section();
         self.ensure_text_section();
@@ -809,7 +1051,7 @@ impl<'a> Engine<'a> {
             &mut self.page_ops,
             self.font_set,
             flags,
-            &page_str,
+            page_str,
             size_pt,
             self.letter_spacing_pt,
         );
@@ -828,14 +1070,114 @@ impl<'a> Engine<'a> {
         self.advance_y(line_h);
     }
 
+    /// Pages of `pdf-include` PDFs spliced in before body page `idx`.
+    /// Only meaningful while `after_page` still counts body pages.
+    fn included_pages_before(&self, idx: usize) -> usize {
+        self.pdf_includes
+            .iter()
+            .filter(|inc| inc.after_page <= idx)
+            .map(|inc| inc.pages)
+            .sum()
+    }
+
+    /// 0-based position in the finished PDF of body page `idx`, behind
+    /// `prefix` title / TOC pages. Included PDF pages are inserted
+    /// after layout, so they aren't in `raw_pages` but still count.
+    fn page_number_of(&self, idx: usize, prefix: usize) -> usize {
+        idx + prefix + self.included_pages_before(idx)
+    }
+
+    /// The `{page}` text of the first `count` pages for `furniture`'s
+    /// numbering (arabic from 1 without one), with the `<!-- numbering
+    /// -->` marks applied. Marks sit `offset` pages further on than
+    /// their recorded `page_idx`.
+    fn page_labels(
+        &self,
+        furniture: Option<&ResolvedPageFurniture>,
+        offset: usize,
+        count: usize,
+    ) -> Vec<String> {
+        let (mut style, mut number) = furniture.map_or((PageNumberStyle::Arabic, 1), |f| {
+            (f.page_number_style, f.start_at)
+        });
+        let mut marks = self.numbering_marks.iter().peekable();
+        (0..count)
+            .map(|idx| {
+                while let Some(mark) = marks.next_if(|m| m.page_idx + offset <= idx) {
+                    style = mark.style;
+                    if mark.reset {
+                        number = 1;
+                    }
+                }
+                let label = style.format(number);
+                number += 1;
+                label
+            })
+            .collect()
+    }
+
     fn push_current_page(&mut self) {
         if self.page_ops.is_empty() {
             return;
         }
         let ops = std::mem::take(&mut self.page_ops);
+        // Keep one page over `[limits].max_pages` so the caller can
+        // tell the cap was hit, and drop the rest: a single huge block
+        // would otherwise still pile up pages before the block loop in
+        // `lay_out_pages` gets a chance to stop.
+        if self.page_limit_exceeded() {
+            return;
+        }
         self.raw_pages.push(ops);
     }
 
+    /// Font size and baseline (y-from-top points) for one inline
+    /// segment of a `size_pt` line sitting on `baseline_y_pt`.
+    /// Superscript follows `[superscript]` (70% size raised by 32% by
+    /// default); subscript, small caps and `<small>` use fixed ratios.
+    /// Only the glyphs move: the line keeps the leading of `size_pt`.
+    fn segment_metrics(&self, flags: RunFlags, size_pt: f32, baseline_y_pt: f32) -> (f32, f32) {
+        let size_pt = self.emphasis_size(flags, size_pt);
+        let sup = self.style.superscript;
+        if flags.superscript {
+            (size_pt * sup.scale, baseline_y_pt - size_pt * sup.rise)
+        } else if flags.subscript {
+            (size_pt * 0.70, baseline_y_pt + size_pt * 0.20)
+        } else if flags.small_caps {
+            (size_pt * 0.78, baseline_y_pt)
+        } else if flags.small {
+            (size_pt * 0.85, baseline_y_pt)
+        } else {
+            (size_pt, baseline_y_pt)
+        }
+    }
+
+    /// The `[emphasis]` / `[strong_emphasis]` / `[emphasis_strong]`
+    /// table for a run inside Markdown emphasis.
+    fn emphasis_style(&self, flags: RunFlags) -> Option<&ResolvedEmphasis> {
+        match flags.emphasis {
+            0 => None,
+            1 => Some(&self.style.emphasis),
+            2 => Some(&self.style.strong_emphasis),
+            _ => Some(&self.style.emphasis_strong),
+        }
+    }
+
+    /// `size_pt`, or the emphasis level's `font_size_pt` when it sets
+    /// one. Line spacing still follows the block size.
+    fn emphasis_size(&self, flags: RunFlags, size_pt: f32) -> f32 {
+        self.emphasis_style(flags)
+            .and_then(|e| e.font_size_pt)
+            .unwrap_or(size_pt)
+    }
+
+    fn page_limit_exceeded(&self) -> bool {
+        self.style
+            .limits
+            .max_pages
+            .is_some_and(|max| self.raw_pages.len() > max)
+    }
+
     fn top_margin_pt(&self) -> f32 {
         mm_to_pt(self.style.page.margins_mm.top.max(1.0))
     }
@@ -846,7 +1188,7 @@ impl<'a> Engine<'a> {
     /// the actual measured heights so the heuristic can adapt to
     /// multi-line headings and non-paragraph follow blocks.
     fn keep_with_next_break(&mut self, header_h: f32, follow_h: f32) {
-        if (self.y_from_top_pt - self.top_margin_pt()).abs() < 0.01 {
+        if (self.y_from_top_pt - self.column_top_pt).abs() < 0.01 {
             return;
         }
         let needed = header_h + follow_h;
@@ -912,7 +1254,7 @@ impl<'a> Engine<'a> {
                 let idx = (*level).clamp(1, 6) as usize - 1;
                 &self.style.headings[idx]
             }
-            Some(Block::Code { .. }) => &self.style.code_block,
+            Some(Block::Code { .. } | Block::Diagram { .. }) => &self.style.code_block,
             Some(Block::Quote { .. }) => &self.style.blockquote,
             Some(Block::List { entries }) => {
                 if let Some(first) = entries.first() {
@@ -954,8 +1296,10 @@ impl<'a> Engine<'a> {
         mm_to_pt(self.style.page.margins_mm.bottom.max(1.0))
     }
 
+    /// Left edge of the body, inside `[page] max_content_width`'s
+    /// inset when one applies.
     fn left_margin_pt(&self) -> f32 {
-        mm_to_pt(self.style.page.margins_mm.left.max(1.0))
+        body_edges_pt(&self.style.page, self.page_width_pt()).0
     }
 
     fn page_height_pt(&self) -> f32 {
@@ -970,6 +1314,23 @@ impl<'a> Engine<'a> {
         self.indent_right_pt - self.indent_left_pt
     }
 
+    /// The page margins' edges, for a `full_width` image or a rule to
+    /// span when `[page] full_width_spans_margins` is set and
+    /// `max_content_width` has drawn the body in from them. `None` in
+    /// a multi-column layout or inside a list, quote or other nested
+    /// block, which keep to their own width.
+    fn margin_span_pt(&self) -> Option<(f32, f32)> {
+        let page = &self.style.page;
+        if !page.full_width_spans_margins || self.num_columns > 1 {
+            return None;
+        }
+        let at_body = (self.indent_left_pt - self.column_body_left_pt(0)).abs() < 0.01
+            && (self.indent_right_pt - self.column_body_right_pt(0)).abs() < 0.01;
+        let left = mm_to_pt(page.margins_mm.left.max(1.0));
+        let right = self.page_width_pt() - mm_to_pt(page.margins_mm.right.max(1.0));
+        (at_body && right - left > self.content_width_pt() + 0.01).then_some((left, right))
+    }
+
     /// Per-character small-caps expansion: every lowercase character
     /// in each run becomes an uppercase character with the
     /// `small_caps` flag set (which the segment loop renders at ~78%
@@ -989,6 +1350,7 @@ impl<'a> Engine<'a> {
                     f.small_caps = buf_lower == Some(true);
                     out.push(InlineRun {
                         math: None,
+                        image: None,
                         text: std::mem::take(&mut buf),
                         flags: f,
                         link: run.link.clone(),
@@ -1008,6 +1370,7 @@ impl<'a> Engine<'a> {
                 f.small_caps = buf_lower == Some(true);
                 out.push(InlineRun {
                     math: None,
+                    image: None,
                     text: buf,
                     flags: f,
                     link: run.link.clone(),
@@ -1036,8 +1399,9 @@ impl<'a> Engine<'a> {
     ) -> Vec<InlineRun> {
         let mut out: Vec<InlineRun> = Vec::with_capacity(words.len());
         for word in words {
-            if word.math.is_some() {
-                // Inline-math boxes are atomic — never char-split.
+            if word.math.is_some() || word.image.is_some() {
+                // Inline-math and image boxes are atomic — never
+                // char-split.
                 out.push(word);
                 continue;
             }
@@ -1098,6 +1462,7 @@ impl<'a> Engine<'a> {
                     let chunk_text = word.text[chunk_start_byte..b].to_string();
                     out.push(InlineRun {
                         math: None,
+                        image: None,
                         text: chunk_text,
                         flags: word.flags,
                         link: word.link.clone(),
@@ -1130,6 +1495,7 @@ impl<'a> Engine<'a> {
                     chunk_text.push('-');
                     out.push(InlineRun {
                         math: None,
+                        image: None,
                         text: chunk_text,
                         flags: word.flags,
                         link: word.link.clone(),
@@ -1145,10 +1511,9 @@ impl<'a> Engine<'a> {
                 // chopping (longest prefix that fits in max_width).
                 let mut last_fit = chunk_start_char;
                 let mut j = chunk_start_char;
+                let mut w = 0.0f32;
                 while j < chars.len() {
-                    let end_byte = chars.get(j + 1).map(|c| c.0).unwrap_or(word.text.len());
-                    let prefix = &word.text[chunk_start_byte..end_byte];
-                    let w = self.measure_text(word.flags, prefix, size_pt);
+                    w += self.measure_char(word.flags, chars[j].1, size_pt);
                     if w > max_width {
                         if last_fit == chunk_start_char {
                             last_fit = j;
@@ -1165,6 +1530,7 @@ impl<'a> Engine<'a> {
                 let chunk_text = word.text[chunk_start_byte..end_byte].to_string();
                 out.push(InlineRun {
                     math: None,
+                    image: None,
                     text: chunk_text,
                     flags: word.flags,
                     link: word.link.clone(),
@@ -1191,6 +1557,50 @@ impl<'a> Engine<'a> {
         }
     }
 
+    /// Advance a block's before- or after-margin, collapsing it
+    /// CSS-style into a margin advanced just before it: when nothing
+    /// was drawn in between, the gap grows to the larger of the two
+    /// instead of their sum. Stops a list's after-margin and the next
+    /// paragraph's before-margin from stacking into a double gap.
+    fn advance_margin(&mut self, pt: f32) {
+        let pt = pt.max(0.0);
+        let already = self
+            .pending_margin
+            .filter(|m| {
+                m.page == self.raw_pages.len()
+                    && m.column == self.current_column
+                    && (m.y - self.y_from_top_pt).abs() < 0.01
+            })
+            .map_or(0.0, |m| m.pt);
+        self.advance_y((pt - already).max(0.0));
+        self.pending_margin = Some(PendingMargin {
+            pt: pt.max(already),
+            y: self.y_from_top_pt,
+            column: self.current_column,
+            page: self.raw_pages.len(),
+        });
+    }
+
+    /// `(left shift, lost width)` the active floated image imposes on
+    /// a text line whose top sits at `y_top_pt`.
+    fn float_inset(&self, y_top_pt: f32) -> (f32, f32) {
+        match self.float {
+            Some(f) if y_top_pt < f.bottom_pt => match f.side {
+                ImageFloat::Left => (f.width_pt, f.width_pt),
+                _ => (0.0, f.width_pt),
+            },
+            _ => (0.0, 0.0),
+        }
+    }
+
+    /// Stop wrapping around a floated image, moving the cursor below
+    /// it if text beside it ended higher up.
+    fn clear_float(&mut self) {
+        if let Some(f) = self.float.take() {
+            self.advance_y((f.bottom_pt - self.y_from_top_pt).max(0.0));
+        }
+    }
+
     /// Left edge (points) of column `col`'s body area, measured from
     /// the page's left edge. Column 0 sits at `left_margin_pt()`;
     /// each subsequent column shifts right by `column_width_pt +
@@ -1230,6 +1640,7 @@ impl<'a> Engine<'a> {
     /// indent inside any open block (a blockquote that page-broke
     /// keeps its left/right padding on the new page).
     fn start_new_page(&mut self) {
+        self.float = None;
         self.close_text_section();
         self.paint_open_bg_fragments();
         self.push_current_page();
@@ -1239,6 +1650,8 @@ impl<'a> Engine<'a> {
         let delta_r = prev_col_right - self.indent_right_pt;
         self.current_column = 0;
         self.y_from_top_pt = self.top_margin_pt();
+        self.column_top_pt = self.y_from_top_pt;
+        self.column_band_bottom_pt = self.y_from_top_pt;
         let new_col_left = self.column_body_left_pt(0);
         let new_col_right = self.column_body_right_pt(0);
         self.indent_left_pt = new_col_left + delta_l;
@@ -1270,11 +1683,8 @@ impl<'a> Engine<'a> {
             column_width_pt: self.column_width_pt,
             current_column: self.current_column,
         };
-        let page_width_pt = self.page_width_pt();
-        let body_w = (page_width_pt
-            - mm_to_pt(self.style.page.margins_mm.left.max(1.0))
-            - mm_to_pt(self.style.page.margins_mm.right.max(1.0)))
-        .max(10.0);
+        let (left, right) = body_edges_pt(&self.style.page, self.page_width_pt());
+        let body_w = (right - left).max(10.0);
         self.num_columns = 1;
         self.column_gap_pt = 0.0;
         self.column_width_pt = body_w;
@@ -1293,6 +1703,36 @@ impl<'a> Engine<'a> {
         self.current_column = saved.current_column;
     }
 
+    fn headings_span_columns(&self) -> bool {
+        self.style.headings_span_columns && self.num_columns > 1
+    }
+
+    /// Render a top-level `block` across the full body width of a
+    /// multi-column page. It starts below the deepest column of the
+    /// current band, and a fresh band of columns opens underneath it,
+    /// so `column 1` never runs up past the block. Only used for
+    /// headings under `[headings].span_columns`.
+    fn render_spanning_block(&mut self, block: &Block, next: Option<&Block>) {
+        self.close_text_section();
+        self.y_from_top_pt = self.y_from_top_pt.max(self.column_band_bottom_pt);
+        let saved_columns = self.snapshot_columns_single();
+        self.indent_left_pt = self.column_body_left_pt(0);
+        self.indent_right_pt = self.column_body_right_pt(0);
+        // Full width, only the page top counts as "already at the top";
+        // a block stuck under the previous band must still page-break.
+        self.column_top_pt = self.top_margin_pt();
+
+        self.render_block(block, next);
+
+        self.close_text_section();
+        self.restore_columns(saved_columns);
+        self.current_column = 0;
+        self.column_top_pt = self.y_from_top_pt;
+        self.column_band_bottom_pt = self.y_from_top_pt;
+        self.indent_left_pt = self.column_body_left_pt(0);
+        self.indent_right_pt = self.column_body_right_pt(0);
+    }
+
     /// Move to the next column on the same page; if the current column
     /// was the last, fall through to `start_new_page`. Any open block
     /// background paints the fragment that fit in the now-leaving
@@ -1304,19 +1744,21 @@ impl<'a> Engine<'a> {
             self.start_new_page();
             return;
         }
+        self.float = None;
         self.close_text_section();
         self.paint_open_bg_fragments();
         let prev_col_left = self.column_body_left_pt(self.current_column);
         let prev_col_right = self.column_body_right_pt(self.current_column);
         let delta_l = self.indent_left_pt - prev_col_left;
         let delta_r = prev_col_right - self.indent_right_pt;
+        self.column_band_bottom_pt = self.column_band_bottom_pt.max(self.y_from_top_pt);
         self.current_column += 1;
-        self.y_from_top_pt = self.top_margin_pt();
+        self.y_from_top_pt = self.column_top_pt;
         let new_col_left = self.column_body_left_pt(self.current_column);
         let new_col_right = self.column_body_right_pt(self.current_column);
         self.indent_left_pt = new_col_left + delta_l;
         self.indent_right_pt = new_col_right - delta_r;
-        let new_top = self.top_margin_pt();
+        let new_top = self.column_top_pt;
         for ob in self.open_bg.iter_mut() {
             ob.top_y = new_top;
             ob.marker = 0;
@@ -1428,9 +1870,9 @@ impl<'a> Engine<'a> {
         // margin at the top of each column so col 0 (H1) and col 1+
         // (paragraph) align. Single-column layouts keep the original
         // breathing room.
-        let at_column_top = (self.y_from_top_pt - self.top_margin_pt()).abs() < 0.01;
+        let at_column_top = (self.y_from_top_pt - self.column_top_pt).abs() < 0.01;
         if !(self.num_columns > 1 && at_column_top) {
-            self.advance_y(style.margin_before_pt);
+            self.advance_margin(style.margin_before_pt);
         }
         let outer_y_top = self.y_from_top_pt;
         let outer_x_left = self.indent_left_pt;
@@ -1479,6 +1921,31 @@ impl<'a> Engine<'a> {
     /// fragments were already painted by [`start_new_page`]), then the
     /// border. Borders on a block that spanned a page break are still
     /// skipped — a partial box looks worse than none.
+    /// Pop the innermost open background and fill it from its top down
+    /// to the cursor, under the text drawn since it opened. The entry's
+    /// top_y / marker were reset by start_new_page on every page break,
+    /// so they describe the *final* fragment regardless of how many
+    /// pages the background crossed.
+    fn close_open_bg(&mut self) {
+        let bottom = self.y_from_top_pt;
+        if let Some(ob) = self.open_bg.pop()
+            && bottom > ob.top_y
+        {
+            let mut bg_ops: Vec<Op> = Vec::new();
+            draw_filled_rect(
+                &mut bg_ops,
+                ob.x_left,
+                ob.top_y,
+                ob.x_right,
+                bottom,
+                rgb_color(ob.color),
+                self.page_height_pt(),
+            );
+            let insert_at = ob.marker.min(self.page_ops.len());
+            self.page_ops.splice(insert_at..insert_at, bg_ops);
+        }
+    }
+
     fn end_block(&mut self, ctx: BlockPaintCtx) {
         self.close_text_section();
         self.advance_y(ctx.padding_bottom);
@@ -1488,26 +1955,7 @@ impl<'a> Engine<'a> {
         let page_h = self.page_height_pt();
 
         if ctx.background_color.is_some() {
-            // The open-bg entry's top_y / marker were reset by
-            // start_new_page on every page break, so they describe
-            // the *final* fragment regardless of how many pages the
-            // block crossed.
-            if let Some(ob) = self.open_bg.pop()
-                && outer_y_bottom > ob.top_y
-            {
-                let mut bg_ops: Vec<Op> = Vec::new();
-                draw_filled_rect(
-                    &mut bg_ops,
-                    ob.x_left,
-                    ob.top_y,
-                    ob.x_right,
-                    outer_y_bottom,
-                    rgb_color(ob.color),
-                    page_h,
-                );
-                let insert_at = ob.marker.min(self.page_ops.len());
-                self.page_ops.splice(insert_at..insert_at, bg_ops);
-            }
+            self.close_open_bg();
         }
 
         if has_any_border(&ctx.border) && !spanned_page {
@@ -1541,7 +1989,7 @@ impl<'a> Engine<'a> {
             self.indent_right_pt = cur_col_right - delta_r;
         }
         self.letter_spacing_pt = ctx.saved_letter_spacing;
-        self.advance_y(ctx.margin_after_pt);
+        self.advance_margin(ctx.margin_after_pt);
     }
 
     /// Build the op sequence for a single header or footer, ready to
@@ -1551,6 +1999,7 @@ impl<'a> Engine<'a> {
         &self,
         furniture: Option<&ResolvedPageFurniture>,
         ctx: &TemplateContext,
+        page_label: &str,
         pos: FurniturePosition,
     ) -> Vec<Op> {
         let Some(f) = furniture else {
@@ -1580,7 +2029,7 @@ impl<'a> Engine<'a> {
             (f.right.as_ref(), FurnitureAnchor::Right),
         ] {
             let Some(template) = raw else { continue };
-            let text = ctx.expand(template);
+            let text = ctx.expand(template, page_label);
             if text.is_empty() {
                 continue;
             }
@@ -1609,6 +2058,9 @@ impl<'a> Engine<'a> {
             small: false,
             underline: false,
             inline_code: false,
+            heading: false,
+            blockquote: false,
+            emphasis: 0,
         };
         let size_pt = style.font_size_pt;
         let measured = self.measure_text(flags, text, size_pt);
@@ -1646,10 +2098,35 @@ impl<'a> Engine<'a> {
     }
 
     fn render_block(&mut self, block: &Block, next: Option<&Block>) {
+        // Record this block before dispatch so a container's first
+        // child never sees the paragraph that preceded the container,
+        // and again after, since nested children overwrite it.
+        let prev = std::mem::replace(&mut self.last_block, BlockKind::of(block));
+        // Only paragraphs wrap beside a floated image.
+        if !matches!(block, Block::Paragraph { .. }) {
+            self.clear_float();
+        }
         match block {
-            Block::Heading { level, runs } => self.render_heading(*level, runs, next),
-            Block::Paragraph { runs } => self.render_paragraph(runs),
-            Block::Code { lines } => self.render_code_block(lines),
+            Block::Heading {
+                level,
+                runs,
+                id,
+                text,
+            } => self.render_heading(*level, runs, id.as_deref(), text, next),
+            Block::Paragraph { runs } => {
+                if self.style.image.keep_with_previous
+                    && let Some(Block::Image { path, alt, caption }) = next
+                {
+                    self.keep_paragraph_with_image(runs, path, alt, caption.as_deref());
+                }
+                self.render_paragraph(runs, prev)
+            }
+            Block::Code { lines, attrs, .. } => match self.style.code_block_max_lines {
+                Some(max) if lines.len() > max => {
+                    self.render_truncated_code_block(&lines[..max], attrs, lines.len() - max)
+                }
+                _ => self.render_code_block(lines, attrs),
+            },
             Block::HorizontalRule => self.render_horizontal_rule(),
             Block::List { entries } => self.render_list(entries),
             Block::Quote { body } => self.render_blockquote(body),
@@ -1665,12 +2142,27 @@ impl<'a> Engine<'a> {
                 rows,
             } => self.render_table(headers, aligns, rows),
             Block::Image { path, alt, caption } => self.render_image(path, alt, caption.as_deref()),
+            Block::Gallery { images, runs } => {
+                if self.style.image.gallery {
+                    self.render_gallery(images)
+                } else {
+                    self.render_paragraph(runs, prev)
+                }
+            }
+            Block::Diagram { png, lines } => self.render_diagram(png, lines),
             Block::Html { content } => self.render_html_block(content),
             Block::PageBreak => self.start_new_page(),
+            Block::PageNumbering { style, reset } => self.numbering_marks.push(NumberingMark {
+                page_idx: self.raw_pages.len(),
+                style: *style,
+                reset: *reset,
+            }),
+            Block::PdfInclude { path } => self.render_pdf_include(path),
             Block::FootnoteDefinitions { entries } => self.render_footnote_definitions(entries),
             Block::DefinitionList { entries } => self.render_definition_list(entries),
             Block::Math { content } => self.render_math_block(content),
         }
+        self.last_block = BlockKind::of(block);
     }
 
     /// Lazily parse STIX Two Math plus the body / fallback text faces
@@ -1984,6 +2476,7 @@ impl<'a> Engine<'a> {
             }
             let run = InlineRun {
                 math: None,
+                image: None,
                 text: line.to_string(),
                 flags: base,
                 link: None,
@@ -2013,7 +2506,7 @@ impl<'a> Engine<'a> {
 
         for (idx, entry) in entries.iter().enumerate() {
             if idx == 0 {
-                self.advance_y(body_style.margin_before_pt);
+                self.advance_margin(body_style.margin_before_pt);
             } else {
                 self.advance_y(body_style.margin_before_pt * 0.5);
             }
@@ -2053,7 +2546,7 @@ impl<'a> Engine<'a> {
             self.indent_left_pt = outer_left;
             self.indent_right_pt = outer_right;
         }
-        self.advance_y(body_style.margin_after_pt);
+        self.advance_margin(body_style.margin_after_pt);
     }
 
     fn render_footnote_definitions(&mut self, entries: &[crate::render::ir::FootnoteEntry]) {
@@ -2063,6 +2556,7 @@ impl<'a> Engine<'a> {
         let h2 = self.style.headings[1].clone();
         let title_runs = vec![InlineRun {
             math: None,
+            image: None,
             text: "Footnotes".to_string(),
             flags: RunFlags::default(),
             link: None,
@@ -2071,7 +2565,7 @@ impl<'a> Engine<'a> {
             let lines = self.estimate_wrapped_lines(
                 &title_runs,
                 h2.font_size_pt,
-                base_flags_from_block(&h2),
+                heading_flags_from_block(&h2),
             );
             h2.margin_before_pt
                 + h2.padding.top
@@ -2095,6 +2589,9 @@ impl<'a> Engine<'a> {
             small_caps: false,
             small: false,
             inline_code: false,
+            heading: true,
+            blockquote: false,
+            emphasis: 0,
         };
         let ctx = self.begin_block(&h2);
         self.write_wrapped_runs(&title_runs, h2.font_size_pt, h2.line_height, flags, color);
@@ -2116,12 +2613,14 @@ impl<'a> Engine<'a> {
             let mut runs: Vec<InlineRun> = Vec::with_capacity(entry.runs.len() + 2);
             runs.push(InlineRun {
                 math: None,
-                text: format!("{}", entry.number),
+                image: None,
+                text: self.style.digits.localize(&entry.number.to_string()),
                 flags: RunFlags::default().with_superscript(),
                 link: None,
             });
             runs.push(InlineRun {
                 math: None,
+                image: None,
                 text: "  ".to_string(),
                 flags: RunFlags::default(),
                 link: None,
@@ -2146,7 +2645,7 @@ impl<'a> Engine<'a> {
     /// content stays visible and clearly tagged as source-as-data.
     fn render_html_block(&mut self, content: &str) {
         let lines: Vec<String> = content.split('\n').map(|s| s.to_string()).collect();
-        self.render_code_block(&lines);
+        self.render_code_block(&lines, &CodeAttrs::default());
     }
 
     /// Fetch a remote image into memory, caching by URL. The actual
@@ -2185,21 +2684,25 @@ impl<'a> Engine<'a> {
         if alt.trim().is_empty() {
             return;
         }
-        self.render_paragraph(&[InlineRun {
-            math: None,
-            text: format!("[image: {}]", alt),
-            flags: RunFlags::default().with_italic(),
-            link: None,
-        }]);
+        self.render_paragraph(
+            &[InlineRun {
+                math: None,
+                image: None,
+                text: format!("[image: {}]", alt),
+                flags: RunFlags::default().with_italic(),
+                link: None,
+            }],
+            BlockKind::Other,
+        );
     }
 
-    /// Decode an image from a local path or URL into a `RawImage`,
-    /// applying the 4000px dimension cap. Returns `None` on any
+    /// Decode an image from a local path or URL, applying the 4000px
+    /// dimension cap. Returns `None` on any
     /// fetch / decode / conversion failure (logged), and also on a
     /// refusal from the operator's `[security]` policy — the two cases
     /// share the same graceful degradation to alt text. URL fetch is
     /// gated under the `fetch` feature; SVG rasterization under `svg`.
-    fn decode_image_file(&mut self, path: &std::path::Path) -> Option<RawImage> {
+    fn decode_image_file(&mut self, path: &std::path::Path) -> Option<image::DynamicImage> {
         let path_str = path.to_string_lossy();
         let is_url = is_http_url(path_str.as_ref());
         let bytes_result: Result<Vec<u8>, String> = if is_url {
@@ -2233,59 +2736,74 @@ impl<'a> Engine<'a> {
                 }
             }
         };
-        let decode_result: Result<image::DynamicImage, String> = bytes_result.and_then(|bytes| {
-            if looks_like_svg(&bytes) {
-                decode_svg_bytes(&bytes)
-            } else {
-                let cursor = std::io::Cursor::new(bytes);
-                image::ImageReader::new(cursor)
-                    .with_guessed_format()
-                    .map_err(|e| e.to_string())
-                    .and_then(|r| r.decode().map_err(|e| e.to_string()))
-            }
-        });
-        let img = match decode_result {
-            Ok(d) => d,
+        match bytes_result {
+            Ok(bytes) => decode_image_bytes(bytes, path),
             Err(e) => {
                 log::warn!("could not decode image {:?}: {}", path, e);
-                return None;
+                None
             }
-        };
-
-        // Degenerate dimensions: a 0-px image can't produce a valid
-        // XObject. Treat it like a decode failure.
-        if img.width() == 0 || img.height() == 0 {
-            log::warn!("image {:?} has zero dimension; skipping", path);
-            return None;
         }
+    }
 
-        // Bound decoded pixel dimensions. The URL fetch cap limits the
-        // *download* size, but a small compressed PNG can decompress
-        // to an enormous raster (memory + PDF-size blowup). Mirror the
-        // SVG ceiling: downscale so neither dimension exceeds
-        // `MAX_IMG_PX`, preserving aspect ratio.
-        const MAX_IMG_PX: u32 = 4000;
-        let img = if img.width() > MAX_IMG_PX || img.height() > MAX_IMG_PX {
-            log::warn!(
-                "image {:?} is {}x{}; downscaling to fit {}px",
-                path,
-                img.width(),
-                img.height(),
-                MAX_IMG_PX
+    /// Splice the pages of the PDF at `path` in here: the current page
+    /// ends and the included pages follow it. A PDF that can't be read
+    /// or parsed is logged and shown as a link to the file instead.
+    fn render_pdf_include(&mut self, path: &std::path::Path) {
+        let Some(doc) = self.load_pdf_include(path) else {
+            self.render_paragraph(
+                &[InlineRun {
+                    math: None,
+                    image: None,
+                    text: path.display().to_string(),
+                    flags: RunFlags::default(),
+                    link: Some(path.to_string_lossy().into_owned()),
+                }],
+                BlockKind::Other,
             );
-            img.resize(
-                MAX_IMG_PX,
-                MAX_IMG_PX,
-                image::imageops::FilterType::Triangle,
-            )
-        } else {
-            img
+            return;
         };
+        self.start_new_page();
+        self.pdf_includes.push(PdfInclude {
+            after_page: self.raw_pages.len(),
+            pages: doc.get_pages().len(),
+            doc,
+        });
+    }
 
-        match RawImage::from_dynamic_image(img) {
-            Ok(r) => Some(r),
+    /// Read and parse an included PDF under the same `[security]`
+    /// confinement as local images. Remote PDFs are not fetched.
+    fn load_pdf_include(&self, path: &std::path::Path) -> Option<lopdf::Document> {
+        if is_http_url(&path.to_string_lossy()) {
+            log::warn!(
+                "pdf-include {:?} refused: only local files can be included",
+                path
+            );
+            return None;
+        }
+        let security = &self.style.security;
+        let resolved = match resolve_image_path(
+            path,
+            security.image_root.as_deref(),
+            security.allow_absolute_image_paths,
+        ) {
+            Ok(resolved) => resolved,
+            Err(ImagePathRefusal::Policy(msg)) => {
+                log::warn!("pdf-include {:?} refused by security policy: {}", path, msg);
+                return None;
+            }
+            Err(ImagePathRefusal::NotFound(msg)) => {
+                log::warn!("{}", msg);
+                return None;
+            }
+        };
+        match lopdf::Document::load(&resolved) {
+            Ok(doc) if !doc.get_pages().is_empty() => Some(doc),
+            Ok(_) => {
+                log::warn!("pdf-include {:?} has no pages", path);
+                None
+            }
             Err(e) => {
-                log::warn!("could not convert image {:?}: {}", path, e);
+                log::warn!("could not read pdf-include {:?}: {}", path, e);
                 None
             }
         }
@@ -2294,100 +2812,537 @@ impl<'a> Engine<'a> {
     fn render_image(&mut self, path: &std::path::Path, alt: &str, caption: Option<&str>) {
         // Decode the image; on any failure degrade to an italic
         // alt-text paragraph so the document doesn't lose content.
-        let raw = match self.decode_image_file(path) {
-            Some(r) => r,
-            None => {
-                self.render_image_fallback(alt);
-                return;
+        let img = match self.pending_image.take() {
+            Some((pending, img)) if pending == path => img,
+            _ => self.decode_image_file(path),
+        };
+        match img {
+            Some(img) => self.render_decoded_image(img, alt, caption),
+            None => self.render_image_fallback(alt),
+        }
+    }
+
+    /// `[image] keep_with_previous`: decode the image after a paragraph
+    /// up front and, when the paragraph and the image fit on one page
+    /// together but not in what is left of this one, move to the next
+    /// column first so the paragraph starts there. Cover images get a
+    /// page of their own anyway and are left alone.
+    fn keep_paragraph_with_image(
+        &mut self,
+        runs: &[InlineRun],
+        path: &std::path::Path,
+        alt: &str,
+        caption: Option<&str>,
+    ) {
+        if self.style.image.mode == ImageMode::Cover {
+            return;
+        }
+        let img = self.decode_image_file(path);
+        let image_h = img.as_ref().map(|img| {
+            let natural_w_pt = img.width() as f32 / IMAGE_DPI * 72.0;
+            let natural_h_pt = img.height() as f32 / IMAGE_DPI * 72.0;
+            let (w, h) = self.placed_image_size(natural_w_pt, natural_h_pt);
+            let caption_h = self
+                .image_caption(alt, caption)
+                .map_or(0.0, |text| self.caption_height_pt(text, w));
+            self.style.image.margin_before_pt + h + caption_h
+        });
+        self.pending_image = Some((path.to_path_buf(), img));
+        let Some(image_h) = image_h else {
+            return;
+        };
+
+        let p = &self.style.paragraph;
+        let lines = self.estimate_wrapped_lines(runs, p.font_size_pt, base_flags_from_block(p));
+        let para_h = p.margin_before_pt
+            + p.padding.top
+            + lines as f32 * p.font_size_pt * p.line_height.max(0.5)
+            + p.padding.bottom
+            + p.margin_after_pt;
+        let body_h = self.page_height_pt() - self.top_margin_pt() - self.bottom_margin_pt();
+        if para_h + image_h <= body_h {
+            self.keep_with_next_break(para_h, image_h);
+        }
+    }
+
+    /// Placed width and height of a block image with the given natural
+    /// size, per `[image] mode` and `max_width_pct`.
+    fn placed_image_size(&self, natural_w_pt: f32, natural_h_pt: f32) -> (f32, f32) {
+        let column_w_pt = match (self.style.image.mode, self.margin_span_pt()) {
+            (ImageMode::FullWidth, Some((left, right))) => right - left,
+            _ => self.content_width_pt(),
+        };
+        let scale = match self.style.image.mode {
+            // `full_width` fills the content column, growing small
+            // images and shrinking large ones — but never past the
+            // page's body height, so a tall banner still fits.
+            ImageMode::FullWidth => {
+                let body_h_pt =
+                    self.page_height_pt() - self.top_margin_pt() - self.bottom_margin_pt();
+                (column_w_pt / natural_w_pt).min(body_h_pt.max(1.0) / natural_h_pt)
+            }
+            // `image.max_width_pct` is a hard cap as a percentage of
+            // the content column. 100 = full column; smaller values
+            // shrink the image regardless of its natural size.
+            ImageMode::Inline | ImageMode::Cover => {
+                let cap_pct = self.style.image.max_width_pct.clamp(1.0, 100.0) / 100.0;
+                let max_w_pt = column_w_pt * cap_pct;
+                if natural_w_pt > max_w_pt {
+                    max_w_pt / natural_w_pt
+                } else {
+                    1.0
+                }
             }
         };
+        (natural_w_pt * scale, natural_h_pt * scale)
+    }
+
+    /// The caption drawn under an image: the title attribute, or with
+    /// `show_caption` the alt text when there's no title.
+    fn image_caption<'a>(&self, alt: &'a str, caption: Option<&'a str>) -> Option<&'a str> {
+        caption
+            .filter(|s| !s.trim().is_empty())
+            .or_else(|| Some(alt).filter(|s| self.style.image.show_caption && !s.trim().is_empty()))
+    }
+
+    /// Estimated height of an image caption wrapped to `width_pt`,
+    /// including its before-margin.
+    fn caption_height_pt(&self, text: &str, width_pt: f32) -> f32 {
+        let cap = &self.style.image.caption;
+        let text = match &self.style.image.caption_prefix {
+            Some(prefix) => format!("{}{}", prefix.replace("{n}", "00"), text),
+            None => text.to_string(),
+        };
+        let runs = [InlineRun {
+            math: None,
+            image: None,
+            text,
+            flags: RunFlags::default(),
+            link: None,
+        }];
+        let lines =
+            self.estimate_wrapped_lines(&runs, cap.font_size_pt, base_flags_from_block(cap));
+        // The estimate wraps at the column width; a narrower image
+        // wraps its caption sooner.
+        let column_w_pt = self.content_width_pt();
+        let lines = if width_pt > 0.0 && width_pt < column_w_pt {
+            (lines as f32 * column_w_pt / width_pt).ceil()
+        } else {
+            lines as f32
+        };
+        cap.margin_before_pt + lines * cap.font_size_pt * cap.line_height.max(0.5)
+    }
+
+    /// Draw a `mermaid` / `dot` diagram rendered by the `diagrams`
+    /// pass, centered in the column and shrunk to fit it. `[image]
+    /// mode` and `float` are for authored images and don't apply. A
+    /// PNG that fails to decode falls back to the source as a code
+    /// block, same as a missing tool would.
+    fn render_diagram(&mut self, png: &[u8], lines: &[String]) {
+        let label = std::path::Path::new("<diagram>");
+        let Some(img) = decode_image_bytes(png.to_vec(), label) else {
+            self.render_code_block(lines, &CodeAttrs::default());
+            return;
+        };
+        let natural_w_pt = img.width().max(1) as f32 / IMAGE_DPI * 72.0;
+        let natural_h_pt = img.height().max(1) as f32 / IMAGE_DPI * 72.0;
+        let column_w_pt = self.content_width_pt();
+        let body_h_pt = self.page_height_pt() - self.top_margin_pt() - self.bottom_margin_pt();
+        let scale = (column_w_pt / natural_w_pt)
+            .min(body_h_pt.max(1.0) / natural_h_pt)
+            .min(1.0);
+        let (w_pt, h_pt) = (natural_w_pt * scale, natural_h_pt * scale);
+        let Some(embedded) = self.embed_image(img, w_pt, h_pt) else {
+            self.render_code_block(lines, &CodeAttrs::default());
+            return;
+        };
 
-        let px_w = raw.width as f32;
-        let px_h = raw.height as f32;
-        let dpi = 300.0_f32;
+        self.advance_margin(self.style.image.margin_before_pt);
+        let room = self.page_height_pt() - self.bottom_margin_pt() - self.y_from_top_pt;
+        if h_pt > room {
+            self.advance_column();
+        }
+        let x_pt = self.indent_left_pt + ((column_w_pt - w_pt) / 2.0).max(0.0);
+        self.draw_embedded_image(&embedded, x_pt, h_pt);
+        self.y_from_top_pt += h_pt;
+        self.advance_margin(self.style.image.margin_after_pt);
+    }
+
+    fn render_decoded_image(&mut self, img: image::DynamicImage, alt: &str, caption: Option<&str>) {
+        let label = std::path::Path::new("<image>");
+        let px_w = img.width() as f32;
+        let px_h = img.height() as f32;
+        let dpi = IMAGE_DPI;
         let natural_w_pt = px_w / dpi * 72.0;
         let natural_h_pt = px_h / dpi * 72.0;
 
-        // `image.max_width_pct` is a hard cap as a percentage of the
-        // content column. 100 = full column; smaller values shrink the
-        // image regardless of its natural size.
+        if self.style.image.mode == ImageMode::Cover {
+            match to_raw_image(img, label) {
+                Some(raw) => self.render_cover_image(&raw, natural_w_pt, natural_h_pt, dpi),
+                None => self.render_image_fallback(alt),
+            }
+            return;
+        }
+
+        // A `full_width` image may run out past a narrowed measure to
+        // the page margins; the body's edges come back afterwards.
+        let span = match self.style.image.mode {
+            ImageMode::FullWidth => self.margin_span_pt(),
+            _ => None,
+        };
+        let Some((left, right)) = span else {
+            self.place_decoded_image(img, natural_w_pt, natural_h_pt, alt, caption);
+            return;
+        };
+        let saved = (self.indent_left_pt, self.indent_right_pt);
+        (self.indent_left_pt, self.indent_right_pt) = (left, right);
+        self.place_decoded_image(img, natural_w_pt, natural_h_pt, alt, caption);
+        (self.indent_left_pt, self.indent_right_pt) = saved;
+    }
+
+    /// Draw a block image at the cursor, sized and aligned within the
+    /// current indents.
+    fn place_decoded_image(
+        &mut self,
+        img: image::DynamicImage,
+        natural_w_pt: f32,
+        natural_h_pt: f32,
+        alt: &str,
+        caption: Option<&str>,
+    ) {
         let column_w_pt = self.content_width_pt();
-        let cap_pct = self.style.image.max_width_pct.clamp(1.0, 100.0) / 100.0;
-        let max_w_pt = column_w_pt * cap_pct;
-        let scale = if natural_w_pt > max_w_pt {
-            max_w_pt / natural_w_pt
-        } else {
-            1.0
+        let (rendered_w_pt, rendered_h_pt) = self.placed_image_size(natural_w_pt, natural_h_pt);
+        let Some(embedded) = self.embed_image(img, rendered_w_pt, rendered_h_pt) else {
+            self.render_image_fallback(alt);
+            return;
         };
-        let rendered_w_pt = natural_w_pt * scale;
-        let rendered_h_pt = natural_h_pt * scale;
 
-        self.advance_y(self.style.image.margin_before_pt);
-        if self.y_from_top_pt + rendered_h_pt + self.bottom_margin_pt() > self.page_height_pt() {
+        self.advance_margin(self.style.image.margin_before_pt);
+        // With `keep_with_caption` the caption counts toward the fit, so
+        // the pair moves on together; an image at the top of a column
+        // stays put, since moving it again wouldn't help.
+        let caption = self.image_caption(alt, caption);
+        let caption_h = match caption {
+            Some(text) if self.style.image.keep_with_caption => {
+                self.caption_height_pt(text, rendered_w_pt)
+            }
+            _ => 0.0,
+        };
+        let room = self.page_height_pt() - self.bottom_margin_pt() - self.y_from_top_pt;
+        let at_column_top = (self.y_from_top_pt - self.column_top_pt).abs() < 0.01;
+        if rendered_h_pt > room || (rendered_h_pt + caption_h > room && !at_column_top) {
             self.advance_column();
         }
 
-        let xobject_id: XObjectId = self.doc.add_image(&raw);
-        self.close_text_section();
+        // A floated image pins to its side; one too wide to leave a
+        // usable measure beside it, or nested in a list / quote (whose
+        // markers and borders would collide with it), stays on its own
+        // line.
+        let float = self.style.image.float;
+        let col = self.current_column;
+        let full_measure = (self.indent_left_pt - self.column_body_left_pt(col)).abs() < 0.01
+            && (self.indent_right_pt - self.column_body_right_pt(col)).abs() < 0.01;
+        let floats = float != ImageFloat::None
+            && full_measure
+            && rendered_w_pt + FLOAT_GAP_PT + MIN_FLOAT_TEXT_PT <= column_w_pt;
+        let align = match float {
+            _ if !floats => self.style.image.align,
+            ImageFloat::Left => ImageAlign::Left,
+            _ => ImageAlign::Right,
+        };
+        let top_y_pt = self.y_from_top_pt;
+        let page = self.raw_pages.len();
 
-        let page_h_pt = self.page_height_pt();
-        let x_pt = match self.style.image.align {
+        let x_pt = match align {
             ImageAlign::Left => self.indent_left_pt,
             ImageAlign::Right => self.indent_left_pt + (column_w_pt - rendered_w_pt).max(0.0),
             ImageAlign::Center => {
                 self.indent_left_pt + ((column_w_pt - rendered_w_pt) / 2.0).max(0.0)
             }
         };
+        self.draw_embedded_image(&embedded, x_pt, rendered_h_pt);
+        self.y_from_top_pt += rendered_h_pt;
+
+        if let Some(text) = caption {
+            self.draw_image_caption(text, x_pt, rendered_w_pt);
+        }
+
+        // Hand the strip to the following paragraphs and rewind to the
+        // image's top so they start beside it. A caption that spilled
+        // into the next column leaves nothing to wrap around.
+        if floats && self.raw_pages.len() == page && self.current_column == col {
+            self.float = Some(FloatRegion {
+                side: float,
+                width_pt: rendered_w_pt + FLOAT_GAP_PT,
+                bottom_pt: self.y_from_top_pt + self.style.image.margin_after_pt,
+            });
+            self.y_from_top_pt = top_y_pt;
+            return;
+        }
+        self.advance_margin(self.style.image.margin_after_pt);
+    }
+
+    /// Add `img` to the document for placement at `w_pt` by `h_pt`.
+    /// `[image] max_dpi` applies here: an image denser than the cap at
+    /// that size is resampled down to it, and the placement scale grows
+    /// to keep the same size on the page. `None` when the image can't
+    /// be converted (logged).
+    fn embed_image(
+        &mut self,
+        img: image::DynamicImage,
+        w_pt: f32,
+        h_pt: f32,
+    ) -> Option<EmbeddedImage> {
+        let px_w = img.width() as f32;
+        let img = match self.style.image.max_dpi {
+            Some(max_dpi) if px_w / (w_pt / 72.0) > max_dpi => {
+                let w = (w_pt / 72.0 * max_dpi).round().max(1.0) as u32;
+                let h = (h_pt / 72.0 * max_dpi).round().max(1.0) as u32;
+                img.resize_exact(w, h, image::imageops::FilterType::Triangle)
+            }
+            _ => img,
+        };
+        let scale_x = w_pt / (img.width() as f32 / IMAGE_DPI * 72.0);
+        let scale_y = h_pt / (img.height() as f32 / IMAGE_DPI * 72.0);
+        let raw = to_raw_image(img, std::path::Path::new("<image>"))?;
+        Some(EmbeddedImage {
+            id: self.doc.add_image(&raw),
+            scale_x,
+            scale_y,
+        })
+    }
+
+    /// `runs` with every inline image loaded. One that can't be is
+    /// turned back into plain text, its `[image: …]` fallback.
+    fn load_inline_images(&mut self, runs: &[InlineRun], size_pt: f32) -> Vec<InlineRun> {
+        let mut out = runs.to_vec();
+        for run in &mut out {
+            let Some(src) = run.image.clone() else {
+                continue;
+            };
+            if !self.inline_images.contains_key(&src) {
+                let loaded = self
+                    .decode_image_file(std::path::Path::new(&src))
+                    .and_then(|img| {
+                        let h_pt = size_pt.max(1.0);
+                        let w_pt = h_pt * img.width() as f32 / img.height().max(1) as f32;
+                        let image = self.embed_image(img, w_pt, h_pt)?;
+                        Some(InlineImage { image, w_pt, h_pt })
+                    });
+                self.inline_images.insert(src.clone(), loaded);
+            }
+            if matches!(self.inline_images.get(&src), Some(None)) {
+                run.image = None;
+            }
+        }
+        out
+    }
+
+    /// Width of loaded inline image `src` drawn `size_pt` tall.
+    fn inline_image_width(&self, src: &str, size_pt: f32) -> Option<f32> {
+        let loaded = self.inline_images.get(src)?.as_ref()?;
+        Some(loaded.w_pt * size_pt / loaded.h_pt)
+    }
+
+    /// Draw loaded inline image `src` `size_pt` tall with its left
+    /// edge at `x_pt`, dipping below the baseline as far as a
+    /// descender would. Returns its width.
+    fn draw_inline_image(
+        &mut self,
+        src: &str,
+        x_pt: f32,
+        baseline_y_pt: f32,
+        size_pt: f32,
+    ) -> Option<f32> {
+        let loaded = self.inline_images.get(src)?.as_ref()?;
+        let k = size_pt / loaded.h_pt;
+        let (id, scale_x, scale_y) = (
+            loaded.image.id.clone(),
+            loaded.image.scale_x * k,
+            loaded.image.scale_y * k,
+        );
+        let w_pt = loaded.w_pt * k;
+        self.close_text_section();
+        let y_bot_pt = self.page_height_pt() - baseline_y_pt - size_pt * 0.2;
+        self.page_ops.push(Op::UseXobject {
+            id,
+            transform: XObjectTransform {
+                translate_x: Some(Pt(x_pt)),
+                translate_y: Some(Pt(y_bot_pt)),
+                rotate: None,
+                scale_x: Some(scale_x),
+                scale_y: Some(scale_y),
+                dpi: Some(IMAGE_DPI),
+            },
+        });
+        Some(w_pt)
+    }
+
+    /// Draw an embedded image with its left edge at `x_pt` and its top
+    /// at the cursor. The cursor doesn't move.
+    fn draw_embedded_image(&mut self, image: &EmbeddedImage, x_pt: f32, h_pt: f32) {
+        self.close_text_section();
         // printpdf places the image at translate_x/translate_y from
         // the page's bottom-left.
-        let y_bot_pt = page_h_pt - self.y_from_top_pt - rendered_h_pt;
-
+        let y_bot_pt = self.page_height_pt() - self.y_from_top_pt - h_pt;
         self.page_ops.push(Op::UseXobject {
-            id: xobject_id,
+            id: image.id.clone(),
             transform: XObjectTransform {
                 translate_x: Some(Pt(x_pt)),
                 translate_y: Some(Pt(y_bot_pt)),
                 rotate: None,
-                scale_x: Some(scale),
-                scale_y: Some(scale),
-                dpi: Some(dpi),
+                scale_x: Some(image.scale_x),
+                scale_y: Some(image.scale_y),
+                dpi: Some(IMAGE_DPI),
             },
         });
-        self.y_from_top_pt += rendered_h_pt;
+    }
 
-        if let Some(text) = caption.filter(|s| !s.trim().is_empty()) {
-            // Caption line styled by `[image.caption]`, wrapped within
-            // the image's width when the image is narrower than the
-            // column.
-            let cap = self.style.image.caption.clone();
-            self.advance_y(cap.margin_before_pt);
-            let base_flags = base_flags_from_block(&cap);
-            let saved_left = self.indent_left_pt;
-            let saved_right = self.indent_right_pt;
-            let saved_column = self.current_column;
-            if rendered_w_pt < self.content_width_pt() {
-                self.indent_left_pt = x_pt;
-                self.indent_right_pt = x_pt + rendered_w_pt;
+    /// Caption line styled by `[image.caption]` below the cursor, with
+    /// the `caption_prefix` figure number applied, wrapped within the
+    /// image's width when the image is narrower than the column.
+    fn draw_image_caption(&mut self, text: &str, x_pt: f32, width_pt: f32) {
+        let text = match &self.style.image.caption_prefix {
+            Some(prefix) => {
+                self.figure_count += 1;
+                let n = self.figure_count.to_string();
+                format!("{}{}", prefix.replace("{n}", &n), text)
+            }
+            None => text.to_string(),
+        };
+        let cap = self.style.image.caption.clone();
+        self.advance_y(cap.margin_before_pt);
+        let base_flags = base_flags_from_block(&cap);
+        let saved_left = self.indent_left_pt;
+        let saved_right = self.indent_right_pt;
+        let saved_column = self.current_column;
+        if width_pt < self.content_width_pt() {
+            self.indent_left_pt = x_pt;
+            self.indent_right_pt = x_pt + width_pt;
+        }
+        let runs = vec![InlineRun {
+            math: None,
+            image: None,
+            text,
+            flags: RunFlags::default(),
+            link: None,
+        }];
+        let color = Some(rgb_color(cap.text_color_rgb()));
+        let saved_align = self.current_text_align;
+        self.current_text_align = cap.text_align;
+        self.write_wrapped_runs(&runs, cap.font_size_pt, cap.line_height, base_flags, color);
+        self.current_text_align = saved_align;
+        let (l, r) = self.rebase_indents(saved_left, saved_right, saved_column);
+        self.indent_left_pt = l;
+        self.indent_right_pt = r;
+    }
+
+    /// `[image] gallery`: a paragraph of two or more images laid out
+    /// `gallery_columns` to a row, each shrunk to fit its cell and
+    /// centered in it, `gallery_gap_pt` apart. A row moves to the next
+    /// page or column whole. Captions sit under their image; images
+    /// that fail to load fall back to their alt text after the grid.
+    fn render_gallery(&mut self, images: &[GalleryImage]) {
+        let cols = self.style.image.gallery_columns.max(1);
+        let gap = self.style.image.gallery_gap_pt.max(0.0);
+        let column_w_pt = self.content_width_pt();
+        let cell_w = ((column_w_pt - gap * (cols - 1) as f32) / cols as f32).max(1.0);
+
+        let mut failed: Vec<&str> = Vec::new();
+        let mut cells = Vec::with_capacity(images.len());
+        for image in images {
+            let Some(img) = self.decode_image_file(&image.path) else {
+                failed.push(&image.alt);
+                continue;
+            };
+            let natural_w_pt = img.width() as f32 / IMAGE_DPI * 72.0;
+            let natural_h_pt = img.height() as f32 / IMAGE_DPI * 72.0;
+            let scale = (cell_w / natural_w_pt).min(1.0);
+            let (w, h) = (natural_w_pt * scale, natural_h_pt * scale);
+            match self.embed_image(img, w, h) {
+                Some(embedded) => {
+                    let caption = self.image_caption(&image.alt, image.caption.as_deref());
+                    cells.push((embedded, w, h, caption));
+                }
+                None => failed.push(&image.alt),
             }
-            let runs = vec![InlineRun {
-                math: None,
-                text: text.to_string(),
-                flags: RunFlags::default(),
-                link: None,
-            }];
-            let color = Some(rgb_color(cap.text_color_rgb()));
-            let saved_align = self.current_text_align;
-            self.current_text_align = cap.text_align;
-            self.write_wrapped_runs(&runs, cap.font_size_pt, cap.line_height, base_flags, color);
-            self.current_text_align = saved_align;
-            let (l, r) = self.rebase_indents(saved_left, saved_right, saved_column);
-            self.indent_left_pt = l;
-            self.indent_right_pt = r;
         }
 
-        self.advance_y(self.style.image.margin_after_pt);
+        self.advance_margin(self.style.image.margin_before_pt);
+        for (row_idx, row) in cells.chunks(cols).enumerate() {
+            if row_idx > 0 {
+                self.advance_y(gap);
+            }
+            let row_h = row
+                .iter()
+                .map(|(_, w, h, caption)| {
+                    h + caption.map_or(0.0, |c| self.caption_height_pt(c, *w))
+                })
+                .fold(0.0f32, f32::max);
+            let room = self.page_height_pt() - self.bottom_margin_pt() - self.y_from_top_pt;
+            let at_column_top = (self.y_from_top_pt - self.column_top_pt).abs() < 0.01;
+            if row_h > room && !at_column_top {
+                self.advance_column();
+            }
+            let top = self.y_from_top_pt;
+            let mut bottom = top;
+            for (i, (embedded, w, h, caption)) in row.iter().enumerate() {
+                let cell_x = self.indent_left_pt + i as f32 * (cell_w + gap);
+                let x_pt = cell_x + (cell_w - w) / 2.0;
+                self.y_from_top_pt = top;
+                self.draw_embedded_image(embedded, x_pt, *h);
+                self.y_from_top_pt += h;
+                if let Some(text) = caption {
+                    self.draw_image_caption(text, x_pt, *w);
+                }
+                bottom = bottom.max(self.y_from_top_pt);
+            }
+            self.y_from_top_pt = bottom;
+        }
+        self.advance_margin(self.style.image.margin_after_pt);
+
+        for alt in failed {
+            self.render_image_fallback(alt);
+        }
+    }
+
+    /// `[image] mode = "cover"`: the image gets a page of its own,
+    /// scaled to cover the whole page (margins ignored) and centered,
+    /// with any overflow cropped by the page edges. Flow resumes at
+    /// the top of the following page; captions are not drawn.
+    fn render_cover_image(
+        &mut self,
+        raw: &RawImage,
+        natural_w_pt: f32,
+        natural_h_pt: f32,
+        dpi: f32,
+    ) {
+        // `start_new_page` never emits a blank page, so a cover at the
+        // very top of the document lands on page one.
+        self.start_new_page();
+        let page_w_pt = self.page_width_pt();
+        let page_h_pt = self.page_height_pt();
+        let scale = (page_w_pt / natural_w_pt).max(page_h_pt / natural_h_pt);
+        let rendered_w_pt = natural_w_pt * scale;
+        let rendered_h_pt = natural_h_pt * scale;
+
+        let xobject_id: XObjectId = self.doc.add_image(raw);
+        self.page_ops.push(Op::UseXobject {
+            id: xobject_id,
+            transform: XObjectTransform {
+                translate_x: Some(Pt((page_w_pt - rendered_w_pt) / 2.0)),
+                translate_y: Some(Pt((page_h_pt - rendered_h_pt) / 2.0)),
+                rotate: None,
+                scale_x: Some(scale),
+                scale_y: Some(scale),
+                dpi: Some(dpi),
+            },
+        });
+        self.start_new_page();
     }
 
+    /// A table, handled per `[table] overflow` when its columns,
+    /// unwrapped, are wider than the space it sits in.
     fn render_table(
         &mut self,
         headers: &[TableCell<InlineRun>],
@@ -2397,9 +3352,144 @@ impl<'a> Engine<'a> {
         if headers.is_empty() {
             return;
         }
+        let col_count = headers.len();
+        let mut table_rows: Vec<Vec<TableCell<InlineRun>>> = rows.to_vec();
+        for row in &mut table_rows {
+            row.resize_with(col_count, || TableCell::new(Vec::new()));
+            row.truncate(col_count);
+        }
+
+        let style = self.style;
+        let table = &style.table;
+        let natural = self.natural_column_widths(
+            headers,
+            &table_rows,
+            table.header.font_size_pt,
+            table.cell.font_size_pt,
+        );
+        let wanted: f32 = natural
+            .iter()
+            .map(|w| w.min(table.max_column_width_pt.unwrap_or(f32::INFINITY)))
+            .sum();
+        let room = self.content_width_pt();
+        if wanted <= room + 0.01 {
+            self.lay_out_table(headers, aligns, &table_rows, 1.0, None);
+            return;
+        }
+        match table.overflow {
+            TableOverflow::Wrap => self.lay_out_table(headers, aligns, &table_rows, 1.0, None),
+            TableOverflow::Scale => {
+                // Text width scales with the font size; the padding
+                // doesn't, so only the part above it shrinks.
+                let pad = table.cell_padding;
+                let pads = (pad.left + pad.right) * col_count as f32;
+                let scale = ((room - pads) / (wanted - pads).max(1.0)).clamp(MIN_TABLE_SCALE, 1.0);
+                self.lay_out_table(headers, aligns, &table_rows, scale, None);
+            }
+            TableOverflow::Rotate if self.at_body_level() => {
+                self.render_landscape_table(headers, aligns, &table_rows);
+            }
+            // Nested in a list or quote, or in columns, a page of its
+            // own would tear the enclosing block apart; wrap instead.
+            TableOverflow::Rotate => self.lay_out_table(headers, aligns, &table_rows, 1.0, None),
+            TableOverflow::Scroll => {
+                let floor = table.cell_padding.left + table.cell_padding.right + 1.0;
+                let min = table.min_column_width_pt.max(floor);
+                let mut used = 0.0;
+                let kept = natural
+                    .iter()
+                    .map(|w| {
+                        w.clamp(
+                            min,
+                            table.max_column_width_pt.unwrap_or(f32::INFINITY).max(min),
+                        )
+                    })
+                    .take_while(|w| {
+                        used += w;
+                        used <= room + 0.01
+                    })
+                    .count()
+                    .max(1);
+                let hidden = col_count - kept;
+                let note = (hidden > 0).then(|| {
+                    format!(
+                        "\u{2026} {} more column{} not shown",
+                        hidden,
+                        if hidden == 1 { "" } else { "s" }
+                    )
+                });
+                let headers = clip_table_row(headers, kept);
+                let table_rows: Vec<_> = table_rows
+                    .iter()
+                    .map(|row| clip_table_row(row, kept))
+                    .collect();
+                let aligns = &aligns[..kept.min(aligns.len())];
+                self.lay_out_table(&headers, aligns, &table_rows, 1.0, note.as_deref());
+            }
+        }
+    }
+
+    /// `true` when the cursor sits straight in the body of a single
+    /// column page, outside any list, quote or other nested block.
+    fn at_body_level(&self) -> bool {
+        self.num_columns == 1
+            && self.open_bg.is_empty()
+            && (self.indent_left_pt - self.column_body_left_pt(0)).abs() < 0.01
+            && (self.indent_right_pt - self.column_body_right_pt(0)).abs() < 0.01
+    }
+
+    /// `[table] overflow = "rotate"`: lay the table out on landscape
+    /// pages of its own, then carry on in portrait on a fresh page.
+    fn render_landscape_table(
+        &mut self,
+        headers: &[TableCell<InlineRun>],
+        aligns: &[crate::markdown::TableAlignment],
+        rows: &[Vec<TableCell<InlineRun>>],
+    ) {
+        self.start_new_page();
+        let saved_columns = self.snapshot_columns_single();
+        std::mem::swap(&mut self.page_width_mm, &mut self.page_height_mm);
+        let first = self.raw_pages.len();
+        self.enter_page_geometry();
+
+        self.lay_out_table(headers, aligns, rows, 1.0, None);
+        self.start_new_page();
+        self.landscape_pages.extend(first..self.raw_pages.len());
+
+        std::mem::swap(&mut self.page_width_mm, &mut self.page_height_mm);
+        self.restore_columns(saved_columns);
+        self.current_column = 0;
+        self.enter_page_geometry();
+    }
+
+    /// Put the cursor at the top of column 0 for the current page size:
+    /// the column width and both indents follow the page's body edges.
+    fn enter_page_geometry(&mut self) {
+        let (left, right) = body_edges_pt(&self.style.page, self.page_width_pt());
+        if self.num_columns <= 1 {
+            self.column_width_pt = (right - left).max(10.0);
+        }
+        self.y_from_top_pt = self.top_margin_pt();
+        self.column_top_pt = self.y_from_top_pt;
+        self.column_band_bottom_pt = self.y_from_top_pt;
+        self.indent_left_pt = self.column_body_left_pt(0);
+        self.indent_right_pt = self.column_body_right_pt(0);
+    }
 
-        let s_header = self.style.table.header.clone();
-        let s_cell = self.style.table.cell.clone();
+    /// Lay out a table with its cell text at `text_scale` times the
+    /// configured sizes, and `note` in lighter italics under it.
+    fn lay_out_table(
+        &mut self,
+        headers: &[TableCell<InlineRun>],
+        aligns: &[crate::markdown::TableAlignment],
+        table_rows: &[Vec<TableCell<InlineRun>>],
+        text_scale: f32,
+        note: Option<&str>,
+    ) {
+        let mut s_header = self.style.table.header.clone();
+        let mut s_cell = self.style.table.cell.clone();
+        s_header.font_size_pt *= text_scale;
+        s_cell.font_size_pt *= text_scale;
         // Table-level margins come from `[table]` directly (separate
         // from per-cell margins). Row gap comes from `[table.row_gap_pt]`.
         let before_pt = self.style.table.margin_before_pt;
@@ -2411,21 +3501,39 @@ impl<'a> Engine<'a> {
         let saved_letter_spacing = self.letter_spacing_pt;
         self.letter_spacing_pt = s_header.letter_spacing_pt;
 
-        self.advance_y(before_pt);
+        self.advance_margin(before_pt);
 
         let col_count = headers.len();
-        let total_width = self.content_width_pt();
-        // Floor the column wide enough that the inner cell box
+        // Floor every column wide enough that the inner cell box
         // (left+pad .. right-pad) can't invert.
         let pad = self.style.table.cell_padding;
-        let min_col_width_pt = pad.left + pad.right + 1.0;
-        let col_width = (total_width / col_count as f32).max(min_col_width_pt);
+        let widths = fit_column_widths(
+            &self.natural_column_widths(
+                headers,
+                table_rows,
+                s_header.font_size_pt,
+                s_cell.font_size_pt,
+            ),
+            self.style.table.min_column_width_pt,
+            pad.left + pad.right + 1.0,
+            self.style.table.max_column_width_pt,
+            self.content_width_pt(),
+        );
+        // Left edge of each column relative to the table, plus the
+        // right edge of the last one.
+        let col_x: Vec<f32> = std::iter::once(0.0)
+            .chain(widths.iter().scan(0.0, |x, w| {
+                *x += w;
+                Some(*x)
+            }))
+            .collect();
+        let table_width = col_x[col_count];
 
         let header_height = self.measure_row_height(
             headers,
             s_header.font_size_pt,
             s_header.line_height,
-            col_width,
+            &col_x,
             true,
         );
         let header_background = s_header.background_color_rgb();
@@ -2434,7 +3542,7 @@ impl<'a> Engine<'a> {
         }
         let header_top = self.y_from_top_pt;
         if let Some(bg) = header_background {
-            self.draw_table_row_background(header_top, header_height, col_width, col_count, bg);
+            self.draw_table_row_background(header_top, header_height, table_width, bg);
         }
         self.draw_row(
             headers,
@@ -2442,7 +3550,7 @@ impl<'a> Engine<'a> {
             RowLayout {
                 row_offset: 0,
                 row_heights: &[header_height],
-                col_width,
+                col_x: &col_x,
             },
             RowTextStyle {
                 font_size: s_header.font_size_pt,
@@ -2456,33 +3564,22 @@ impl<'a> Engine<'a> {
         self.advance_y(row_gap_pt);
 
         self.letter_spacing_pt = s_cell.letter_spacing_pt;
-        let mut table_rows: Vec<Vec<TableCell<InlineRun>>> = rows.to_vec();
-        for row in &mut table_rows {
-            row.resize_with(col_count, || TableCell::new(Vec::new()));
-            row.truncate(col_count);
-        }
         let row_heights = self.measure_table_row_heights(
-            &table_rows,
+            table_rows,
             s_cell.font_size_pt,
             s_cell.line_height,
-            col_width,
+            &col_x,
         );
         let mut row_idx = 0usize;
         while row_idx < table_rows.len() {
-            let group_end = rowspan_group_end(&table_rows, row_idx);
+            let group_end = rowspan_group_end(table_rows, row_idx);
             let group_height: f32 = row_heights[row_idx..group_end].iter().sum();
             if self.y_from_top_pt + group_height + self.bottom_margin_pt() > self.page_height_pt() {
                 self.advance_column();
                 // Reprint headers on the new column (or page).
                 let header_top = self.y_from_top_pt;
                 if let Some(bg) = header_background {
-                    self.draw_table_row_background(
-                        header_top,
-                        header_height,
-                        col_width,
-                        col_count,
-                        bg,
-                    );
+                    self.draw_table_row_background(header_top, header_height, table_width, bg);
                 }
                 self.draw_row(
                     headers,
@@ -2490,7 +3587,7 @@ impl<'a> Engine<'a> {
                     RowLayout {
                         row_offset: 0,
                         row_heights: &[header_height],
-                        col_width,
+                        col_x: &col_x,
                     },
                     RowTextStyle {
                         font_size: s_header.font_size_pt,
@@ -2512,8 +3609,7 @@ impl<'a> Engine<'a> {
                 self.draw_table_row_background(
                     group_top,
                     group_height,
-                    col_width,
-                    col_count,
+                    table_width,
                     (bg.r, bg.g, bg.b),
                 );
             }
@@ -2526,7 +3622,7 @@ impl<'a> Engine<'a> {
                     RowLayout {
                         row_offset: local_idx,
                         row_heights: group_heights,
-                        col_width,
+                        col_x: &col_x,
                     },
                     RowTextStyle {
                         font_size: s_cell.font_size_pt,
@@ -2541,20 +3637,40 @@ impl<'a> Engine<'a> {
             row_idx = group_end;
         }
 
+        if let Some(note) = note {
+            let base = base_flags_from_block(&s_cell).with_italic();
+            let (r, g, b) = s_cell.text_color_rgb();
+            let lighten = |c: u8| c + (255 - c) / 2;
+            let run = InlineRun {
+                math: None,
+                image: None,
+                text: note.to_string(),
+                flags: base,
+                link: None,
+            };
+            self.current_text_align = TextAlignment::Left;
+            self.write_wrapped_runs(
+                std::slice::from_ref(&run),
+                s_cell.font_size_pt,
+                s_cell.line_height,
+                base,
+                Some(rgb_color((lighten(r), lighten(g), lighten(b)))),
+            );
+        }
+
         self.letter_spacing_pt = saved_letter_spacing;
-        self.advance_y(after_pt);
+        self.advance_margin(after_pt);
     }
 
     fn draw_table_row_background(
         &mut self,
         row_top: f32,
         row_height: f32,
-        col_width: f32,
-        col_count: usize,
+        table_width: f32,
         bg: (u8, u8, u8),
     ) {
         let table_left = self.indent_left_pt;
-        let table_right = table_left + col_width * col_count as f32;
+        let table_right = table_left + table_width;
         let page_h = self.page_height_pt();
         let fill = rgb_color(bg);
         self.close_text_section();
@@ -2569,33 +3685,94 @@ impl<'a> Engine<'a> {
         );
     }
 
+    /// Widest single-line width of each column's content, cell padding
+    /// included: the header in `[table.header]` type, data cells in
+    /// `[table.cell]`. Cells spanning several columns don't count
+    /// toward any one of them.
+    fn natural_column_widths(
+        &mut self,
+        headers: &[TableCell<InlineRun>],
+        rows: &[Vec<TableCell<InlineRun>>],
+        header_size_pt: f32,
+        cell_size_pt: f32,
+    ) -> Vec<f32> {
+        let style = self.style;
+        let pad = style.table.cell_padding;
+        let (s_header, s_cell) = (&style.table.header, &style.table.cell);
+        let sources = std::iter::once((headers, s_header, header_size_pt, true)).chain(
+            rows.iter()
+                .map(|row| (row.as_slice(), s_cell, cell_size_pt, false)),
+        );
+        let saved_letter_spacing = self.letter_spacing_pt;
+        let mut widths = vec![0.0f32; headers.len()];
+        for (row, s, size_pt, bold) in sources {
+            self.letter_spacing_pt = s.letter_spacing_pt;
+            for (width, cell) in widths.iter_mut().zip(row) {
+                if cell.covered || cell.colspan > 1 {
+                    continue;
+                }
+                let content = self.measure_runs_width(&cell.content, size_pt, bold);
+                *width = width.max(content + pad.left + pad.right);
+            }
+        }
+        self.letter_spacing_pt = saved_letter_spacing;
+        widths
+    }
+
     fn measure_row_height(
         &self,
         cells: &[TableCell<InlineRun>],
         font_size: f32,
         line_height_mult: f32,
-        col_width: f32,
+        col_x: &[f32],
+        bold: bool,
+    ) -> f32 {
+        cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| !cell.covered)
+            .map(|(i, cell)| {
+                self.measure_cell_height(cell, i, font_size, line_height_mult, col_x, bold)
+            })
+            .fold(
+                self.measure_cell_lines_height(1, font_size, line_height_mult),
+                f32::max,
+            )
+    }
+
+    /// Height of `cell` set in the column(s) starting at `col`.
+    fn measure_cell_height(
+        &self,
+        cell: &TableCell<InlineRun>,
+        col: usize,
+        font_size: f32,
+        line_height_mult: f32,
+        col_x: &[f32],
         bold: bool,
     ) -> f32 {
-        let line_h = font_size * line_height_mult.max(0.5);
         let pad = self.style.table.cell_padding;
-        let mut max_lines = 1usize;
-        for cell in cells {
-            if cell.covered {
-                continue;
-            }
-            let n_lines = count_wrapped_lines(
-                &cell.content,
-                font_size,
-                line_height_mult,
-                col_width * cell.colspan.max(1) as f32 - (pad.left + pad.right),
-                self.font_set,
-                bold,
-                self.letter_spacing_pt,
-            );
-            max_lines = max_lines.max(n_lines);
-        }
-        max_lines as f32 * line_h + pad.top + pad.bottom
+        let end = (col + cell.colspan.max(1)).min(col_x.len() - 1);
+        let n_lines = count_wrapped_lines(
+            &cell.content,
+            font_size,
+            line_height_mult,
+            col_x[end] - col_x[col] - (pad.left + pad.right),
+            self.font_set,
+            bold,
+            self.letter_spacing_pt,
+        );
+        self.measure_cell_lines_height(n_lines, font_size, line_height_mult)
+    }
+
+    /// Padded height of a cell holding `lines` lines of text.
+    fn measure_cell_lines_height(
+        &self,
+        lines: usize,
+        font_size: f32,
+        line_height_mult: f32,
+    ) -> f32 {
+        let pad = self.style.table.cell_padding;
+        lines as f32 * font_size * line_height_mult.max(0.5) + pad.top + pad.bottom
     }
 
     fn measure_table_row_heights(
@@ -2603,25 +3780,20 @@ impl<'a> Engine<'a> {
         rows: &[Vec<TableCell<InlineRun>>],
         font_size: f32,
         line_height_mult: f32,
-        col_width: f32,
+        col_x: &[f32],
     ) -> Vec<f32> {
         let mut heights: Vec<f32> = rows
             .iter()
-            .map(|row| self.measure_row_height(row, font_size, line_height_mult, col_width, false))
+            .map(|row| self.measure_row_height(row, font_size, line_height_mult, col_x, false))
             .collect();
         for (r, row) in rows.iter().enumerate() {
-            for cell in row {
+            for (c, cell) in row.iter().enumerate() {
                 let span = cell.rowspan.max(1);
                 if cell.covered || span <= 1 || r + span > rows.len() {
                     continue;
                 }
-                let need = self.measure_row_height(
-                    std::slice::from_ref(cell),
-                    font_size,
-                    line_height_mult,
-                    col_width,
-                    false,
-                );
+                let need =
+                    self.measure_cell_height(cell, c, font_size, line_height_mult, col_x, false);
                 let have: f32 = heights[r..r + span].iter().sum();
                 if need > have {
                     let extra = (need - have) / span as f32;
@@ -2647,6 +3819,12 @@ impl<'a> Engine<'a> {
             + self.letter_spacing_pt * text.chars().count() as f32
     }
 
+    /// Advance of one char, letter spacing included — the per-char
+    /// step of [`Self::measure_text`].
+    fn measure_char(&self, flags: RunFlags, c: char, size_pt: f32) -> f32 {
+        self.font_set.char_advance(flags, c, size_pt) + self.letter_spacing_pt
+    }
+
     fn measure_runs_width(&self, runs: &[InlineRun], font_size: f32, bold: bool) -> f32 {
         let mut total = 0.0f32;
         for run in runs {
@@ -2669,7 +3847,7 @@ impl<'a> Engine<'a> {
         let RowLayout {
             row_offset,
             row_heights,
-            col_width,
+            col_x,
         } = layout;
         let RowTextStyle {
             font_size,
@@ -2690,8 +3868,8 @@ impl<'a> Engine<'a> {
             let colspan = cell.colspan.max(1).min(col_count - i);
             let rowspan = cell.rowspan.max(1).min(row_heights.len() - row_offset);
             let region_height: f32 = row_heights[row_offset..row_offset + rowspan].iter().sum();
-            let cell_left = saved_left + col_width * i as f32 + pad.left;
-            let cell_right = saved_left + col_width * (i + colspan) as f32 - pad.right;
+            let cell_left = saved_left + col_x[i] + pad.left;
+            let cell_right = saved_left + col_x[i + colspan] - pad.right;
             let inner_width = cell_right - cell_left;
             let mut runs = cell.content.clone();
             if bold {
@@ -2699,23 +3877,7 @@ impl<'a> Engine<'a> {
                     r.flags = r.flags.with_bold();
                 }
             }
-            // Single-line column alignment. Multi-line cells wrap from
-            // the shifted left edge — full per-line alignment is a
-            // Knuth-Plass-adjacent follow-up; the schema field works
-            // for the single-line case that 99% of tables use.
-            let measured = self.measure_runs_width(&runs, font_size, false);
-            let align = aligns
-                .get(i)
-                .copied()
-                .unwrap_or(crate::markdown::TableAlignment::Left);
-            let shift = match align {
-                crate::markdown::TableAlignment::Left => 0.0,
-                crate::markdown::TableAlignment::Center => {
-                    ((inner_width - measured) / 2.0).max(0.0)
-                }
-                crate::markdown::TableAlignment::Right => (inner_width - measured).max(0.0),
-            };
-            self.indent_left_pt = cell_left + shift;
+            self.indent_left_pt = cell_left;
             self.indent_right_pt = cell_right;
             // A row-spanning cell is vertically centered within the
             // merged region. Every other cell keeps the original
@@ -2736,6 +3898,13 @@ impl<'a> Engine<'a> {
             } else {
                 row_top + pad.top
             };
+            // Column alignment goes through the same per-line path as
+            // paragraph alignment, so a wrapped cell aligns every line.
+            let saved_align = self.current_text_align;
+            self.current_text_align = aligns
+                .get(i)
+                .copied()
+                .map_or(TextAlignment::Left, Into::into);
             self.write_wrapped_runs(
                 &runs,
                 font_size,
@@ -2743,8 +3912,14 @@ impl<'a> Engine<'a> {
                 RunFlags::default(),
                 Some(rgb_color(color)),
             );
+            self.current_text_align = saved_align;
             self.indent_left_pt = saved_left;
-            self.draw_cell_border(row_top, row_top + region_height, i, i + colspan, col_width);
+            self.draw_cell_border(
+                row_top,
+                row_top + region_height,
+                col_x[i],
+                col_x[i + colspan],
+            );
         }
         let (l, r) = self.rebase_indents(saved_left, saved_right, saved_column);
         self.indent_left_pt = l;
@@ -2752,20 +3927,13 @@ impl<'a> Engine<'a> {
         self.y_from_top_pt = row_top;
     }
 
-    fn draw_cell_border(
-        &mut self,
-        row_top: f32,
-        row_bottom: f32,
-        col_start: usize,
-        col_end: usize,
-        col_width: f32,
-    ) {
+    fn draw_cell_border(&mut self, row_top: f32, row_bottom: f32, x_start: f32, x_end: f32) {
         self.close_text_section();
         let page_h = self.page_height_pt();
         let border_color = rgb_color((180, 180, 180));
         let left = self.indent_left_pt;
-        let x0 = left + col_width * col_start as f32;
-        let x1 = left + col_width * col_end as f32;
+        let x0 = left + x_start;
+        let x1 = left + x_end;
         // Horizontal lines: top and bottom of the row.
         draw_horizontal_line(
             &mut self.page_ops,
@@ -2798,6 +3966,9 @@ impl<'a> Engine<'a> {
         // CommonMark §5.3: the whole list is loose if any item is loose.
         // Pre-compute once so every iteration uses the same gap.
         let any_loose = entries.iter().any(|e| e.loose);
+        // `[list].align_numbers`: width of the widest number in this
+        // list, measured on its first ordered item.
+        let mut number_gutter: Option<f32> = None;
 
         for (idx, entry) in entries.iter().enumerate() {
             let mut list_style: ResolvedList = match entry.bullet {
@@ -2828,15 +3999,31 @@ impl<'a> Engine<'a> {
                 list_style.item_spacing_tight_pt
             };
 
-            let bullet_text = format_bullet(&entry.bullet, &list_style);
+            let bullet_text = format_bullet(&entry.bullet, &list_style, self.style.digits);
             let bullet_flags = RunFlags::default();
             let bullet_width = self.measure_text(bullet_flags, &bullet_text, size_pt);
+            // Numbers right-align in the gutter so every item's text
+            // starts at the same x; other markers keep their own width.
+            let gutter_width = match entry.bullet {
+                ListBullet::Ordered(_) if self.style.list_align_numbers => *number_gutter
+                    .get_or_insert_with(|| {
+                        entries
+                            .iter()
+                            .filter(|e| matches!(e.bullet, ListBullet::Ordered(_)))
+                            .map(|e| {
+                                let text = format_bullet(&e.bullet, &list_style, self.style.digits);
+                                self.measure_text(bullet_flags, &text, size_pt)
+                            })
+                            .fold(bullet_width, f32::max)
+                    }),
+                _ => bullet_width,
+            };
 
             // First item: honor `block.margin_before_pt` (list-level
             // "space before the whole list"). Subsequent items use the
             // tight/loose inter-item gap.
             if idx == 0 {
-                self.advance_y(s.margin_before_pt.max(0.5));
+                self.advance_margin(s.margin_before_pt.max(0.5));
             } else {
                 self.advance_y(inter_item_gap.max(0.0));
             }
@@ -2847,13 +4034,13 @@ impl<'a> Engine<'a> {
             // text wraps onto the next page, and the glyph is left
             // alone at the bottom of the previous page. Skip at column
             // top (already maximum room).
-            if (self.y_from_top_pt - self.top_margin_pt()).abs() >= 0.01 {
+            if (self.y_from_top_pt - self.column_top_pt).abs() >= 0.01 {
                 let line_h = size_pt * line_height.max(0.5);
                 if self.y_from_top_pt + line_h + self.bottom_margin_pt() > self.page_height_pt() {
                     self.advance_column();
                 }
             }
-            let bullet_x = saved_left;
+            let bullet_x = saved_left + (gutter_width - bullet_width);
             let bullet_y = self.y_from_top_pt + size_pt;
             // An unordered bullet whose configured glyph the active
             // font can't represent (the default `•` under built-in
@@ -2864,7 +4051,11 @@ impl<'a> Engine<'a> {
             // get a real checkbox rather than literal `[ ]`/`[x]`.
             let needs_xlit = self.font_set.needs_transliteration(bullet_flags);
             let glyph_unrepresentable = needs_xlit && to_win1252(&bullet_text) != bullet_text;
-            let bullet_col = rgb_color(s.text_color_rgb());
+            let bullet_col = rgb_color(
+                list_style
+                    .marker_color
+                    .map_or_else(|| s.text_color_rgb(), |c| (c.r, c.g, c.b)),
+            );
             let page_h = self.page_height_pt();
             // Vertical centre of the lowercase text the bullet sits
             // beside (baseline is `bullet_y`).
@@ -2933,15 +4124,19 @@ impl<'a> Engine<'a> {
                 }
             }
 
-            let text_indent = (saved_left + bullet_width + list_style.bullet_gap_pt)
+            let text_indent = (saved_left + gutter_width + list_style.bullet_gap_pt)
                 .min(self.indent_right_pt - 10.0);
             self.indent_left_pt = text_indent;
 
+            let base_flags = RunFlags {
+                blockquote: self.in_blockquote,
+                ..base_flags_from_block(s)
+            };
             self.write_wrapped_runs(
                 &entry.runs,
                 size_pt,
                 line_height,
-                base_flags_from_block(s),
+                base_flags,
                 Some(rgb_color(s.text_color_rgb())),
             );
 
@@ -2966,7 +4161,7 @@ impl<'a> Engine<'a> {
             // "space after the whole list"). The inter-item gap is
             // applied at the *start* of the next iteration.
             if idx + 1 == entries.len() {
-                self.advance_y(s.margin_after_pt.max(0.0));
+                self.advance_margin(s.margin_after_pt);
             }
         }
         self.letter_spacing_pt = saved_letter_spacing;
@@ -2982,10 +4177,12 @@ impl<'a> Engine<'a> {
         let ctx = self.begin_block(&s);
         let saved_override = self.text_style_override.take();
         self.text_style_override = Some(s.clone());
+        let saved_in_blockquote = std::mem::replace(&mut self.in_blockquote, true);
         let mut it = body.iter().peekable();
         while let Some(child) = it.next() {
             self.render_block(child, it.peek().copied());
         }
+        self.in_blockquote = saved_in_blockquote;
         self.text_style_override = saved_override;
         self.end_block(ctx);
     }
@@ -3062,6 +4259,7 @@ impl<'a> Engine<'a> {
                 };
                 vec![InlineRun {
                     math: None,
+                    image: None,
                     text: label_text,
                     flags: RunFlags::default().with_bold(),
                     link: None,
@@ -3125,10 +4323,23 @@ impl<'a> Engine<'a> {
         self.end_block(ctx);
     }
 
-    fn render_heading(&mut self, level: u8, runs: &[InlineRun], next: Option<&Block>) {
+    fn render_heading(
+        &mut self,
+        level: u8,
+        runs: &[InlineRun],
+        id: Option<&str>,
+        source_text: &str,
+        next: Option<&Block>,
+    ) {
         let idx = level.clamp(1, 6) as usize - 1;
         let s = self.style.headings[idx].clone();
-        let base_flags = base_flags_from_block(&s);
+        let base_flags = heading_flags_from_block(&s);
+        // Slugs come from the source spelling so `#links` written
+        // against it keep resolving; everything drawn uses the recased
+        // runs. The TOC and bookmarks show the recased plain text,
+        // without footnote markers or image placeholders.
+        let transformed = transform_heading_runs(runs, self.style.headings_transform);
+        let runs = transformed.as_slice();
         let line_count = self.estimate_wrapped_lines(runs, s.font_size_pt, base_flags);
         let header_h = s.margin_before_pt
             + s.padding.top
@@ -3139,21 +4350,29 @@ impl<'a> Engine<'a> {
         self.keep_with_next_break(header_h, follow_h);
         let color = Some(rgb_color(s.text_color_rgb()));
 
-        let text = collect_heading_text(runs);
-        let base_slug = {
-            let s = slugify(&text);
-            if s.is_empty() {
-                "section".to_string()
-            } else {
-                s
+        let text = transform_heading_text(source_text, self.style.headings_transform);
+        // An explicit `{#id}` is the author's chosen name and is used
+        // verbatim; only auto slugs get the `-2`, `-3`, … suffixes.
+        let slug = match id {
+            Some(id) => id.to_string(),
+            None => {
+                let base_slug = {
+                    let s = slugify(source_text);
+                    if s.is_empty() {
+                        "section".to_string()
+                    } else {
+                        s
+                    }
+                };
+                let mut slug = base_slug.clone();
+                let mut n = 2usize;
+                while self.used_slugs.contains(&slug) {
+                    slug = format!("{}-{}", base_slug, n);
+                    n += 1;
+                }
+                slug
             }
         };
-        let mut slug = base_slug.clone();
-        let mut n = 2usize;
-        while self.used_slugs.contains(&slug) {
-            slug = format!("{}-{}", base_slug, n);
-            n += 1;
-        }
         self.used_slugs.insert(slug.clone());
         // The bookmark / GoTo target is the heading's TOP y (before
         // begin_block consumes margin_before_pt + padding).
@@ -3180,7 +4399,7 @@ impl<'a> Engine<'a> {
         self.end_block(ctx);
     }
 
-    fn render_paragraph(&mut self, runs: &[InlineRun]) {
+    fn render_paragraph(&mut self, runs: &[InlineRun], prev: BlockKind) {
         let mut s = self.style.paragraph.clone();
         // Inside a blockquote / admonition, body paragraphs inherit
         // the container's text typography; structural fields (margins,
@@ -3199,8 +4418,20 @@ impl<'a> Engine<'a> {
             s.letter_spacing_pt = ov.letter_spacing_pt;
             s.indent_pt = ov.indent_pt;
         }
+        // The before-margin collapses into the previous paragraph's
+        // after-margin, so the gap ends up `max(gap, margin_after_pt)`.
+        if let Some(gap) = self.style.paragraph_spacing_pt {
+            match prev {
+                BlockKind::Heading => s.margin_before_pt = 0.0,
+                BlockKind::Paragraph => s.margin_before_pt = gap,
+                BlockKind::Other => {}
+            }
+        }
         let color = Some(rgb_color(s.text_color_rgb()));
-        let base = base_flags_from_block(&s);
+        let base = RunFlags {
+            blockquote: self.in_blockquote,
+            ..base_flags_from_block(&s)
+        };
         let ctx = self.begin_block(&s);
         let owned_runs;
         let runs_ref: &[InlineRun] = if s.small_caps {
@@ -3216,17 +4447,57 @@ impl<'a> Engine<'a> {
         self.end_block(ctx);
     }
 
-    fn render_code_block(&mut self, lines: &[String]) {
-        let s = self.style.code_block.clone();
+    fn render_code_block(&mut self, lines: &[String], attrs: &CodeAttrs) {
+        self.render_code_lines(lines, attrs, None);
+    }
+
+    /// A code block cut short by `[code_block] max_lines`: the kept
+    /// `lines`, then a "… N more lines" note in a lighter shade of the
+    /// code colour.
+    fn render_truncated_code_block(&mut self, lines: &[String], attrs: &CodeAttrs, hidden: usize) {
+        let note = format!(
+            "\u{2026} {} more line{}",
+            hidden,
+            if hidden == 1 { "" } else { "s" }
+        );
+        self.render_code_lines(lines, attrs, Some(&note));
+    }
+
+    /// The block body, under its `caption` when the fence has one,
+    /// with the `highlight` lines set on a tinted band.
+    fn render_code_lines(&mut self, lines: &[String], attrs: &CodeAttrs, note: Option<&str>) {
+        let mut s = self.style.code_block.clone();
+        if let Some(caption) = &attrs.caption {
+            self.draw_code_caption(caption, &s);
+            s.margin_before_pt = 0.0;
+        }
+        let tint = self.style.code_block_highlight;
         let color = Some(rgb_color(s.text_color_rgb()));
         let base = base_flags_from_block(&s).with_monospace();
         let ctx = self.begin_block(&s);
         self.in_code_block = true;
-        self.current_text_align = s.text_align;
+        // Justifying would stretch the spaces that line up columns and
+        // ASCII art, so a justified code block sets flush left.
+        self.current_text_align = match s.text_align {
+            TextAlignment::Justify => TextAlignment::Left,
+            align => align,
+        };
         self.first_line_indent_pt = s.indent_pt;
-        for line in lines {
+        for (i, line) in lines.iter().enumerate() {
+            let highlighted = attrs.highlights(i + 1);
+            if highlighted {
+                self.close_text_section();
+                self.open_bg.push(OpenBlockBg {
+                    x_left: ctx.outer_x_left,
+                    x_right: ctx.outer_x_right,
+                    top_y: self.y_from_top_pt,
+                    color: (tint.r, tint.g, tint.b),
+                    marker: self.page_ops.len(),
+                });
+            }
             let run = InlineRun {
                 math: None,
+                image: None,
                 text: line.clone(),
                 flags: base,
                 link: None,
@@ -3238,21 +4509,79 @@ impl<'a> Engine<'a> {
                 base,
                 color.clone(),
             );
+            if highlighted {
+                self.close_text_section();
+                self.close_open_bg();
+            }
+        }
+        if let Some(note) = note {
+            let (r, g, b) = s.text_color_rgb();
+            let lighten = |c: u8| c + (255 - c) / 2;
+            let run = InlineRun {
+                math: None,
+                image: None,
+                text: note.to_string(),
+                flags: base,
+                link: None,
+            };
+            self.write_wrapped_runs(
+                std::slice::from_ref(&run),
+                s.font_size_pt,
+                s.line_height,
+                base,
+                Some(rgb_color((lighten(r), lighten(g), lighten(b)))),
+            );
         }
         self.current_text_align = TextAlignment::Left;
         self.in_code_block = false;
         self.end_block(ctx);
     }
 
+    /// A fence's `caption` above its block, styled by
+    /// `[code_block.caption]`. The block's top margin goes above the
+    /// caption, and the pair moves to the next column when the caption
+    /// and the block's first line won't both fit.
+    fn draw_code_caption(&mut self, text: &str, block: &ResolvedBlock) {
+        let cap = self.style.code_block_caption.clone();
+        self.advance_margin(block.margin_before_pt);
+        let runs = vec![InlineRun {
+            math: None,
+            image: None,
+            text: text.to_string(),
+            flags: RunFlags::default(),
+            link: None,
+        }];
+        let base_flags = base_flags_from_block(&cap);
+        let lines = self.estimate_wrapped_lines(&runs, cap.font_size_pt, base_flags) as f32;
+        let needed = cap.margin_before_pt
+            + lines * cap.font_size_pt * cap.line_height.max(0.5)
+            + cap.margin_after_pt
+            + block.padding.top
+            + block.font_size_pt * block.line_height;
+        let room = self.page_height_pt() - self.bottom_margin_pt() - self.y_from_top_pt;
+        let at_column_top = (self.y_from_top_pt - self.column_top_pt).abs() < 0.01;
+        if needed > room && !at_column_top {
+            self.advance_column();
+        }
+        self.advance_y(cap.margin_before_pt);
+        let color = Some(rgb_color(cap.text_color_rgb()));
+        let saved_align = self.current_text_align;
+        self.current_text_align = cap.text_align;
+        self.write_wrapped_runs(&runs, cap.font_size_pt, cap.line_height, base_flags, color);
+        self.current_text_align = saved_align;
+        self.advance_y(cap.margin_after_pt);
+    }
+
     fn render_horizontal_rule(&mut self) {
         self.close_text_section();
 
-        let s = &self.style.horizontal_rule;
+        let s = self.style.horizontal_rule;
         let thickness = s.thickness_pt.max(0.1);
         let color = rgb_color(s.color_rgb());
         let dash = dash_pattern_for(s.style);
 
-        self.advance_y(s.margin_before_pt + thickness * 0.5);
+        self.advance_margin(s.margin_before_pt);
+        self.advance_y(thickness * 0.5);
 
         // The rule spans the current column / block region — using the
         // active indents instead of the page margins keeps it inside a
@@ -3260,8 +4589,9 @@ impl<'a> Engine<'a> {
         // single-column document with no enclosing block, these are
         // exactly the page margins, so the line is identical to the
         // pre-column rendering.
-        let mut x_left_pt = self.indent_left_pt;
-        let mut x_right_pt = self.indent_right_pt;
+        let (mut x_left_pt, mut x_right_pt) = self
+            .margin_span_pt()
+            .unwrap_or((self.indent_left_pt, self.indent_right_pt));
         let pct = (s.width_pct / 100.0).clamp(0.05, 1.0);
         if pct < 1.0 {
             let full = x_right_pt - x_left_pt;
@@ -3289,7 +4619,7 @@ impl<'a> Engine<'a> {
             },
         );
 
-        self.advance_y(s.margin_after_pt);
+        self.advance_margin(s.margin_after_pt);
     }
 
     /// Wrap `runs` to the page width and emit one ShowText per line.
@@ -3330,9 +4660,18 @@ impl<'a> Engine<'a> {
             &merged_runs
         };
 
+        // Inline images that can't be loaded fall back to their
+        // `[image: …]` text before wrapping.
+        let loaded;
+        let runs = if runs.iter().any(|r| r.image.is_some()) {
+            loaded = self.load_inline_images(runs, size_pt);
+            &loaded[..]
+        } else {
+            runs
+        };
         // Split runs into a flat sequence of (word, flags) pairs.
         // Whitespace is the only break opportunity in this phase.
-        let mut words = words_from_runs(runs);
+        let mut words = words_from_runs(runs, self.style.text.collapse_whitespace);
         if words.is_empty() {
             return;
         }
@@ -3340,8 +4679,10 @@ impl<'a> Engine<'a> {
         let max_width = self.content_width_pt();
         // Any word that on its own exceeds the column width gets
         // chopped at character boundaries so the chunks each fit. URLs,
-        // long identifiers, CJK runs without spaces, etc.
-        words = self.split_long_words(words, max_width, size_pt);
+        // long identifiers, CJK runs without spaces, etc. Beside a
+        // floated image the narrower measure is the limit.
+        let float_w = self.float_inset(self.y_from_top_pt).1;
+        words = self.split_long_words(words, max_width - float_w, size_pt);
         // `[code_inline].padding` is applied to the first / last word
         // of each contiguous inline-code span: pad.left on the first,
         // pad.right on the last. Middle words and runs that aren't
@@ -3374,28 +4715,45 @@ impl<'a> Engine<'a> {
 
         for (wi, word) in words.iter().enumerate() {
             let (pad_before_pt, pad_after_pt) = word_pads[wi];
-            let word_width = match &word.math {
-                Some(tex) => self
+            let visible = word.text.strip_suffix(SOFT_HYPHEN);
+            let word_width = match (&word.math, &word.image) {
+                (Some(tex), _) => self
                     .inline_math_frag(tex, size_pt)
                     .map(|f| f.w)
                     .unwrap_or(0.0),
-                None => self.measure_text(word.flags, &word.text, size_pt),
+                (None, Some(src)) => self.inline_image_width(src, size_pt).unwrap_or(0.0),
+                (None, None) => self.measure_text(
+                    word.flags,
+                    visible.unwrap_or(&word.text),
+                    self.emphasis_size(word.flags, size_pt),
+                ),
             } + pad_before_pt
                 + pad_after_pt;
-
-            // The first line is narrowed by the first-line indent.
-            let line_limit = if lines.is_empty() {
-                max_width - first_line_indent_pt
+            // A piece ending in a soft hyphen needs room for the `-`
+            // it shows if the line breaks after it.
+            let hyphen_pt = if visible.is_some() {
+                self.measure_text(word.flags, "-", self.emphasis_size(word.flags, size_pt))
             } else {
-                max_width
+                0.0
             };
+
+            // The first line is narrowed by the first-line indent, and
+            // lines beside a floated image by the image's strip.
+            let line_top_pt = self.y_from_top_pt + lines.len() as f32 * line_height_pt;
+            let line_limit = max_width
+                - self.float_inset(line_top_pt).1
+                - if lines.is_empty() {
+                    first_line_indent_pt
+                } else {
+                    0.0
+                };
             // If the very first piece of a line is wider than the
             // page, push it anyway — we don't break inside a word.
-            if !current.is_empty() && current_width + word_width > line_limit {
+            if !current.is_empty() && current_width + word_width + hyphen_pt > line_limit {
                 lines.push(std::mem::take(&mut current));
                 current_width = 0.0;
                 // Drop any leading whitespace on the new line.
-                if word.math.is_none() && word.text.trim().is_empty() {
+                if word.math.is_none() && word.image.is_none() && word.text.trim().is_empty() {
                     continue;
                 }
             }
@@ -3405,6 +4763,7 @@ impl<'a> Engine<'a> {
                 flags: word.flags,
                 link: word.link.clone(),
                 math: word.math.clone(),
+                image: word.image.clone(),
                 pad_before_pt,
                 pad_after_pt,
             });
@@ -3414,6 +4773,24 @@ impl<'a> Engine<'a> {
             lines.push(current);
         }
 
+        // Soft hyphens: one that ends a broken line becomes a visible
+        // `-`; every other one vanishes.
+        let last_line_idx = lines.len().saturating_sub(1);
+        for (line_idx, line) in lines.iter_mut().enumerate() {
+            if line_idx != last_line_idx
+                && let Some(seg) = line.last_mut()
+                && seg.text.ends_with(SOFT_HYPHEN)
+            {
+                seg.text.pop();
+                seg.text.push('-');
+            }
+            for seg in line.iter_mut() {
+                if seg.text.contains(SOFT_HYPHEN) {
+                    seg.text.retain(|c| c != SOFT_HYPHEN);
+                }
+            }
+        }
+
         // Merge adjacent segments on each line that share identical
         // flags + link. The wrap stage split text into per-word /
         // per-whitespace pieces to make line-break decisions; once
@@ -3427,6 +4804,8 @@ impl<'a> Engine<'a> {
             line.dedup_by(|next, prev| {
                 if prev.math.is_none()
                     && next.math.is_none()
+                    && prev.image.is_none()
+                    && next.image.is_none()
                     && prev.flags == next.flags
                     && prev.link == next.link
                 {
@@ -3448,7 +4827,6 @@ impl<'a> Engine<'a> {
         // of this paragraph stay inside one BT and use T*.
         self.close_text_section();
         let align = self.current_text_align;
-        let last_line_idx = lines.len().saturating_sub(1);
         let mut prev_line_x_start = 0.0f32;
         let mut prev_baseline_y_pt = 0.0f32;
         for (line_idx, line) in lines.iter().enumerate() {
@@ -3478,15 +4856,11 @@ impl<'a> Engine<'a> {
                         .unwrap_or(0.0);
                     continue;
                 }
-                let s_size = if seg.flags.superscript || seg.flags.subscript {
-                    size_pt * 0.70
-                } else if seg.flags.small_caps {
-                    size_pt * 0.78
-                } else if seg.flags.small {
-                    size_pt * 0.85
-                } else {
-                    size_pt
-                };
+                if let Some(src) = &seg.image {
+                    natural_w_pt += self.inline_image_width(src, size_pt).unwrap_or(0.0);
+                    continue;
+                }
+                let (s_size, _) = self.segment_metrics(seg.flags, size_pt, 0.0);
                 natural_w_pt += self.measure_text(seg.flags, &seg.text, s_size)
                     + seg.pad_before_pt
                     + seg.pad_after_pt;
@@ -3501,8 +4875,9 @@ impl<'a> Engine<'a> {
             } else {
                 0.0
             };
-            let eff_left = self.indent_left_pt + line_indent;
-            let eff_max_width = (max_width - line_indent).max(0.0);
+            let (float_shift, float_w) = self.float_inset(self.y_from_top_pt);
+            let eff_left = self.indent_left_pt + line_indent + float_shift;
+            let eff_max_width = (max_width - line_indent - float_w).max(0.0);
             let slack_pt = (eff_max_width - natural_w_pt).max(0.0);
             let is_last_line = line_idx == last_line_idx;
 
@@ -3512,21 +4887,32 @@ impl<'a> Engine<'a> {
                 TextAlignment::Right => (eff_left + slack_pt, 0.0),
                 TextAlignment::Justify => {
                     // Don't justify the last line of a paragraph, lines
-                    // with no break opportunities, or lines whose slack
-                    // would stretch spaces beyond ~30% of the column
-                    // (a sign the wrap had no good fit, like an isolated
-                    // short word).
-                    let stretch_ok =
-                        space_count > 0 && slack_pt > 0.0 && slack_pt < eff_max_width * 0.30;
-                    let tw = if !is_last_line && stretch_ok {
-                        (slack_pt / space_count as f32).min(size_pt * 0.5)
+                    // with no break opportunities, or lines whose spaces
+                    // would each have to grow by more than
+                    // `[text] max_space_stretch` times their own width
+                    // (a sign the wrap had no good fit, like two long
+                    // words in a narrow column). Those are set flush
+                    // left (ragged right) rather than open a river.
+                    let space_w_pt = self.measure_text(RunFlags::default(), " ", size_pt);
+                    let tw = if space_count > 0 && slack_pt > 0.0 {
+                        slack_pt / space_count as f32
                     } else {
                         0.0
                     };
-                    (eff_left, tw)
+                    let stretch_ok = tw > 0.0
+                        && self
+                            .style
+                            .text
+                            .max_space_stretch
+                            .is_none_or(|max| tw <= space_w_pt * max);
+                    (eff_left, if !is_last_line && stretch_ok { tw } else { 0.0 })
                 }
             };
-            let needs_absolute_td = !matches!(align, TextAlignment::Left | TextAlignment::Justify);
+            // `T*` keeps the previous line's left edge, so a line that
+            // starts elsewhere (after a first-line indent, or once a
+            // float ends) needs an explicit move too.
+            let needs_absolute_td = !matches!(align, TextAlignment::Left | TextAlignment::Justify)
+                || (line_x_start - prev_line_x_start).abs() > 0.01;
 
             if opened_now {
                 self.move_cursor_to(line_x_start, baseline_y_pt);
@@ -3579,22 +4965,38 @@ impl<'a> Engine<'a> {
                     }
                     continue;
                 }
-                // Superscript: render at 70% size on a baseline raised
-                // by ~32% of the original size. Implemented as a
-                // self-contained little text section so it doesn't
-                // disturb the line's main BT/ET. The next segment
-                // re-establishes its cursor via Td.
-                let (seg_size, seg_baseline) = if seg.flags.superscript {
-                    (size_pt * 0.70, baseline_y_pt - size_pt * 0.32)
-                } else if seg.flags.subscript {
-                    (size_pt * 0.70, baseline_y_pt + size_pt * 0.20)
-                } else if seg.flags.small_caps {
-                    (size_pt * 0.78, baseline_y_pt)
-                } else if seg.flags.small {
-                    (size_pt * 0.85, baseline_y_pt)
-                } else {
-                    (size_pt, baseline_y_pt)
-                };
+                // Inline image: the same break-out as math, drawn as
+                // an XObject sitting on the baseline.
+                if let Some(src) = &seg.image {
+                    if let Some(w) =
+                        self.draw_inline_image(src, x_cursor_pt, baseline_y_pt, size_pt)
+                    {
+                        // A linked image (a README badge) keeps the
+                        // link rect its alt-text placeholder had.
+                        if seg.link.is_some() {
+                            self.pending_decorations.push(PendingDecoration {
+                                kind: DecorationKind::None,
+                                x0_pt: x_cursor_pt,
+                                x1_pt: x_cursor_pt + w,
+                                y_pt: baseline_y_pt,
+                                link: seg.link.clone(),
+                                size_pt,
+                                baseline_y_pt,
+                            });
+                        }
+                        x_cursor_pt += w;
+                        cursor_needs_reset = true;
+                        line_was_broken = true;
+                    }
+                    continue;
+                }
+                // Super/subscript render smaller on a shifted baseline
+                // (see `segment_metrics`), each in a self-contained
+                // little text section so it doesn't disturb the line's
+                // main BT/ET. The next segment re-establishes its
+                // cursor via Td.
+                let (seg_size, seg_baseline) =
+                    self.segment_metrics(seg.flags, size_pt, baseline_y_pt);
                 let seg_width = self.measure_text(seg.flags, &seg.text, seg_size);
                 // Justified lines widen every space via the PDF `Tw`
                 // operator. `seg_width` (glyphs + letter spacing) does
@@ -3667,7 +5069,8 @@ impl<'a> Engine<'a> {
                     }
                     // Restore the text fill colour: link colour for a
                     // link, `[mark]` colour for a highlight, `[code_inline]`
-                    // colour for inline code, otherwise the block colour.
+                    // colour for inline code, the emphasis level's colour
+                    // when it sets one, otherwise the block colour.
                     if seg.link.is_some() {
                         let lc = if self.is_unresolved_internal_link(&seg.link) {
                             rgb_color(UNRESOLVED_LINK_COLOR)
@@ -3683,6 +5086,12 @@ impl<'a> Engine<'a> {
                         self.page_ops.push(Op::SetFillColor {
                             col: code_inline_color.clone(),
                         });
+                    } else if let Some(c) =
+                        self.emphasis_style(seg.flags).and_then(|e| e.text_color)
+                    {
+                        self.page_ops.push(Op::SetFillColor {
+                            col: rgb_color((c.r, c.g, c.b)),
+                        });
                     } else if let Some(c) = color.clone() {
                         self.page_ops.push(Op::SetFillColor { col: c });
                     }
@@ -3896,6 +5305,14 @@ struct HeadingAnchor {
     y_pt: f32,
 }
 
+/// A `<!-- numbering -->` marker: from page `page_idx` on, page
+/// numbers are written in `style`, restarting at 1 when `reset`.
+struct NumberingMark {
+    page_idx: usize,
+    style: PageNumberStyle,
+    reset: bool,
+}
+
 /// A `[text](#slug)` link annotation deferred until the destination
 /// heading's position is known. The page hosting the link rect is
 /// captured at creation time; the destination is resolved in
@@ -4026,6 +5443,67 @@ fn to_win1252(s: &str) -> String {
     out
 }
 
+/// The first `keep` cells of a table row. A cell spanning past the cut
+/// is trimmed to end at it.
+fn clip_table_row(row: &[TableCell<InlineRun>], keep: usize) -> Vec<TableCell<InlineRun>> {
+    row.iter()
+        .take(keep)
+        .enumerate()
+        .map(|(col, cell)| TableCell {
+            colspan: cell.colspan.min(keep - col),
+            ..cell.clone()
+        })
+        .collect()
+}
+
+/// Share `total` points of width among table columns whose content
+/// wants `natural` points each. Every column stays within
+/// `[min, max]`; when even the minimums don't fit they give way
+/// (cells wrap more), though never below `floor`. Spare width goes to
+/// the columns in proportion to their content until each reaches
+/// `max`; a table whose columns all top out ends up narrower than
+/// `total`. Overfull content shrinks each column in proportion to how
+/// far it sits above the minimum.
+fn fit_column_widths(
+    natural: &[f32],
+    min: f32,
+    floor: f32,
+    max: Option<f32>,
+    total: f32,
+) -> Vec<f32> {
+    if natural.is_empty() {
+        return Vec::new();
+    }
+    let n = natural.len() as f32;
+    let min = min.min(total / n).max(floor);
+    let max = max.unwrap_or(f32::INFINITY).max(min);
+    let mut widths: Vec<f32> = natural.iter().map(|w| w.clamp(min, max)).collect();
+    let wanted: f32 = widths.iter().sum();
+    if wanted > total {
+        let slack: f32 = widths.iter().map(|w| w - min).sum();
+        if slack > 0.0 {
+            let k = ((wanted - total) / slack).min(1.0);
+            for w in &mut widths {
+                *w -= (*w - min) * k;
+            }
+        }
+        return widths;
+    }
+    // Each pass either uses up the spare width or tops out at least
+    // one more column, so `n` passes always settle it.
+    for _ in 0..natural.len() {
+        let spare = total - widths.iter().sum::<f32>();
+        let basis: f32 = widths.iter().filter(|w| **w < max).sum();
+        if spare <= 0.01 || basis <= 0.0 {
+            break;
+        }
+        for w in widths.iter_mut().filter(|w| **w < max) {
+            *w = (*w + spare * *w / basis).min(max);
+        }
+    }
+    widths
+}
+
 /// Approximate the number of wrapped lines a run sequence would
 /// occupy in a column of `max_width` points.
 fn count_wrapped_lines(
@@ -4065,7 +5543,7 @@ fn count_wrapped_lines(
     lines
 }
 
-fn format_bullet(b: &ListBullet, style: &ResolvedList) -> String {
+fn format_bullet(b: &ListBullet, style: &ResolvedList, digits: Digits) -> String {
     // External (Unicode) fonts render `•` directly. Built-in
     // Helvetica falls back through `to_win1252`, which maps `•` to
     // `*` so the bullet still appears.
@@ -4076,9 +5554,10 @@ fn format_bullet(b: &ListBullet, style: &ResolvedList) -> String {
             format!("{}  ", g)
         }
         ListBullet::Ordered(n) => {
+            let n = digits.localize(&n.to_string());
             let template = style.bullet.trim();
             if template.contains('1') {
-                let rendered = template.replacen("1", &n.to_string(), 1);
+                let rendered = template.replacen("1", &n, 1);
                 format!("{}  ", rendered)
             } else if template.is_empty() {
                 format!("{}.  ", n)
@@ -4653,6 +6132,8 @@ struct TextSegment {
     link: Option<String>,
     /// Raw TeX when this segment is an inline-math box (`text` empty).
     math: Option<String>,
+    /// Path or URL when this segment is an inline image.
+    image: Option<String>,
     /// Horizontal pt of padding to insert before this segment's glyphs
     /// (and after, respectively). Non-zero only for the first / last
     /// segment of a contiguous inline-code span when
@@ -4665,13 +6146,19 @@ struct TextSegment {
 
 /// Flatten a run list to a sequence of (word | whitespace) pieces,
 /// preserving the originating run's flags. Whitespace pieces become
-/// break opportunities in the wrapping pass; words don't.
-fn words_from_runs(runs: &[InlineRun]) -> Vec<InlineRun> {
+/// break opportunities in the wrapping pass; words don't, except where
+/// a soft hyphen or zero-width space splits them (see
+/// [`push_word_at_break_hints`]).
+///
+/// With `collapse_whitespace`, each whitespace run outside inline code
+/// becomes a single space, including runs that straddle two inline
+/// runs. Otherwise the source whitespace is kept as written.
+fn words_from_runs(runs: &[InlineRun], collapse_whitespace: bool) -> Vec<InlineRun> {
     let mut out = Vec::new();
     for run in runs {
-        if run.math.is_some() {
-            // An inline-math box is one indivisible word — never
-            // split on whitespace, never merged with neighbours.
+        if run.math.is_some() || run.image.is_some() {
+            // An inline-math or image box is one indivisible word —
+            // never split on whitespace, never merged with neighbours.
             out.push(run.clone());
             continue;
         }
@@ -4688,14 +6175,31 @@ fn words_from_runs(runs: &[InlineRun]) -> Vec<InlineRun> {
             } else {
                 run.text.len()
             };
-            let slice = &run.text[chars[i].0..end_byte];
-            if !slice.is_empty() {
+            let mut slice = &run.text[chars[i].0..end_byte];
+            if is_space && collapse_whitespace && !run.flags.inline_code && !run.flags.monospace {
+                let after_space = out.last().is_some_and(|w: &InlineRun| {
+                    w.math.is_none()
+                        && w.image.is_none()
+                        && !w.flags.inline_code
+                        && !w.flags.monospace
+                        && w.text == " "
+                });
+                if after_space {
+                    i = j;
+                    continue;
+                }
+                slice = " ";
+            }
+            if is_space {
                 out.push(InlineRun {
                     math: None,
+                    image: None,
                     text: slice.to_string(),
                     flags: run.flags,
                     link: run.link.clone(),
                 });
+            } else {
+                push_word_at_break_hints(&mut out, slice, run);
             }
             i = j;
         }
@@ -4703,6 +6207,43 @@ fn words_from_runs(runs: &[InlineRun]) -> Vec<InlineRun> {
     out
 }
 
+/// Soft hyphen (U+00AD): an invisible break hint that shows as `-`
+/// only when a line actually breaks there.
+const SOFT_HYPHEN: char = '\u{00AD}';
+
+/// Zero-width space (U+200B): an invisible break opportunity.
+const ZERO_WIDTH_SPACE: char = '\u{200B}';
+
+/// Push `word` as one or more pieces, split at the author's break
+/// hints. A zero-width space is dropped, leaving a bare break between
+/// the pieces around it; a soft hyphen stays at the end of the piece
+/// before it so `write_wrapped_runs` can show it as `-` if the line
+/// breaks there (and drop it otherwise).
+fn push_word_at_break_hints(out: &mut Vec<InlineRun>, word: &str, run: &InlineRun) {
+    let mut rest = word;
+    while !rest.is_empty() {
+        let (piece, tail) = match rest.find([SOFT_HYPHEN, ZERO_WIDTH_SPACE]) {
+            Some(at) if rest[at..].starts_with(SOFT_HYPHEN) => {
+                let end = at + SOFT_HYPHEN.len_utf8();
+                // A hint with nothing before it can't break anything.
+                (if at == 0 { "" } else { &rest[..end] }, &rest[end..])
+            }
+            Some(at) => (&rest[..at], &rest[at + ZERO_WIDTH_SPACE.len_utf8()..]),
+            None => (rest, ""),
+        };
+        if !piece.is_empty() {
+            out.push(InlineRun {
+                math: None,
+                image: None,
+                text: piece.to_string(),
+                flags: run.flags,
+                link: run.link.clone(),
+            });
+        }
+        rest = tail;
+    }
+}
+
 /// True for whitespace that is a *line-break opportunity*. Excludes
 /// the non-breaking space family: U+00A0 (NBSP), U+202F (narrow
 /// NBSP), U+2007 (figure space). Those render with space advance but
@@ -4738,6 +6279,15 @@ fn base_flags_from_block(s: &ResolvedBlock) -> RunFlags {
     }
 }
 
+/// [`base_flags_from_block`] for a heading, tagged so its runs pick
+/// up the `[fonts.map] heading` family.
+fn heading_flags_from_block(s: &ResolvedBlock) -> RunFlags {
+    RunFlags {
+        heading: true,
+        ..base_flags_from_block(s)
+    }
+}
+
 /// Concatenate the plain text of a heading's inline runs. The PDF
 /// outline + slug source. Markdown emphasis / inline code inside a
 /// heading collapses to its literal text.
@@ -4749,6 +6299,111 @@ fn collect_heading_text(runs: &[InlineRun]) -> String {
     out
 }
 
+/// Plain heading text recased per `[headings].transform`.
+fn transform_heading_text(text: &str, transform: HeadingTransform) -> String {
+    let run = InlineRun {
+        text: text.to_string(),
+        flags: RunFlags::default(),
+        link: None,
+        math: None,
+        image: None,
+    };
+    collect_heading_text(&transform_heading_runs(&[run], transform))
+}
+
+/// Words `title` case leaves lowercase unless they open or close the
+/// heading.
+const TITLE_CASE_MINOR_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "but", "by", "for", "in", "nor", "of", "on", "or", "per", "so",
+    "the", "to", "up", "via", "vs", "yet",
+];
+
+/// `runs` recased per `[headings].transform`. Words are found across
+/// run boundaries, so a word split by emphasis is still one word, and
+/// each run keeps its own flags and link. Inline code and math runs
+/// keep their spelling but still count as words for `title`. In
+/// `title` case only a word's first letter changes, so acronyms like
+/// `PDF` survive.
+fn transform_heading_runs(runs: &[InlineRun], transform: HeadingTransform) -> Vec<InlineRun> {
+    let verbatim =
+        |run: &InlineRun| run.flags.inline_code || run.math.is_some() || run.image.is_some();
+    match transform {
+        HeadingTransform::None => return runs.to_vec(),
+        HeadingTransform::Upper | HeadingTransform::Lower => {
+            return runs
+                .iter()
+                .map(|run| {
+                    let mut run = run.clone();
+                    if !verbatim(&run) {
+                        run.text = if transform == HeadingTransform::Upper {
+                            run.text.to_uppercase()
+                        } else {
+                            run.text.to_lowercase()
+                        };
+                    }
+                    run
+                })
+                .collect();
+        }
+        HeadingTransform::Title => {}
+    }
+
+    // Word spans over the flattened text, as char indices.
+    let chars: Vec<char> = runs.iter().flat_map(|r| r.text.chars()).collect();
+    let mut words: Vec<(usize, usize)> = Vec::new();
+    let mut start = None;
+    for (i, c) in chars.iter().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                words.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push((s, chars.len()));
+    }
+
+    // 1 = uppercase, -1 = lowercase, 0 = leave as is, per char.
+    let mut case = vec![0i8; chars.len()];
+    for (k, &(s, e)) in words.iter().enumerate() {
+        let word: String = chars[s..e]
+            .iter()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+        let inner = k != 0 && k + 1 != words.len();
+        if inner && TITLE_CASE_MINOR_WORDS.contains(&word.as_str()) {
+            case[s..e].fill(-1);
+        } else if let Some(first) = (s..e).find(|&i| chars[i].is_alphabetic()) {
+            case[first] = 1;
+        }
+    }
+
+    let mut out = Vec::with_capacity(runs.len());
+    let mut i = 0;
+    for run in runs {
+        let mut run = run.clone();
+        let n = run.text.chars().count();
+        if !verbatim(&run) {
+            let mut text = String::with_capacity(run.text.len());
+            for (c, &rule) in run.text.chars().zip(&case[i..i + n]) {
+                match rule {
+                    1 => text.extend(c.to_uppercase()),
+                    -1 => text.extend(c.to_lowercase()),
+                    _ => text.push(c),
+                }
+            }
+            run.text = text;
+        }
+        i += n;
+        out.push(run);
+    }
+    out
+}
+
 fn rgb_color((r, g, b): (u8, u8, u8)) -> Color {
     Color::Rgb(Rgb {
         r: f32::from(r) / 255.0,
@@ -4802,9 +6457,9 @@ struct TemplateContext<'a> {
 }
 
 impl TemplateContext<'_> {
-    fn expand(&self, template: &str) -> String {
+    fn expand(&self, template: &str, page_label: &str) -> String {
         template
-            .replace("{page}", &self.page.to_string())
+            .replace("{page}", page_label)
             .replace("{total_pages}", &self.total_pages.to_string())
             .replace("{title}", self.title)
             .replace("{author}", self.author)
@@ -4841,6 +6496,70 @@ fn civil_from_days(days: i64) -> (i32, u32, u32) {
     (y as i32, m, d)
 }
 
+/// Decode raster or SVG `bytes`, applying the 4000px dimension cap.
+/// `path` only labels the log messages.
+fn decode_image_bytes(bytes: Vec<u8>, path: &std::path::Path) -> Option<image::DynamicImage> {
+    let decode_result: Result<image::DynamicImage, String> = if looks_like_svg(&bytes) {
+        decode_svg_bytes(&bytes)
+    } else {
+        let cursor = std::io::Cursor::new(bytes);
+        image::ImageReader::new(cursor)
+            .with_guessed_format()
+            .map_err(|e| e.to_string())
+            .and_then(|r| r.decode().map_err(|e| e.to_string()))
+    };
+    let img = match decode_result {
+        Ok(d) => d,
+        Err(e) => {
+            log::warn!("could not decode image {:?}: {}", path, e);
+            return None;
+        }
+    };
+
+    // Degenerate dimensions: a 0-px image can't produce a valid
+    // XObject. Treat it like a decode failure.
+    if img.width() == 0 || img.height() == 0 {
+        log::warn!("image {:?} has zero dimension; skipping", path);
+        return None;
+    }
+
+    // Bound decoded pixel dimensions. The URL fetch cap limits the
+    // *download* size, but a small compressed PNG can decompress
+    // to an enormous raster (memory + PDF-size blowup). Mirror the
+    // SVG ceiling: downscale so neither dimension exceeds
+    // `MAX_IMG_PX`, preserving aspect ratio.
+    const MAX_IMG_PX: u32 = 4000;
+    let img = if img.width() > MAX_IMG_PX || img.height() > MAX_IMG_PX {
+        log::warn!(
+            "image {:?} is {}x{}; downscaling to fit {}px",
+            path,
+            img.width(),
+            img.height(),
+            MAX_IMG_PX
+        );
+        img.resize(
+            MAX_IMG_PX,
+            MAX_IMG_PX,
+            image::imageops::FilterType::Triangle,
+        )
+    } else {
+        img
+    };
+    Some(img)
+}
+
+/// Convert a decoded image to printpdf's embeddable form. `path` only
+/// labels the log message.
+fn to_raw_image(img: image::DynamicImage, path: &std::path::Path) -> Option<RawImage> {
+    match RawImage::from_dynamic_image(img) {
+        Ok(r) => Some(r),
+        Err(e) => {
+            log::warn!("could not convert image {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
 /// Cheap content sniff: does this buffer look like an SVG document?
 /// Skips a UTF-8 BOM and ASCII whitespace, accepts `<?xml`-prefixed
 /// SVGs and bare `<svg ...>` openings. Big enough to keep us from
@@ -4865,13 +6584,17 @@ fn looks_like_svg(bytes: &[u8]) -> bool {
     lower.starts_with(b"<svg")
 }
 
-/// Rasterize an SVG byte buffer to an `image::DynamicImage`. Rendered
-/// at `2x` the SVG's intrinsic size for crisp output on print DPIs;
-/// hard upper cap of 4000px per dimension so an unbounded
-/// `width="999999"` doesn't blow up memory.
+/// Rasterize an SVG byte buffer to an `image::DynamicImage`. The
+/// SVG's intrinsic size is in CSS pixels (96 per inch); rendering at
+/// `IMAGE_DPI / 96` of it makes the raster come out at that physical
+/// size when placed, with print-density pixels behind it, and keeps
+/// it crisp when `max_width_pct` shrinks it further. Hard upper cap
+/// of 4000px per dimension so an unbounded `width="999999"` doesn't
+/// blow up memory.
 #[cfg(feature = "svg")]
 fn decode_svg_bytes(bytes: &[u8]) -> Result<image::DynamicImage, String> {
     const MAX_PX: u32 = 4000;
+    const CSS_PX_PER_INCH: f32 = 96.0;
     // An untrusted SVG must not be able to pull in external resources
     // while parsing: usvg's default string resolver will happily read
     // `<image href="/etc/…">` off disk. Data URIs stay allowed — they
@@ -4887,7 +6610,7 @@ fn decode_svg_bytes(bytes: &[u8]) -> Result<image::DynamicImage, String> {
     };
     let tree = resvg::usvg::Tree::from_data(bytes, &opts).map_err(|e| e.to_string())?;
     let size = tree.size();
-    let scale = 2.0_f32;
+    let scale = IMAGE_DPI / CSS_PX_PER_INCH;
     let mut w_px = (size.width() * scale).ceil() as u32;
     let mut h_px = (size.height() * scale).ceil() as u32;
     if w_px == 0 || h_px == 0 {
@@ -4920,6 +6643,129 @@ mod tests {
     use super::*;
     use crate::styling::ResolvedStyle;
 
+    const SVG_FIXTURE: &[u8] = br##"<?xml version="1.0"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="96" height="48">
+  <rect width="96" height="48" fill="#1E6FD9"/>
+</svg>"##;
+
+    fn heading_runs(parts: &[(&str, RunFlags)]) -> Vec<InlineRun> {
+        parts
+            .iter()
+            .map(|&(text, flags)| InlineRun {
+                math: None,
+                image: None,
+                text: text.to_string(),
+                flags,
+                link: None,
+            })
+            .collect()
+    }
+
+    fn heading_text(runs: &[InlineRun], transform: HeadingTransform) -> String {
+        collect_heading_text(&transform_heading_runs(runs, transform))
+    }
+
+    #[test]
+    fn column_widths_follow_content_within_bounds() {
+        // Spare width goes out in proportion to content.
+        assert_eq!(
+            fit_column_widths(&[10.0, 30.0], 0.0, 1.0, None, 80.0),
+            [20.0, 60.0]
+        );
+        // A capped column stops growing and the table ends narrower.
+        assert_eq!(
+            fit_column_widths(&[10.0, 30.0], 0.0, 1.0, Some(25.0), 80.0),
+            [25.0, 25.0]
+        );
+        // Overfull content shrinks towards the minimum.
+        let shrunk = fit_column_widths(&[100.0, 20.0], 20.0, 1.0, None, 80.0);
+        assert_eq!(shrunk, [60.0, 20.0]);
+        // Minimums that can't fit share the width equally.
+        assert_eq!(
+            fit_column_widths(&[5.0, 5.0], 60.0, 1.0, None, 80.0),
+            [40.0, 40.0]
+        );
+    }
+
+    #[test]
+    fn heading_transform_upper_and_lower_skip_code() {
+        let plain = RunFlags::default();
+        let code = RunFlags {
+            inline_code: true,
+            ..plain
+        };
+        let runs = heading_runs(&[("Using ", plain), ("fooBar", code), (" in Rust", plain)]);
+        assert_eq!(
+            heading_text(&runs, HeadingTransform::Upper),
+            "USING fooBar IN RUST"
+        );
+        assert_eq!(
+            heading_text(&runs, HeadingTransform::Lower),
+            "using fooBar in rust"
+        );
+        assert_eq!(
+            heading_text(&runs, HeadingTransform::None),
+            "Using fooBar in Rust"
+        );
+    }
+
+    #[test]
+    fn heading_transform_title_case_keeps_minor_words_and_acronyms() {
+        let plain = RunFlags::default();
+        let runs = heading_runs(&[("the state OF the PDF art to build on", plain)]);
+        assert_eq!(
+            heading_text(&runs, HeadingTransform::Title),
+            "The State of the PDF Art to Build On"
+        );
+        // A word split by emphasis is still one word, and each run
+        // keeps its flags.
+        let bold = plain.with_bold();
+        let runs = heading_runs(&[("a ", plain), ("quick", bold), ("start guide", plain)]);
+        let out = transform_heading_runs(&runs, HeadingTransform::Title);
+        assert_eq!(collect_heading_text(&out), "A Quickstart Guide");
+        assert!(out[1].flags.bold);
+    }
+
+    #[test]
+    fn svg_sniff_accepts_xml_prolog_and_bare_svg() {
+        assert!(looks_like_svg(SVG_FIXTURE));
+        assert!(looks_like_svg(b"\xEF\xBB\xBF  <svg width=\"1\"/>"));
+        assert!(!looks_like_svg(b"<?xml version=\"1.0\"?><html/>"));
+        assert!(!looks_like_svg(b"\x89PNG\r\n"));
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn svg_rasterizes_at_print_density_for_its_intrinsic_size() {
+        // 96x48 CSS px is one inch by half an inch: IMAGE_DPI pixels
+        // across, so it places at exactly its intrinsic size.
+        let img = decode_svg_bytes(SVG_FIXTURE).unwrap();
+        assert_eq!((img.width(), img.height()), (300, 150));
+        let raw = decode_image_bytes(SVG_FIXTURE.to_vec(), std::path::Path::new("t.svg"));
+        assert!(raw.is_some());
+    }
+
+    #[cfg(not(feature = "svg"))]
+    #[test]
+    fn svg_without_feature_fails_to_decode() {
+        assert!(decode_svg_bytes(SVG_FIXTURE).is_err());
+        assert!(decode_image_bytes(SVG_FIXTURE.to_vec(), std::path::Path::new("t.svg")).is_none());
+    }
+
+    #[test]
+    fn ordered_markers_use_the_configured_digits() {
+        let style = ResolvedStyle::default();
+        let list = &style.list_ordered;
+        let third = ListBullet::Ordered(3);
+        let latin = format_bullet(&third, list, Digits::Latin);
+        assert!(latin.starts_with('3'), "{latin:?}");
+        let arabic = format_bullet(&third, list, Digits::ArabicIndic);
+        assert!(arabic.starts_with('\u{0663}'), "{arabic:?}");
+        assert!(!arabic.contains('3'));
+        let devanagari = format_bullet(&ListBullet::Ordered(12), list, Digits::Devanagari);
+        assert!(devanagari.starts_with("\u{0967}\u{0968}"), "{devanagari:?}");
+    }
+
     #[test]
     fn empty_block_list_produces_no_pages() {
         let font_set = FontSet::load(
@@ -4927,6 +6773,7 @@ mod tests {
             &[],
             crate::render::ir::VariantUsage::default(),
             &mut PdfDocument::new("test"),
+            &crate::render::FontCache::default(),
         );
         let style = ResolvedStyle::default();
         let pages = lay_out_pages(
@@ -4935,7 +6782,9 @@ mod tests {
             &font_set,
             &HashSet::new(),
             &mut PdfDocument::new("test"),
-        );
+            &mut |_| {},
+        )
+        .pages;
         assert!(pages.is_empty());
     }
 
@@ -4946,6 +6795,7 @@ mod tests {
             &[],
             crate::render::ir::VariantUsage::default(),
             &mut PdfDocument::new("test"),
+            &crate::render::FontCache::default(),
         );
         let style = ResolvedStyle::default();
         let blocks = vec![Block::Paragraph {
@@ -4957,7 +6807,9 @@ mod tests {
             &font_set,
             &HashSet::new(),
             &mut PdfDocument::new("test"),
-        );
+            &mut |_| {},
+        )
+        .pages;
         assert_eq!(pages.len(), 1);
     }
 
@@ -4968,6 +6820,7 @@ mod tests {
             &[],
             crate::render::ir::VariantUsage::default(),
             &mut PdfDocument::new("test"),
+            &crate::render::FontCache::default(),
         );
         let style = ResolvedStyle::default();
         let blocks: Vec<_> = (0..200)
@@ -4981,7 +6834,9 @@ mod tests {
             &font_set,
             &HashSet::new(),
             &mut PdfDocument::new("test"),
-        );
+            &mut |_| {},
+        )
+        .pages;
         assert!(pages.len() >= 2, "expected page split, got {}", pages.len());
     }
 
@@ -4996,6 +6851,7 @@ mod tests {
             &[],
             crate::render::ir::VariantUsage::default(),
             &mut PdfDocument::new("test"),
+            &crate::render::FontCache::default(),
         );
         let style = ResolvedStyle::default();
         let long_text = "word ".repeat(200);
@@ -5008,7 +6864,9 @@ mod tests {
             &font_set,
             &HashSet::new(),
             &mut PdfDocument::new("test"),
-        );
+            &mut |_| {},
+        )
+        .pages;
         assert!(!pages.is_empty());
     }
 }
@@ -10,9 +10,11 @@
 //! appears, just without distinctive layout.
 
 use crate::markdown::{TableCell, Token};
+use crate::styling::{HtmlMode, LinkDisplay, RelativeUrlPolicy, ResolvedLinkDisplay};
 
 use super::ir::{
-    Block, DefinitionEntry, FootnoteEntry, InlineRun, ListBullet, ListEntry, RunFlags,
+    Block, DefinitionEntry, FootnoteEntry, GalleryImage, InlineRun, ListBullet, ListEntry,
+    RunFlags, visit_runs_mut,
 };
 use std::collections::HashMap;
 
@@ -25,7 +27,7 @@ pub fn lower(tokens: &[Token]) -> Vec<Block> {
     // instead of re-numbering local labels from 1.
     let footnote_numbers = collect_footnote_numbering(tokens);
     let mut footnote_definitions: HashMap<String, Vec<InlineRun>> = HashMap::new();
-    collect_inline_footnote_defs(tokens, &footnote_numbers, &mut footnote_definitions);
+    collect_footnote_defs(tokens, &footnote_numbers, &mut footnote_definitions);
 
     let mut out = lower_blocks(tokens, &footnote_numbers, &mut footnote_definitions);
 
@@ -82,7 +84,7 @@ fn lower_blocks(
         // an inline `$…$` would be silently dropped.
         let all_empty = buffered
             .iter()
-            .all(|r| r.text.trim().is_empty() && r.math.is_none());
+            .all(|r| r.text.trim().is_empty() && r.math.is_none() && r.image.is_none());
         if !all_empty {
             out.push(Block::Paragraph {
                 runs: std::mem::take(buffered),
@@ -110,23 +112,44 @@ fn lower_blocks(
                 flush_paragraph(&mut out, &mut buffered_inline);
                 i += 1;
             }
-            Token::Heading(content, level) => {
+            Token::Heading(content, level, id) => {
                 flush_paragraph(&mut out, &mut buffered_inline);
                 let runs = flatten_inline(content, RunFlags::default(), None, footnote_numbers);
                 out.push(Block::Heading {
                     level: (*level).clamp(1, 6) as u8,
                     runs,
+                    id: id.clone(),
+                    text: Token::plain_text_of(content),
                 });
                 i += 1;
             }
             Token::Code {
+                language,
                 content,
                 block: true,
                 ..
+            } if language == "pdf-include" => {
+                flush_paragraph(&mut out, &mut buffered_inline);
+                if let Some(path) = content.lines().map(str::trim).find(|l| !l.is_empty()) {
+                    out.push(Block::PdfInclude {
+                        path: std::path::PathBuf::from(path),
+                    });
+                }
+                i += 1;
+            }
+            Token::Code {
+                language,
+                content,
+                block: true,
+                attrs,
             } => {
                 flush_paragraph(&mut out, &mut buffered_inline);
-                let lines = content.split('\n').map(|s| s.to_string()).collect();
-                out.push(Block::Code { lines });
+                let lines = content.split('\n').map(expand_tabs).collect();
+                out.push(Block::Code {
+                    lines,
+                    language: language.clone(),
+                    attrs: attrs.clone(),
+                });
                 i += 1;
             }
             Token::HorizontalRule => {
@@ -138,6 +161,8 @@ fn lower_blocks(
                 flush_paragraph(&mut out, &mut buffered_inline);
                 if is_pagebreak_marker(content) {
                     out.push(Block::PageBreak);
+                } else if let Some((style, reset)) = parse_numbering_marker(content) {
+                    out.push(Block::PageNumbering { style, reset });
                 } else if let Some(img) = parse_html_img_block(content) {
                     out.push(Block::Image {
                         path: std::path::PathBuf::from(&img.src),
@@ -231,9 +256,9 @@ fn lower_blocks(
                 // Definitions don't produce a Block at their source
                 // position; they're collected into a single
                 // `Block::FootnoteDefinitions` appended at the end of
-                // the document below. Pre-flatten the content's
-                // inline runs so the post-pass doesn't have to lower
-                // recursively.
+                // the document. `collect_footnote_defs` has already
+                // registered every one in the token tree; this only
+                // adds those re-lexed out of an HTML wrapper.
                 let runs = flatten_inline(content, RunFlags::default(), None, footnote_numbers);
                 footnote_definitions.entry(label.clone()).or_insert(runs);
                 i += 1;
@@ -300,6 +325,33 @@ fn lower_blocks(
                 });
                 i += 1;
             }
+            // Two or more images alone on one line form a gallery. The
+            // paragraph they'd otherwise be is kept for when
+            // `[image] gallery` is off.
+            Token::Image { .. }
+                if buffered_inline.is_empty() && image_gallery_end(tokens, i).is_some() =>
+            {
+                let end = image_gallery_end(tokens, i).unwrap_or(i + 1);
+                let images = tokens[i..end]
+                    .iter()
+                    .filter_map(|t| match t {
+                        Token::Image { alt, url, title } => Some(GalleryImage {
+                            path: std::path::PathBuf::from(url),
+                            alt: crate::markdown::Token::collect_all_text(alt),
+                            caption: title.clone(),
+                        }),
+                        _ => None,
+                    })
+                    .collect();
+                let runs = flatten_inline(
+                    &tokens[i..end],
+                    root_html_depth.apply(RunFlags::default()),
+                    None,
+                    footnote_numbers,
+                );
+                out.push(Block::Gallery { images, runs });
+                i = end;
+            }
             // A bare Token::Image at the top level (not surrounded
             // by other inline content) gets promoted to a block-level
             // image. We require the buffered paragraph to be empty
@@ -315,12 +367,19 @@ fn lower_blocks(
                 if buffered_inline.is_empty() && image_is_standalone(tokens, i) =>
             {
                 let path = std::path::PathBuf::from(url);
-                let alt_text = crate::markdown::Token::collect_all_text(alt);
-                out.push(Block::Image {
-                    path,
-                    alt: alt_text,
-                    caption: title.clone(),
-                });
+                let is_pdf = path
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+                if is_pdf {
+                    out.push(Block::PdfInclude { path });
+                } else {
+                    let alt_text = crate::markdown::Token::collect_all_text(alt);
+                    out.push(Block::Image {
+                        path,
+                        alt: alt_text,
+                        caption: title.clone(),
+                    });
+                }
                 i += 1;
             }
             // Inline-level tokens at the root accumulate into the
@@ -367,6 +426,28 @@ fn lower_blocks(
 /// Returns true if a Token::Image at `idx` should be lifted to a
 /// block-level [`Block::Image`] — i.e. nothing comes after it on the
 /// same paragraph.
+/// Replace each tab in a code line with spaces up to the next
+/// 4-column stop. A tab glyph has no fixed advance in most fonts, so
+/// leaving it in would break the alignment of tab-indented columns.
+fn expand_tabs(line: &str) -> String {
+    if !line.contains('\t') {
+        return line.to_string();
+    }
+    let mut out = String::with_capacity(line.len() + 8);
+    let mut col = 0;
+    for ch in line.chars() {
+        if ch == '\t' {
+            let n = 4 - col % 4;
+            out.extend(std::iter::repeat_n(' ', n));
+            col += n;
+        } else {
+            out.push(ch);
+            col += 1;
+        }
+    }
+    out
+}
+
 /// True if `s` is exactly `<!-- pagebreak -->` (whitespace-tolerant,
 /// case-insensitive). Standalone-comment convention borrowed from
 /// Pandoc / mdBook / GitBook: a single HTML comment whose payload is
@@ -380,6 +461,25 @@ fn is_pagebreak_marker(s: &str) -> bool {
     matches!(inner, Some(word) if word.eq_ignore_ascii_case("pagebreak"))
 }
 
+/// The style and reset flag of a standalone `<!-- numbering: roman -->`
+/// or `<!-- numbering: arabic reset -->` comment. Case-insensitive;
+/// `None` for any other block or an unknown style.
+fn parse_numbering_marker(s: &str) -> Option<(crate::styling::PageNumberStyle, bool)> {
+    let inner = s.trim().strip_prefix("<!--")?.strip_suffix("-->")?.trim();
+    let (key, value) = inner.split_once(':')?;
+    if !key.trim().eq_ignore_ascii_case("numbering") {
+        return None;
+    }
+    let mut words = value.split_whitespace();
+    let style = crate::styling::PageNumberStyle::from_name(words.next()?)?;
+    let reset = match words.next() {
+        None => false,
+        Some(word) if word.eq_ignore_ascii_case("reset") => true,
+        Some(_) => return None,
+    };
+    words.next().is_none().then_some((style, reset))
+}
+
 struct HtmlImg {
     src: String,
     alt: String,
@@ -518,6 +618,116 @@ fn is_void_br(raw: &str) -> bool {
     matches!(s.as_str(), "<br>" | "<br/>" | "<br />" | "</br>")
 }
 
+/// The text stand-in for a self-contained `<input type="checkbox">`
+/// or `<input type="radio">`: `[x]` / `[ ]` and `(x)` / `( )`, the
+/// same ASCII marks the task-list bullet falls back to. `Some("")` for
+/// any other `<input>` (a form field has no print equivalent), `None`
+/// when `raw` is not an `<input>` tag at all.
+fn html_input_marker(raw: &str) -> Option<&'static str> {
+    let inner = raw.trim().strip_prefix('<')?.strip_suffix('>')?;
+    if !inner.get(..5)?.eq_ignore_ascii_case("input") {
+        return None;
+    }
+    let rest = &inner[5..];
+    if !(rest.is_empty() || rest.starts_with(|c: char| c.is_ascii_whitespace() || c == '/')) {
+        return None;
+    }
+    let attrs = parse_html_attrs(rest.trim_end_matches('/'));
+    let kind = attrs
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("type"))
+        .map(|(_, v)| v.trim().to_ascii_lowercase());
+    let checked = attrs.iter().any(|(k, _)| k.eq_ignore_ascii_case("checked"));
+    Some(match (kind.as_deref(), checked) {
+        (Some("checkbox"), true) => "[x]",
+        (Some("checkbox"), false) => "[ ]",
+        (Some("radio"), true) => "(x)",
+        (Some("radio"), false) => "( )",
+        _ => "",
+    })
+}
+
+/// True for an inline tag that [`flatten_one`] would print as
+/// written because nothing else gives it meaning: not a styling tag,
+/// `<br>`, `<hr>`, `<input>` or a comment. These are what `[html]
+/// mode` acts on.
+pub(crate) fn is_uninterpreted_inline_html(tag: &str) -> bool {
+    let lower = tag.trim().to_ascii_lowercase();
+    classify_inline_html_tag(tag).is_none()
+        && !lower.starts_with("<br")
+        && !lower.starts_with("</br")
+        && !lower.starts_with("<!--")
+        && !is_void_hr(tag)
+        && html_input_marker(tag).is_none()
+}
+
+/// Apply `[html] mode` to the verbatim [`Block::Html`] blocks left
+/// after lowering, recursing into container blocks. `raw` keeps them;
+/// `ignore` and `comment` remove them (`comment` logs a warning);
+/// `strip_tags` replaces each with paragraphs of its text.
+pub(crate) fn apply_html_mode(blocks: &mut Vec<Block>, mode: HtmlMode) {
+    if mode == HtmlMode::Raw {
+        return;
+    }
+    let mut out = Vec::with_capacity(blocks.len());
+    for mut block in std::mem::take(blocks) {
+        match &mut block {
+            Block::Html { content } => {
+                match mode {
+                    HtmlMode::Comment => log::warn!(
+                        "dropped raw HTML block: {}",
+                        content.lines().next().unwrap_or("").trim()
+                    ),
+                    HtmlMode::StripTags => {
+                        out.extend(html_text_paragraphs(content));
+                    }
+                    HtmlMode::Ignore | HtmlMode::Raw => {}
+                }
+                continue;
+            }
+            Block::List { entries } => {
+                for entry in entries {
+                    apply_html_mode(&mut entry.children, mode);
+                }
+            }
+            Block::Quote { body } | Block::Admonition { body, .. } => apply_html_mode(body, mode),
+            Block::DefinitionList { entries } => {
+                for entry in entries {
+                    for def in &mut entry.definitions {
+                        apply_html_mode(def, mode);
+                    }
+                }
+            }
+            _ => {}
+        }
+        out.push(block);
+    }
+    *blocks = out;
+}
+
+/// The text of an HTML fragment with every `<…>` tag removed, one
+/// paragraph per blank-line-separated chunk, whitespace collapsed.
+fn html_text_paragraphs(html: &str) -> Vec<Block> {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in strip_html_comments(html).chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("\r\n", "\n")
+        .split("\n\n")
+        .map(|chunk| chunk.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|chunk| !chunk.is_empty())
+        .map(|chunk| Block::Paragraph {
+            runs: vec![InlineRun::new(chunk)],
+        })
+        .collect()
+}
+
 /// True for any spelling of `<hr>` / `<hr/>` / `<hr />` / `</hr>`.
 fn is_void_hr(raw: &str) -> bool {
     let s = raw.trim().to_ascii_lowercase();
@@ -636,7 +846,7 @@ fn collect_footnote_numbering(tokens: &[Token]) -> HashMap<String, usize> {
                     walk(c, map);
                 }
             }
-            Token::Heading(inner, _)
+            Token::Heading(inner, _, _)
             | Token::Emphasis { content: inner, .. }
             | Token::StrongEmphasis(inner)
             | Token::Strikethrough(inner)
@@ -701,14 +911,15 @@ fn collect_footnote_numbering(tokens: &[Token]) -> HashMap<String, usize> {
     map
 }
 
-/// Recursively gather every inline-footnote (`text^[body]`) body,
-/// keyed by its lexer-assigned label, flattening each to inline runs.
-/// These feed the same `footnote_definitions` map that block `[^id]:`
-/// definitions populate, so the tail "Footnotes" section and the
-/// back-link anchors come out of the existing machinery unchanged.
-/// The body is collected here rather than at the marker's position so
-/// it never splits the paragraph the marker sits in.
-fn collect_inline_footnote_defs(
+/// Recursively gather every footnote body, flattened to inline runs:
+/// `[^id]:` definitions wherever they sit (before their reference,
+/// inside a list item, a table cell…) and inline `text^[body]`
+/// footnotes under their lexer-assigned label. The first definition
+/// of a label wins. Collecting them up front is what lets a reference
+/// find a definition anywhere in the document, and it keeps an
+/// inline footnote's body from splitting the paragraph its marker
+/// sits in.
+fn collect_footnote_defs(
     tokens: &[Token],
     footnotes: &HashMap<String, usize>,
     out: &mut HashMap<String, Vec<InlineRun>>,
@@ -719,7 +930,8 @@ fn collect_inline_footnote_defs(
         out: &mut HashMap<String, Vec<InlineRun>>,
     ) {
         match t {
-            Token::InlineFootnote { label, content } => {
+            Token::InlineFootnote { label, content }
+            | Token::FootnoteDefinition { label, content } => {
                 // Nested footnotes inside the body, if any, first.
                 for c in content {
                     walk(c, footnotes, out);
@@ -727,7 +939,7 @@ fn collect_inline_footnote_defs(
                 let runs = flatten_inline(content, RunFlags::default(), None, footnotes);
                 out.entry(label.clone()).or_insert(runs);
             }
-            Token::Heading(inner, _)
+            Token::Heading(inner, _, _)
             | Token::Emphasis { content: inner, .. }
             | Token::StrongEmphasis(inner)
             | Token::Strikethrough(inner)
@@ -735,8 +947,7 @@ fn collect_inline_footnote_defs(
             | Token::BlockQuote(inner)
             | Token::ListItem { content: inner, .. }
             | Token::Link { content: inner, .. }
-            | Token::Image { alt: inner, .. }
-            | Token::FootnoteDefinition { content: inner, .. } => {
+            | Token::Image { alt: inner, .. } => {
                 for c in inner {
                     walk(c, footnotes, out);
                 }
@@ -787,6 +998,32 @@ fn collect_inline_footnote_defs(
     }
 }
 
+/// When the token at `idx` starts a paragraph holding nothing but two
+/// or more images on one line, separated only by spaces, the index
+/// just past the line's last token (the line break itself is left for
+/// the caller).
+fn image_gallery_end(tokens: &[Token], idx: usize) -> Option<usize> {
+    let mut images = 0;
+    let mut end = tokens.len();
+    for (i, tok) in tokens.iter().enumerate().skip(idx) {
+        match tok {
+            Token::Image { .. } => images += 1,
+            Token::Text(s) if s.trim().is_empty() => {}
+            // A soft break only ends the paragraph before a blank line;
+            // otherwise more text follows and this isn't a gallery.
+            Token::Newline if !matches!(tokens.get(i + 1), None | Some(Token::Newline)) => {
+                return None;
+            }
+            Token::Newline | Token::HardBreak => {
+                end = i;
+                break;
+            }
+            _ => return None,
+        }
+    }
+    (images >= 2).then_some(end)
+}
+
 fn image_is_standalone(tokens: &[Token], idx: usize) -> bool {
     match tokens.get(idx + 1) {
         Some(Token::Newline | Token::HardBreak) | None => true,
@@ -816,19 +1053,13 @@ fn make_list_entry(
     // The "header" inline content sits before any nested block-level
     // children (nested lists, paragraphs from a blank line, etc.).
     // Walk until we hit a block-level token, then lower the tail
-    // recursively.
+    // recursively, so quotes, code, tables and sub-lists nest to any
+    // depth.
     let mut inline_end = 0;
     for (i, tok) in content.iter().enumerate() {
-        if matches!(
-            tok,
-            Token::ListItem { .. }
-                | Token::Heading(..)
-                | Token::Code { block: true, .. }
-                | Token::HorizontalRule
-                | Token::BlockQuote(_)
-                | Token::Admonition { .. }
-                | Token::Table { .. }
-        ) {
+        let blank_line =
+            matches!(tok, Token::Newline) && matches!(content.get(i + 1), Some(Token::Newline));
+        if blank_line || starts_nested_block(tok) {
             inline_end = i;
             break;
         }
@@ -853,6 +1084,24 @@ fn make_list_entry(
     }
 }
 
+/// `true` for a token that opens a block of its own inside a list
+/// item, ending the item's first line of inline text. Display math is
+/// left out: mid-line `$$…$$` in an item stays an inline box.
+fn starts_nested_block(tok: &Token) -> bool {
+    matches!(
+        tok,
+        Token::ListItem { .. }
+            | Token::Heading(..)
+            | Token::Code { block: true, .. }
+            | Token::HorizontalRule
+            | Token::BlockQuote(_)
+            | Token::Admonition { .. }
+            | Token::Table { .. }
+            | Token::DefinitionList { .. }
+            | Token::HtmlBlock(_)
+    )
+}
+
 /// Recursively flatten a slice of inline-level tokens into runs,
 /// propagating `flags` and the current link URL through nested
 /// style wrappers.
@@ -893,6 +1142,8 @@ enum InlineHtmlTag {
     SmallClose,
     KbdOpen,
     KbdClose,
+    MarkOpen,
+    MarkClose,
     BoldOpen,
     BoldClose,
     ItalicOpen,
@@ -922,6 +1173,7 @@ fn classify_inline_html_tag(raw: &str) -> Option<InlineHtmlTag> {
         "s" | "del" | "strike" => InlineHtmlTag::StrikeOpen,
         "small" => InlineHtmlTag::SmallOpen,
         "kbd" => InlineHtmlTag::KbdOpen,
+        "mark" => InlineHtmlTag::MarkOpen,
         "strong" | "b" => InlineHtmlTag::BoldOpen,
         "em" | "i" => InlineHtmlTag::ItalicOpen,
         "code" => InlineHtmlTag::CodeOpen,
@@ -936,6 +1188,7 @@ fn classify_inline_html_tag(raw: &str) -> Option<InlineHtmlTag> {
             InlineHtmlTag::StrikeOpen => InlineHtmlTag::StrikeClose,
             InlineHtmlTag::SmallOpen => InlineHtmlTag::SmallClose,
             InlineHtmlTag::KbdOpen => InlineHtmlTag::KbdClose,
+            InlineHtmlTag::MarkOpen => InlineHtmlTag::MarkClose,
             InlineHtmlTag::BoldOpen => InlineHtmlTag::BoldClose,
             InlineHtmlTag::ItalicOpen => InlineHtmlTag::ItalicClose,
             InlineHtmlTag::CodeOpen => InlineHtmlTag::CodeClose,
@@ -955,6 +1208,7 @@ struct InlineHtmlDepth {
     strike: u32,
     small: u32,
     kbd: u32,
+    mark: u32,
     bold: u32,
     italic: u32,
     code: u32,
@@ -977,6 +1231,8 @@ impl InlineHtmlDepth {
             InlineHtmlTag::SmallClose => self.small = self.small.saturating_sub(1),
             InlineHtmlTag::KbdOpen => self.kbd += 1,
             InlineHtmlTag::KbdClose => self.kbd = self.kbd.saturating_sub(1),
+            InlineHtmlTag::MarkOpen => self.mark += 1,
+            InlineHtmlTag::MarkClose => self.mark = self.mark.saturating_sub(1),
             InlineHtmlTag::BoldOpen => self.bold += 1,
             InlineHtmlTag::BoldClose => self.bold = self.bold.saturating_sub(1),
             InlineHtmlTag::ItalicOpen => self.italic += 1,
@@ -1008,6 +1264,9 @@ impl InlineHtmlDepth {
         if self.kbd > 0 {
             flags = flags.with_inline_code();
         }
+        if self.mark > 0 {
+            flags = flags.with_highlight();
+        }
         if self.bold > 0 {
             flags = flags.with_bold();
         }
@@ -1031,17 +1290,20 @@ fn flatten_one(
     match tok {
         Token::Text(s) => push_text(out, s, flags, link),
         Token::Emphasis { level, content } => {
+            // Level contract is documented on `Token::Emphasis`; level 2
+            // and `StrongEmphasis` must stay interchangeable.
             let nested = match level {
                 1 => flags.with_italic(),
                 2 => flags.with_bold(),
                 _ => flags.with_bold().with_italic(),
-            };
+            }
+            .with_emphasis(*level);
             for t in content {
                 flatten_one(t, nested, link, out, footnotes);
             }
         }
         Token::StrongEmphasis(content) => {
-            let nested = flags.with_bold();
+            let nested = flags.with_bold().with_emphasis(2);
             for t in content {
                 flatten_one(t, nested, link, out, footnotes);
             }
@@ -1101,6 +1363,7 @@ fn flatten_one(
             let sup_flags = flags.with_superscript();
             out.push(InlineRun {
                 math: None,
+                image: None,
                 text: display,
                 flags: sup_flags,
                 link: anchor_link,
@@ -1116,6 +1379,7 @@ fn flatten_one(
             if let Some(n) = footnotes.get(label).copied() {
                 out.push(InlineRun {
                     math: None,
+                    image: None,
                     text: n.to_string(),
                     flags: flags.with_superscript(),
                     link: Some(format!("#footnote-{}", n)),
@@ -1123,36 +1387,37 @@ fn flatten_one(
             }
         }
         Token::FootnoteDefinition { .. } => {
-            // Definitions handled at the top-level lower loop; this
-            // arm is unreachable in practice but kept exhaustive.
-        }
-        Token::Image { alt, .. } => {
-            // Inline images render their alt text wrapped in an
-            // italic `[image: …]` placeholder so readers can tell at
-            // a glance which inline glyphs stood in for an image,
-            // regardless of context (paragraph, list item, table
-            // cell, admonition, blockquote). Block-level standalone
-            // images that successfully load are promoted to
-            // `Block::Image` in the top-level lower loop and get the
-            // full embedded image; a failed Block::Image goes through
-            // `render_image_fallback`, which produces the same italic
-            // wrapper as a paragraph — so every "image not shown"
-            // path renders identically.
+            // Bodies are gathered by `collect_footnote_defs` before
+            // lowering, so one nested in a list item or table cell
+            // still reaches the footnote section; nothing goes inline.
+        }
+        Token::Image { alt, url, .. } => {
+            // An image inside a line of text (paragraph, list item,
+            // table cell, admonition, blockquote) is drawn inline at
+            // the text's height, like an icon. Block-level standalone
+            // images are promoted to `Block::Image` in the top-level
+            // lower loop and get the full embedded image instead.
             //
-            // Empty-alt images stay invisible: `[image: ]` is uglier
-            // than skipping, and the author signaled the image was
-            // decorative (or didn't bother with alt) so dropping it
-            // matches `render_image_fallback`'s same-case behavior.
+            // The run's text is what shows if the image can't be
+            // loaded: the alt text in an italic `[image: …]`
+            // placeholder, the same wrapper a failed `Block::Image`
+            // gets from `render_image_fallback` — so every "image not
+            // shown" path renders identically. Empty-alt images fall
+            // back to nothing: `[image: ]` is uglier than skipping,
+            // and the author signaled the image was decorative.
             let alt_text = crate::markdown::Token::collect_all_text(alt);
-            if alt_text.trim().is_empty() {
-                return;
-            }
-            let italic = flags.with_italic();
-            push_text(out, "[image: ", italic, link);
-            for t in alt {
-                flatten_one(t, italic, link, out, footnotes);
-            }
-            push_text(out, "]", italic, link);
+            let fallback = if alt_text.trim().is_empty() {
+                String::new()
+            } else {
+                format!("[image: {}]", alt_text)
+            };
+            out.push(InlineRun {
+                text: fallback,
+                flags: flags.with_italic(),
+                link: link.map(str::to_string),
+                math: None,
+                image: Some(url.clone()),
+            });
         }
         Token::HtmlInline(tag) => {
             // Tags we semantically handle (sup/sub/u/s/del/small/kbd)
@@ -1170,6 +1435,10 @@ fn flatten_one(
                 push_text(out, " ", flags, link);
             } else if lower.starts_with("<!--") {
                 // Inline HTML comment payload — drop silently.
+            } else if let Some(marker) = html_input_marker(tag) {
+                if !marker.is_empty() {
+                    push_text(out, marker, flags, link);
+                }
             } else {
                 // Unknown tag — emit verbatim so users see something
                 // rather than have it silently disappear.
@@ -1183,7 +1452,7 @@ fn flatten_one(
         }
         Token::Newline => push_text(out, " ", flags, link),
         Token::HardBreak => push_text(out, " ", flags, link),
-        Token::Heading(content, _)
+        Token::Heading(content, _, _)
         | Token::BlockQuote(content)
         | Token::ListItem { content, .. } => {
             for t in content {
@@ -1232,6 +1501,7 @@ fn push_text(out: &mut Vec<InlineRun>, text: &str, flags: RunFlags, link: Option
     let link_owned = link.map(|s| s.to_string());
     if let Some(last) = out.last_mut()
         && last.math.is_none()
+        && last.image.is_none()
         && last.flags == flags
         && last.link == link_owned
     {
@@ -1240,16 +1510,280 @@ fn push_text(out: &mut Vec<InlineRun>, text: &str, flags: RunFlags, link: Option
     }
     out.push(InlineRun {
         math: None,
+        image: None,
         text: text.to_string(),
         flags,
         link: link_owned,
     });
 }
 
+/// Split English ordinal suffixes (`1st`, `22nd`, `103rd`, `4th`) off
+/// their digits and mark them superscript. Only the suffix that matches
+/// the number counts, so `1th` or `21th` stay as written. Code, math,
+/// and runs that are already super/subscript are left alone.
+pub(crate) fn raise_ordinal_suffixes(runs: &mut Vec<InlineRun>) {
+    if !runs
+        .iter()
+        .any(|r| r.text.bytes().any(|b| b.is_ascii_digit()))
+    {
+        return;
+    }
+    let mut out = Vec::with_capacity(runs.len());
+    for run in runs.drain(..) {
+        let f = run.flags;
+        if run.math.is_some()
+            || run.image.is_some()
+            || f.monospace
+            || f.inline_code
+            || f.superscript
+            || f.subscript
+        {
+            out.push(run);
+            continue;
+        }
+        let spans = ordinal_suffix_spans(&run.text);
+        if spans.is_empty() {
+            out.push(run);
+            continue;
+        }
+        let mut pieces = Vec::with_capacity(spans.len() * 2 + 1);
+        let mut prev = 0;
+        for (start, end) in spans {
+            pieces.push((&run.text[prev..start], f));
+            pieces.push((&run.text[start..end], f.with_superscript()));
+            prev = end;
+        }
+        pieces.push((&run.text[prev..], f));
+        for (text, flags) in pieces.into_iter().filter(|(t, _)| !t.is_empty()) {
+            out.push(InlineRun {
+                math: None,
+                image: None,
+                text: text.to_string(),
+                flags,
+                link: run.link.clone(),
+            });
+        }
+    }
+    *runs = out;
+}
+
+/// Byte ranges of the ordinal suffixes in `text`. A match needs a
+/// standalone digit run (no letter or digit right before it) followed
+/// by exactly the right two-letter suffix and then a word boundary.
+fn ordinal_suffix_spans(text: &str) -> Vec<(usize, usize)> {
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80;
+    let bytes = text.as_bytes();
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() || (i > 0 && is_word(bytes[i - 1])) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        let end = i + 2;
+        if end <= bytes.len()
+            && bytes[i..end] == *ordinal_suffix(&bytes[start..i]).as_bytes()
+            && (end == bytes.len() || !is_word(bytes[end]))
+        {
+            spans.push((i, end));
+            i = end;
+        }
+    }
+    spans
+}
+
+/// The English ordinal suffix for a run of ASCII digits: `th` for
+/// anything ending in 11–13, otherwise by the last digit.
+fn ordinal_suffix(digits: &[u8]) -> &'static str {
+    let last = digits[digits.len() - 1];
+    let tens = if digits.len() >= 2 {
+        digits[digits.len() - 2]
+    } else {
+        b'0'
+    };
+    match (tens, last) {
+        (b'1', _) => "th",
+        (_, b'1') => "st",
+        (_, b'2') => "nd",
+        (_, b'3') => "rd",
+        _ => "th",
+    }
+}
+
+/// Print link targets next to the link text per `[link] display`.
+/// `parenthetical` appends ` (url)`; `footnote` appends a superscript
+/// marker and lists the URL after the document's own footnotes (one
+/// entry per distinct URL). Fragment and relative targets follow
+/// `relative_urls`, and links whose text already spells the URL
+/// (autolinks) are left alone. With `print_titles`, a URL that has an
+/// entry in `titles` is printed as `Title: url`. Returns the inserted
+/// text so the caller can seed the font subset with it.
+pub(crate) fn print_link_targets(
+    blocks: &mut Vec<Block>,
+    cfg: &ResolvedLinkDisplay,
+    titles: &HashMap<String, String>,
+) -> String {
+    let mut printed_text = String::new();
+    if cfg.display == LinkDisplay::Inline {
+        return printed_text;
+    }
+    let mut next_number = blocks
+        .iter()
+        .filter_map(|b| match b {
+            Block::FootnoteDefinitions { entries } => entries.iter().map(|e| e.number).max(),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let mut numbers: HashMap<String, usize> = HashMap::new();
+    let mut new_entries: Vec<FootnoteEntry> = Vec::new();
+    visit_runs_mut(blocks, &mut |runs| {
+        let mut i = 0;
+        while i < runs.len() {
+            let Some(url) = runs[i].link.clone() else {
+                i += 1;
+                continue;
+            };
+            let mut end = i + 1;
+            while end < runs.len() && runs[end].link.as_deref() == Some(url.as_str()) {
+                end += 1;
+            }
+            let text: String = runs[i..end].iter().map(|r| r.text.as_str()).collect();
+            let Some(target) = printed_link_target(&url, &text, cfg) else {
+                i = end;
+                continue;
+            };
+            let target = match titles.get(&url) {
+                Some(title) if cfg.print_titles && !title.trim().is_empty() => {
+                    format!("{}: {}", title.trim(), target)
+                }
+                _ => target,
+            };
+            let flags = runs[end - 1].flags;
+            let suffix = if cfg.display == LinkDisplay::Footnote {
+                let n = *numbers.entry(target.clone()).or_insert_with(|| {
+                    let n = next_number;
+                    next_number += 1;
+                    printed_text.push_str(&target);
+                    new_entries.push(FootnoteEntry {
+                        label: target.clone(),
+                        number: n,
+                        runs: vec![InlineRun {
+                            math: None,
+                            image: None,
+                            text: target.clone(),
+                            flags: RunFlags::default(),
+                            link: Some(url.clone()),
+                        }],
+                    });
+                    n
+                });
+                InlineRun {
+                    math: None,
+                    image: None,
+                    text: n.to_string(),
+                    flags: flags.with_superscript(),
+                    link: Some(format!("#footnote-{}", n)),
+                }
+            } else {
+                InlineRun {
+                    math: None,
+                    image: None,
+                    text: format!(" ({})", target),
+                    flags,
+                    link: None,
+                }
+            };
+            printed_text.push_str(&suffix.text);
+            runs.insert(end, suffix);
+            i = end + 1;
+        }
+    });
+    if !new_entries.is_empty() {
+        let existing = blocks.iter_mut().rev().find_map(|b| match b {
+            Block::FootnoteDefinitions { entries } => Some(entries),
+            _ => None,
+        });
+        match existing {
+            Some(entries) => entries.extend(new_entries),
+            None => blocks.push(Block::FootnoteDefinitions {
+                entries: new_entries,
+            }),
+        }
+    }
+    printed_text
+}
+
+/// What to print for a link to `url` whose visible text is `text`, or
+/// `None` to print nothing.
+fn printed_link_target(url: &str, text: &str, cfg: &ResolvedLinkDisplay) -> Option<String> {
+    // Footnote markers are synthesized links, not author links.
+    if url.starts_with("#footnote-") {
+        return None;
+    }
+    let text = text.trim();
+    if text == url
+        || url.strip_prefix("mailto:") == Some(text)
+        || url.strip_prefix("http://") == Some(text)
+    {
+        return None;
+    }
+    if !is_relative_url(url) {
+        return Some(url.to_string());
+    }
+    match cfg.relative_urls {
+        RelativeUrlPolicy::Keep => Some(url.to_string()),
+        RelativeUrlPolicy::Strip => None,
+        RelativeUrlPolicy::ResolveBase => {
+            cfg.base_url.as_deref().map(|base| join_base_url(base, url))
+        }
+    }
+}
+
+/// True for fragment (`#x`) and relative (`./a.md`, `/docs`) targets:
+/// anything without a `scheme:` prefix or a `//host` authority.
+fn is_relative_url(url: &str) -> bool {
+    if url.starts_with("//") {
+        return false;
+    }
+    let scheme = url.split_once(':').map(|(s, _)| s);
+    !scheme.is_some_and(|s| {
+        s.starts_with(|c: char| c.is_ascii_alphabetic())
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+/// Join a relative target onto `base`: fragments replace the base's
+/// fragment, `/`-rooted paths replace its path, anything else resolves
+/// against its directory.
+fn join_base_url(base: &str, target: &str) -> String {
+    let base = base.split('#').next().unwrap_or(base);
+    if target.starts_with('#') {
+        return format!("{}{}", base, target);
+    }
+    let path_start = base.find("://").map(|i| i + 3).map_or(0, |host| {
+        base[host..].find('/').map_or(base.len(), |j| host + j)
+    });
+    if target.starts_with('/') {
+        return format!("{}{}", &base[..path_start], target);
+    }
+    let target = target.strip_prefix("./").unwrap_or(target);
+    match base[path_start..].rfind('/') {
+        Some(j) => format!("{}{}", &base[..path_start + j + 1], target),
+        None => format!("{}/{}", base, target),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::markdown::Token;
+    use crate::markdown::{CodeAttrs, Token};
 
     #[test]
     fn plain_text_to_paragraph() {
@@ -1263,11 +1797,28 @@ mod tests {
         assert!(!runs[0].flags.bold);
     }
 
+    #[test]
+    fn stray_table_alignment_adds_no_text() {
+        let blocks = lower(&[
+            Token::Text("a ".to_string()),
+            Token::TableAlignment(crate::markdown::TableAlignment::Center),
+            Token::Text("b".to_string()),
+        ]);
+        assert_eq!(blocks.len(), 1);
+        let Block::Paragraph { runs } = &blocks[0] else {
+            panic!("expected paragraph");
+        };
+        assert_eq!(runs[0].text, "a b");
+
+        let blocks = lower(&[Token::TableAlignment(crate::markdown::TableAlignment::Left)]);
+        assert!(blocks.is_empty(), "got {blocks:?}");
+    }
+
     #[test]
     fn heading_lifts_to_block() {
-        let blocks = lower(&[Token::Heading(vec![Token::Text("Hi".into())], 2)]);
+        let blocks = lower(&[Token::Heading(vec![Token::Text("Hi".into())], 2, None)]);
         assert_eq!(blocks.len(), 1);
-        let Block::Heading { level, runs } = &blocks[0] else {
+        let Block::Heading { level, runs, .. } = &blocks[0] else {
             panic!("expected heading");
         };
         assert_eq!(*level, 2);
@@ -1294,6 +1845,73 @@ mod tests {
         assert!(!runs[2].flags.bold);
     }
 
+    /// Lex `src` and return `(bold, italic)` for the run whose text is
+    /// `word`.
+    fn flags_of(src: &str, word: &str) -> RunFlags {
+        let tokens = crate::markdown::Lexer::new(src.to_string())
+            .parse()
+            .unwrap();
+        let blocks = lower(&tokens);
+        let Block::Paragraph { runs } = &blocks[0] else {
+            panic!("expected paragraph");
+        };
+        runs.iter()
+            .find(|r| r.text == word)
+            .unwrap_or_else(|| panic!("no run `{word}` in {runs:?}"))
+            .flags
+    }
+
+    fn style_of(src: &str, word: &str) -> (bool, bool) {
+        let flags = flags_of(src, word);
+        (flags.bold, flags.italic)
+    }
+
+    #[test]
+    fn emphasis_levels_are_recorded_for_styling() {
+        assert_eq!(flags_of("a *i* b", "i").emphasis, 1);
+        assert_eq!(flags_of("a **b** c", "b").emphasis, 2);
+        assert_eq!(flags_of("***bi***", "bi").emphasis, 3);
+        assert_eq!(flags_of("**_mixed_**", "mixed").emphasis, 3);
+    }
+
+    #[test]
+    fn emphasis_levels_map_to_consistent_styles() {
+        assert_eq!(style_of("*i*", "i"), (false, true));
+        assert_eq!(style_of("_i_", "i"), (false, true));
+        assert_eq!(style_of("**b**", "b"), (true, false));
+        assert_eq!(style_of("__b__", "b"), (true, false));
+        assert_eq!(style_of("***bi***", "bi"), (true, true));
+        assert_eq!(style_of("**_mixed_**", "mixed"), (true, true));
+        assert_eq!(style_of("_**mixed**_", "mixed"), (true, true));
+    }
+
+    #[test]
+    fn strong_variant_and_high_levels_match_lexer_output() {
+        let text = |s: &str| vec![Token::Text(s.into())];
+        let blocks = lower(&[
+            Token::StrongEmphasis(text("a")),
+            Token::Emphasis {
+                level: 2,
+                content: text("b"),
+            },
+            Token::Emphasis {
+                level: 3,
+                content: text("c"),
+            },
+        ]);
+        let Block::Paragraph { runs } = &blocks[0] else {
+            panic!("expected paragraph");
+        };
+        // Adjacent runs only merge when their flags are identical, so
+        // `StrongEmphasis` and level 2 collapsing into one run proves
+        // they style the same.
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].text, "ab");
+        assert!(runs[0].flags.bold && !runs[0].flags.italic);
+        assert_eq!(runs[1].text, "c");
+        assert!(runs[1].flags.bold && runs[1].flags.italic);
+    }
+
     #[test]
     fn double_newline_separates_paragraphs() {
         let blocks = lower(&[
@@ -1315,6 +1933,7 @@ mod tests {
                 language: String::new(),
                 content: "foo".into(),
                 block: false,
+                attrs: CodeAttrs::default(),
             },
         ]);
         let Block::Paragraph { runs } = &blocks[0] else {
@@ -1360,6 +1979,26 @@ mod tests {
         assert!(runs.iter().any(|r| r.math.as_deref() == Some("x+y=z")));
     }
 
+    #[test]
+    fn image_inside_text_becomes_one_image_run() {
+        let blocks = lower(&[
+            Token::Text("Click ".into()),
+            Token::Image {
+                alt: vec![Token::Text("gear".into())],
+                url: "icons/gear.png".into(),
+                title: None,
+            },
+            Token::Text(" to open settings.".into()),
+        ]);
+        let Block::Paragraph { runs } = &blocks[0] else {
+            panic!("expected paragraph, got {:?}", blocks[0]);
+        };
+        let images: Vec<_> = runs.iter().filter(|r| r.image.is_some()).collect();
+        assert_eq!(images.len(), 1, "{runs:?}");
+        assert_eq!(images[0].image.as_deref(), Some("icons/gear.png"));
+        assert_eq!(images[0].text, "[image: gear]");
+    }
+
     #[test]
     fn display_math_becomes_centered_block_and_flushes_paragraphs() {
         let blocks = lower(&[
@@ -1397,18 +2036,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn quoted_list_item_keeps_its_code_block_and_later_paragraph() {
+        let blocks = lower(&lex(
+            "> - item\n>\n>   ```\n>   let x = 1;\n>   ```\n>\n>   more text\n",
+        ));
+        let [Block::Quote { body }] = &blocks[..] else {
+            panic!("expected one quote, got {blocks:?}");
+        };
+        let [Block::List { entries }] = &body[..] else {
+            panic!("expected a list in the quote, got {body:?}");
+        };
+        let children = &entries[0].children;
+        assert!(
+            matches!(&children[..], [Block::Code { lines, .. }, Block::Paragraph { .. }]
+                if lines.iter().any(|l| l == "let x = 1;")),
+            "got {children:?}"
+        );
+        assert!(entries[0].runs.iter().all(|r| !r.text.contains("more")));
+    }
+
     #[test]
     fn code_block_becomes_codeblock() {
         let blocks = lower(&[Token::Code {
             language: "rust".into(),
             content: "fn main()\n{}".into(),
             block: true,
+            attrs: CodeAttrs::default(),
         }]);
         assert_eq!(blocks.len(), 1);
-        let Block::Code { lines } = &blocks[0] else {
+        let Block::Code {
+            lines, language, ..
+        } = &blocks[0]
+        else {
             panic!();
         };
         assert_eq!(lines, &vec!["fn main()".to_string(), "{}".to_string()]);
+        assert_eq!(language, "rust");
+    }
+
+    #[test]
+    fn code_block_tabs_expand_to_four_column_stops() {
+        let blocks = lower(&[Token::Code {
+            language: "text".into(),
+            content: "a\tb\n\tc\nabcd\te".into(),
+            block: true,
+            attrs: CodeAttrs::default(),
+        }]);
+        let Block::Code { lines, .. } = &blocks[0] else {
+            panic!();
+        };
+        assert_eq!(lines, &vec!["a   b", "    c", "abcd    e"]);
     }
 
     fn lex(src: &str) -> Vec<Token> {
@@ -1417,6 +2095,47 @@ mod tests {
             .unwrap()
     }
 
+    fn paragraph_texts(blocks: &[Block]) -> Vec<String> {
+        blocks
+            .iter()
+            .filter_map(|b| match b {
+                Block::Paragraph { runs } => {
+                    Some(runs.iter().map(|r| r.text.as_str()).collect::<String>())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn html_br_breaks_the_line() {
+        let blocks = lower(&lex("first line<br>second line"));
+        assert_eq!(paragraph_texts(&blocks), vec!["first line", "second line"]);
+    }
+
+    #[test]
+    fn html_checkbox_input_renders_as_a_mark() {
+        let blocks = lower(&lex(
+            "Done <input type=\"checkbox\" checked> and todo <input type=checkbox disabled />",
+        ));
+        assert_eq!(paragraph_texts(&blocks), vec!["Done [x] and todo [ ]"]);
+    }
+
+    #[test]
+    fn html_input_whitelist() {
+        assert_eq!(
+            html_input_marker("<input type=\"radio\" checked>"),
+            Some("(x)")
+        );
+        assert_eq!(html_input_marker("<INPUT TYPE='Radio'>"), Some("( )"));
+        assert_eq!(
+            html_input_marker("<input type=\"text\" value=\"x\">"),
+            Some("")
+        );
+        assert_eq!(html_input_marker("<inputs>"), None);
+        assert_eq!(html_input_marker("<kbd>"), None);
+    }
+
     fn footnote_section(blocks: &[Block]) -> &[FootnoteEntry] {
         blocks
             .iter()
@@ -1473,6 +2192,63 @@ mod tests {
         assert!(second.contains("ref def"), "got {second:?}");
     }
 
+    #[test]
+    fn footnote_defined_before_its_reference_is_collected() {
+        let blocks = lower(&lex("[^a]: early note\n\nBody[^a]."));
+        let entries = footnote_section(&blocks);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].number, 1);
+        let body: String = entries[0].runs.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(body, "early note");
+    }
+
+    #[test]
+    fn footnote_defined_inside_a_list_item_reaches_the_tail() {
+        // A definition nested where content is flattened inline used
+        // to be dropped; it must land in the footnote section instead
+        // of the item's text.
+        let tokens = vec![
+            Token::ListItem {
+                content: vec![
+                    Token::Text("item".to_string()),
+                    Token::FootnoteDefinition {
+                        label: "n".to_string(),
+                        content: vec![Token::Text("nested note".to_string())],
+                    },
+                ],
+                ordered: false,
+                number: None,
+                marker: '-',
+                checked: None,
+                loose: false,
+            },
+            Token::Text("Ref".to_string()),
+            Token::FootnoteReference("n".to_string()),
+        ];
+        let blocks = lower(&tokens);
+        let entries = footnote_section(&blocks);
+        assert_eq!(entries.len(), 1);
+        let body: String = entries[0].runs.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(body, "nested note");
+        let Some(Block::List { entries: items }) = blocks.first() else {
+            panic!("expected a list first, got {blocks:?}");
+        };
+        let head: String = items[0].runs.iter().map(|r| r.text.as_str()).collect();
+        assert!(
+            !head.contains("nested note"),
+            "body leaked inline: {head:?}"
+        );
+    }
+
+    #[test]
+    fn first_footnote_definition_wins() {
+        let blocks = lower(&lex("Ref[^a].\n\n[^a]: first\n\n[^a]: second"));
+        let entries = footnote_section(&blocks);
+        assert_eq!(entries.len(), 1);
+        let body: String = entries[0].runs.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(body, "first");
+    }
+
     fn walk_superscript_markers(blocks: &[Block], out: &mut Vec<String>) {
         for b in blocks {
             match b {
@@ -1623,4 +2399,208 @@ mod tests {
     fn empty_wrapper_body_returns_none() {
         assert_eq!(strip_framing_wrapper("<p></p>"), None);
     }
+
+    fn ordinal_runs(text: &str) -> Vec<(String, bool)> {
+        let mut runs = vec![InlineRun::new(text)];
+        raise_ordinal_suffixes(&mut runs);
+        runs.into_iter()
+            .map(|r| (r.text, r.flags.superscript))
+            .collect()
+    }
+
+    #[test]
+    fn ordinal_suffixes_are_raised() {
+        assert_eq!(
+            ordinal_runs("the 21st and 112th"),
+            vec![
+                ("the 21".to_string(), false),
+                ("st".to_string(), true),
+                (" and 112".to_string(), false),
+                ("th".to_string(), true),
+            ]
+        );
+        assert_eq!(
+            ordinal_runs("2nd, 3rd."),
+            vec![
+                ("2".to_string(), false),
+                ("nd".to_string(), true),
+                (", 3".to_string(), false),
+                ("rd".to_string(), true),
+                (".".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn mismatched_or_embedded_ordinals_stay_plain() {
+        for text in ["1th", "21th", "11st", "a1st", "1stly", "version 3rdparty"] {
+            assert_eq!(
+                ordinal_runs(text),
+                vec![(text.to_string(), false)],
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn ordinals_skip_inline_code() {
+        let mut runs = vec![InlineRun {
+            flags: RunFlags::default().with_monospace().with_inline_code(),
+            ..InlineRun::new("1st")
+        }];
+        raise_ordinal_suffixes(&mut runs);
+        assert_eq!(runs.len(), 1);
+        assert!(!runs[0].flags.superscript);
+    }
+
+    fn link_paragraph(url: &str) -> Vec<Block> {
+        lower(&lex(&format!("See [setup]({}).", url)))
+    }
+
+    fn paragraph_text(blocks: &[Block]) -> String {
+        let Block::Paragraph { runs } = &blocks[0] else {
+            panic!("expected paragraph, got {:?}", blocks[0]);
+        };
+        runs.iter().map(|r| r.text.as_str()).collect()
+    }
+
+    fn link_cfg(
+        display: LinkDisplay,
+        relative_urls: RelativeUrlPolicy,
+        base_url: Option<&str>,
+    ) -> ResolvedLinkDisplay {
+        ResolvedLinkDisplay {
+            display,
+            relative_urls,
+            base_url: base_url.map(str::to_string),
+            print_titles: false,
+        }
+    }
+
+    #[test]
+    fn inline_link_display_prints_nothing() {
+        let mut blocks = link_paragraph("https://example.com");
+        let cfg = link_cfg(LinkDisplay::Inline, RelativeUrlPolicy::Keep, None);
+        assert_eq!(print_link_targets(&mut blocks, &cfg, &HashMap::new()), "");
+        assert_eq!(paragraph_text(&blocks), "See setup.");
+    }
+
+    #[test]
+    fn parenthetical_prints_absolute_urls() {
+        let mut blocks = link_paragraph("https://example.com");
+        let cfg = link_cfg(LinkDisplay::Parenthetical, RelativeUrlPolicy::Strip, None);
+        print_link_targets(&mut blocks, &cfg, &HashMap::new());
+        assert_eq!(paragraph_text(&blocks), "See setup (https://example.com).");
+    }
+
+    #[test]
+    fn print_titles_puts_the_title_before_the_url() {
+        let titles =
+            HashMap::from([("https://example.com".to_string(), "Setup guide".to_string())]);
+        let mut cfg = link_cfg(LinkDisplay::Parenthetical, RelativeUrlPolicy::Strip, None);
+        let mut blocks = link_paragraph("https://example.com");
+        print_link_targets(&mut blocks, &cfg, &titles);
+        assert_eq!(paragraph_text(&blocks), "See setup (https://example.com).");
+
+        cfg.print_titles = true;
+        let mut blocks = link_paragraph("https://example.com");
+        print_link_targets(&mut blocks, &cfg, &titles);
+        assert_eq!(
+            paragraph_text(&blocks),
+            "See setup (Setup guide: https://example.com)."
+        );
+    }
+
+    #[test]
+    fn fragment_link_keep_policy_prints_fragment() {
+        let mut blocks = link_paragraph("#setup");
+        let cfg = link_cfg(LinkDisplay::Parenthetical, RelativeUrlPolicy::Keep, None);
+        print_link_targets(&mut blocks, &cfg, &HashMap::new());
+        assert_eq!(paragraph_text(&blocks), "See setup (#setup).");
+    }
+
+    #[test]
+    fn fragment_link_strip_policy_prints_nothing() {
+        let mut blocks = link_paragraph("#setup");
+        let cfg = link_cfg(LinkDisplay::Parenthetical, RelativeUrlPolicy::Strip, None);
+        print_link_targets(&mut blocks, &cfg, &HashMap::new());
+        assert_eq!(paragraph_text(&blocks), "See setup.");
+    }
+
+    #[test]
+    fn fragment_link_resolve_base_policy_joins_base() {
+        let mut blocks = link_paragraph("#setup");
+        let cfg = link_cfg(
+            LinkDisplay::Parenthetical,
+            RelativeUrlPolicy::ResolveBase,
+            Some("https://example.com/docs/guide.html#intro"),
+        );
+        print_link_targets(&mut blocks, &cfg, &HashMap::new());
+        assert_eq!(
+            paragraph_text(&blocks),
+            "See setup (https://example.com/docs/guide.html#setup)."
+        );
+
+        // Without a base there is nothing meaningful to print.
+        let mut blocks = link_paragraph("#setup");
+        let cfg = link_cfg(
+            LinkDisplay::Parenthetical,
+            RelativeUrlPolicy::ResolveBase,
+            None,
+        );
+        print_link_targets(&mut blocks, &cfg, &HashMap::new());
+        assert_eq!(paragraph_text(&blocks), "See setup.");
+    }
+
+    #[test]
+    fn footnote_display_numbers_urls_after_document_footnotes() {
+        let mut blocks = lower(&lex(
+            "a[^n] [x](https://x.test) [y](https://x.test)\n\n[^n]: note",
+        ));
+        let cfg = link_cfg(LinkDisplay::Footnote, RelativeUrlPolicy::Strip, None);
+        print_link_targets(&mut blocks, &cfg, &HashMap::new());
+        let entries = footnote_section(&blocks);
+        assert_eq!(entries.len(), 2, "same URL shares one entry");
+        assert_eq!(entries[1].number, 2);
+        assert_eq!(entries[1].runs[0].text, "https://x.test");
+        let markers: Vec<_> = blocks
+            .iter()
+            .filter_map(|b| match b {
+                Block::Paragraph { runs } => Some(runs),
+                _ => None,
+            })
+            .flatten()
+            .filter(|r| r.link.as_deref() == Some("#footnote-2"))
+            .collect();
+        assert_eq!(markers.len(), 2);
+        assert!(markers.iter().all(|r| r.flags.superscript && r.text == "2"));
+    }
+
+    #[test]
+    fn autolinks_and_relative_paths() {
+        let cfg = link_cfg(
+            LinkDisplay::Parenthetical,
+            RelativeUrlPolicy::ResolveBase,
+            Some("https://example.com/docs/guide.html"),
+        );
+        assert_eq!(
+            printed_link_target("https://a.test", "https://a.test", &cfg),
+            None
+        );
+        assert_eq!(
+            printed_link_target("http://www.a.test", "www.a.test", &cfg),
+            None
+        );
+        assert_eq!(
+            printed_link_target("./other.md", "other", &cfg).as_deref(),
+            Some("https://example.com/docs/other.md")
+        );
+        assert_eq!(
+            printed_link_target("/root.md", "root", &cfg).as_deref(),
+            Some("https://example.com/root.md")
+        );
+        assert!(!is_relative_url("mailto:a@b.test"));
+        assert!(!is_relative_url("//cdn.test/x"));
+        assert!(is_relative_url("docs/a:b.md"));
+    }
 }
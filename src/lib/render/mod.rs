@@ -36,6 +36,8 @@
 //! - GFM tables with per-column alignment, header repeat across pages
 //! - Local-file images (PNG / JPEG); URL fetch is gated under the
 //!   `fetch` feature
+//! - `mermaid` / `dot` fenced blocks drawn as diagrams through the
+//!   external `mmdc` / `dot` tools, gated under the `diagrams` feature
 //! - Hyperlinks as PDF link annotations
 //! - Block-level HTML treated as monospace; comment-only blocks are
 //!   invisible per CommonMark §4.6
@@ -50,6 +52,8 @@
 //! - URL image fetching, inline link tooltips, footnotes, headers /
 //!   footers, page numbers, TOC, bookmarks — all roadmap items
 
+#[cfg(feature = "diagrams")]
+mod diagram;
 mod font;
 mod hyphenate;
 mod image_policy;
@@ -60,16 +64,48 @@ mod math;
 #[cfg(feature = "fetch")]
 mod net_guard;
 #[cfg(feature = "fetch")]
-mod net_read;
+pub(crate) mod net_read;
 mod postprocess;
 mod preprocess;
 
 use crate::markdown::Token;
-use crate::styling::ResolvedStyle;
+use crate::styling::{Digits, PageNumberStyle, ResolvedStyle, TableOverflow};
 use crate::{MdpError, fonts::FontConfig};
 
 use printpdf::{PdfDocument, PdfSaveOptions};
 
+pub(crate) use font::{FontCache, missing_variants};
+
+/// A step of a conversion, reported to the callback given to
+/// [`crate::parse_into_file_with_progress`]. Events arrive in the
+/// order declared here; `RenderingBlock` repeats once per top-level
+/// block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// The config is resolved and the input is within limits.
+    StylingLoaded,
+    /// The markdown is about to be lexed.
+    Lexing,
+    /// Top-level block `.0` (0-based) of `.1` is about to be laid out.
+    RenderingBlock(usize, usize),
+    /// Layout is done; the PDF is being serialized and written.
+    Writing,
+}
+
+/// Figures about a finished render, returned with the bytes by
+/// [`crate::convert`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// Pages in the PDF, including those of `pdf-include` files.
+    pub pages: usize,
+    /// Top-level blocks laid out: headings, paragraphs, lists, tables…
+    pub blocks: usize,
+    /// Whitespace-separated words in the text of headings, paragraphs,
+    /// list items, table cells and other inline content. Code blocks
+    /// aren't counted.
+    pub words: usize,
+}
+
 /// Render a token stream to a PDF file at `path`.
 pub fn render_to_file(
     tokens: Vec<Token>,
@@ -77,27 +113,81 @@ pub fn render_to_file(
     font_config: Option<&FontConfig>,
     path: impl AsRef<std::path::Path>,
 ) -> Result<(), MdpError> {
-    let path = path.as_ref();
-    let bytes = render_to_bytes(tokens, style, font_config)?;
+    render_to_file_with_progress(
+        tokens,
+        style,
+        font_config,
+        path.as_ref(),
+        &mut |_| {},
+        &mut |bytes| Ok(bytes),
+    )
+}
+
+/// Render to `path`, reporting each step to `progress` and passing the
+/// finished bytes through `post_process` before they are written. A
+/// hook error is returned as is and nothing is written.
+pub(crate) fn render_to_file_with_progress(
+    tokens: Vec<Token>,
+    style: ResolvedStyle,
+    font_config: Option<&FontConfig>,
+    path: &std::path::Path,
+    progress: &mut dyn FnMut(ProgressEvent),
+    post_process: &mut dyn FnMut(Vec<u8>) -> Result<Vec<u8>, MdpError>,
+) -> Result<(), MdpError> {
+    let bytes =
+        render_to_bytes_with_progress(tokens, style, font_config, &FontCache::default(), progress)?;
+    let bytes = post_process(bytes)?;
     std::fs::write(path, bytes).map_err(|e| MdpError::PdfError {
         message: e.to_string(),
         path: Some(path.display().to_string()),
         suggestion: Some(
             "Check that the output directory exists and you have write permissions".to_string(),
         ),
+        source: Some(Box::new(e)),
     })
 }
 
 /// Render a token stream to PDF bytes.
 pub fn render_to_bytes(
-    mut tokens: Vec<Token>,
+    tokens: Vec<Token>,
     style: ResolvedStyle,
     font_config: Option<&FontConfig>,
 ) -> Result<Vec<u8>, MdpError> {
+    render_to_bytes_with_progress(
+        tokens,
+        style,
+        font_config,
+        &FontCache::default(),
+        &mut |_| {},
+    )
+}
+
+/// Render to bytes, reporting each step to `progress`. Font files and
+/// system font lookups go through `fonts`, which a [`crate::Renderer`]
+/// keeps between calls.
+pub(crate) fn render_to_bytes_with_progress(
+    tokens: Vec<Token>,
+    style: ResolvedStyle,
+    font_config: Option<&FontConfig>,
+    fonts: &FontCache,
+    progress: &mut dyn FnMut(ProgressEvent),
+) -> Result<Vec<u8>, MdpError> {
+    render_with_stats(tokens, style, font_config, fonts, progress).map(|(bytes, _)| bytes)
+}
+
+/// [`render_to_bytes_with_progress`] that also counts what it rendered.
+pub(crate) fn render_with_stats(
+    mut tokens: Vec<Token>,
+    style: ResolvedStyle,
+    font_config: Option<&FontConfig>,
+    fonts: &FontCache,
+    progress: &mut dyn FnMut(ProgressEvent),
+) -> Result<(Vec<u8>, RenderStats), MdpError> {
     // Recognise inline `<a href="…">…</a>` HTML up front so the
     // renderer's normal link path (and the tooltip post-pass below)
     // handles it like any markdown link.
     preprocess::rewrite_html_anchors(&mut tokens);
+    preprocess::apply_inline_html_mode(&mut tokens, style.html.mode);
 
     let doc_title = style
         .metadata
@@ -124,7 +214,31 @@ pub fn render_to_bytes(
     }
 
     let body_text = Token::collect_all_text(&tokens);
-    let blocks = lower::lower(&tokens);
+    let mut blocks = lower::lower(&tokens);
+    lower::apply_html_mode(&mut blocks, style.html.mode);
+    #[cfg(feature = "diagrams")]
+    diagram::render_diagrams(&mut blocks);
+    if style.superscript.ordinals {
+        ir::visit_runs_mut(&mut blocks, &mut lower::raise_ordinal_suffixes);
+    }
+    // Titles from `[text](url "title")`, keyed by URL: printed with
+    // the URL when `[link] print_titles` is on, and always attached
+    // as hover tooltips once the PDF is saved.
+    let tooltips = postprocess::collect_link_tooltips(&tokens);
+    let link_targets = lower::print_link_targets(&mut blocks, &style.link_display, &tooltips);
+    if style.digits != Digits::Latin {
+        ir::visit_runs_mut(&mut blocks, &mut |runs| {
+            for run in runs.iter_mut() {
+                if run
+                    .link
+                    .as_deref()
+                    .is_some_and(|l| l.starts_with("#footnote-"))
+                {
+                    run.text = style.digits.localize(&run.text);
+                }
+            }
+        });
+    }
     // Codepoint set seeded from the source body, then extended with
     // every string the layout pass synthesizes (admonition kind
     // labels, the auto "Footnotes" heading, TOC title, title-page
@@ -134,7 +248,7 @@ pub fn render_to_bytes(
     // every letter in `IMPORTANT` that didn't happen to appear in
     // the source body.
     let used_codepoints: Vec<char> = {
-        let mut chars: Vec<char> = body_text.chars().collect();
+        let mut chars: Vec<char> = body_text.chars().chain(link_targets.chars()).collect();
         collect_synthesized_codepoints(&blocks, &style, &mut chars);
         collect_style_codepoints(&style, &mut chars);
         chars.sort();
@@ -176,16 +290,71 @@ pub fn render_to_bytes(
         (Some(ci), Some(cb)) if ci.eq_ignore_ascii_case(cb) => None,
         (ci, _) => ci,
     };
-    let font_set = font::FontSet::load_with_style_fallbacks(
-        font_config,
-        &style.fallback_fonts,
-        code_inline_font,
-        &used_codepoints,
-        usage,
+    // `[output] embed_fonts` overrides the automatic choice: `false`
+    // skips every font load, `true` swaps built-in faces for system
+    // fonts before loading.
+    let forced_config;
+    let font_config = match style.output.embed_fonts {
+        Some(true) => {
+            forced_config = font::force_embedded(font_config);
+            Some(&forced_config)
+        }
+        _ => font_config,
+    };
+    let font_set = if style.output.embed_fonts == Some(false) {
+        font::FontSet::builtin_only()
+    } else {
+        font::FontSet::load_with_style_fallbacks(
+            font_config,
+            &style.fallback_fonts,
+            code_inline_font,
+            &used_codepoints,
+            usage,
+            &mut doc,
+            fonts,
+        )
+    };
+    let mut words = 0;
+    ir::visit_runs_mut(&mut blocks, &mut |runs| {
+        let text: String = runs.iter().map(|r| r.text.as_str()).collect();
+        words += text.split_whitespace().count();
+    });
+    let known_heading_slugs = collect_heading_slugs(&blocks);
+    let layout::LaidOut {
+        pages,
+        pdf_includes,
+        outline,
+    } = layout::lay_out_pages(
+        &blocks,
+        &style,
+        &font_set,
+        &known_heading_slugs,
         &mut doc,
+        progress,
     );
-    let known_heading_slugs = collect_heading_slugs(&blocks);
-    let pages = layout::lay_out_pages(&blocks, &style, &font_set, &known_heading_slugs, &mut doc);
+    if let Some(max) = style.limits.max_pages
+        && pages.len() > max
+    {
+        return Err(MdpError::PdfError {
+            message: format!("Document exceeds the configured limit of {} pages", max),
+            path: None,
+            suggestion: Some(
+                "Shorten the document or raise `max_pages` under [limits] in the config"
+                    .to_string(),
+            ),
+            source: None,
+        });
+    }
+
+    let stats = RenderStats {
+        pages: pages.len().max(1)
+            + pdf_includes
+                .iter()
+                .map(|inc| inc.doc.get_pages().len())
+                .sum::<usize>(),
+        blocks: blocks.len(),
+        words,
+    };
 
     let (fallback_w, fallback_h) = layout::page_dimensions_mm(&style.page);
     let pages = if pages.is_empty() {
@@ -198,6 +367,7 @@ pub fn render_to_bytes(
         pages
     };
 
+    progress(ProgressEvent::Writing);
     let mut warnings = Vec::new();
     let bytes = doc
         .with_pages(pages)
@@ -207,11 +377,18 @@ pub fn render_to_bytes(
         log::warn!("printpdf: {:?}", w);
     }
 
+    // Nested heading outline. Written before the includes go in: it
+    // refers to pages by object, so inserted pages don't shift it.
+    let bytes = postprocess::write_outline(bytes, &outline);
+
+    // Pages of `pdf-include` PDFs. printpdf can't import pages, so
+    // they are moved into the saved file's page tree with lopdf.
+    let bytes = postprocess::insert_pdf_pages(bytes, pdf_includes);
+
     // Inject `/Contents` (tooltip) entries on link annotations using
     // titles from `[text](url "title")`. printpdf 0.9 doesn't expose
     // `/Contents` on its `LinkAnnotation` struct, so we parse the
     // serialized bytes back with lopdf and patch them in.
-    let tooltips = postprocess::collect_link_tooltips(&tokens);
     let bytes = postprocess::inject_link_tooltips(bytes, &tooltips);
 
     // Catalog `/Lang` for accessibility — printpdf 0.9 doesn't expose
@@ -225,7 +402,154 @@ pub fn render_to_bytes(
     // (math vector outlines make raw page streams very large).
     let bytes = postprocess::compress(bytes);
 
-    Ok(bytes)
+    // Encryption goes last: every earlier pass needs to read the
+    // objects it would scramble.
+    let bytes = match &style.output.encryption {
+        Some(enc) => postprocess::encrypt(&bytes, enc).map_err(|e| MdpError::ConfigError {
+            message: format!("Could not encrypt the PDF: {}", e),
+            suggestion: "Remove `password` / `owner_password` under [output] to write it \
+                         unencrypted"
+                .to_string(),
+            source: None,
+        })?,
+        None => bytes,
+    };
+
+    Ok((bytes, stats))
+}
+
+/// Render a single token as a document of its own, for tests and
+/// snapshots that look at one element at a time. The same as
+/// [`render_to_bytes`] on `vec![token]`.
+///
+/// ```rust
+/// use markdown2pdf::{markdown::Token, render::render_token_to_bytes, styling::ResolvedStyle};
+///
+/// let heading = Token::Heading(vec![Token::Text("Title".into())], 1, None);
+/// let pdf = render_token_to_bytes(heading, ResolvedStyle::default(), None).unwrap();
+/// assert!(pdf.starts_with(b"%PDF-"));
+/// ```
+pub fn render_token_to_bytes(
+    token: Token,
+    style: ResolvedStyle,
+    font_config: Option<&FontConfig>,
+) -> Result<Vec<u8>, MdpError> {
+    render_to_bytes(vec![token], style, font_config)
+}
+
+/// Assembles one PDF from several separately-lexed token streams.
+///
+/// Fragments are laid out in push order as if they were one document:
+/// headings share a slug namespace, footnotes collect into a single
+/// tail section, and a fragment always starts a new paragraph.
+/// [`push_page_break`](Self::push_page_break) inserts the same break
+/// as a `<!-- pagebreak -->` marker. [`render_to_bytes`] remains the
+/// one-shot path for a single stream.
+///
+/// ```rust
+/// use markdown2pdf::{markdown::Lexer, render::DocumentBuilder, styling::ResolvedStyle};
+///
+/// let lex = |md: &str| Lexer::new(md.to_string()).parse().unwrap();
+/// let mut doc = DocumentBuilder::new();
+/// doc.push_tokens(lex("# Summary\n\nShort version."))
+///     .push_page_break()
+///     .push_tokens(lex("# Details\n\nLong version."));
+/// let pdf = doc.render_to_bytes(ResolvedStyle::default(), None).unwrap();
+/// assert!(pdf.starts_with(b"%PDF-"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DocumentBuilder {
+    tokens: Vec<Token>,
+}
+
+impl DocumentBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a fragment's tokens after everything pushed so far.
+    pub fn push_tokens(&mut self, tokens: Vec<Token>) -> &mut Self {
+        if tokens.is_empty() {
+            return self;
+        }
+        // A blank line between fragments so the last paragraph of one
+        // never runs on into the first paragraph of the next.
+        if !self.tokens.is_empty() {
+            self.tokens.extend([Token::Newline, Token::Newline]);
+        }
+        self.tokens.extend(tokens);
+        self
+    }
+
+    /// Start the next fragment on a new page.
+    pub fn push_page_break(&mut self) -> &mut Self {
+        self.push_tokens(vec![Token::HtmlBlock("<!-- pagebreak -->".to_string())])
+    }
+
+    /// Every token pushed so far, fragment separators included.
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// Render the assembled document to PDF bytes.
+    pub fn render_to_bytes(
+        self,
+        style: ResolvedStyle,
+        font_config: Option<&FontConfig>,
+    ) -> Result<Vec<u8>, MdpError> {
+        render_to_bytes(self.tokens, style, font_config)
+    }
+
+    /// Render the assembled document to a PDF file at `path`.
+    pub fn render_to_file(
+        self,
+        style: ResolvedStyle,
+        font_config: Option<&FontConfig>,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<(), MdpError> {
+        render_to_file(self.tokens, style, font_config, path)
+    }
+}
+
+/// `#anchor` link targets in `tokens` that match no heading anchor,
+/// in document order and without repeats. These render as plain
+/// styled text with no link annotation; [`crate::validation`] surfaces
+/// them as warnings before the render. Footnote markers are excluded.
+pub fn unresolved_internal_links(tokens: &[Token]) -> Vec<String> {
+    let mut tokens = tokens.to_vec();
+    preprocess::rewrite_html_anchors(&mut tokens);
+    let mut blocks = lower::lower(&tokens);
+    let known = collect_heading_slugs(&blocks);
+    let mut out: Vec<String> = Vec::new();
+    ir::visit_runs_mut(&mut blocks, &mut |runs| {
+        for url in runs.iter().filter_map(|r| r.link.as_deref()) {
+            if let Some(slug) = url.strip_prefix('#')
+                && !url.starts_with("#footnote-")
+                && !known.contains(slug)
+                && !out.iter().any(|u| u == url)
+            {
+                out.push(url.to_string());
+            }
+        }
+    });
+    out
+}
+
+/// Every distinct link target in `tokens`, in document order, as the
+/// layout will see it: inline, reference and autolinks alike, in any
+/// block. Footnote markers aren't included. Backs the broken-link
+/// check in `validation`.
+pub(crate) fn link_targets(tokens: &[Token]) -> Vec<String> {
+    let mut blocks = lower::lower(tokens);
+    let mut out: Vec<String> = Vec::new();
+    ir::visit_runs_mut(&mut blocks, &mut |runs| {
+        for url in runs.iter().filter_map(|r| r.link.as_deref()) {
+            if !url.starts_with("#footnote-") && !out.iter().any(|u| u == url) {
+                out.push(url.to_string());
+            }
+        }
+    });
+    out
 }
 
 /// Collect every heading's slug from the lowered IR so the layout
@@ -239,10 +563,12 @@ fn collect_heading_slugs(blocks: &[ir::Block]) -> std::collections::HashSet<Stri
     fn walk(blocks: &[ir::Block], out: &mut std::collections::HashSet<String>) {
         for b in blocks {
             match b {
-                ir::Block::Heading { runs, .. } => {
-                    let text: String = runs.iter().map(|r| r.text.as_str()).collect();
+                ir::Block::Heading { id: Some(id), .. } => {
+                    out.insert(id.clone());
+                }
+                ir::Block::Heading { text, .. } => {
                     let base = {
-                        let s = slugify(&text);
+                        let s = slugify(text);
                         if s.is_empty() {
                             "section".to_string()
                         } else {
@@ -281,6 +607,9 @@ fn collect_heading_slugs(blocks: &[ir::Block]) -> std::collections::HashSet<Stri
 /// user-configurable strings the body text need not contain, so an
 /// external font's subset has to be told about them up front.
 fn collect_style_codepoints(style: &ResolvedStyle, out: &mut Vec<char>) {
+    if style.digits != Digits::Latin {
+        out.extend(style.digits.localize("0123456789").chars());
+    }
     if let Some(toc) = &style.toc {
         out.extend(toc.title.chars());
     }
@@ -306,6 +635,17 @@ fn collect_style_codepoints(style: &ResolvedStyle, out: &mut Vec<char>) {
         {
             out.extend(t.chars());
         }
+        out.extend(roman_numeral_chars(f.page_number_style).chars());
+    }
+}
+
+/// The letters `style` writes page numbers with; empty for arabic,
+/// whose digits the font subset always keeps.
+fn roman_numeral_chars(style: PageNumberStyle) -> &'static str {
+    match style {
+        PageNumberStyle::Arabic => "",
+        PageNumberStyle::RomanLower => "ivxlcdm",
+        PageNumberStyle::RomanUpper => "IVXLCDM",
     }
 }
 
@@ -346,6 +686,22 @@ fn collect_synthesized_codepoints(
                     collect_synthesized_codepoints(&entry.children, style, out);
                 }
             }
+            ir::Block::Code { lines, .. } => {
+                // A truncated block ends in "… N more lines".
+                if style
+                    .code_block_max_lines
+                    .is_some_and(|max| lines.len() > max)
+                {
+                    out.extend("\u{2026} more lines0123456789".chars());
+                }
+            }
+            ir::Block::Table { .. } if style.table.overflow == TableOverflow::Scroll => {
+                // A clipped table ends in "… N more columns not shown".
+                out.extend("\u{2026} more columns not shown0123456789".chars());
+            }
+            ir::Block::PageNumbering { style, .. } => {
+                out.extend(roman_numeral_chars(*style).chars());
+            }
             ir::Block::FootnoteDefinitions { .. } => {
                 // render_footnote_definitions auto-emits "Footnotes"
                 // as the section heading text.
@@ -380,7 +736,7 @@ mod tests {
 
     #[test]
     fn heading_produces_valid_pdf() {
-        let tokens = vec![Token::Heading(vec![Token::Text("Hi".into())], 1)];
+        let tokens = vec![Token::Heading(vec![Token::Text("Hi".into())], 1, None)];
         let bytes = render_to_bytes(tokens, default_style(), None).unwrap();
         assert!(bytes.starts_with(b"%PDF-"));
     }
@@ -403,7 +759,7 @@ mod tests {
         let path = std::env::temp_dir().join("m2p_phase1.pdf");
         let path_s = path.to_str().unwrap();
         let tokens = vec![
-            Token::Heading(vec![Token::Text("Hello".into())], 1),
+            Token::Heading(vec![Token::Text("Hello".into())], 1, None),
             Token::Text("World".into()),
         ];
         render_to_file(tokens, default_style(), None, path_s).unwrap();
@@ -1,6 +1,9 @@
 //! lopdf post-processing for features printpdf 0.9 doesn't expose:
 //! - Inline link tooltips (`/Contents` on Link annotations)
 //! - PDF/A-1b conformance metadata (XMP, OutputIntent, document ID)
+//! - Standard-security-handler encryption for `[output] password`
+//! - Pages of other PDFs spliced in by `pdf-include` blocks
+//! - A heading outline nested by level (printpdf's is flat)
 //!
 //! The post-passes parse the bytes printpdf produced, mutate the
 //! relevant objects, and re-serialize. Failures degrade silently
@@ -8,8 +11,14 @@
 //! the user just doesn't get the polish.
 
 use crate::markdown::Token;
-use lopdf::{Dictionary, Document, Object, SaveOptions};
-use std::collections::HashMap;
+use crate::styling::{PdfPermission, ResolvedEncryption};
+use lopdf::encryption::crypt_filters::{Aes128CryptFilter, CryptFilter};
+use lopdf::{
+    Dictionary, Document, EncryptionState, EncryptionVersion, Object, ObjectId, Permissions,
+    SaveOptions, StringFormat,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 
 /// Walk the token tree and collect a URL → tooltip map from
 /// `Token::Link { title, url, .. }`. Multiple links pointing at the
@@ -34,7 +43,7 @@ fn walk(tokens: &[Token], map: &mut HashMap<String, String>) {
                 walk(content, map);
             }
             Token::Link { content, .. } => walk(content, map),
-            Token::Heading(inner, _)
+            Token::Heading(inner, _, _)
             | Token::Emphasis { content: inner, .. }
             | Token::StrongEmphasis(inner)
             | Token::Strikethrough(inner)
@@ -143,6 +152,261 @@ pub fn inject_link_tooltips(bytes: Vec<u8>, tooltips: &HashMap<String, String>)
     }
 }
 
+/// One heading in the document outline.
+pub struct OutlineEntry {
+    pub title: String,
+    /// Heading level, 1–6. Deeper entries nest under the nearest
+    /// shallower one before them.
+    pub level: u8,
+    /// 0-based page the heading sits on.
+    pub page_idx: usize,
+    /// Top of the heading in PDF user space (from the page bottom).
+    pub top_pt: f32,
+}
+
+/// Write `entries` as the document outline, replacing any printpdf
+/// emitted. Each heading nests under the closest earlier heading of a
+/// lower level, every item starts expanded, and each one jumps to the
+/// top of its heading. No-op when `entries` is empty.
+pub fn write_outline(bytes: Vec<u8>, entries: &[OutlineEntry]) -> Vec<u8> {
+    if entries.is_empty() {
+        return bytes;
+    }
+    let Ok(mut doc) = Document::load_mem(&bytes) else {
+        return bytes;
+    };
+    let pages = doc.get_pages();
+    let Ok(root_id) = doc.trailer.get(b"Root").and_then(Object::as_reference) else {
+        return bytes;
+    };
+
+    // children[0] is the outline root; children[i + 1] belongs to
+    // entries[i].
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); entries.len() + 1];
+    let mut parent_of = vec![0usize; entries.len()];
+    let mut open: Vec<(u8, usize)> = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        while open.last().is_some_and(|&(level, _)| level >= entry.level) {
+            open.pop();
+        }
+        let parent = open.last().map_or(0, |&(_, idx)| idx + 1);
+        parent_of[i] = parent;
+        children[parent].push(i);
+        open.push((entry.level, i));
+    }
+    // Every item is open, so each node's count is its descendant
+    // total. Children always follow their parent, so summing from the
+    // back sees every subtree finished.
+    let mut descendants = vec![0i64; entries.len() + 1];
+    for i in (0..entries.len()).rev() {
+        descendants[parent_of[i]] += descendants[i + 1] + 1;
+    }
+
+    let outline_root = doc.new_object_id();
+    let ids: Vec<ObjectId> = entries.iter().map(|_| doc.new_object_id()).collect();
+    let node_id = |node: usize| {
+        if node == 0 {
+            outline_root
+        } else {
+            ids[node - 1]
+        }
+    };
+    let mut root = Dictionary::new();
+    root.set("Type", Object::Name(b"Outlines".to_vec()));
+    for (i, entry) in entries.iter().enumerate() {
+        let mut item = Dictionary::new();
+        let mut title = vec![0xFE, 0xFF];
+        title.extend(entry.title.encode_utf16().flat_map(u16::to_be_bytes));
+        item.set("Title", Object::String(title, StringFormat::Hexadecimal));
+        item.set("Parent", Object::Reference(node_id(parent_of[i])));
+        if let Some(&page) = pages.get(&(entry.page_idx as u32 + 1)) {
+            item.set(
+                "Dest",
+                Object::Array(vec![
+                    Object::Reference(page),
+                    Object::Name(b"XYZ".to_vec()),
+                    Object::Null,
+                    Object::Real(entry.top_pt),
+                    Object::Null,
+                ]),
+            );
+        }
+        doc.objects.insert(ids[i], Object::Dictionary(item));
+    }
+    for (node, kids) in children.iter().enumerate() {
+        let (Some(&first), Some(&last)) = (kids.first(), kids.last()) else {
+            continue;
+        };
+        for pair in kids.windows(2) {
+            if let Ok(d) = doc.get_dictionary_mut(ids[pair[0]]) {
+                d.set("Next", Object::Reference(ids[pair[1]]));
+            }
+            if let Ok(d) = doc.get_dictionary_mut(ids[pair[1]]) {
+                d.set("Prev", Object::Reference(ids[pair[0]]));
+            }
+        }
+        let dict = if node == 0 {
+            &mut root
+        } else {
+            match doc.get_dictionary_mut(ids[node - 1]) {
+                Ok(d) => d,
+                Err(_) => continue,
+            }
+        };
+        dict.set("First", Object::Reference(ids[first]));
+        dict.set("Last", Object::Reference(ids[last]));
+        dict.set("Count", Object::Integer(descendants[node]));
+    }
+    doc.objects.insert(outline_root, Object::Dictionary(root));
+
+    let Some(Object::Dictionary(catalog)) = doc.objects.get_mut(&root_id) else {
+        return bytes;
+    };
+    catalog.set("Outlines", Object::Reference(outline_root));
+    catalog.set("PageMode", Object::Name(b"UseOutlines".to_vec()));
+    let mut out = Vec::new();
+    if doc.save_to(&mut out).is_ok() {
+        out
+    } else {
+        bytes
+    }
+}
+
+/// An existing PDF whose pages go into the output after its first
+/// `after_page` pages. Produced by the layout for `pdf-include` blocks.
+pub struct PdfInclude {
+    pub after_page: usize,
+    /// Page count of `doc`, kept for page numbering.
+    pub pages: usize,
+    pub doc: Document,
+}
+
+/// Splice each include's pages into the rendered PDF, in document
+/// order. Pages are moved into the output's page tree with the
+/// attributes they inherited (`MediaBox`, `Resources`, …) copied onto
+/// them; the included file's catalog and outline are dropped. An
+/// include that can't be placed is logged and skipped, and a parse /
+/// serialize failure returns the input bytes.
+pub fn insert_pdf_pages(bytes: Vec<u8>, includes: Vec<PdfInclude>) -> Vec<u8> {
+    if includes.is_empty() {
+        return bytes;
+    }
+    let Ok(mut doc) = Document::load_mem(&bytes) else {
+        return bytes;
+    };
+    // Every include shifts the ones after it by its own page count.
+    let mut inserted = 0usize;
+    for include in includes {
+        match splice_pages(&mut doc, include.after_page + inserted, include.doc) {
+            Some(n) => inserted += n,
+            None => log::warn!("could not insert the pages of an included PDF"),
+        }
+    }
+    let mut out = Vec::new();
+    if doc.save_to(&mut out).is_ok() {
+        out
+    } else {
+        bytes
+    }
+}
+
+/// Move every page of `inc` into `doc` so the first lands at 0-based
+/// position `at`. Returns the number of pages added, or `None` (with
+/// `doc` untouched) when `doc`'s page tree can't be read.
+fn splice_pages(doc: &mut Document, at: usize, mut inc: Document) -> Option<usize> {
+    let pages = doc.get_pages();
+    let (anchor, append) = match pages.get(&(at as u32 + 1)) {
+        Some(&id) => (id, false),
+        None => (*pages.values().last()?, true),
+    };
+    let parent = doc
+        .get_dictionary(anchor)
+        .ok()?
+        .get(b"Parent")
+        .and_then(Object::as_reference)
+        .ok()?;
+    let kid_idx = doc
+        .get_dictionary(parent)
+        .ok()?
+        .get(b"Kids")
+        .and_then(Object::as_array)
+        .ok()?
+        .iter()
+        .position(|kid| kid.as_reference().ok() == Some(anchor))?
+        + usize::from(append);
+
+    inc.renumber_objects_with(doc.max_id + 1);
+    let new_pages: Vec<ObjectId> = inc.get_pages().into_values().collect();
+    let mut page_dicts = Vec::with_capacity(new_pages.len());
+    for &id in &new_pages {
+        let mut page = inc.get_dictionary(id).ok()?.clone();
+        for key in [&b"MediaBox"[..], b"CropBox", b"Resources", b"Rotate"] {
+            if !page.has(key)
+                && let Some(value) = inherited_attribute(&inc, id, key)
+            {
+                page.set(key, value);
+            }
+        }
+        page.set("Parent", Object::Reference(parent));
+        page_dicts.push((id, page));
+    }
+
+    doc.max_id = doc.max_id.max(inc.max_id);
+    for (id, object) in inc.objects {
+        let is_tree_node = matches!(
+            &object,
+            Object::Dictionary(d)
+                if matches!(d.get(b"Type").and_then(Object::as_name), Ok(b"Pages" | b"Catalog"))
+        );
+        if !is_tree_node {
+            doc.objects.insert(id, object);
+        }
+    }
+    for (id, page) in page_dicts {
+        doc.objects.insert(id, Object::Dictionary(page));
+    }
+
+    let added = new_pages.len();
+    if let Ok(Object::Array(kids)) = doc
+        .get_dictionary_mut(parent)
+        .and_then(|d| d.get_mut(b"Kids"))
+    {
+        kids.splice(
+            kid_idx..kid_idx,
+            new_pages.into_iter().map(Object::Reference),
+        );
+    }
+    let mut node = Some(parent);
+    while let Some(id) = node {
+        let Ok(dict) = doc.get_dictionary_mut(id) else {
+            break;
+        };
+        let count = dict.get(b"Count").and_then(Object::as_i64).unwrap_or(0);
+        dict.set("Count", count + added as i64);
+        node = dict.get(b"Parent").and_then(Object::as_reference).ok();
+    }
+    Some(added)
+}
+
+/// `key` from the nearest ancestor of `page` in its page tree.
+fn inherited_attribute(doc: &Document, page: ObjectId, key: &[u8]) -> Option<Object> {
+    let mut node = doc
+        .get_dictionary(page)
+        .ok()?
+        .get(b"Parent")
+        .and_then(Object::as_reference)
+        .ok();
+    // Bounded so a malformed tree with a `/Parent` cycle terminates.
+    for _ in 0..64 {
+        let dict = doc.get_dictionary(node?).ok()?;
+        if let Ok(value) = dict.get(key) {
+            return Some(value.clone());
+        }
+        node = dict.get(b"Parent").and_then(Object::as_reference).ok();
+    }
+    None
+}
+
 /// Set the document Catalog's `/Lang` entry to `lang` (a BCP-47 tag
 /// like `"en-US"`). printpdf 0.9 doesn't expose this. Screen readers
 /// and `Tagged PDF`-aware tools use it to pick a pronunciation
@@ -211,6 +475,84 @@ pub fn compress(bytes: Vec<u8>) -> Vec<u8> {
     }
 }
 
+/// Encrypt the finished PDF with the standard security handler
+/// (revision 4, 128-bit AES for strings and streams). Unlike the other
+/// post-passes this one fails loudly: quietly writing a confidential
+/// report in the clear is worse than writing nothing. The key is
+/// derived from the trailer `/ID`, so one is added when printpdf left
+/// it out.
+pub fn encrypt(bytes: &[u8], enc: &ResolvedEncryption) -> Result<Vec<u8>, String> {
+    let mut doc = Document::load_mem(bytes).map_err(|e| e.to_string())?;
+    if doc.trailer.get(b"ID").is_err() {
+        let id = Object::String(file_id(bytes), StringFormat::Hexadecimal);
+        doc.trailer.set("ID", Object::Array(vec![id.clone(), id]));
+    }
+    let owner_password = match &enc.owner_password {
+        Some(password) => password.clone(),
+        None => random_owner_password()?,
+    };
+    let filter: Arc<dyn CryptFilter> = Arc::new(Aes128CryptFilter);
+    let version = EncryptionVersion::V4 {
+        document: &doc,
+        encrypt_metadata: true,
+        crypt_filters: BTreeMap::from([(b"StdCF".to_vec(), filter)]),
+        stream_filter: b"StdCF".to_vec(),
+        string_filter: b"StdCF".to_vec(),
+        owner_password: &owner_password,
+        user_password: &enc.user_password,
+        permissions: permissions(&enc.permissions),
+    };
+    let state = EncryptionState::try_from(version).map_err(|e| e.to_string())?;
+    doc.encrypt(&state).map_err(|e| e.to_string())?;
+    let mut out = Vec::new();
+    doc.save_to(&mut out).map_err(|e| e.to_string())?;
+    Ok(out)
+}
+
+/// The permission bits for the granted rights. Text extraction for
+/// accessibility is always allowed, so screen readers keep working on
+/// a copy-protected file.
+fn permissions(granted: &[PdfPermission]) -> Permissions {
+    let mut bits = Permissions::COPYABLE_FOR_ACCESSIBILITY;
+    for permission in granted {
+        bits |= match permission {
+            PdfPermission::Print => Permissions::PRINTABLE | Permissions::PRINTABLE_IN_HIGH_QUALITY,
+            PdfPermission::Copy => Permissions::COPYABLE,
+            PdfPermission::Modify => {
+                Permissions::MODIFIABLE | Permissions::ASSEMBLABLE | Permissions::FILLABLE
+            }
+            PdfPermission::Annotate => Permissions::ANNOTABLE,
+        };
+    }
+    bits
+}
+
+/// A 16-byte file identifier derived from the document bytes, stable
+/// for identical output.
+fn file_id(bytes: &[u8]) -> Vec<u8> {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+    let mut id = Vec::with_capacity(16);
+    for seed in 0u8..2 {
+        let mut hasher = DefaultHasher::new();
+        seed.hash(&mut hasher);
+        bytes.hash(&mut hasher);
+        id.extend_from_slice(&hasher.finish().to_be_bytes());
+    }
+    id
+}
+
+/// An owner password nobody knows, for a file that only sets the
+/// user password: 16 bytes from the OS random number generator,
+/// hex-encoded. It is all that guards the permissions, so a failing
+/// RNG fails the encryption rather than fall back to something
+/// guessable.
+fn random_owner_password() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes)
+        .map_err(|e| format!("no OS randomness for the owner password: {e}"))?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
 /// printpdf 0.9's `FormXObject` serializer omits the spec-required
 /// `/BBox` and writes `/FormType` as a name instead of the integer
 /// `1`. The math engine emits one Form XObject per glyph (its outline
@@ -7,10 +7,14 @@
 //! (the tag alone on a line, wrapping content via blank-line breaks)
 //! is still treated as a raw HTML block — the inline form is the
 //! common case and the only one covered here.
+//!
+//! It also applies the inline half of `[html] mode`, dropping tags
+//! the renderer would otherwise print verbatim.
 
 use crate::markdown::Token;
+use crate::styling::HtmlMode;
 
-use super::lower::parse_html_attrs;
+use super::lower::{is_uninterpreted_inline_html, parse_html_attrs};
 
 /// Walk the token tree and replace every inline `<a href="…">…</a>`
 /// pair with a `Token::Link` carrying the parsed `href` (and optional
@@ -18,14 +22,38 @@ use super::lower::parse_html_attrs;
 /// they degrade to the existing pass-through behavior.
 pub fn rewrite_html_anchors(tokens: &mut Vec<Token>) {
     for t in tokens.iter_mut() {
-        descend(t);
+        for_each_child(t, &mut rewrite_html_anchors);
     }
     *tokens = pair_anchors(std::mem::take(tokens));
 }
 
-fn descend(tok: &mut Token) {
+/// Apply `[html] mode` to inline tags the renderer has no meaning
+/// for. Every mode but `raw` removes the tag tokens themselves; the
+/// text between an opener and its closer is ordinary text and stays.
+/// `comment` logs each dropped tag. Runs after
+/// [`rewrite_html_anchors`] so paired `<a>` tags are already links.
+pub fn apply_inline_html_mode(tokens: &mut Vec<Token>, mode: HtmlMode) {
+    if mode == HtmlMode::Raw {
+        return;
+    }
+    tokens.retain(|t| match t {
+        Token::HtmlInline(tag) if is_uninterpreted_inline_html(tag) => {
+            if mode == HtmlMode::Comment {
+                log::warn!("dropped inline HTML tag {}", tag.trim());
+            }
+            false
+        }
+        _ => true,
+    });
+    for t in tokens.iter_mut() {
+        for_each_child(t, &mut |c| apply_inline_html_mode(c, mode));
+    }
+}
+
+/// Call `f` on every nested token list directly inside `tok`.
+fn for_each_child(tok: &mut Token, f: &mut impl FnMut(&mut Vec<Token>)) {
     match tok {
-        Token::Heading(content, _)
+        Token::Heading(content, _, _)
         | Token::StrongEmphasis(content)
         | Token::Strikethrough(content)
         | Token::Highlight(content)
@@ -33,32 +61,32 @@ fn descend(tok: &mut Token) {
         | Token::ListItem { content, .. }
         | Token::Link { content, .. }
         | Token::FootnoteDefinition { content, .. }
-        | Token::InlineFootnote { content, .. } => rewrite_html_anchors(content),
-        Token::Emphasis { content, .. } => rewrite_html_anchors(content),
-        Token::Image { alt, .. } => rewrite_html_anchors(alt),
+        | Token::InlineFootnote { content, .. } => f(content),
+        Token::Emphasis { content, .. } => f(content),
+        Token::Image { alt, .. } => f(alt),
         Token::Admonition { title, body, .. } => {
             if let Some(t) = title {
-                rewrite_html_anchors(t);
+                f(t);
             }
-            rewrite_html_anchors(body);
+            f(body);
         }
         Token::Table { headers, rows, .. } => {
             for cell in headers {
-                rewrite_html_anchors(&mut cell.content);
+                f(&mut cell.content);
             }
             for row in rows {
                 for cell in row {
-                    rewrite_html_anchors(&mut cell.content);
+                    f(&mut cell.content);
                 }
             }
         }
         Token::DefinitionList { entries } => {
             for e in entries {
                 for t in &mut e.terms {
-                    rewrite_html_anchors(t);
+                    f(t);
                 }
                 for d in &mut e.definitions {
-                    rewrite_html_anchors(d);
+                    f(d);
                 }
             }
         }
@@ -291,7 +319,7 @@ mod tests {
                         out.push((url.clone(), title.clone()));
                         walk(content, out);
                     }
-                    Token::Heading(c, _)
+                    Token::Heading(c, _, _)
                     | Token::Emphasis { content: c, .. }
                     | Token::StrongEmphasis(c)
                     | Token::Strikethrough(c)
@@ -543,4 +571,30 @@ mod tests {
         assert_eq!(links.len(), 1, "soft break should stay inside the link");
         assert_eq!(links[0].0, "u");
     }
+
+    fn html_tags(tokens: &mut Vec<Token>, out: &mut Vec<String>) {
+        for t in tokens.iter_mut() {
+            if let Token::HtmlInline(tag) = t {
+                out.push(tag.clone());
+            }
+            for_each_child(t, &mut |c| html_tags(c, out));
+        }
+    }
+
+    #[test]
+    fn inline_html_mode_drops_only_uninterpreted_tags() {
+        let src = "a <kbd>K</kbd> <weird>w</weird> **<span>s</span>**";
+        let mut raw = lex(src);
+        apply_inline_html_mode(&mut raw, HtmlMode::Raw);
+        let mut tags = Vec::new();
+        html_tags(&mut raw, &mut tags);
+        assert!(tags.iter().any(|t| t == "<weird>"));
+        assert!(tags.iter().any(|t| t == "<span>"));
+
+        let mut stripped = lex(src);
+        apply_inline_html_mode(&mut stripped, HtmlMode::StripTags);
+        let mut tags = Vec::new();
+        html_tags(&mut stripped, &mut tags);
+        assert_eq!(tags, ["<kbd>", "</kbd>"]);
+    }
 }
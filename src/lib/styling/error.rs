@@ -26,6 +26,12 @@ pub enum ResolveError {
         name: String,
         suggestion: Option<String>,
     },
+    /// A colour field named a colour (`text_color = "accent"`) that no
+    /// `[palette]` in scope defines.
+    UnknownPaletteColor {
+        name: String,
+        suggestion: Option<String>,
+    },
     /// `inherits = "a"`, where a inherits from b, where b inherits from a.
     InheritsCycle(Vec<String>),
     /// After all merges, a required field is still unset. This is a
@@ -78,6 +84,13 @@ impl fmt::Display for ResolveError {
                 }
                 Ok(())
             }
+            ResolveError::UnknownPaletteColor { name, suggestion } => {
+                write!(f, "color `{}` is not defined in [palette]", name)?;
+                if let Some(s) = suggestion {
+                    write!(f, "\n  did you mean `{}`?", s)?;
+                }
+                Ok(())
+            }
             ResolveError::InheritsCycle(chain) => {
                 write!(f, "theme inheritance cycle: {}", chain.join(" -> "))
             }
@@ -159,7 +172,7 @@ pub(crate) fn unknown_field_suggestion(msg: &str) -> Option<String> {
 /// unknown theme names. Limited to small inputs (TOML keys), so the
 /// O(n*m) cost is irrelevant. Returns the closest candidate whose
 /// edit distance is at most `cutoff`, or None.
-pub(super) fn closest_match<'a, I: IntoIterator<Item = &'a str>>(
+pub(crate) fn closest_match<'a, I: IntoIterator<Item = &'a str>>(
     target: &str,
     candidates: I,
     cutoff: usize,
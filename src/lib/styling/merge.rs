@@ -15,9 +15,11 @@
 use super::error::ResolveError;
 use super::resolved::{
     ResolvedAdmonition, ResolvedAdmonitionKind, ResolvedBlock, ResolvedBorder, ResolvedBorderSide,
-    ResolvedImage, ResolvedInline, ResolvedList, ResolvedMath, ResolvedMetadata, ResolvedPage,
-    ResolvedPageFurniture, ResolvedRule, ResolvedSecurity, ResolvedStyle, ResolvedTable,
-    ResolvedTitlePage, ResolvedToc,
+    ResolvedEmphasis, ResolvedEncryption, ResolvedHtml, ResolvedImage, ResolvedInline,
+    ResolvedLimits, ResolvedLinkDisplay, ResolvedList, ResolvedMath, ResolvedMetadata,
+    ResolvedOutput, ResolvedPage, ResolvedPageFurniture, ResolvedRule, ResolvedSecurity,
+    ResolvedStyle, ResolvedSuperscript, ResolvedTable, ResolvedText, ResolvedTitlePage,
+    ResolvedToc,
 };
 use super::schema::*;
 use super::themes::load_theme_preset;
@@ -46,11 +48,143 @@ pub fn resolve_with_overrides(
         .or_else(|| user.theme.clone())
         .unwrap_or_else(|| "default".to_string());
     let preset = load_theme_preset(&theme_name)?;
+    // The caller's own layers, without the preset: a text colour set
+    // here is never touched by `auto_contrast`.
+    let explicit = match &overrides {
+        Some(ov) => merge_documents(user.clone(), ov.clone()),
+        None => user.clone(),
+    };
     let mut merged = merge_documents(preset, user);
     if let Some(ov) = overrides {
         merged = merge_documents(merged, ov);
     }
-    lower(&theme_name, merged)
+    let auto_contrast = merged
+        .document
+        .as_ref()
+        .and_then(|d| d.auto_contrast)
+        .unwrap_or(false);
+    let mut style = lower(&theme_name, merged)?;
+    if auto_contrast {
+        apply_auto_contrast(&mut style, &explicit);
+    }
+    Ok(style)
+}
+
+/// Lowest text-to-background contrast `auto_contrast` accepts: the
+/// WCAG AA level for body text.
+const MIN_CONTRAST: f32 = 4.5;
+
+/// Give every element with its own background a readable text
+/// colour, leaving alone the ones whose `text_color` `explicit` sets.
+fn apply_auto_contrast(style: &mut ResolvedStyle, explicit: &DocumentConfig) {
+    fn fix(text: &mut Color, background: Option<Color>, pinned: bool) {
+        if let Some(bg) = background
+            && !pinned
+        {
+            *text = text.readable_on(bg, MIN_CONTRAST);
+        }
+    }
+    fn pinned(cfg: Option<&BlockConfig>) -> bool {
+        cfg.is_some_and(|c| c.text_color.is_some())
+    }
+
+    let headings = explicit.headings.as_ref();
+    let heading_cfgs = [
+        headings.and_then(|h| h.h1.as_ref()),
+        headings.and_then(|h| h.h2.as_ref()),
+        headings.and_then(|h| h.h3.as_ref()),
+        headings.and_then(|h| h.h4.as_ref()),
+        headings.and_then(|h| h.h5.as_ref()),
+        headings.and_then(|h| h.h6.as_ref()),
+    ];
+    let code_block = explicit.code_block.as_ref();
+    let table = explicit.table.as_ref();
+    let mut blocks = vec![
+        (&mut style.paragraph, pinned(explicit.paragraph.as_ref())),
+        (&mut style.blockquote, pinned(explicit.blockquote.as_ref())),
+        (&mut style.code_block, pinned(code_block.map(|c| &c.block))),
+        (
+            &mut style.code_block_caption,
+            pinned(code_block.and_then(|c| c.caption.as_ref())),
+        ),
+        (
+            &mut style.table.header,
+            pinned(table.and_then(|t| t.header.as_ref())),
+        ),
+        (
+            &mut style.table.cell,
+            pinned(table.and_then(|t| t.cell.as_ref())),
+        ),
+    ];
+    for (block, cfg) in style.headings.iter_mut().zip(heading_cfgs) {
+        blocks.push((block, pinned(cfg)));
+    }
+
+    let admonition = explicit.admonition.as_ref();
+    let shared = pinned(admonition.map(|a| &a.defaults));
+    for (kind, cfg) in [
+        (
+            &mut style.admonition.note,
+            admonition.and_then(|a| a.note.as_ref()),
+        ),
+        (
+            &mut style.admonition.info,
+            admonition.and_then(|a| a.info.as_ref()),
+        ),
+        (
+            &mut style.admonition.tip,
+            admonition.and_then(|a| a.tip.as_ref()),
+        ),
+        (
+            &mut style.admonition.warning,
+            admonition.and_then(|a| a.warning.as_ref()),
+        ),
+        (
+            &mut style.admonition.danger,
+            admonition.and_then(|a| a.danger.as_ref()),
+        ),
+        (
+            &mut style.admonition.generic,
+            admonition.and_then(|a| a.generic.as_ref()),
+        ),
+    ] {
+        blocks.push((&mut kind.block, shared || pinned(cfg.map(|k| &k.block))));
+    }
+
+    let lists = explicit.list.as_ref();
+    let common = pinned(lists.and_then(|l| l.common.as_ref()).map(|c| &c.block));
+    for (list, cfg) in [
+        (
+            &mut style.list_ordered,
+            lists.and_then(|l| l.ordered.as_ref()),
+        ),
+        (
+            &mut style.list_unordered,
+            lists.and_then(|l| l.unordered.as_ref()),
+        ),
+        (&mut style.list_task, lists.and_then(|l| l.task.as_ref())),
+    ] {
+        blocks.push((&mut list.block, common || pinned(cfg.map(|c| &c.block))));
+    }
+
+    for (block, pinned) in blocks {
+        fix(&mut block.text_color, block.background_color, pinned);
+    }
+
+    let inline_pinned = |cfg: Option<&InlineConfig>| cfg.is_some_and(|c| c.text_color.is_some());
+    for (inline, pinned) in [
+        (
+            &mut style.code_inline,
+            inline_pinned(explicit.code_inline.as_ref()),
+        ),
+        (
+            &mut style.link,
+            inline_pinned(explicit.link.as_ref().map(|l| &l.inline)),
+        ),
+        (&mut style.mark, inline_pinned(explicit.mark.as_ref())),
+    ] {
+        fix(&mut inline.text_color, inline.background_color, pinned);
+    }
 }
 
 /// Merge two `DocumentConfig` values field by field. Overlay wins on
@@ -61,27 +195,46 @@ pub fn merge_documents(base: DocumentConfig, overlay: DocumentConfig) -> Documen
     DocumentConfig {
         theme: overlay.theme.or(base.theme),
         inherits: overlay.inherits.or(base.inherits),
+        palette: merge_optional(base.palette, overlay.palette, merge_palette),
+        document: merge_optional(base.document, overlay.document, merge_document_settings),
         page: merge_optional(base.page, overlay.page, merge_page),
         defaults: merge_optional(base.defaults, overlay.defaults, merge_block),
         headings: merge_optional(base.headings, overlay.headings, merge_headings),
         paragraph: merge_optional(base.paragraph, overlay.paragraph, merge_block),
-        code_block: merge_optional(base.code_block, overlay.code_block, merge_block),
+        code_block: merge_optional(base.code_block, overlay.code_block, merge_code_block),
         code_inline: merge_optional(base.code_inline, overlay.code_inline, merge_inline),
         blockquote: merge_optional(base.blockquote, overlay.blockquote, merge_block),
         admonition: merge_optional(base.admonition, overlay.admonition, merge_admonition),
         list: merge_optional(base.list, overlay.list, merge_lists),
         table: merge_optional(base.table, overlay.table, merge_table),
         image: merge_optional(base.image, overlay.image, merge_image),
-        link: merge_optional(base.link, overlay.link, merge_inline),
+        link: merge_optional(base.link, overlay.link, merge_link),
         mark: merge_optional(base.mark, overlay.mark, merge_inline),
+        emphasis: merge_optional(base.emphasis, overlay.emphasis, merge_emphasis),
+        strong_emphasis: merge_optional(
+            base.strong_emphasis,
+            overlay.strong_emphasis,
+            merge_emphasis,
+        ),
+        emphasis_strong: merge_optional(
+            base.emphasis_strong,
+            overlay.emphasis_strong,
+            merge_emphasis,
+        ),
         horizontal_rule: merge_optional(base.horizontal_rule, overlay.horizontal_rule, merge_rule),
         math: merge_optional(base.math, overlay.math, merge_math),
+        superscript: merge_optional(base.superscript, overlay.superscript, merge_superscript),
         metadata: merge_optional(base.metadata, overlay.metadata, merge_metadata),
         header: merge_optional(base.header, overlay.header, merge_furniture),
         footer: merge_optional(base.footer, overlay.footer, merge_furniture),
         title_page: merge_optional(base.title_page, overlay.title_page, merge_title_page),
         toc: merge_optional(base.toc, overlay.toc, merge_toc),
         security: merge_optional(base.security, overlay.security, merge_security),
+        limits: merge_optional(base.limits, overlay.limits, merge_limits),
+        output: merge_optional(base.output, overlay.output, merge_output),
+        text: merge_optional(base.text, overlay.text, merge_text),
+        html: merge_optional(base.html, overlay.html, merge_html),
+        fonts: merge_optional(base.fonts, overlay.fonts, merge_fonts),
     }
 }
 
@@ -92,6 +245,16 @@ fn merge_optional<T, F: FnOnce(T, T) -> T>(base: Option<T>, overlay: Option<T>,
     }
 }
 
+fn merge_palette(base: PaletteConfig, overlay: PaletteConfig) -> PaletteConfig {
+    PaletteConfig {
+        primary: overlay.primary.or(base.primary),
+        text: overlay.text.or(base.text),
+        muted: overlay.muted.or(base.muted),
+        accent: overlay.accent.or(base.accent),
+        background: overlay.background.or(base.background),
+    }
+}
+
 fn merge_page(base: PageConfig, overlay: PageConfig) -> PageConfig {
     PageConfig {
         size: overlay.size.or(base.size),
@@ -99,6 +262,10 @@ fn merge_page(base: PageConfig, overlay: PageConfig) -> PageConfig {
         margins: overlay.margins.or(base.margins),
         columns: overlay.columns.or(base.columns),
         column_gap_mm: overlay.column_gap_mm.or(base.column_gap_mm),
+        max_content_width: overlay.max_content_width.or(base.max_content_width),
+        full_width_spans_margins: overlay
+            .full_width_spans_margins
+            .or(base.full_width_spans_margins),
     }
 }
 
@@ -145,6 +312,7 @@ fn merge_block(base: BlockConfig, overlay: BlockConfig) -> BlockConfig {
         underline: overlay.underline.or(base.underline),
         small_caps: overlay.small_caps.or(base.small_caps),
         fallback_fonts: overlay.fallback_fonts.or(base.fallback_fonts),
+        paragraph_spacing_pt: overlay.paragraph_spacing_pt.or(base.paragraph_spacing_pt),
     }
 }
 
@@ -162,7 +330,40 @@ fn merge_inline(base: InlineConfig, overlay: InlineConfig) -> InlineConfig {
     }
 }
 
-fn merge_headings(base: HeadingsConfig, overlay: HeadingsConfig) -> HeadingsConfig {
+fn merge_emphasis(base: EmphasisConfig, overlay: EmphasisConfig) -> EmphasisConfig {
+    EmphasisConfig {
+        font_size_pt: overlay.font_size_pt.or(base.font_size_pt),
+        text_color: overlay.text_color.or(base.text_color),
+    }
+}
+
+fn merge_link(base: LinkConfig, overlay: LinkConfig) -> LinkConfig {
+    LinkConfig {
+        inline: merge_inline(base.inline, overlay.inline),
+        display: overlay.display.or(base.display),
+        relative_urls: overlay.relative_urls.or(base.relative_urls),
+        base_url: overlay.base_url.or(base.base_url),
+        print_titles: overlay.print_titles.or(base.print_titles),
+    }
+}
+
+fn merge_headings(mut base: HeadingsConfig, overlay: HeadingsConfig) -> HeadingsConfig {
+    // A later layer's section-wide `text_align` beats the per-level
+    // alignments of the layers under it (a theme's centered h1); its
+    // own per-level values still win over it.
+    if overlay.text_align.is_some() {
+        let levels = [
+            &mut base.h1,
+            &mut base.h2,
+            &mut base.h3,
+            &mut base.h4,
+            &mut base.h5,
+            &mut base.h6,
+        ];
+        for level in levels.into_iter().flatten() {
+            level.text_align = None;
+        }
+    }
     HeadingsConfig {
         h1: merge_optional(base.h1, overlay.h1, merge_block),
         h2: merge_optional(base.h2, overlay.h2, merge_block),
@@ -170,6 +371,9 @@ fn merge_headings(base: HeadingsConfig, overlay: HeadingsConfig) -> HeadingsConf
         h4: merge_optional(base.h4, overlay.h4, merge_block),
         h5: merge_optional(base.h5, overlay.h5, merge_block),
         h6: merge_optional(base.h6, overlay.h6, merge_block),
+        span_columns: overlay.span_columns.or(base.span_columns),
+        transform: overlay.transform.or(base.transform),
+        text_align: overlay.text_align.or(base.text_align),
     }
 }
 
@@ -179,6 +383,16 @@ fn merge_lists(base: ListsConfig, overlay: ListsConfig) -> ListsConfig {
         unordered: merge_optional(base.unordered, overlay.unordered, merge_list_style),
         task: merge_optional(base.task, overlay.task, merge_list_style),
         common: merge_optional(base.common, overlay.common, merge_list_style),
+        align_numbers: overlay.align_numbers.or(base.align_numbers),
+    }
+}
+
+fn merge_code_block(base: CodeBlockConfig, overlay: CodeBlockConfig) -> CodeBlockConfig {
+    CodeBlockConfig {
+        block: merge_block(base.block, overlay.block),
+        max_lines: overlay.max_lines.or(base.max_lines),
+        caption: merge_optional(base.caption, overlay.caption, merge_block),
+        highlight_color: overlay.highlight_color.or(base.highlight_color),
     }
 }
 
@@ -190,6 +404,7 @@ fn merge_list_style(base: ListStyleConfig, overlay: ListStyleConfig) -> ListStyl
         item_spacing_tight_pt: overlay.item_spacing_tight_pt.or(base.item_spacing_tight_pt),
         item_spacing_loose_pt: overlay.item_spacing_loose_pt.or(base.item_spacing_loose_pt),
         bullet_gap_pt: overlay.bullet_gap_pt.or(base.bullet_gap_pt),
+        marker_color: overlay.marker_color.or(base.marker_color),
     }
 }
 
@@ -202,6 +417,9 @@ fn merge_table(base: TableConfig, overlay: TableConfig) -> TableConfig {
             .alternating_row_background
             .or(base.alternating_row_background),
         cell_padding: overlay.cell_padding.or(base.cell_padding),
+        min_column_width_pt: overlay.min_column_width_pt.or(base.min_column_width_pt),
+        max_column_width_pt: overlay.max_column_width_pt.or(base.max_column_width_pt),
+        overflow: overlay.overflow.or(base.overflow),
         row_gap_pt: overlay.row_gap_pt.or(base.row_gap_pt),
         margin_before_pt: overlay.margin_before_pt.or(base.margin_before_pt),
         margin_after_pt: overlay.margin_after_pt.or(base.margin_after_pt),
@@ -210,9 +428,19 @@ fn merge_table(base: TableConfig, overlay: TableConfig) -> TableConfig {
 
 fn merge_image(base: ImageConfig, overlay: ImageConfig) -> ImageConfig {
     ImageConfig {
+        mode: overlay.mode.or(base.mode),
         max_width_pct: overlay.max_width_pct.or(base.max_width_pct),
         align: overlay.align.or(base.align),
+        float: overlay.float.or(base.float),
+        max_dpi: overlay.max_dpi.or(base.max_dpi),
         caption: merge_optional(base.caption, overlay.caption, merge_block),
+        show_caption: overlay.show_caption.or(base.show_caption),
+        keep_with_caption: overlay.keep_with_caption.or(base.keep_with_caption),
+        keep_with_previous: overlay.keep_with_previous.or(base.keep_with_previous),
+        gallery: overlay.gallery.or(base.gallery),
+        gallery_columns: overlay.gallery_columns.or(base.gallery_columns),
+        gallery_gap_pt: overlay.gallery_gap_pt.or(base.gallery_gap_pt),
+        caption_prefix: overlay.caption_prefix.or(base.caption_prefix),
         margin_before_pt: overlay.margin_before_pt.or(base.margin_before_pt),
         margin_after_pt: overlay.margin_after_pt.or(base.margin_after_pt),
     }
@@ -247,6 +475,8 @@ fn merge_metadata(base: MetadataConfig, overlay: MetadataConfig) -> MetadataConf
         keywords: overlay.keywords.or(base.keywords),
         creator: overlay.creator.or(base.creator),
         language: overlay.language.or(base.language),
+        render_table: overlay.render_table.or(base.render_table),
+        table_keys: overlay.table_keys.or(base.table_keys),
     }
 }
 
@@ -258,11 +488,14 @@ fn merge_furniture(base: PageFurnitureConfig, overlay: PageFurnitureConfig) -> P
         style: merge_optional(base.style, overlay.style, merge_block),
         show_on_first_page: overlay.show_on_first_page.or(base.show_on_first_page),
         gap_pt: overlay.gap_pt.or(base.gap_pt),
+        page_number_style: overlay.page_number_style.or(base.page_number_style),
+        start_at: overlay.start_at.or(base.start_at),
     }
 }
 
 fn merge_title_page(base: TitlePageConfig, overlay: TitlePageConfig) -> TitlePageConfig {
     TitlePageConfig {
+        enabled: overlay.enabled.or(base.enabled),
         title: overlay.title.or(base.title),
         subtitle: overlay.subtitle.or(base.subtitle),
         author: overlay.author.or(base.author),
@@ -281,6 +514,27 @@ fn merge_toc(base: TocConfig, overlay: TocConfig) -> TocConfig {
     }
 }
 
+fn merge_document_settings(
+    base: DocumentSettingsConfig,
+    overlay: DocumentSettingsConfig,
+) -> DocumentSettingsConfig {
+    DocumentSettingsConfig {
+        scale: overlay.scale.or(base.scale),
+        digits: overlay.digits.or(base.digits),
+        bookmarks: overlay.bookmarks.or(base.bookmarks),
+        flags: overlay.flags.or(base.flags),
+        auto_contrast: overlay.auto_contrast.or(base.auto_contrast),
+    }
+}
+
+fn merge_superscript(base: SuperscriptConfig, overlay: SuperscriptConfig) -> SuperscriptConfig {
+    SuperscriptConfig {
+        scale: overlay.scale.or(base.scale),
+        rise: overlay.rise.or(base.rise),
+        ordinals: overlay.ordinals.or(base.ordinals),
+    }
+}
+
 fn merge_security(base: SecurityConfig, overlay: SecurityConfig) -> SecurityConfig {
     SecurityConfig {
         image_root: overlay.image_root.or(base.image_root),
@@ -291,6 +545,56 @@ fn merge_security(base: SecurityConfig, overlay: SecurityConfig) -> SecurityConf
     }
 }
 
+fn merge_limits(base: LimitsConfig, overlay: LimitsConfig) -> LimitsConfig {
+    LimitsConfig {
+        max_input_bytes: overlay.max_input_bytes.or(base.max_input_bytes),
+        max_pages: overlay.max_pages.or(base.max_pages),
+    }
+}
+
+fn merge_output(base: OutputConfig, overlay: OutputConfig) -> OutputConfig {
+    OutputConfig {
+        embed_fonts: overlay.embed_fonts.or(base.embed_fonts),
+        password: overlay.password.or(base.password),
+        owner_password: overlay.owner_password.or(base.owner_password),
+        permissions: overlay.permissions.or(base.permissions),
+    }
+}
+
+fn merge_text(base: TextConfig, overlay: TextConfig) -> TextConfig {
+    TextConfig {
+        collapse_whitespace: overlay.collapse_whitespace.or(base.collapse_whitespace),
+        max_space_stretch: overlay.max_space_stretch.or(base.max_space_stretch),
+    }
+}
+
+fn merge_html(base: HtmlConfig, overlay: HtmlConfig) -> HtmlConfig {
+    HtmlConfig {
+        mode: overlay.mode.or(base.mode),
+    }
+}
+
+fn merge_fonts(base: FontsConfig, overlay: FontsConfig) -> FontsConfig {
+    FontsConfig {
+        default_font: overlay.default_font.or(base.default_font),
+        code_font: overlay.code_font.or(base.code_font),
+        custom_paths: overlay.custom_paths.or(base.custom_paths),
+        fallback_fonts: overlay.fallback_fonts.or(base.fallback_fonts),
+        enable_subsetting: overlay.enable_subsetting.or(base.enable_subsetting),
+        auto_emoji: overlay.auto_emoji.or(base.auto_emoji),
+        map: merge_optional(base.map, overlay.map, merge_font_map),
+    }
+}
+
+fn merge_font_map(base: FontMapConfig, overlay: FontMapConfig) -> FontMapConfig {
+    FontMapConfig {
+        heading: overlay.heading.or(base.heading),
+        text: overlay.text.or(base.text),
+        code: overlay.code.or(base.code),
+        blockquote: overlay.blockquote.or(base.blockquote),
+    }
+}
+
 fn merge_border(base: BorderConfig, overlay: BorderConfig) -> BorderConfig {
     BorderConfig {
         all: overlay.all.or(base.all),
@@ -305,6 +609,21 @@ fn lower(theme: &str, cfg: DocumentConfig) -> Result<ResolvedStyle, ResolveError
     let defaults = cfg.defaults.unwrap_or_default();
     let page_cfg = cfg.page.ok_or_else(|| missing(theme, "page"))?;
     let headings_cfg = cfg.headings.unwrap_or_default();
+    let headings_span_columns = headings_cfg.span_columns.unwrap_or(false);
+    let headings_transform = headings_cfg.transform.unwrap_or_default();
+    // `[headings].text_align` sits between `[defaults]` and each level.
+    let heading_defaults = BlockConfig {
+        text_align: headings_cfg.text_align.or(defaults.text_align),
+        ..defaults.clone()
+    };
+
+    let paragraph_cfg = cfg.paragraph.unwrap_or_default();
+    // Negative or non-finite spacing falls back to the plain margins.
+    let paragraph_spacing_pt = paragraph_cfg
+        .paragraph_spacing_pt
+        .or(defaults.paragraph_spacing_pt)
+        .filter(|v| v.is_finite() && *v >= 0.0);
+    let paragraph = lower_block(theme, "paragraph", &defaults, paragraph_cfg)?;
 
     let page = ResolvedPage {
         size: page_cfg.size.ok_or_else(|| missing(theme, "page.size"))?,
@@ -316,56 +635,62 @@ fn lower(theme: &str, cfg: DocumentConfig) -> Result<ResolvedStyle, ResolveError
             .ok_or_else(|| missing(theme, "page.margins"))?,
         columns: page_cfg.columns.unwrap_or(1),
         column_gap_mm: page_cfg.column_gap_mm.unwrap_or(0.0),
+        // An em is the body text size. A non-positive or non-finite
+        // width means no cap rather than a column that can't hold text.
+        max_content_width_pt: page_cfg
+            .max_content_width
+            .map(|w| w.to_pt(paragraph.font_size_pt))
+            .filter(|w| w.is_finite() && *w > 0.0),
+        full_width_spans_margins: page_cfg.full_width_spans_margins.unwrap_or(false),
     };
-
-    let paragraph = lower_block(
-        theme,
-        "paragraph",
-        &defaults,
-        cfg.paragraph.unwrap_or_default(),
-    )?;
     let h1 = lower_block(
         theme,
         "headings.h1",
-        &defaults,
+        &heading_defaults,
         headings_cfg.h1.unwrap_or_default(),
     )?;
     let h2 = lower_block(
         theme,
         "headings.h2",
-        &defaults,
+        &heading_defaults,
         headings_cfg.h2.unwrap_or_default(),
     )?;
     let h3 = lower_block(
         theme,
         "headings.h3",
-        &defaults,
+        &heading_defaults,
         headings_cfg.h3.unwrap_or_default(),
     )?;
     let h4 = lower_block(
         theme,
         "headings.h4",
-        &defaults,
+        &heading_defaults,
         headings_cfg.h4.unwrap_or_default(),
     )?;
     let h5 = lower_block(
         theme,
         "headings.h5",
-        &defaults,
+        &heading_defaults,
         headings_cfg.h5.unwrap_or_default(),
     )?;
     let h6 = lower_block(
         theme,
         "headings.h6",
-        &defaults,
+        &heading_defaults,
         headings_cfg.h6.unwrap_or_default(),
     )?;
-    let code_block = lower_block(
+    let code_block_cfg = cfg.code_block.unwrap_or_default();
+    let code_block_max_lines = code_block_cfg.max_lines.filter(|n| *n > 0);
+    let code_block_caption = lower_block(
         theme,
-        "code_block",
+        "code_block.caption",
         &defaults,
-        cfg.code_block.unwrap_or_default(),
+        code_block_cfg.caption.unwrap_or_default(),
     )?;
+    let code_block_highlight = code_block_cfg
+        .highlight_color
+        .unwrap_or(Color::rgb(255, 243, 176));
+    let code_block = lower_block(theme, "code_block", &defaults, code_block_cfg.block)?;
     let code_inline = lower_inline(
         theme,
         "code_inline",
@@ -379,10 +704,26 @@ fn lower(theme: &str, cfg: DocumentConfig) -> Result<ResolvedStyle, ResolveError
         cfg.blockquote.unwrap_or_default(),
     )?;
     let admonition = lower_admonition(theme, &defaults, cfg.admonition.unwrap_or_default())?;
-    let link = lower_inline(theme, "link", &defaults, cfg.link.unwrap_or_default())?;
+    let link_cfg = cfg.link.unwrap_or_default();
+    let link = lower_inline(theme, "link", &defaults, link_cfg.inline)?;
+    let link_display = ResolvedLinkDisplay {
+        display: link_cfg.display.unwrap_or_default(),
+        relative_urls: link_cfg.relative_urls.unwrap_or_default(),
+        base_url: link_cfg.base_url.filter(|b| !b.trim().is_empty()),
+        print_titles: link_cfg.print_titles.unwrap_or(false),
+    };
     let mark = lower_inline(theme, "mark", &defaults, cfg.mark.unwrap_or_default())?;
+    let [emphasis, strong_emphasis, emphasis_strong] =
+        [cfg.emphasis, cfg.strong_emphasis, cfg.emphasis_strong].map(|raw| {
+            let raw = raw.unwrap_or_default();
+            ResolvedEmphasis {
+                font_size_pt: raw.font_size_pt.map(safe_font_size),
+                text_color: raw.text_color,
+            }
+        });
 
     let list_cfg = cfg.list.unwrap_or_default();
+    let list_align_numbers = list_cfg.align_numbers.unwrap_or(false);
     let list_common = list_cfg.common.unwrap_or_default();
     let list_unordered = lower_list(
         theme,
@@ -407,6 +748,15 @@ fn lower(theme: &str, cfg: DocumentConfig) -> Result<ResolvedStyle, ResolveError
     )?;
 
     let table_cfg = cfg.table.unwrap_or_default();
+    let table_cell = lower_block(
+        theme,
+        "table.cell",
+        &defaults,
+        table_cfg.cell.unwrap_or_default(),
+    )?;
+    // `em` margins around a table, image or rule follow the text they
+    // sit beside: the table's cells, or the body text.
+    let table_em = table_cell.font_size_pt;
     let table = ResolvedTable {
         header: lower_block(
             theme,
@@ -414,34 +764,42 @@ fn lower(theme: &str, cfg: DocumentConfig) -> Result<ResolvedStyle, ResolveError
             &defaults,
             table_cfg.header.unwrap_or_default(),
         )?,
-        cell: lower_block(
-            theme,
-            "table.cell",
-            &defaults,
-            table_cfg.cell.unwrap_or_default(),
-        )?,
+        cell: table_cell,
         border: lower_border(table_cfg.border.unwrap_or_default()),
         alternating_row_background: table_cfg.alternating_row_background,
         cell_padding: table_cfg
             .cell_padding
             .unwrap_or_else(|| Sides::uniform(0.0)),
+        min_column_width_pt: table_cfg.min_column_width_pt.unwrap_or(0.0).max(0.0),
+        max_column_width_pt: table_cfg.max_column_width_pt.filter(|w| *w > 0.0),
+        overflow: table_cfg.overflow.unwrap_or_default(),
         row_gap_pt: table_cfg.row_gap_pt.unwrap_or(0.0),
-        margin_before_pt: table_cfg.margin_before_pt.unwrap_or(0.0),
-        margin_after_pt: table_cfg.margin_after_pt.unwrap_or(0.0),
+        margin_before_pt: spacing_pt(table_cfg.margin_before_pt, table_em),
+        margin_after_pt: spacing_pt(table_cfg.margin_after_pt, table_em),
     };
 
     let image_cfg = cfg.image.unwrap_or_default();
     let image = ResolvedImage {
+        mode: image_cfg.mode.unwrap_or_default(),
         max_width_pct: image_cfg.max_width_pct.unwrap_or(100.0),
         align: image_cfg.align.unwrap_or(ImageAlign::Center),
-        margin_before_pt: image_cfg.margin_before_pt.unwrap_or(0.0),
-        margin_after_pt: image_cfg.margin_after_pt.unwrap_or(0.0),
+        float: image_cfg.float.unwrap_or_default(),
+        max_dpi: image_cfg.max_dpi.filter(|dpi| *dpi > 0.0),
+        margin_before_pt: spacing_pt(image_cfg.margin_before_pt, paragraph.font_size_pt),
+        margin_after_pt: spacing_pt(image_cfg.margin_after_pt, paragraph.font_size_pt),
         caption: lower_block(
             theme,
             "image.caption",
             &defaults,
             image_cfg.caption.unwrap_or_default(),
         )?,
+        show_caption: image_cfg.show_caption.unwrap_or(false),
+        keep_with_caption: image_cfg.keep_with_caption.unwrap_or(true),
+        keep_with_previous: image_cfg.keep_with_previous.unwrap_or(false),
+        gallery: image_cfg.gallery.unwrap_or(false),
+        gallery_columns: image_cfg.gallery_columns.unwrap_or(2).max(1),
+        gallery_gap_pt: image_cfg.gallery_gap_pt.unwrap_or(8.0),
+        caption_prefix: image_cfg.caption_prefix,
     };
 
     let rule_cfg = cfg.horizontal_rule.unwrap_or_default();
@@ -450,23 +808,25 @@ fn lower(theme: &str, cfg: DocumentConfig) -> Result<ResolvedStyle, ResolveError
         thickness_pt: rule_cfg.thickness_pt.unwrap_or(0.5),
         style: rule_cfg.style.unwrap_or(BorderStyle::Solid),
         width_pct: rule_cfg.width_pct.unwrap_or(100.0),
-        margin_before_pt: rule_cfg.margin_before_pt.unwrap_or(0.0),
-        margin_after_pt: rule_cfg.margin_after_pt.unwrap_or(0.0),
+        margin_before_pt: spacing_pt(rule_cfg.margin_before_pt, paragraph.font_size_pt),
+        margin_after_pt: spacing_pt(rule_cfg.margin_after_pt, paragraph.font_size_pt),
     };
 
     let math_cfg = cfg.math.unwrap_or_default();
+    let math_scale = math_cfg.scale.unwrap_or(1.08).max(0.05);
+    let math_em = paragraph.font_size_pt * math_scale;
     let math = ResolvedMath {
         align: math_cfg.align.unwrap_or(TextAlignment::Center),
-        scale: math_cfg.scale.unwrap_or(1.08).max(0.05),
+        scale: math_scale,
         color: math_cfg.color.unwrap_or(paragraph.text_color),
         // Default to the paragraph's block spacing so a display
         // equation sits like a normal paragraph unless overridden.
         margin_before_pt: math_cfg
             .margin_before_pt
-            .unwrap_or(paragraph.margin_before_pt),
+            .map_or(paragraph.margin_before_pt, |s| s.to_pt(math_em)),
         margin_after_pt: math_cfg
             .margin_after_pt
-            .unwrap_or(paragraph.margin_after_pt),
+            .map_or(paragraph.margin_after_pt, |s| s.to_pt(math_em)),
     };
 
     let metadata_cfg = cfg.metadata.unwrap_or_default();
@@ -477,6 +837,10 @@ fn lower(theme: &str, cfg: DocumentConfig) -> Result<ResolvedStyle, ResolveError
         keywords: metadata_cfg.keywords.unwrap_or_default(),
         creator: metadata_cfg.creator,
         language: metadata_cfg.language,
+        render_table: metadata_cfg.render_table.unwrap_or(false),
+        table_keys: metadata_cfg
+            .table_keys
+            .unwrap_or_else(|| vec![MetadataKey::Title, MetadataKey::Author, MetadataKey::Date]),
     };
 
     let header = lower_furniture(theme, "header", &defaults, cfg.header)?;
@@ -496,7 +860,85 @@ fn lower(theme: &str, cfg: DocumentConfig) -> Result<ResolvedStyle, ResolveError
         allow_remote_images: security_cfg.allow_remote_images.unwrap_or(true),
     };
 
-    Ok(ResolvedStyle {
+    // Scale and rise are clamped so a hostile value can't produce
+    // invisible or page-sized glyphs; NaN falls back to the default.
+    let sup_cfg = cfg.superscript.unwrap_or_default();
+    let superscript = ResolvedSuperscript {
+        scale: sup_cfg
+            .scale
+            .filter(|v| v.is_finite())
+            .map_or(0.70, |v| v.clamp(0.2, 1.0)),
+        rise: sup_cfg
+            .rise
+            .filter(|v| v.is_finite())
+            .map_or(0.32, |v| v.clamp(0.0, 1.0)),
+        ordinals: sup_cfg.ordinals.unwrap_or(false),
+    };
+
+    let limits_cfg = cfg.limits.unwrap_or_default();
+    let limits = ResolvedLimits {
+        max_input_bytes: limits_cfg.max_input_bytes,
+        max_pages: limits_cfg.max_pages,
+    };
+
+    let output_cfg = cfg.output.unwrap_or_default();
+    let encryption = match (output_cfg.password, output_cfg.owner_password) {
+        (None, None) => None,
+        (user, owner) => Some(ResolvedEncryption {
+            user_password: user.unwrap_or_default(),
+            owner_password: owner,
+            permissions: output_cfg.permissions.unwrap_or_else(|| {
+                vec![
+                    PdfPermission::Print,
+                    PdfPermission::Copy,
+                    PdfPermission::Modify,
+                    PdfPermission::Annotate,
+                ]
+            }),
+        }),
+    };
+    let output = ResolvedOutput {
+        embed_fonts: output_cfg.embed_fonts,
+        encryption,
+    };
+
+    let text_cfg = cfg.text.unwrap_or_default();
+    let text = ResolvedText {
+        collapse_whitespace: text_cfg.collapse_whitespace.unwrap_or(true),
+        max_space_stretch: text_cfg
+            .max_space_stretch
+            .filter(|v| v.is_finite())
+            .map(|v| v.max(0.0)),
+    };
+
+    let html = ResolvedHtml {
+        mode: cfg.html.and_then(|h| h.mode).unwrap_or_default(),
+    };
+
+    let digits = cfg
+        .document
+        .as_ref()
+        .and_then(|d| d.digits)
+        .unwrap_or_default();
+    let bookmarks = cfg
+        .document
+        .as_ref()
+        .and_then(|d| d.bookmarks)
+        .unwrap_or(true);
+    let flags = cfg
+        .document
+        .as_ref()
+        .and_then(|d| d.flags.clone())
+        .unwrap_or_default();
+    // Clamped like a font size: a zero or negative factor would stall
+    // layout the same way a zero font size does.
+    let scale = cfg
+        .document
+        .and_then(|d| d.scale)
+        .filter(|v| v.is_finite() && *v > 0.0)
+        .map_or(1.0, |v| v.clamp(0.1, 10.0));
+
+    let mut style = ResolvedStyle {
         page,
         headings: [h1, h2, h3, h4, h5, h6],
         paragraph,
@@ -507,20 +949,43 @@ fn lower(theme: &str, cfg: DocumentConfig) -> Result<ResolvedStyle, ResolveError
         list_ordered,
         list_unordered,
         list_task,
+        list_align_numbers,
         table,
         image,
         link,
+        link_display,
         mark,
+        emphasis,
+        strong_emphasis,
+        emphasis_strong,
         horizontal_rule,
         math,
+        superscript,
         metadata,
         header,
         footer,
         title_page,
         toc,
         fallback_fonts,
+        paragraph_spacing_pt,
+        headings_span_columns,
+        headings_transform,
+        digits,
+        bookmarks,
+        flags,
+        code_block_max_lines,
+        code_block_caption,
+        code_block_highlight,
         security,
-    })
+        limits,
+        output,
+        text,
+        html,
+    };
+    if scale != 1.0 {
+        style.scale(scale);
+    }
+    Ok(style)
 }
 
 /// Clamp a font size to a finite, strictly-positive value. A
@@ -620,6 +1085,13 @@ fn lower_admonition_kind(
     })
 }
 
+/// Resolve an optional margin to points against `em_pt`. Unset,
+/// negative and non-finite values all become 0.
+fn spacing_pt(spacing: Option<Spacing>, em_pt: f32) -> f32 {
+    let pt = spacing.map_or(0.0, |s| s.to_pt(em_pt));
+    if pt.is_finite() && pt > 0.0 { pt } else { 0.0 }
+}
+
 fn lower_block(
     theme: &str,
     where_: &str,
@@ -658,8 +1130,8 @@ fn lower_block(
         text_align: merged.text_align.unwrap_or(TextAlignment::Left),
         border: lower_border(merged.border.unwrap_or_default()),
         padding,
-        margin_before_pt: clamp_nonneg(merged.margin_before_pt.unwrap_or(0.0)),
-        margin_after_pt: clamp_nonneg(merged.margin_after_pt.unwrap_or(0.0)),
+        margin_before_pt: spacing_pt(merged.margin_before_pt, font_size_pt),
+        margin_after_pt: spacing_pt(merged.margin_after_pt, font_size_pt),
         indent_pt: clamp_nonneg(merged.indent_pt.unwrap_or(0.0)),
         letter_spacing_pt: safe_letter_spacing(merged.letter_spacing_pt.unwrap_or(0.0)),
         strikethrough: merged.strikethrough.unwrap_or(false),
@@ -737,6 +1209,7 @@ fn lower_list(
             .or(common.item_spacing_loose_pt)
             .unwrap_or(2.0),
         bullet_gap_pt: raw.bullet_gap_pt.or(common.bullet_gap_pt).unwrap_or(5.67),
+        marker_color: raw.marker_color.or(common.marker_color),
     })
 }
 
@@ -773,6 +1246,8 @@ fn lower_furniture(
         style,
         show_on_first_page: raw.show_on_first_page.unwrap_or(true),
         gap_pt: raw.gap_pt.unwrap_or(14.0),
+        page_number_style: raw.page_number_style.unwrap_or_default(),
+        start_at: raw.start_at.unwrap_or(1),
     }))
 }
 
@@ -782,8 +1257,14 @@ fn lower_title_page(
     raw: Option<TitlePageConfig>,
 ) -> Result<Option<ResolvedTitlePage>, ResolveError> {
     let Some(raw) = raw else { return Ok(None) };
-    let Some(title) = raw.title else {
-        return Ok(None);
+    // An enabled page with no configured title is kept with an empty
+    // one; front-matter fills it in at render time, and the page is
+    // dropped there if nothing does.
+    let title = match (raw.enabled, raw.title) {
+        (Some(false), _) => return Ok(None),
+        (_, Some(title)) => title,
+        (Some(true), None) => String::new(),
+        (None, None) => return Ok(None),
     };
     let style = lower_block(theme, "title_page", defaults, raw.style.unwrap_or_default())?;
     Ok(Some(ResolvedTitlePage {
@@ -8,11 +8,12 @@
 use serde::Serialize;
 
 pub use super::schema::{
-    BorderStyle, Color, FontStyleVariant, FontWeight, ImageAlign, Orientation, PageSize, Sides,
-    TextAlignment,
+    BorderStyle, Color, Digits, FontStyleVariant, FontWeight, HeadingTransform, HtmlMode,
+    ImageAlign, ImageFloat, ImageMode, LinkDisplay, MetadataKey, Orientation, PageNumberStyle,
+    PageSize, PdfPermission, RelativeUrlPolicy, Sides, TableOverflow, TextAlignment,
 };
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedStyle {
     pub page: ResolvedPage,
@@ -25,12 +26,20 @@ pub struct ResolvedStyle {
     pub list_ordered: ResolvedList,
     pub list_unordered: ResolvedList,
     pub list_task: ResolvedList,
+    /// `[list].align_numbers`: ordered numbers right-aligned in a
+    /// shared gutter.
+    pub list_align_numbers: bool,
     pub table: ResolvedTable,
     pub image: ResolvedImage,
     pub link: ResolvedInline,
+    pub link_display: ResolvedLinkDisplay,
     pub mark: ResolvedInline,
+    pub emphasis: ResolvedEmphasis,
+    pub strong_emphasis: ResolvedEmphasis,
+    pub emphasis_strong: ResolvedEmphasis,
     pub horizontal_rule: ResolvedRule,
     pub math: ResolvedMath,
+    pub superscript: ResolvedSuperscript,
     pub metadata: ResolvedMetadata,
     pub header: Option<ResolvedPageFurniture>,
     pub footer: Option<ResolvedPageFurniture>,
@@ -41,12 +50,40 @@ pub struct ResolvedStyle {
     /// order when the primary body / code font lacks a glyph for a
     /// codepoint.
     pub fallback_fonts: Vec<String>,
+    /// Gap (points) between successive body paragraphs, from
+    /// `[paragraph].paragraph_spacing_pt`. `None` keeps the plain
+    /// collapsed `margin_after_pt` / `margin_before_pt` spacing.
+    pub paragraph_spacing_pt: Option<f32>,
+    /// `[headings].span_columns`: headings break out of a multi-column
+    /// layout to the full body width.
+    pub headings_span_columns: bool,
+    /// `[headings].transform`: case applied to heading text.
+    pub headings_transform: HeadingTransform,
+    /// `[document].digits`: digit set for list and footnote numbers.
+    pub digits: Digits,
+    /// `[document].bookmarks`: emit a nested heading outline.
+    pub bookmarks: bool,
+    /// `[document].flags`: active flags for conditional regions.
+    pub flags: Vec<String>,
+    /// `[code_block].max_lines`: code blocks longer than this show
+    /// their first lines and a count of the rest.
+    pub code_block_max_lines: Option<usize>,
+    /// `[code_block.caption]`: styling for a fence's `caption` line.
+    pub code_block_caption: ResolvedBlock,
+    /// `[code_block].highlight_color`: tint behind a fence's
+    /// `highlight` lines.
+    pub code_block_highlight: Color,
     /// Operator-only policy on what the document may pull in while
     /// rendering. Never influenced by document content.
     pub security: ResolvedSecurity,
+    /// Operator-only hard limits on input size and page count.
+    pub limits: ResolvedLimits,
+    pub output: ResolvedOutput,
+    pub text: ResolvedText,
+    pub html: ResolvedHtml,
 }
 
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedPage {
     pub size: PageSize,
@@ -54,9 +91,12 @@ pub struct ResolvedPage {
     pub margins_mm: Sides<f32>,
     pub columns: u8,
     pub column_gap_mm: f32,
+    /// `[page].max_content_width` in points; `None` fills the margins.
+    pub max_content_width_pt: Option<f32>,
+    pub full_width_spans_margins: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedBlock {
     pub font_family: Option<String>,
@@ -78,7 +118,7 @@ pub struct ResolvedBlock {
     pub small_caps: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedInline {
     pub font_family: Option<String>,
@@ -92,7 +132,16 @@ pub struct ResolvedInline {
     pub underline: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// Colour and size overrides for one emphasis level. `None` keeps the
+/// surrounding text's value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ResolvedEmphasis {
+    pub font_size_pt: Option<f32>,
+    pub text_color: Option<Color>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedList {
     pub block: ResolvedBlock,
@@ -101,9 +150,11 @@ pub struct ResolvedList {
     pub item_spacing_tight_pt: f32,
     pub item_spacing_loose_pt: f32,
     pub bullet_gap_pt: f32,
+    /// `None` paints the marker in the item's text colour.
+    pub marker_color: Option<Color>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedTable {
     pub header: ResolvedBlock,
@@ -111,23 +162,47 @@ pub struct ResolvedTable {
     pub border: ResolvedBorder,
     pub alternating_row_background: Option<Color>,
     pub cell_padding: Sides<f32>,
+    /// Columns are never set narrower than this, padding included.
+    pub min_column_width_pt: f32,
+    /// Columns are never set wider than this. `None` is unbounded.
+    pub max_column_width_pt: Option<f32>,
+    pub overflow: TableOverflow,
     pub row_gap_pt: f32,
     pub margin_before_pt: f32,
     pub margin_after_pt: f32,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedImage {
+    pub mode: ImageMode,
     pub max_width_pct: f32,
     pub align: ImageAlign,
+    pub float: ImageFloat,
+    /// Downsample block images above this resolution at their placed
+    /// size. `None` embeds every decoded pixel.
+    pub max_dpi: Option<f32>,
     pub margin_before_pt: f32,
     pub margin_after_pt: f32,
     /// Styling for the caption line drawn under an image.
     pub caption: ResolvedBlock,
+    /// Fall back to the alt text as the caption when the image has no
+    /// title attribute.
+    pub show_caption: bool,
+    /// Caption prefix template; `{n}` is the 1-based figure number.
+    pub caption_prefix: Option<String>,
+    /// Place an image and its caption on the same page.
+    pub keep_with_caption: bool,
+    /// Place an image on the same page as the paragraph before it.
+    pub keep_with_previous: bool,
+    /// Grid layout for a paragraph of images on one line.
+    pub gallery: bool,
+    /// Images per gallery row, at least 1.
+    pub gallery_columns: usize,
+    pub gallery_gap_pt: f32,
 }
 
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedRule {
     pub color: Color,
@@ -141,7 +216,7 @@ pub struct ResolvedRule {
 /// Resolved math styling. `align` / `margin_*` drive display
 /// (`$$…$$`) blocks; `scale` multiplies the body size for display
 /// math; `color` is the math ink.
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedMath {
     pub align: TextAlignment,
@@ -151,7 +226,29 @@ pub struct ResolvedMath {
     pub margin_after_pt: f32,
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+/// Print-time URL policy from `[link]`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ResolvedLinkDisplay {
+    pub display: LinkDisplay,
+    pub relative_urls: RelativeUrlPolicy,
+    pub base_url: Option<String>,
+    /// `[link].print_titles`: print link titles with the URLs.
+    pub print_titles: bool,
+}
+
+/// Superscript geometry, as fractions of the surrounding text size.
+/// The line height is never affected: a raised segment sits inside the
+/// line's existing leading.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ResolvedSuperscript {
+    pub scale: f32,
+    pub rise: f32,
+    pub ordinals: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedMetadata {
     pub title: Option<String>,
@@ -160,9 +257,11 @@ pub struct ResolvedMetadata {
     pub keywords: Vec<String>,
     pub creator: Option<String>,
     pub language: Option<String>,
+    pub render_table: bool,
+    pub table_keys: Vec<MetadataKey>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedPageFurniture {
     pub left: Option<String>,
@@ -173,11 +272,18 @@ pub struct ResolvedPageFurniture {
     /// Gap in points from the body's content edge to the furniture's
     /// baseline (above for headers, below for footers).
     pub gap_pt: f32,
+    /// How `{page}` is written until a `<!-- numbering -->` marker
+    /// switches it.
+    pub page_number_style: PageNumberStyle,
+    /// The number `{page}` shows on the first page.
+    pub start_at: usize,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedTitlePage {
+    /// Empty until front-matter supplies it when the page was only
+    /// `enabled` in config.
     pub title: String,
     pub subtitle: Option<String>,
     pub author: Option<String>,
@@ -186,7 +292,7 @@ pub struct ResolvedTitlePage {
     pub style: ResolvedBlock,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedToc {
     pub title: String,
@@ -200,7 +306,7 @@ pub struct ResolvedToc {
 /// a document may reference an absolute local path or a remote URL at
 /// all. All defaults preserve pre-existing behavior — see
 /// `SecurityConfig` for the rationale.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedSecurity {
     pub image_root: Option<std::path::PathBuf>,
@@ -208,10 +314,58 @@ pub struct ResolvedSecurity {
     pub allow_remote_images: bool,
 }
 
+/// Resolved hard limits. `None` means unlimited (the default).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ResolvedLimits {
+    pub max_input_bytes: Option<usize>,
+    pub max_pages: Option<usize>,
+}
+
+/// Resolved `[output]` settings. `embed_fonts: None` leaves the font
+/// choice to the loader; `encryption: None` writes an unencrypted PDF.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ResolvedOutput {
+    pub embed_fonts: Option<bool>,
+    pub encryption: Option<ResolvedEncryption>,
+}
+
+/// Standard PDF encryption for the written file. The passwords are
+/// never serialized, so `--print-effective-config` can't leak them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ResolvedEncryption {
+    #[serde(skip)]
+    pub user_password: String,
+    /// `None` without `owner_password`: each file then gets a random
+    /// one, so the user password can't lift `permissions`.
+    #[serde(skip)]
+    pub owner_password: Option<String>,
+    pub permissions: Vec<PdfPermission>,
+}
+
+/// Resolved `[text]` settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ResolvedText {
+    pub collapse_whitespace: bool,
+    /// Largest extra width a justified line may add to each space, as
+    /// a multiple of the space's width. `None` is no limit.
+    pub max_space_stretch: Option<f32>,
+}
+
+/// Resolved `[html]` settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ResolvedHtml {
+    pub mode: HtmlMode,
+}
+
 /// Resolved admonition styling. The renderer picks the matching
 /// per-kind block via [`for_kind`]; unknown canonical kinds fall back
 /// to `generic`.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedAdmonition {
     pub note: ResolvedAdmonitionKind,
@@ -226,7 +380,7 @@ pub struct ResolvedAdmonition {
 /// shape (padding, margins, font, background); `accent_color` drives
 /// the icon and left-edge border; `label` is the header text the
 /// renderer shows when the author didn't write an explicit title.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedAdmonitionKind {
     pub block: ResolvedBlock,
@@ -249,7 +403,7 @@ impl ResolvedAdmonition {
     }
 }
 
-#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedBorder {
     pub top: Option<ResolvedBorderSide>,
@@ -258,7 +412,7 @@ pub struct ResolvedBorder {
     pub left: Option<ResolvedBorderSide>,
 }
 
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ResolvedBorderSide {
     pub width_pt: f32,
@@ -267,6 +421,15 @@ pub struct ResolvedBorderSide {
 }
 
 impl ResolvedBlock {
+    fn scale(&mut self, k: f32) {
+        self.font_size_pt = (self.font_size_pt * k).min(1000.0);
+        self.padding = scale_sides(self.padding, k);
+        self.margin_before_pt *= k;
+        self.margin_after_pt *= k;
+        self.indent_pt *= k;
+        self.letter_spacing_pt *= k;
+    }
+
     /// True for bold-or-heavier weights (CSS-style 600+ counts).
     pub fn is_bold(&self) -> bool {
         match self.font_weight {
@@ -311,6 +474,97 @@ impl ResolvedRule {
     }
 }
 
+impl ResolvedStyle {
+    /// Multiply every font size and spacing value by `k` — the
+    /// `[document].scale` knob. Page geometry, border widths,
+    /// percentages and unitless ratios (line height, superscript
+    /// scale) are proportional already and stay put.
+    pub(crate) fn scale(&mut self, k: f32) {
+        for block in self.headings.iter_mut().chain([
+            &mut self.paragraph,
+            &mut self.code_block,
+            &mut self.blockquote,
+            &mut self.table.header,
+            &mut self.table.cell,
+            &mut self.image.caption,
+            &mut self.code_block_caption,
+        ]) {
+            block.scale(k);
+        }
+        for kind in [
+            &mut self.admonition.note,
+            &mut self.admonition.info,
+            &mut self.admonition.tip,
+            &mut self.admonition.warning,
+            &mut self.admonition.danger,
+            &mut self.admonition.generic,
+        ] {
+            kind.block.scale(k);
+        }
+        for list in [
+            &mut self.list_ordered,
+            &mut self.list_unordered,
+            &mut self.list_task,
+        ] {
+            list.block.scale(k);
+            list.indent_per_level_pt *= k;
+            list.item_spacing_tight_pt *= k;
+            list.item_spacing_loose_pt *= k;
+            list.bullet_gap_pt *= k;
+        }
+        for inline in [&mut self.code_inline, &mut self.link, &mut self.mark] {
+            inline.font_size_pt *= k;
+            inline.padding = scale_sides(inline.padding, k);
+        }
+        for emphasis in [
+            &mut self.emphasis,
+            &mut self.strong_emphasis,
+            &mut self.emphasis_strong,
+        ] {
+            if let Some(size) = &mut emphasis.font_size_pt {
+                *size *= k;
+            }
+        }
+        self.table.cell_padding = scale_sides(self.table.cell_padding, k);
+        self.table.row_gap_pt *= k;
+        self.table.margin_before_pt *= k;
+        self.table.margin_after_pt *= k;
+        self.image.margin_before_pt *= k;
+        self.image.margin_after_pt *= k;
+        self.horizontal_rule.margin_before_pt *= k;
+        self.horizontal_rule.margin_after_pt *= k;
+        self.math.margin_before_pt *= k;
+        self.math.margin_after_pt *= k;
+        // The text measure tracks the text it holds, unlike the rest of
+        // the page geometry.
+        if let Some(width) = &mut self.page.max_content_width_pt {
+            *width *= k;
+        }
+        for furniture in [&mut self.header, &mut self.footer].into_iter().flatten() {
+            furniture.style.scale(k);
+            furniture.gap_pt *= k;
+        }
+        if let Some(title_page) = &mut self.title_page {
+            title_page.style.scale(k);
+        }
+        if let Some(toc) = &mut self.toc {
+            toc.style.scale(k);
+        }
+        if let Some(gap) = &mut self.paragraph_spacing_pt {
+            *gap *= k;
+        }
+    }
+}
+
+fn scale_sides(sides: Sides<f32>, k: f32) -> Sides<f32> {
+    Sides {
+        top: sides.top * k,
+        right: sides.right * k,
+        bottom: sides.bottom * k,
+        left: sides.left * k,
+    }
+}
+
 impl Default for ResolvedStyle {
     /// Synchronously load the bundled `default` theme preset. Panics
     /// only if the bundled `default.toml` itself is malformed — that's
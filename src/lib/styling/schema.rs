@@ -7,6 +7,7 @@
 //! `super::resolved::ResolvedStyle` that the renderer consumes.
 
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::path::PathBuf;
 
 #[derive(Deserialize, Debug, Clone, Default)]
 #[serde(deny_unknown_fields, rename_all = "snake_case")]
@@ -17,11 +18,17 @@ pub struct DocumentConfig {
     /// Theme presets use this to chain off another preset (e.g.
     /// `github.toml` has `inherits = "default"`).
     pub inherits: Option<String>,
+    /// Named colours that any colour field in the same file can use
+    /// in place of a literal (`text_color = "accent"`). See
+    /// [`PaletteConfig`].
+    pub palette: Option<PaletteConfig>,
+    /// Whole-document settings. See [`DocumentSettingsConfig`].
+    pub document: Option<DocumentSettingsConfig>,
     pub page: Option<PageConfig>,
     pub defaults: Option<BlockConfig>,
     pub headings: Option<HeadingsConfig>,
     pub paragraph: Option<BlockConfig>,
-    pub code_block: Option<BlockConfig>,
+    pub code_block: Option<CodeBlockConfig>,
     pub code_inline: Option<InlineConfig>,
     pub blockquote: Option<BlockConfig>,
     /// Per-kind callout / admonition styling. The top-level
@@ -34,16 +41,28 @@ pub struct DocumentConfig {
     pub list: Option<ListsConfig>,
     pub table: Option<TableConfig>,
     pub image: Option<ImageConfig>,
-    pub link: Option<InlineConfig>,
+    /// Hyperlink styling plus how link targets are surfaced in print
+    /// (`display`, `relative_urls`, `base_url`).
+    pub link: Option<LinkConfig>,
     /// Inline highlight (`==text==`). Only `background_color` is
     /// load-bearing today; the rest of `InlineConfig` is accepted for
     /// symmetry with `link`/`code_inline`.
     pub mark: Option<InlineConfig>,
+    /// `*italic*` text. Unset fields follow the surrounding text.
+    pub emphasis: Option<EmphasisConfig>,
+    /// `**bold**` text.
+    pub strong_emphasis: Option<EmphasisConfig>,
+    /// `***bold italic***` text, and emphasis nested in strong or the
+    /// other way round.
+    pub emphasis_strong: Option<EmphasisConfig>,
     pub horizontal_rule: Option<RuleConfig>,
     /// LaTeX math (`$…$` / `$$…$$`). Display blocks honour `align`,
     /// `scale`, `color`, and block margins; inline math always flows
     /// with its surrounding text at the body size.
     pub math: Option<MathConfig>,
+    /// Superscript geometry (footnote markers, `<sup>`) and the
+    /// optional raised ordinal suffixes.
+    pub superscript: Option<SuperscriptConfig>,
     pub metadata: Option<MetadataConfig>,
     pub header: Option<PageFurnitureConfig>,
     pub footer: Option<PageFurnitureConfig>,
@@ -52,6 +71,126 @@ pub struct DocumentConfig {
     /// Operator-only policy on what the document is allowed to pull in
     /// while rendering. See [`SecurityConfig`].
     pub security: Option<SecurityConfig>,
+    /// Operator-only hard caps on input size and output length. See
+    /// [`LimitsConfig`].
+    pub limits: Option<LimitsConfig>,
+    /// How the PDF file itself is written. See [`OutputConfig`].
+    pub output: Option<OutputConfig>,
+    /// Handling of the text inside blocks. See [`TextConfig`].
+    pub text: Option<TextConfig>,
+    /// Treatment of raw HTML the renderer has no meaning for. See
+    /// [`HtmlConfig`].
+    pub html: Option<HtmlConfig>,
+    /// Font selection, read by [`crate::config::load_font_config`]
+    /// rather than the style resolver. See [`FontsConfig`].
+    pub fonts: Option<FontsConfig>,
+}
+
+/// `[text]`: how inline text is treated before it is set.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct TextConfig {
+    /// Collapse each run of spaces, tabs and line breaks to a single
+    /// space, as HTML renderers of CommonMark do. Inline code keeps its
+    /// spacing either way. Default `true`.
+    pub collapse_whitespace: Option<bool>,
+    /// How far a justified line may widen each inter-word space, as a
+    /// multiple of the space's own width. A line that would need more
+    /// is set flush left instead. Unset, spaces stretch without limit.
+    pub max_space_stretch: Option<f32>,
+}
+
+/// `[fonts]`: the fonts a config file selects, the TOML form of
+/// [`crate::fonts::FontConfig`].
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct FontsConfig {
+    /// Body font: a built-in name, a system font name or a file path.
+    pub default_font: Option<String>,
+    /// Code font, named the same way as `default_font`.
+    pub code_font: Option<String>,
+    /// Directories searched before the system font directories when a
+    /// font is given by name. Relative paths are taken from the
+    /// config file's directory.
+    pub custom_paths: Option<Vec<PathBuf>>,
+    /// Ordered fallback fonts for characters the body font lacks.
+    pub fallback_fonts: Option<Vec<String>>,
+    /// Subset embedded fonts to the glyphs used. Default `true`.
+    pub enable_subsetting: Option<bool>,
+    /// Append the platform emoji font to the fallback chain when the
+    /// document contains emoji. Default `false`.
+    pub auto_emoji: Option<bool>,
+    /// Fonts picked per element. See [`FontMapConfig`].
+    pub map: Option<FontMapConfig>,
+}
+
+/// `[fonts.map]`: a font for each kind of element, named the same way
+/// as `default_font`. `text` and `code` take the place of
+/// `default_font` and `code_font`; an unset element uses the body
+/// font.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct FontMapConfig {
+    pub heading: Option<String>,
+    pub text: Option<String>,
+    pub code: Option<String>,
+    pub blockquote: Option<String>,
+}
+
+/// `[html]`: what happens to raw HTML the renderer does not
+/// interpret. Tags it does understand (`<sup>`, `<img>`, `<a>`, a
+/// `<div>` wrapping markdown, `<!-- pagebreak -->`, …) are unaffected.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct HtmlConfig {
+    /// Default `raw`.
+    pub mode: Option<HtmlMode>,
+}
+
+/// Policy for uninterpreted HTML blocks and inline tags.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum HtmlMode {
+    /// Drop it.
+    Ignore,
+    /// Drop it and log a warning naming what was dropped.
+    Comment,
+    /// Show the HTML source as a monospace block; inline tags are
+    /// printed as written.
+    #[default]
+    Raw,
+    /// Drop the tags and keep the text between them.
+    StripTags,
+}
+
+/// `[output]`: choices about the written PDF rather than its look.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct OutputConfig {
+    /// `false` renders with the base-14 PDF fonts only (Helvetica for
+    /// body text, Courier for code), ignoring every configured font
+    /// and embedding nothing. `true` embeds a system font even where
+    /// a built-in one was asked for. Unset keeps the automatic choice.
+    pub embed_fonts: Option<bool>,
+    /// Password needed to open the PDF. Setting it (or
+    /// `owner_password`) encrypts the file with 128-bit AES.
+    pub password: Option<String>,
+    /// Password that lifts the `permissions` restrictions. Defaults to
+    /// `password` when unset.
+    pub owner_password: Option<String>,
+    /// What a reader may do without the owner password. Unset grants
+    /// everything; only read when the PDF is encrypted.
+    pub permissions: Option<Vec<PdfPermission>>,
+}
+
+/// One right granted by `[output].permissions` on an encrypted PDF.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PdfPermission {
+    Print,
+    Copy,
+    Modify,
+    Annotate,
 }
 
 /// Operator-controlled limits on what a document is allowed to pull in
@@ -78,6 +217,22 @@ pub struct SecurityConfig {
     pub allow_remote_images: Option<bool>,
 }
 
+/// Operator-controlled hard limits for rendering untrusted input. The
+/// `LargeDocument` validation warning only advises; these refuse. Like
+/// `[security]`, a document can never set them itself. Unset means
+/// unlimited, which keeps the historical behavior.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct LimitsConfig {
+    /// Largest markdown input (in bytes, frontmatter included) that
+    /// will be lexed. Bigger input is rejected before any parsing.
+    pub max_input_bytes: Option<usize>,
+    /// Most pages the output may have, title page and TOC included.
+    /// Layout stops as soon as the body goes past it and the render
+    /// fails.
+    pub max_pages: Option<usize>,
+}
+
 #[derive(Deserialize, Debug, Clone, Default)]
 #[serde(deny_unknown_fields, rename_all = "snake_case")]
 pub struct PageConfig {
@@ -86,6 +241,14 @@ pub struct PageConfig {
     pub margins: Option<Sides<f32>>,
     pub columns: Option<u8>,
     pub column_gap_mm: Option<f32>,
+    /// Widest the body text column may be, in points or `"em"` of the
+    /// paragraph font size. A wider page centers the column between
+    /// the margins. Unset fills the margins.
+    pub max_content_width: Option<Spacing>,
+    /// With `max_content_width`, let `full_width` images and
+    /// horizontal rules run out to the page margins instead of the
+    /// narrowed column. Default `false`.
+    pub full_width_spans_margins: Option<bool>,
 }
 
 #[derive(Deserialize, Debug, Clone, Default)]
@@ -97,6 +260,15 @@ pub struct HeadingsConfig {
     pub h4: Option<BlockConfig>,
     pub h5: Option<BlockConfig>,
     pub h6: Option<BlockConfig>,
+    /// With `[page] columns` > 1, lay headings across the full body
+    /// width and start a new band of columns under them.
+    pub span_columns: Option<bool>,
+    /// Case applied to every heading's text at render time, whatever
+    /// the source spelling. Default `none`.
+    pub transform: Option<HeadingTransform>,
+    /// Alignment for every level that doesn't set its own
+    /// `text_align`. Unset falls back to `[defaults].text_align`.
+    pub text_align: Option<TextAlignment>,
 }
 
 /// The workhorse style block. Applies to any flowable block: paragraph,
@@ -116,8 +288,8 @@ pub struct BlockConfig {
     pub text_align: Option<TextAlignment>,
     pub border: Option<BorderConfig>,
     pub padding: Option<Sides<f32>>,
-    pub margin_before_pt: Option<f32>,
-    pub margin_after_pt: Option<f32>,
+    pub margin_before_pt: Option<Spacing>,
+    pub margin_after_pt: Option<Spacing>,
     pub indent_pt: Option<f32>,
     pub letter_spacing_pt: Option<f32>,
     pub strikethrough: Option<bool>,
@@ -133,6 +305,13 @@ pub struct BlockConfig {
     /// the document `[defaults]` block is read by the renderer — the
     /// field is accepted syntactically on per-block tables but ignored.
     pub fallback_fonts: Option<Vec<String>>,
+    /// Gap (points) between two successive body paragraphs. When set,
+    /// a paragraph that directly follows another one sits this far
+    /// below it (never less than `margin_after_pt`), and the first
+    /// paragraph under a heading gets no before-margin. Only read from
+    /// `[paragraph]` (or inherited from `[defaults]`); other block
+    /// tables accept it but ignore it.
+    pub paragraph_spacing_pt: Option<f32>,
 }
 
 /// Subset of `BlockConfig` for true inline runs (`code_inline`,
@@ -152,6 +331,34 @@ pub struct InlineConfig {
     pub underline: Option<bool>,
 }
 
+/// `[emphasis]`, `[strong_emphasis]`, `[emphasis_strong]`: colour and
+/// size for Markdown emphasis. The bold / italic face comes from the
+/// markup itself.
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct EmphasisConfig {
+    pub font_size_pt: Option<f32>,
+    pub text_color: Option<Color>,
+}
+
+/// `[link]`: the inline run styling plus the print-time URL policy.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct LinkConfig {
+    #[serde(flatten)]
+    pub inline: InlineConfig,
+    /// Whether the target URL is printed next to the link text.
+    pub display: Option<LinkDisplay>,
+    /// What to print for fragment (`#section`) and relative
+    /// (`./other.md`) targets when `display` prints URLs.
+    pub relative_urls: Option<RelativeUrlPolicy>,
+    /// Base URL that `relative_urls = "resolve_base"` joins against.
+    pub base_url: Option<String>,
+    /// Print a link's `"title"` with its URL when `display` prints
+    /// URLs. Default `false`; the title is a hover tooltip either way.
+    pub print_titles: Option<bool>,
+}
+
 /// Per-kind admonition styling. The top-level [admonition] block
 /// flattens a [`BlockConfig`] so shared shape fields (padding, margins,
 /// font defaults) can be set in one place; the per-kind sub-blocks
@@ -193,6 +400,9 @@ pub struct ListsConfig {
     pub task: Option<ListStyleConfig>,
     /// Shared between all list flavors unless overridden per-flavor.
     pub common: Option<ListStyleConfig>,
+    /// Right-align ordered numbers in a gutter as wide as the list's
+    /// widest number, so every item's text starts at the same x.
+    pub align_numbers: Option<bool>,
 }
 
 #[derive(Deserialize, Debug, Clone, Default)]
@@ -211,6 +421,26 @@ pub struct ListStyleConfig {
     pub item_spacing_loose_pt: Option<f32>,
     /// Horizontal gap between the bullet/number and the item text.
     pub bullet_gap_pt: Option<f32>,
+    /// Colour of the bullet, number or task box. Unset draws it in the
+    /// item's `text_color`.
+    pub marker_color: Option<Color>,
+}
+
+/// `[code_block]`: the usual block styling plus code-only options.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct CodeBlockConfig {
+    #[serde(flatten)]
+    pub block: BlockConfig,
+    /// Show at most this many lines of each fenced or indented code
+    /// block, followed by a "… N more lines" note. Unset shows every
+    /// line.
+    pub max_lines: Option<usize>,
+    /// Styling for the line a fence's `caption` attribute draws above
+    /// the block.
+    pub caption: Option<BlockConfig>,
+    /// Tint behind the lines a fence's `highlight` attribute names.
+    pub highlight_color: Option<Color>,
 }
 
 #[derive(Deserialize, Debug, Clone, Default)]
@@ -221,19 +451,53 @@ pub struct TableConfig {
     pub border: Option<BorderConfig>,
     pub alternating_row_background: Option<Color>,
     pub cell_padding: Option<Sides<f32>>,
+    /// Narrowest a column may be, padding included. Default 0 (only
+    /// the cell padding).
+    pub min_column_width_pt: Option<f32>,
+    /// Widest a column may be, padding included. Unset lets a column
+    /// grow to the page width.
+    pub max_column_width_pt: Option<f32>,
+    /// What a table too wide for the body does. See [`TableOverflow`].
+    pub overflow: Option<TableOverflow>,
     pub row_gap_pt: Option<f32>,
-    pub margin_before_pt: Option<f32>,
-    pub margin_after_pt: Option<f32>,
+    pub margin_before_pt: Option<Spacing>,
+    pub margin_after_pt: Option<Spacing>,
 }
 
 #[derive(Deserialize, Debug, Clone, Default)]
 #[serde(deny_unknown_fields, rename_all = "snake_case")]
 pub struct ImageConfig {
+    /// How an image is sized on the page. See [`ImageMode`].
+    pub mode: Option<ImageMode>,
     pub max_width_pct: Option<f32>,
     pub align: Option<ImageAlign>,
+    /// Float the image to one side so following paragraphs wrap
+    /// around it. See [`ImageFloat`].
+    pub float: Option<ImageFloat>,
+    /// Highest resolution a block image keeps at its placed size.
+    /// Images denser than this are downsampled before embedding.
+    /// Unset keeps every pixel.
+    pub max_dpi: Option<f32>,
     pub caption: Option<BlockConfig>,
-    pub margin_before_pt: Option<f32>,
-    pub margin_after_pt: Option<f32>,
+    /// Draw the alt text as a caption when the image has no title.
+    pub show_caption: Option<bool>,
+    /// Prepended to every caption; `{n}` expands to the figure number.
+    pub caption_prefix: Option<String>,
+    /// Move an image to the next page when its caption won't fit
+    /// under it. Defaults to `true`.
+    pub keep_with_caption: Option<bool>,
+    /// Keep an image on the same page as the paragraph right before
+    /// it. Defaults to `false`.
+    pub keep_with_previous: Option<bool>,
+    /// Lay out two or more images on one line as a grid. Defaults to
+    /// `false`, which leaves them inline in the paragraph.
+    pub gallery: Option<bool>,
+    /// Images per gallery row. Defaults to 2.
+    pub gallery_columns: Option<usize>,
+    /// Space between gallery images, across and down. Defaults to 8.
+    pub gallery_gap_pt: Option<f32>,
+    pub margin_before_pt: Option<Spacing>,
+    pub margin_after_pt: Option<Spacing>,
 }
 
 #[derive(Deserialize, Debug, Clone, Default)]
@@ -244,8 +508,8 @@ pub struct RuleConfig {
     pub style: Option<BorderStyle>,
     /// Width as percent of the content column. 100 = full width.
     pub width_pct: Option<f32>,
-    pub margin_before_pt: Option<f32>,
-    pub margin_after_pt: Option<f32>,
+    pub margin_before_pt: Option<Spacing>,
+    pub margin_after_pt: Option<Spacing>,
 }
 
 /// Styling for typeset math. `align` / `margin_*` apply to display
@@ -258,8 +522,151 @@ pub struct MathConfig {
     pub align: Option<TextAlignment>,
     pub scale: Option<f32>,
     pub color: Option<Color>,
-    pub margin_before_pt: Option<f32>,
-    pub margin_after_pt: Option<f32>,
+    pub margin_before_pt: Option<Spacing>,
+    pub margin_after_pt: Option<Spacing>,
+}
+
+/// `[palette]`: a small set of named colours. Colour fields elsewhere
+/// in the same config file, or in a layer stacked above it, may give
+/// one of these names instead of a literal; the name is replaced by
+/// the palette entry when that file is parsed.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct PaletteConfig {
+    pub primary: Option<Color>,
+    pub text: Option<Color>,
+    pub muted: Option<Color>,
+    pub accent: Option<Color>,
+    pub background: Option<Color>,
+}
+
+/// `[document]`: settings that apply across every block at once.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct DocumentSettingsConfig {
+    /// Multiplier applied to every font size and vertical / horizontal
+    /// spacing value after the theme and overrides are merged. Page
+    /// size, page margins, border widths and percentages are left
+    /// alone. Default `1.0`.
+    pub scale: Option<f32>,
+    /// Digit set for the numbers the renderer writes itself: ordered
+    /// list markers and footnote numbers. Default `latin`.
+    pub digits: Option<Digits>,
+    /// Build the PDF outline (the viewer's bookmark pane) from the
+    /// headings, nested by level. Default `true`.
+    pub bookmarks: Option<bool>,
+    /// Flags that switch on `<!-- if: NAME -->` regions. Regions whose
+    /// flag is not listed are dropped before lexing. Default none.
+    pub flags: Option<Vec<String>>,
+    /// Switch the text of any element drawn on its own background to
+    /// black or white when its colour is too close to that background,
+    /// unless the config sets the element's `text_color` itself.
+    /// Default `false`.
+    pub auto_contrast: Option<bool>,
+}
+
+/// The digit shapes used for generated numbers.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Digits {
+    /// `0123456789`.
+    #[default]
+    Latin,
+    /// `٠١٢٣٤٥٦٧٨٩`.
+    #[serde(alias = "arabic-indic")]
+    ArabicIndic,
+    /// `०१२३४५६७८९`.
+    Devanagari,
+}
+
+impl Digits {
+    /// `text` with each ASCII digit swapped for this set's digit.
+    pub fn localize(self, text: &str) -> String {
+        let zero = match self {
+            Digits::Latin => return text.to_string(),
+            Digits::ArabicIndic => 0x0660,
+            Digits::Devanagari => 0x0966,
+        };
+        text.chars()
+            .map(|c| match c.to_digit(10) {
+                Some(d) if c.is_ascii_digit() => char::from_u32(zero + d).unwrap_or(c),
+                _ => c,
+            })
+            .collect()
+    }
+}
+
+/// How a page number is written in headers, footers and the TOC.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum PageNumberStyle {
+    /// `1 2 3 4`.
+    #[default]
+    Arabic,
+    /// `i ii iii iv`.
+    #[serde(alias = "roman")]
+    RomanLower,
+    /// `I II III IV`.
+    RomanUpper,
+}
+
+impl PageNumberStyle {
+    /// Parse the name used in config and in `<!-- numbering: ... -->`
+    /// markers. `roman` means `roman-lower`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "arabic" => Some(PageNumberStyle::Arabic),
+            "roman" | "roman-lower" => Some(PageNumberStyle::RomanLower),
+            "roman-upper" => Some(PageNumberStyle::RomanUpper),
+            _ => None,
+        }
+    }
+
+    /// `n` written in this style. Roman numerals have no zero, so `0`
+    /// stays arabic.
+    pub fn format(self, n: usize) -> String {
+        if self == PageNumberStyle::Arabic || n == 0 {
+            return n.to_string();
+        }
+        const NUMERALS: [(usize, &str); 13] = [
+            (1000, "m"),
+            (900, "cm"),
+            (500, "d"),
+            (400, "cd"),
+            (100, "c"),
+            (90, "xc"),
+            (50, "l"),
+            (40, "xl"),
+            (10, "x"),
+            (9, "ix"),
+            (5, "v"),
+            (4, "iv"),
+            (1, "i"),
+        ];
+        let mut rest = n;
+        let mut out = String::new();
+        for (value, numeral) in NUMERALS {
+            while rest >= value {
+                out.push_str(numeral);
+                rest -= value;
+            }
+        }
+        if self == PageNumberStyle::RomanUpper {
+            out.make_ascii_uppercase();
+        }
+        out
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct SuperscriptConfig {
+    /// Glyph size as a fraction of the surrounding text size.
+    pub scale: Option<f32>,
+    /// Baseline rise as a fraction of the surrounding text size.
+    pub rise: Option<f32>,
+    /// Raise English ordinal suffixes (`1st`, `22nd`, `4th`).
+    pub ordinals: Option<bool>,
 }
 
 #[derive(Deserialize, Debug, Clone, Default)]
@@ -274,6 +681,42 @@ pub struct MetadataConfig {
     /// Emitted as the PDF Catalog `/Lang` entry for screen readers.
     /// Omitted entirely when unset.
     pub language: Option<String>,
+    /// Draw a two-column table of the front-matter values at the top
+    /// of the body. Default `false`.
+    pub render_table: Option<bool>,
+    /// Front-matter keys listed in that table, in order. Keys the
+    /// front-matter leaves unset are skipped. Default
+    /// `["title", "author", "date"]`.
+    pub table_keys: Option<Vec<MetadataKey>>,
+}
+
+/// A front-matter key shown in the metadata table
+/// (`[metadata].table_keys`).
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataKey {
+    Title,
+    Subtitle,
+    Author,
+    Date,
+    Subject,
+    Creator,
+    Keywords,
+}
+
+impl MetadataKey {
+    /// Label printed in the table's first column.
+    pub fn label(self) -> &'static str {
+        match self {
+            MetadataKey::Title => "Title",
+            MetadataKey::Subtitle => "Subtitle",
+            MetadataKey::Author => "Author",
+            MetadataKey::Date => "Date",
+            MetadataKey::Subject => "Subject",
+            MetadataKey::Creator => "Creator",
+            MetadataKey::Keywords => "Keywords",
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone, Default)]
@@ -290,11 +733,19 @@ pub struct PageFurnitureConfig {
     /// below the body's last line. Larger value = more breathing
     /// room. Default ≈ 14pt.
     pub gap_pt: Option<f32>,
+    /// How `{page}` is written. Default `arabic`.
+    pub page_number_style: Option<PageNumberStyle>,
+    /// The number `{page}` shows on the first page. Default `1`.
+    pub start_at: Option<usize>,
 }
 
 #[derive(Deserialize, Debug, Clone, Default)]
 #[serde(deny_unknown_fields, rename_all = "snake_case")]
 pub struct TitlePageConfig {
+    /// `true` draws a title page even without `title` here, taking the
+    /// text from the document's front-matter. `false` turns the page
+    /// off regardless of `title`.
+    pub enabled: Option<bool>,
     pub title: Option<String>,
     pub subtitle: Option<String>,
     pub author: Option<String>,
@@ -321,6 +772,30 @@ pub enum TextAlignment {
     Justify,
 }
 
+/// A table column's GFM alignment (`:--`, `:-:`, `--:`), laid out by
+/// the same code as a paragraph's.
+impl From<crate::markdown::TableAlignment> for TextAlignment {
+    fn from(align: crate::markdown::TableAlignment) -> Self {
+        match align {
+            crate::markdown::TableAlignment::Left => TextAlignment::Left,
+            crate::markdown::TableAlignment::Center => TextAlignment::Center,
+            crate::markdown::TableAlignment::Right => TextAlignment::Right,
+        }
+    }
+}
+
+/// Table columns have no justified setting, so `Justify` becomes
+/// `Left`, the edge a justified paragraph's last line sets against.
+impl From<TextAlignment> for crate::markdown::TableAlignment {
+    fn from(align: TextAlignment) -> Self {
+        match align {
+            TextAlignment::Left | TextAlignment::Justify => crate::markdown::TableAlignment::Left,
+            TextAlignment::Center => crate::markdown::TableAlignment::Center,
+            TextAlignment::Right => crate::markdown::TableAlignment::Right,
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum Orientation {
@@ -328,6 +803,97 @@ pub enum Orientation {
     Landscape,
 }
 
+/// How a link's target URL appears in print. `Inline` (the default)
+/// only underlines and colours the text; the URL lives in the PDF
+/// link annotation.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkDisplay {
+    #[default]
+    Inline,
+    /// `text (https://example.com)`.
+    Parenthetical,
+    /// A numbered marker after the text; the URL is listed with the
+    /// document's footnotes.
+    Footnote,
+}
+
+/// Print policy for fragment and relative link targets, which mean
+/// nothing to someone reading paper.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RelativeUrlPolicy {
+    /// Print the target verbatim.
+    Keep,
+    /// Print nothing for the link.
+    #[default]
+    Strip,
+    /// Join the target onto `base_url`; strip when no base is set.
+    ResolveBase,
+}
+
+/// Case transform for heading text (`[headings].transform`).
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum HeadingTransform {
+    /// Headings render as written.
+    #[default]
+    None,
+    /// Every letter uppercase.
+    Upper,
+    /// Every letter lowercase.
+    Lower,
+    /// Each word capitalized, except short articles, conjunctions and
+    /// prepositions that are neither the first nor the last word.
+    Title,
+}
+
+/// Sizing mode for block images (`[image].mode`).
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageMode {
+    /// Natural size, shrunk to `max_width_pct` of the column.
+    #[default]
+    Inline,
+    /// Scaled (up or down) to the full content width.
+    FullWidth,
+    /// Scaled to fill the whole page, ignoring margins, on a page of
+    /// its own — for covers and section banners.
+    Cover,
+}
+
+/// What happens to a table whose columns, unwrapped, are wider than
+/// the body (`[table].overflow`).
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TableOverflow {
+    /// Columns narrow to fit and their cells wrap.
+    #[default]
+    Wrap,
+    /// The table's text shrinks (to half size at most) until the
+    /// columns fit; past that, cells wrap.
+    Scale,
+    /// The table gets a landscape page of its own; cells still too
+    /// wide for that wrap.
+    Rotate,
+    /// Columns keep their width; the ones past the right edge are left
+    /// out and a note under the table says how many.
+    Scroll,
+}
+
+/// Text wrap around block images (`[image].float`).
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageFloat {
+    /// The image sits on its own line (`align` positions it).
+    #[default]
+    None,
+    /// Pinned to the left edge; paragraphs wrap down its right side.
+    Left,
+    /// Pinned to the right edge; paragraphs wrap down its left side.
+    Right,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ImageAlign {
@@ -394,6 +960,67 @@ impl<'de> Deserialize<'de> for FontWeight {
     }
 }
 
+/// A `margin_before_pt` / `margin_after_pt` value. A bare number is
+/// points; a string takes a unit, `"6pt"` or `"0.5em"`, where an em
+/// is the font size of the element the margin belongs to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Spacing {
+    Pt(f32),
+    Em(f32),
+}
+
+impl Spacing {
+    /// The spacing in points for an element set at `font_size_pt`.
+    pub fn to_pt(self, font_size_pt: f32) -> f32 {
+        match self {
+            Spacing::Pt(v) => v,
+            Spacing::Em(v) => v * font_size_pt,
+        }
+    }
+}
+
+impl From<f32> for Spacing {
+    fn from(pt: f32) -> Self {
+        Spacing::Pt(pt)
+    }
+}
+
+impl<'de> Deserialize<'de> for Spacing {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        use serde::de::{Error, Visitor};
+        struct V;
+        impl<'de> Visitor<'de> for V {
+            type Value = Spacing;
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a number of points, or a string like `\"6pt\"` / `\"0.5em\"`")
+            }
+            fn visit_str<E: Error>(self, s: &str) -> Result<Spacing, E> {
+                let s = s.trim();
+                let (num, em) = if let Some(n) = s.strip_suffix("em") {
+                    (n, true)
+                } else {
+                    (s.strip_suffix("pt").unwrap_or(s), false)
+                };
+                let v: f32 = num
+                    .trim()
+                    .parse()
+                    .map_err(|_| E::custom(format!("invalid spacing `{}`", s)))?;
+                Ok(if em { Spacing::Em(v) } else { Spacing::Pt(v) })
+            }
+            fn visit_f64<E: Error>(self, v: f64) -> Result<Spacing, E> {
+                Ok(Spacing::Pt(v as f32))
+            }
+            fn visit_i64<E: Error>(self, v: i64) -> Result<Spacing, E> {
+                Ok(Spacing::Pt(v as f32))
+            }
+            fn visit_u64<E: Error>(self, v: u64) -> Result<Spacing, E> {
+                Ok(Spacing::Pt(v as f32))
+            }
+        }
+        d.deserialize_any(V)
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum BorderStyle {
@@ -518,6 +1145,43 @@ impl Color {
     pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
         Self { r, g, b }
     }
+
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+
+    /// Relative luminance as WCAG defines it: 0.0 for black, 1.0 for
+    /// white, weighted for how bright each channel looks.
+    pub fn luminance(self) -> f32 {
+        let linear = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+
+    /// WCAG contrast ratio against `other`, from 1.0 (same luminance)
+    /// to 21.0 (black on white). The order of the two doesn't matter.
+    pub fn contrast_ratio(self, other: Color) -> f32 {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// `self` if it reaches a contrast of `min_ratio` on `background`,
+    /// otherwise black or white, whichever stands out more.
+    pub fn readable_on(self, background: Color, min_ratio: f32) -> Color {
+        if self.contrast_ratio(background) >= min_ratio {
+            self
+        } else if Color::BLACK.contrast_ratio(background) >= Color::WHITE.contrast_ratio(background)
+        {
+            Color::BLACK
+        } else {
+            Color::WHITE
+        }
+    }
 }
 
 impl<'de> Deserialize<'de> for Color {
@@ -4,7 +4,8 @@
 //! without blocking PDF generation.
 
 use crate::fonts::{FontConfig, default_body_source};
-use std::path::Path;
+use crate::markdown::Lexer;
+use std::path::{Path, PathBuf};
 
 /// Represents a non-critical warning that doesn't prevent PDF generation
 #[derive(Debug, Clone)]
@@ -29,6 +30,11 @@ pub enum WarningKind {
     LargeDocument,
     /// Potentially problematic markdown syntax
     SyntaxWarning,
+    /// Font has no file for a weight or slant the renderer may need,
+    /// so that style is set in the regular face
+    FontVariantMissing,
+    /// Relative link to a local file that doesn't exist
+    BrokenLink,
 }
 
 impl ValidationWarning {
@@ -51,6 +57,14 @@ impl ValidationWarning {
         }
     }
 
+    pub fn broken_link(target: &str) -> Self {
+        Self {
+            kind: WarningKind::BrokenLink,
+            message: format!("Link target not found: {}", target),
+            suggestion: "Check the link path is correct relative to the document".to_string(),
+        }
+    }
+
     pub fn unicode_without_font(chars: Vec<char>) -> Self {
         let sample: String = chars.iter().take(5).collect();
         Self {
@@ -71,6 +85,30 @@ impl ValidationWarning {
         }
     }
 
+    pub fn large_document_words(word_count: usize) -> Self {
+        Self {
+            kind: WarningKind::LargeDocument,
+            message: format!("Large document detected ({} words)", word_count),
+            suggestion: "Processing may take a moment. Consider breaking into smaller documents if generation is slow".to_string(),
+        }
+    }
+
+    pub fn font_variant_missing(font_name: &str, variants: &[&str]) -> Self {
+        Self {
+            kind: WarningKind::FontVariantMissing,
+            message: format!(
+                "Font '{}' has no {} face; that text will use the regular face",
+                font_name,
+                variants.join(", ")
+            ),
+            suggestion: format!(
+                "Put the matching files next to '{}' (e.g. 'Name Bold.ttf', 'Name Italic.ttf') \
+                 or choose a font family that ships them",
+                font_name
+            ),
+        }
+    }
+
     pub fn syntax_warning(issue: &str) -> Self {
         Self {
             kind: WarningKind::SyntaxWarning,
@@ -88,8 +126,66 @@ impl std::fmt::Display for ValidationWarning {
     }
 }
 
+/// Thresholds for the pre-flight checks in [`validate_conversion_with`].
+///
+/// A `None` threshold disables that check. The defaults match the
+/// behavior of [`validate_conversion`]: warn above 100 000 characters,
+/// no word-count limit, no link check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationConfig {
+    /// Emit [`WarningKind::LargeDocument`] when the source is longer
+    /// than this many bytes.
+    pub max_chars: Option<usize>,
+    /// Emit [`WarningKind::LargeDocument`] when the source contains
+    /// more than this many whitespace-separated words.
+    pub max_words: Option<usize>,
+    /// Directory relative links are resolved against; set through
+    /// [`ValidationConfig::with_base_dir`]. `None` skips the check.
+    base_dir: Option<PathBuf>,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self {
+            max_chars: Some(100_000),
+            max_words: None,
+            base_dir: None,
+        }
+    }
+}
+
+impl ValidationConfig {
+    /// Create a ValidationConfig with default thresholds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the character threshold; `None` disables the check.
+    pub fn with_max_chars(mut self, max: Option<usize>) -> Self {
+        self.max_chars = max;
+        self
+    }
+
+    /// Set the word threshold; `None` disables the check.
+    pub fn with_max_words(mut self, max: Option<usize>) -> Self {
+        self.max_words = max;
+        self
+    }
+
+    /// Check relative links against the files under `dir`, usually the
+    /// one holding the markdown file. Emits [`WarningKind::BrokenLink`]
+    /// for each link there that names a missing file.
+    pub fn with_base_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.base_dir = Some(dir.into());
+        self
+    }
+}
+
 /// Validates markdown content and configuration, returning warnings.
 ///
+/// Uses [`ValidationConfig::default`] thresholds; see
+/// [`validate_conversion_with`] to override them.
+///
 /// `style_fallback_fonts` is the resolved `[defaults].fallback_fonts`
 /// list from the styling config (empty when no TOML config or no
 /// fallbacks set). When non-empty, the Unicode-without-font warning is
@@ -99,11 +195,50 @@ pub fn validate_conversion(
     font_config: Option<&FontConfig>,
     style_fallback_fonts: &[String],
     output_path: Option<&str>,
+) -> Vec<ValidationWarning> {
+    validate_conversion_with(
+        markdown,
+        font_config,
+        style_fallback_fonts,
+        output_path,
+        &ValidationConfig::default(),
+    )
+}
+
+/// Runs the pre-flight checks alone, for linting Markdown without
+/// rendering it.
+///
+/// A thin wrapper over [`validate_conversion`] with no styling
+/// fallback fonts; callers that have resolved a style should pass its
+/// `fallback_fonts` to [`validate_conversion`] instead. An empty
+/// result means the document is clean.
+pub fn validate_only(
+    markdown: &str,
+    font_config: Option<&FontConfig>,
+    output_path: Option<&str>,
+) -> Vec<ValidationWarning> {
+    validate_conversion(markdown, font_config, &[], output_path)
+}
+
+/// Like [`validate_conversion`], with explicit thresholds. At most one
+/// [`WarningKind::LargeDocument`] is emitted; the character limit is
+/// reported when both trip.
+pub fn validate_conversion_with(
+    markdown: &str,
+    font_config: Option<&FontConfig>,
+    style_fallback_fonts: &[String],
+    output_path: Option<&str>,
+    config: &ValidationConfig,
 ) -> Vec<ValidationWarning> {
     let mut warnings = Vec::new();
 
-    if markdown.len() > 100_000 {
+    if config.max_chars.is_some_and(|max| markdown.len() > max) {
         warnings.push(ValidationWarning::large_document(markdown.len()));
+    } else if let Some(max) = config.max_words {
+        let words = markdown.split_whitespace().count();
+        if words > max {
+            warnings.push(ValidationWarning::large_document_words(words));
+        }
     }
 
     if let Some(unicode_chars) = detect_unicode_chars(markdown)
@@ -112,6 +247,12 @@ pub fn validate_conversion(
         warnings.push(ValidationWarning::unicode_without_font(unicode_chars));
     }
 
+    if let Some(config) = font_config {
+        for (name, variants) in crate::render::missing_variants(config) {
+            warnings.push(ValidationWarning::font_variant_missing(&name, &variants));
+        }
+    }
+
     if let Some(path) = output_path
         && let Some(parent) = Path::new(path).parent()
         && !parent.as_os_str().is_empty()
@@ -125,7 +266,12 @@ pub fn validate_conversion(
     }
 
     warnings.extend(check_syntax_issues(markdown));
+    warnings.extend(check_whitespace_issues(markdown));
     warnings.extend(check_image_references(markdown));
+    warnings.extend(check_internal_links(markdown));
+    if let Some(base_dir) = &config.base_dir {
+        warnings.extend(check_relative_links(markdown, base_dir));
+    }
 
     warnings
 }
@@ -214,6 +360,180 @@ fn check_syntax_issues(markdown: &str) -> Vec<ValidationWarning> {
     warnings
 }
 
+/// Flags source whitespace that renders differently from how it
+/// reads: a trailing run of three or more spaces (or one holding a
+/// tab) before a non-blank line, which CommonMark turns into a hard
+/// line break, and tabs in a list item's indentation or after its
+/// marker, which nest the item by tab stops rather than by what the
+/// editor showed. Fenced code is skipped — whitespace there is
+/// content. One warning per issue, listing the first few lines.
+fn check_whitespace_issues(markdown: &str) -> Vec<ValidationWarning> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut hard_breaks = Vec::new();
+    let mut list_tabs = Vec::new();
+    for (i, line, in_code) in source_lines(&lines) {
+        if in_code {
+            continue;
+        }
+        let next_blank = lines.get(i + 1).is_none_or(|l| l.trim().is_empty());
+        if !line.trim().is_empty() && !next_blank && ambiguous_trailing_ws(line) {
+            hard_breaks.push(i + 1);
+        }
+        if list_marker_end(line).is_some_and(|end| line[..=end].contains('\t')) {
+            list_tabs.push(i + 1);
+        }
+    }
+
+    let mut warnings = Vec::new();
+    if !hard_breaks.is_empty() {
+        warnings.push(ValidationWarning {
+            kind: WarningKind::SyntaxWarning,
+            message: format!(
+                "Trailing whitespace creates a hard line break on {}",
+                describe_lines(&hard_breaks)
+            ),
+            suggestion: "End the line with exactly two spaces or a backslash for an intended \
+                         break, or remove the whitespace (--sanitize does this)"
+                .to_string(),
+        });
+    }
+    if !list_tabs.is_empty() {
+        warnings.push(ValidationWarning {
+            kind: WarningKind::SyntaxWarning,
+            message: format!(
+                "Tab in list item indentation on {}",
+                describe_lines(&list_tabs)
+            ),
+            suggestion: "Indent list items with spaces so nesting matches what the editor shows \
+                         (--sanitize expands the tabs)"
+                .to_string(),
+        });
+    }
+    warnings
+}
+
+/// Normalizes the whitespace [`check_whitespace_issues`] warns about:
+/// trailing runs other than exactly two spaces are removed, and tabs
+/// in a list item's indentation expand to the next four-column tab
+/// stop (a tab right after the marker becomes one space). Fenced code
+/// and every other line are left byte-for-byte intact, including
+/// `\r\n` endings.
+pub fn sanitize_markdown(markdown: &str) -> String {
+    let lines: Vec<&str> = markdown.split_inclusive('\n').collect();
+    let bodies: Vec<&str> = lines
+        .iter()
+        .map(|l| l.trim_end_matches('\n').trim_end_matches('\r'))
+        .collect();
+    let mut out = String::with_capacity(markdown.len());
+    for (i, body, in_code) in source_lines(&bodies) {
+        let ending = &lines[i][body.len()..];
+        if in_code {
+            out.push_str(lines[i]);
+            continue;
+        }
+        let mut text = body.to_string();
+        if ambiguous_trailing_ws(&text) {
+            text.truncate(text.trim_end_matches([' ', '\t']).len());
+        }
+        if let Some(end) = list_marker_end(&text)
+            && text[..=end].contains('\t')
+        {
+            text = expand_list_tabs(&text, end);
+        }
+        out.push_str(&text);
+        out.push_str(ending);
+    }
+    out
+}
+
+/// `(index, line, inside fenced code)` for each line. A fence line
+/// itself counts as code so its info string is never touched.
+fn source_lines<'a>(lines: &[&'a str]) -> Vec<(usize, &'a str, bool)> {
+    let mut fence: Option<char> = None;
+    let mut out = Vec::with_capacity(lines.len());
+    for (i, &line) in lines.iter().enumerate() {
+        let t = line.trim_start();
+        let opener = ['`', '~']
+            .into_iter()
+            .find(|&c| t.starts_with(&c.to_string().repeat(3)));
+        match (fence, opener) {
+            (None, Some(c)) => {
+                fence = Some(c);
+                out.push((i, line, true));
+            }
+            (Some(f), Some(c)) if f == c => {
+                fence = None;
+                out.push((i, line, true));
+            }
+            (in_fence, _) => out.push((i, line, in_fence.is_some())),
+        }
+    }
+    out
+}
+
+/// A trailing whitespace run that is not the conventional two-space
+/// hard break: three or more spaces, or any tab.
+fn ambiguous_trailing_ws(line: &str) -> bool {
+    let run = &line[line.trim_end_matches([' ', '\t']).len()..];
+    run.contains('\t') || run.len() >= 3
+}
+
+/// Byte index of the whitespace char that ends a list marker (`-`,
+/// `*`, `+`, or `1.` / `1)`) after optional indentation, or `None`
+/// when the line doesn't open a list item.
+fn list_marker_end(line: &str) -> Option<usize> {
+    let rest = line.trim_start_matches([' ', '\t']);
+    let start = line.len() - rest.len();
+    let digits = rest.chars().take_while(char::is_ascii_digit).count();
+    let marker_len = match rest.as_bytes().first()? {
+        b'-' | b'*' | b'+' => 1,
+        _ if (1..=9).contains(&digits)
+            && matches!(rest.as_bytes().get(digits), Some(b'.' | b')')) =>
+        {
+            digits + 1
+        }
+        _ => return None,
+    };
+    match rest.as_bytes().get(marker_len) {
+        Some(b' ' | b'\t') => Some(start + marker_len),
+        _ => None,
+    }
+}
+
+/// `line` with the tabs up to and including `marker_end` replaced:
+/// indentation tabs expand to four-column stops, the one after the
+/// marker becomes a single space.
+fn expand_list_tabs(line: &str, marker_end: usize) -> String {
+    let mut out = String::with_capacity(line.len() + 8);
+    for (i, c) in line[..=marker_end].char_indices() {
+        match c {
+            '\t' if i == marker_end => out.push(' '),
+            '\t' => {
+                let pad = 4 - out.chars().count() % 4;
+                out.extend(std::iter::repeat_n(' ', pad));
+            }
+            _ => out.push(c),
+        }
+    }
+    out.push_str(&line[marker_end + 1..]);
+    out
+}
+
+/// "line 3" / "lines 3, 7, 9 and 2 more" for a warning message.
+fn describe_lines(lines: &[usize]) -> String {
+    const SHOWN: usize = 5;
+    let listed: Vec<String> = lines.iter().take(SHOWN).map(usize::to_string).collect();
+    let mut text = format!(
+        "{} {}",
+        if lines.len() == 1 { "line" } else { "lines" },
+        listed.join(", ")
+    );
+    if lines.len() > SHOWN {
+        text.push_str(&format!(" and {} more", lines.len() - SHOWN));
+    }
+    text
+}
+
 /// Blanks out the brackets that belong to footnote constructs so the
 /// crude `[` vs `]` tally in [`check_syntax_issues`] only sees real
 /// link brackets. Mirrors the lexer's own acceptance rules:
@@ -277,6 +597,85 @@ fn neutralize_footnote_brackets(md: &str) -> String {
     out.into_iter().collect()
 }
 
+/// Warns about `[text](#anchor)` links whose anchor matches no heading.
+/// They still render, as plain styled text without a jump target.
+fn check_internal_links(markdown: &str) -> Vec<ValidationWarning> {
+    let Ok(tokens) = Lexer::new(markdown.to_string()).parse() else {
+        return Vec::new();
+    };
+    crate::render::unresolved_internal_links(&tokens)
+        .into_iter()
+        .map(|url| ValidationWarning {
+            kind: WarningKind::SyntaxWarning,
+            message: format!("Link target '{}' does not match any heading", url),
+            suggestion: "Check the heading text, or give the heading an explicit {#id}".to_string(),
+        })
+        .collect()
+}
+
+/// Warns about links like `[see](./other.md)` whose file doesn't exist
+/// under `base_dir`. URLs, `mailto:` and other schemes, bare anchors
+/// and absolute paths are left alone; a `#section` or `?query` after
+/// the path is ignored, and `%XX` escapes are decoded first.
+fn check_relative_links(markdown: &str, base_dir: &Path) -> Vec<ValidationWarning> {
+    let Ok(tokens) = Lexer::new(markdown.to_string()).parse() else {
+        return Vec::new();
+    };
+    crate::render::link_targets(&tokens)
+        .into_iter()
+        .filter(|url| {
+            let path = url.split(['#', '?']).next().unwrap_or_default();
+            !path.is_empty()
+                && !has_url_scheme(url)
+                && !url.starts_with("www.")
+                && !Path::new(path).is_absolute()
+                && !path.starts_with('/')
+                && !base_dir.join(percent_decode(path)).exists()
+        })
+        .map(|url| ValidationWarning::broken_link(&url))
+        .collect()
+}
+
+/// Decode `%XX` escapes (`my%20notes.md` → `my notes.md`). A `%` not
+/// followed by two hex digits is kept as is.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(b) => {
+                out.push(b);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// `true` for `https://…`, `mailto:…` and the like: a letter, then
+/// letters, digits, `+`, `-` or `.` up to a `:`. A single letter is a
+/// Windows drive, not a scheme.
+fn has_url_scheme(url: &str) -> bool {
+    let Some((scheme, _)) = url.split_once(':') else {
+        return false;
+    };
+    scheme.len() > 1
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
 /// Checks for image references and validates paths exist
 fn check_image_references(markdown: &str) -> Vec<ValidationWarning> {
     let mut warnings = Vec::new();
@@ -391,6 +790,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn default_thresholds_ignore_short_documents() {
+        let warnings = validate_conversion("just a few words", None, &[], None);
+        assert!(
+            warnings
+                .iter()
+                .all(|w| w.kind != WarningKind::LargeDocument)
+        );
+    }
+
+    #[test]
+    fn custom_char_threshold_triggers_on_short_document() {
+        let config = ValidationConfig::new().with_max_chars(Some(10));
+        let warnings = validate_conversion_with("a short paragraph", None, &[], None, &config);
+        let large: Vec<_> = warnings
+            .iter()
+            .filter(|w| w.kind == WarningKind::LargeDocument)
+            .collect();
+        assert_eq!(large.len(), 1);
+        assert!(large[0].message.contains("17 characters"));
+    }
+
+    #[test]
+    fn custom_word_threshold_triggers_on_short_document() {
+        let config = ValidationConfig::new()
+            .with_max_chars(None)
+            .with_max_words(Some(3));
+        let warnings = validate_conversion_with("one two three four", None, &[], None, &config);
+        let large: Vec<_> = warnings
+            .iter()
+            .filter(|w| w.kind == WarningKind::LargeDocument)
+            .collect();
+        assert_eq!(large.len(), 1);
+        assert!(large[0].message.contains("4 words"));
+
+        let config = config.with_max_words(Some(4));
+        let warnings = validate_conversion_with("one two three four", None, &[], None, &config);
+        assert!(
+            warnings
+                .iter()
+                .all(|w| w.kind != WarningKind::LargeDocument)
+        );
+    }
+
+    #[test]
+    fn both_thresholds_tripping_emit_a_single_warning() {
+        let config = ValidationConfig::new()
+            .with_max_chars(Some(5))
+            .with_max_words(Some(1));
+        let warnings = validate_conversion_with("one two three", None, &[], None, &config);
+        assert_eq!(
+            warnings
+                .iter()
+                .filter(|w| w.kind == WarningKind::LargeDocument)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn single_file_font_warns_about_missing_variants() {
+        let dir = std::env::temp_dir().join(format!("m2p_variants_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let regular = dir.join("Solo.ttf");
+        std::fs::write(&regular, b"").unwrap();
+        let cfg = FontConfig::new().with_default_font(regular.to_string_lossy());
+
+        let warnings = validate_conversion("Some **bold** text", Some(&cfg), &[], None);
+        let missing: Vec<_> = warnings
+            .iter()
+            .filter(|w| w.kind == WarningKind::FontVariantMissing)
+            .collect();
+        assert_eq!(missing.len(), 1, "got {:?}", warnings);
+        assert!(missing[0].message.contains("bold, italic, bold-italic"));
+
+        // Once the siblings exist, the loader finds them and the
+        // warning goes away.
+        for name in ["Solo Bold.ttf", "Solo Italic.ttf", "Solo Bold Italic.ttf"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        let warnings = validate_conversion("Some **bold** text", Some(&cfg), &[], None);
+        assert!(
+            warnings
+                .iter()
+                .all(|w| w.kind != WarningKind::FontVariantMissing)
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn external_font_suppresses_unicode_warning() {
         // Any named external default font qualifies — the renderer's
@@ -400,8 +888,11 @@ mod tests {
             default_font_source: None,
             code_font: None,
             code_font_source: None,
+            heading_font: None,
+            blockquote_font: None,
             fallback_fonts: Vec::new(),
             fallback_font_sources: Vec::new(),
+            auto_emoji: false,
             enable_subsetting: true,
         };
         let warnings = validate_conversion("Hello café", Some(&cfg), &[], None);
@@ -477,4 +968,101 @@ mod tests {
             "FontConfig.fallback_fonts should suppress the Unicode warning"
         );
     }
+
+    #[test]
+    fn three_trailing_spaces_warn_as_hard_break() {
+        let warnings = check_whitespace_issues("first line   \nsecond line\n");
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert_eq!(warnings[0].kind, WarningKind::SyntaxWarning);
+        assert!(
+            warnings[0].message.contains("line 1"),
+            "{}",
+            warnings[0].message
+        );
+
+        // Two spaces is the deliberate hard break; trailing space
+        // before a blank line or inside a fence breaks nothing.
+        assert!(check_whitespace_issues("first  \nsecond\n").is_empty());
+        assert!(check_whitespace_issues("para   \n\nnext\n").is_empty());
+        assert!(check_whitespace_issues("```\ncode   \nmore\n```\n").is_empty());
+    }
+
+    #[test]
+    fn tab_indented_list_item_warns() {
+        let warnings = check_whitespace_issues("- top\n\t- nested\n");
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(
+            warnings[0].message.contains("line 2"),
+            "{}",
+            warnings[0].message
+        );
+        assert_eq!(check_whitespace_issues("1.\tstep\n").len(), 1);
+        // A tab in body text is not a list issue.
+        assert!(check_whitespace_issues("a\tb\n- x\n").is_empty());
+    }
+
+    #[test]
+    fn sanitize_normalizes_only_flagged_whitespace() {
+        let src = "keep  \r\nstrip   \nnext\n\t- item\n-\tx\n```\n\t- code   \n```\n";
+        assert_eq!(
+            sanitize_markdown(src),
+            "keep  \r\nstrip\nnext\n    - item\n- x\n```\n\t- code   \n```\n"
+        );
+        assert!(check_whitespace_issues(&sanitize_markdown(src)).is_empty());
+    }
+
+    #[test]
+    fn unresolved_internal_link_warns() {
+        let text = "# Intro\n\nSee [intro](#intro) and [gone](#missing).\n";
+        let warnings = check_internal_links(text);
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert_eq!(warnings[0].kind, WarningKind::SyntaxWarning);
+        assert!(warnings[0].message.contains("#missing"));
+    }
+
+    #[test]
+    fn explicit_ids_and_footnotes_count_as_resolved() {
+        let text = "## Setup {#install}\n\n[go](#install) and a note[^1].\n\n[^1]: body\n";
+        assert!(check_internal_links(text).is_empty());
+    }
+
+    #[test]
+    fn validate_only_reports_missing_images() {
+        let warnings = validate_only("![logo](no/such/dir/logo.png)", None, None);
+        assert!(
+            warnings
+                .iter()
+                .any(|w| w.kind == WarningKind::MissingImage && w.message.contains("logo.png")),
+            "got {:?}",
+            warnings
+        );
+        assert!(validate_only("plain words", None, None).is_empty());
+    }
+
+    #[test]
+    fn relative_link_to_missing_file_is_a_broken_link() {
+        let dir = std::env::temp_dir().join(format!("m2p_links_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("other.md"), "# Other\n").unwrap();
+        std::fs::write(dir.join("my notes.md"), "# Notes\n").unwrap();
+        let text = "See [other](./other.md#setup), [gone](missing.md), \
+                    [notes](my%20notes.md), \
+                    [site](https://example.com/x.md), [mail](mailto:a@b.c) \
+                    and [top](#top).\n\n# Top\n";
+        let config = ValidationConfig::default().with_base_dir(&dir);
+        let broken: Vec<_> = validate_conversion_with(text, None, &[], None, &config)
+            .into_iter()
+            .filter(|w| w.kind == WarningKind::BrokenLink)
+            .collect();
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(broken.len(), 1, "{broken:?}");
+        assert!(broken[0].message.contains("missing.md"));
+
+        // Without a base directory the check doesn't run.
+        assert!(
+            validate_only("[gone](missing.md)", None, None)
+                .iter()
+                .all(|w| w.kind != WarningKind::BrokenLink)
+        );
+    }
 }
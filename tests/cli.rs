@@ -0,0 +1,403 @@
+//! End-to-end tests that run the `markdown2pdf` binary.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+fn bin() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_markdown2pdf"))
+}
+
+fn temp_markdown(name: &str, body: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("m2p_cli_{}_{}.md", name, std::process::id()));
+    std::fs::write(&path, body).unwrap();
+    path
+}
+
+#[test]
+fn check_with_missing_image_exits_non_zero_and_prints_the_warning() {
+    let md = temp_markdown("check_missing", "# Doc\n\n![logo](no/such/dir/logo.png)\n");
+    let out = bin().arg("-p").arg(&md).arg("--check").output().unwrap();
+    std::fs::remove_file(&md).ok();
+
+    assert!(!out.status.success(), "--check must fail on warnings");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("Image not found: no/such/dir/logo.png"),
+        "stderr: {stderr}"
+    );
+}
+
+#[test]
+fn check_on_clean_markdown_succeeds_without_writing_a_pdf() {
+    let md = temp_markdown("check_clean", "# Doc\n\nPlain words.\n");
+    let pdf = std::env::temp_dir().join(format!("m2p_cli_check_clean_{}.pdf", std::process::id()));
+    let out = bin()
+        .arg("-p")
+        .arg(&md)
+        .arg("-o")
+        .arg(&pdf)
+        .arg("--check")
+        .output()
+        .unwrap();
+    std::fs::remove_file(&md).ok();
+
+    assert!(
+        out.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    assert!(!pdf.exists(), "--check must not write a PDF");
+}
+
+#[test]
+fn windows_1252_input_file_is_decoded_with_a_warning() {
+    let md = std::env::temp_dir().join(format!("m2p_cli_cp1252_{}.md", std::process::id()));
+    std::fs::write(&md, b"Caf\xe9 \x96 \x93quoted\x94\n").unwrap();
+    let pdf = md.with_extension("pdf");
+    let out = bin()
+        .arg("-p")
+        .arg(&md)
+        .arg("-o")
+        .arg(&pdf)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&md).ok();
+    let written = pdf.exists();
+    std::fs::remove_file(&pdf).ok();
+
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(out.status.success(), "stderr: {stderr}");
+    assert!(written, "no PDF written");
+    assert!(
+        stderr.contains("decoded it as windows-1252"),
+        "stderr: {stderr}"
+    );
+}
+
+#[test]
+fn sanitize_clears_trailing_whitespace_warnings() {
+    let md = temp_markdown(
+        "sanitize",
+        "first line   \nsecond line\n\n- top\n\t- nested\n",
+    );
+    let out = bin().arg("-p").arg(&md).arg("--check").output().unwrap();
+    assert!(!out.status.success(), "unsanitized source must warn");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("hard line break on line 1"),
+        "stderr: {stderr}"
+    );
+    assert!(stderr.contains("Tab in list item"), "stderr: {stderr}");
+
+    let out = bin()
+        .arg("-p")
+        .arg(&md)
+        .args(["--check", "--sanitize"])
+        .output()
+        .unwrap();
+    std::fs::remove_file(&md).ok();
+    assert!(
+        out.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
+
+#[test]
+fn json_format_reports_success_as_one_object() {
+    let pdf = std::env::temp_dir().join(format!("m2p_cli_json_ok_{}.pdf", std::process::id()));
+    let out = bin()
+        .args(["-s", "# Doc\n\nPlain words.\n", "--format", "json", "-o"])
+        .arg(&pdf)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&pdf).ok();
+
+    assert!(
+        out.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let v: serde_json::Value = serde_json::from_slice(&out.stdout).expect("stdout is JSON");
+    assert_eq!(v["status"], "ok");
+    assert_eq!(v["output"], pdf.to_str().unwrap());
+    assert_eq!(v["pages"], 1);
+    assert!(v["warnings"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn json_format_reports_errors_as_one_object() {
+    let cfg = std::env::temp_dir().join(format!("m2p_cli_json_bad_{}.toml", std::process::id()));
+    std::fs::write(&cfg, "[paragraph]\nfont_sise_pt = 10.0\n").unwrap();
+    let out = bin()
+        .args(["-s", "# Doc\n", "--format", "json", "-c"])
+        .arg(&cfg)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&cfg).ok();
+
+    assert!(!out.status.success());
+    let v: serde_json::Value = serde_json::from_slice(&out.stdout).expect("stdout is JSON");
+    assert_eq!(v["status"], "error");
+    assert_eq!(v["error"], "conversion");
+    assert!(
+        v["message"].as_str().unwrap().contains("font_sise_pt"),
+        "message: {}",
+        v["message"]
+    );
+}
+
+#[test]
+fn json_format_check_lists_the_warnings() {
+    let md = temp_markdown("json_check", "![logo](no/such/dir/logo.png)\n");
+    let out = bin()
+        .arg("-p")
+        .arg(&md)
+        .args(["--check", "--format", "json"])
+        .output()
+        .unwrap();
+    std::fs::remove_file(&md).ok();
+
+    assert!(!out.status.success());
+    let v: serde_json::Value = serde_json::from_slice(&out.stdout).expect("stdout is JSON");
+    assert_eq!(v["error"], "check");
+    assert_eq!(v["warnings"][0]["kind"], "missing_image");
+}
+
+#[test]
+fn init_config_writes_the_starter_file_and_refuses_to_overwrite() {
+    let path = std::env::temp_dir().join(format!("m2p_cli_init_{}.toml", std::process::id()));
+    std::fs::remove_file(&path).ok();
+
+    let out = bin().arg("--init-config").arg(&path).output().unwrap();
+    assert!(
+        out.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(written, markdown2pdf::config::default_config_toml());
+
+    std::fs::write(&path, "# mine\n").unwrap();
+    let out = bin().arg("--init-config").arg(&path).output().unwrap();
+    assert!(
+        !out.status.success(),
+        "an existing file must not be overwritten"
+    );
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--force"));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "# mine\n");
+
+    let out = bin()
+        .arg("--init-config")
+        .arg(&path)
+        .arg("--force")
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), written);
+    std::fs::remove_file(&path).ok();
+}
+
+/// A fresh empty directory to run the binary in.
+fn temp_workdir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("m2p_cli_{}_{}", name, std::process::id()));
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn path_input_without_output_is_named_after_the_input_file() {
+    let md = temp_markdown("named_output", "# Doc\n\nBody.\n");
+    let dir = temp_workdir("named_output_cwd");
+    let out = bin().current_dir(&dir).arg("-p").arg(&md).output().unwrap();
+    std::fs::remove_file(&md).ok();
+
+    assert!(
+        out.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let expected = dir.join(format!("m2p_cli_named_output_{}.pdf", std::process::id()));
+    assert!(
+        std::fs::read(&expected).unwrap().starts_with(b"%PDF"),
+        "the PDF is written to the working directory under the input's name"
+    );
+    assert!(!dir.join("output.pdf").exists());
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn explicit_output_overrides_the_name_derived_from_the_input() {
+    let md = temp_markdown("explicit_output", "# Doc\n");
+    let dir = temp_workdir("explicit_output_cwd");
+    let out = bin()
+        .current_dir(&dir)
+        .arg("-p")
+        .arg(&md)
+        .args(["-o", "chosen.pdf"])
+        .output()
+        .unwrap();
+    std::fs::remove_file(&md).ok();
+
+    assert!(out.status.success());
+    assert!(dir.join("chosen.pdf").exists());
+    let derived = dir.join(format!(
+        "m2p_cli_explicit_output_{}.pdf",
+        std::process::id()
+    ));
+    assert!(!derived.exists());
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn string_input_without_output_still_writes_output_pdf() {
+    let dir = temp_workdir("string_output_cwd");
+    let out = bin()
+        .current_dir(&dir)
+        .args(["-s", "# Doc\n"])
+        .output()
+        .unwrap();
+
+    assert!(out.status.success());
+    assert!(dir.join("output.pdf").exists());
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+/// Run `cmd` with `stdin` piped in.
+fn output_with_stdin(cmd: &mut Command, stdin: &str) -> std::process::Output {
+    use std::io::Write;
+    let mut child = cmd
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    // A run that never reads stdin may exit before the write lands.
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+const MISSING_IMAGE: &str = "![logo](no/such/dir/logo.png)\n";
+
+#[test]
+fn path_dash_reads_markdown_from_stdin() {
+    let out = output_with_stdin(bin().args(["-p", "-", "--check"]), MISSING_IMAGE);
+    assert!(!out.status.success(), "the piped image must be checked");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("Image not found: no/such/dir/logo.png"),
+        "stderr: {stderr}"
+    );
+}
+
+#[test]
+fn piped_stdin_is_read_without_an_input_flag() {
+    let out = output_with_stdin(bin().args(["--check"]), MISSING_IMAGE);
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("Image not found"), "stderr: {stderr}");
+}
+
+#[test]
+fn explicit_input_flag_wins_over_piped_stdin() {
+    let out = output_with_stdin(bin().args(["-s", "# Clean\n", "--check"]), MISSING_IMAGE);
+    assert!(
+        out.status.success(),
+        "stdin must be ignored when --string is given: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
+
+#[test]
+fn stdin_flag_conflicts_with_other_inputs() {
+    let out = output_with_stdin(bin().args(["--stdin", "-s", "# Doc\n"]), "# Doc\n");
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("--stdin") && stderr.contains("--string"),
+        "stderr: {stderr}"
+    );
+}
+
+#[test]
+fn stdin_input_without_output_writes_output_pdf() {
+    let dir = temp_workdir("stdin_output_cwd");
+    let out = output_with_stdin(bin().current_dir(&dir).args(["-p", "-"]), "# Doc\n");
+
+    assert!(out.status.success());
+    assert!(dir.join("output.pdf").exists());
+    assert!(!dir.join("-.pdf").exists());
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+/// Serve one connection on a local port with `respond`, in the
+/// background. Returns the URL to fetch.
+#[cfg(feature = "fetch")]
+fn mock_server(respond: impl FnOnce(std::net::TcpStream) + Send + 'static) -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        if let Ok((stream, _)) = listener.accept() {
+            respond(stream);
+        }
+    });
+    format!("http://{addr}/doc.md")
+}
+
+#[cfg(feature = "fetch")]
+#[test]
+fn url_fetch_that_never_answers_times_out_with_a_network_error() {
+    let url = mock_server(|stream| {
+        // Hold the connection open without ever replying.
+        std::thread::sleep(std::time::Duration::from_secs(20));
+        drop(stream);
+    });
+    let pdf = std::env::temp_dir().join(format!("m2p_cli_timeout_{}.pdf", std::process::id()));
+    let started = std::time::Instant::now();
+    let out = bin()
+        .arg("--url")
+        .arg(&url)
+        .arg("--url-timeout")
+        .arg("1")
+        .arg("-o")
+        .arg(&pdf)
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success(), "a stalled fetch must fail");
+    assert!(
+        started.elapsed() < std::time::Duration::from_secs(15),
+        "fetch was not cut off by --url-timeout"
+    );
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("Network error"), "stderr: {stderr}");
+    assert!(!pdf.exists());
+}
+
+#[cfg(feature = "fetch")]
+#[test]
+fn url_fetch_of_binary_content_is_refused() {
+    use std::io::{Read, Write};
+    let url = mock_server(|mut stream| {
+        let mut request = [0u8; 1024];
+        let _ = stream.read(&mut request);
+        let body = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+        let _ = stream.write_all(body);
+    });
+    let out = bin()
+        .arg("--url")
+        .arg(&url)
+        .arg("--check")
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("binary"), "stderr: {stderr}");
+}
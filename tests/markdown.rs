@@ -35,9 +35,15 @@ mod code_span_space_strip_tests;
 #[path = "markdown/collect_all_text_tests.rs"]
 mod collect_all_text_tests;
 
+#[path = "markdown/comment_definition_tests.rs"]
+mod comment_definition_tests;
+
 #[path = "markdown/emphasis_flanking_tests.rs"]
 mod emphasis_flanking_tests;
 
+#[path = "markdown/emphasis_nesting_tests.rs"]
+mod emphasis_nesting_tests;
+
 #[path = "markdown/entity_reference_tests.rs"]
 mod entity_reference_tests;
 
@@ -98,6 +104,9 @@ mod hard_line_break_tests;
 #[path = "markdown/heading_hash_in_paragraph_tests.rs"]
 mod heading_hash_in_paragraph_tests;
 
+#[path = "markdown/heading_id_tests.rs"]
+mod heading_id_tests;
+
 #[path = "markdown/heading_strictness_tests.rs"]
 mod heading_strictness_tests;
 
@@ -158,6 +167,9 @@ mod parse_html_comment_tests;
 #[path = "markdown/parse_image_tests.rs"]
 mod parse_image_tests;
 
+#[path = "markdown/plain_text_tests.rs"]
+mod plain_text_tests;
+
 #[path = "markdown/propagate_loose_tight_tests.rs"]
 mod propagate_loose_tight_tests;
 
@@ -176,6 +188,9 @@ mod resolve_emphasis_unit_tests;
 #[path = "markdown/setext_and_thematic_tests.rs"]
 mod setext_and_thematic_tests;
 
+#[path = "markdown/source_span_tests.rs"]
+mod source_span_tests;
+
 #[path = "markdown/spec_atx_heading_corners.rs"]
 mod spec_atx_heading_corners;
 
@@ -215,6 +230,9 @@ mod table_tests;
 #[path = "markdown/tests.rs"]
 mod tests;
 
+#[path = "markdown/token_iterator_tests.rs"]
+mod token_iterator_tests;
+
 #[path = "markdown/try_decode_entity_tests.rs"]
 mod try_decode_entity_tests;
 
@@ -577,7 +577,7 @@ fn mkdocs_body_supports_setext_heading() {
     let tokens = parse(src);
     let (_, _, _, body) = first_admonition(&tokens).expect("Admonition");
     assert!(
-        body.iter().any(|t| matches!(t, Token::Heading(_, 2))),
+        body.iter().any(|t| matches!(t, Token::Heading(_, 2, _))),
         "setext H2 inside body lost: {body:?}"
     );
 }
@@ -1,6 +1,7 @@
 //! Extended tests for `try_parse_autolink` and `looks_like_autolink_start`.
 //! Existing `link_url_paren_and_autolink_tests` covers happy-path autolinks;
-//! this module exercises scheme/charset edge cases and rejection paths.
+//! this module exercises scheme/charset edge cases and rejection paths,
+//! plus the GFM `www.` extension.
 
 use markdown2pdf::markdown::*;
 
@@ -136,3 +137,120 @@ fn adjacent_autolinks() {
         .count();
     assert_eq!(count, 2);
 }
+
+#[test]
+fn url_and_email_autolinks_mid_sentence() {
+    let tokens = parse("Docs at <https://a.com> or <me@a.com>.");
+    let urls: Vec<_> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Link { url, .. } => Some(url.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(urls, ["https://a.com", "mailto:me@a.com"]);
+}
+
+#[test]
+fn html_tags_are_not_autolinks() {
+    let block = parse("<div>\nbody\n</div>\n");
+    assert!(first_autolink_url(&block).is_none());
+    assert!(
+        matches!(block.first(), Some(Token::HtmlBlock(_))),
+        "got {:?}",
+        block
+    );
+
+    let inline = parse("text <span>more</span>");
+    assert!(first_autolink_url(&inline).is_none());
+    assert!(
+        inline
+            .iter()
+            .any(|t| matches!(t, Token::HtmlInline(s) if s == "<span>")),
+        "got {:?}",
+        inline
+    );
+}
+
+#[test]
+fn www_autolink_gets_http_and_drops_trailing_period() {
+    let tokens = parse("visit www.example.com.");
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Text("visit ".to_string()),
+            Token::Link {
+                content: vec![Token::Text("www.example.com".to_string())],
+                url: "http://www.example.com".to_string(),
+                title: None,
+            },
+            Token::Text(".".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn www_autolink_keeps_path_and_balanced_parens() {
+    assert_eq!(
+        first_autolink_url(&parse("see www.example.com/a-(b)), then")).as_deref(),
+        Some("http://www.example.com/a-(b)")
+    );
+    assert_eq!(
+        first_autolink_url(&parse("(www.example.com/x?)")).as_deref(),
+        Some("http://www.example.com/x")
+    );
+}
+
+#[test]
+fn www_autolink_inside_emphasis() {
+    let tokens = parse("*at www.example.com*");
+    let Some(Token::Emphasis { content, .. }) = tokens.first() else {
+        panic!("expected emphasis, got {:?}", tokens);
+    };
+    assert_eq!(
+        first_autolink_url(content).as_deref(),
+        Some("http://www.example.com")
+    );
+}
+
+#[test]
+fn www_needs_a_word_boundary_and_a_domain() {
+    assert!(first_autolink_url(&parse("awww.example.com")).is_none());
+    assert!(first_autolink_url(&parse("just www. here")).is_none());
+    assert!(first_autolink_url(&parse("www.ex_ample.com")).is_none());
+    assert!(first_autolink_url(&parse("`www.example.com`")).is_none());
+}
+
+#[test]
+fn www_inside_link_text_is_not_relinked() {
+    let tokens = parse("[www.example.com](https://other.test)");
+    let Some(Token::Link { content, url, .. }) = tokens.first() else {
+        panic!("expected link, got {:?}", tokens);
+    };
+    assert_eq!(url, "https://other.test");
+    assert_eq!(content, &vec![Token::Text("www.example.com".to_string())]);
+}
+
+#[test]
+fn www_autolink_inside_a_list_item() {
+    let tokens = parse("- see www.example.com");
+    let Some(Token::ListItem { content, .. }) = tokens.first() else {
+        panic!("expected list item, got {:?}", tokens);
+    };
+    assert_eq!(
+        first_autolink_url(content).as_deref(),
+        Some("http://www.example.com")
+    );
+}
+
+#[test]
+fn www_autolink_inside_a_table_cell() {
+    let tokens = parse("| Site |\n|------|\n| www.example.com |\n");
+    let Some(Token::Table { rows, .. }) = tokens.first() else {
+        panic!("expected table, got {:?}", tokens);
+    };
+    assert_eq!(
+        first_autolink_url(&rows[0][0].content).as_deref(),
+        Some("http://www.example.com")
+    );
+}
@@ -134,8 +134,8 @@ fn escape_underscore_inside_emphasis() {
 #[test]
 fn escape_inside_heading() {
     let tokens = parse(r"# Header with \*literal asterisks\*");
-    assert!(matches!(tokens[0], Token::Heading(_, 1)));
-    if let Token::Heading(content, _) = &tokens[0] {
+    assert!(matches!(tokens[0], Token::Heading(_, 1, _)));
+    if let Token::Heading(content, _, _) = &tokens[0] {
         let inner = Token::collect_all_text(content);
         assert!(inner.contains("*literal asterisks*"), "got {:?}", inner);
     }
@@ -150,7 +150,8 @@ fn escape_not_active_in_inline_code() {
         vec![Token::Code {
             language: "".to_string(),
             content: r"\*literal\*".to_string(),
-            block: false
+            block: false,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
@@ -232,7 +233,8 @@ fn escape_inside_inline_code_span_is_literal() {
         vec![Token::Code {
             language: "".to_string(),
             content: r"\*not emphasis\*".to_string(),
-            block: false
+            block: false,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
@@ -245,7 +247,8 @@ fn escape_inside_multi_backtick_code_span_is_literal() {
         vec![Token::Code {
             language: "".to_string(),
             content: r"a \` b".to_string(),
-            block: false
+            block: false,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
@@ -346,7 +349,7 @@ fn escape_propagates_through_heading() {
     // Heading inline content reuses parse_text, so escapes should also
     // apply inside an ATX heading.
     let tokens = parse(r"# foo \* bar");
-    if let Token::Heading(content, 1) = &tokens[0] {
+    if let Token::Heading(content, 1, _) = &tokens[0] {
         let text = Token::collect_all_text(content);
         assert!(text.contains("foo * bar"), "got {:?}", text);
         // And no Emphasis should have formed inside.
@@ -15,7 +15,7 @@ fn setext_h2_inside_blockquote() {
     let tokens = parse("> Title\n> ---");
     let body = block_body(&tokens[0]);
     assert!(
-        body.iter().any(|t| matches!(t, Token::Heading(_, 2))),
+        body.iter().any(|t| matches!(t, Token::Heading(_, 2, _))),
         "expected H2 inside quote, got {:?}",
         body
     );
@@ -26,7 +26,7 @@ fn setext_h1_inside_blockquote() {
     let tokens = parse("> Big\n> ===");
     let body = block_body(&tokens[0]);
     assert!(
-        body.iter().any(|t| matches!(t, Token::Heading(_, 1))),
+        body.iter().any(|t| matches!(t, Token::Heading(_, 1, _))),
         "expected H1 inside quote, got {:?}",
         body
     );
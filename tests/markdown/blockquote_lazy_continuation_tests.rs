@@ -109,7 +109,7 @@ fn atx_heading_interrupts_lazy() {
     assert!(
         tokens[1..]
             .iter()
-            .any(|t| matches!(t, Token::Heading(_, 1))),
+            .any(|t| matches!(t, Token::Heading(_, 1, _))),
         "expected H1 after quote, got {}",
         Token::slice_to_compact(&tokens)
     );
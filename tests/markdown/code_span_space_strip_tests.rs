@@ -50,7 +50,8 @@ fn no_surrounding_space_unchanged() {
         vec![Token::Code {
             language: "".to_string(),
             content: "foo".to_string(),
-            block: false
+            block: false,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
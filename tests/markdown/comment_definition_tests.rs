@@ -0,0 +1,68 @@
+use markdown2pdf::markdown::*;
+
+use super::common::parse;
+
+#[test]
+fn comment_definition_produces_no_tokens() {
+    assert_eq!(parse("[//]: # (this is a comment)"), vec![]);
+    assert_eq!(parse("[//]: # \"quoted comment\"\n"), vec![]);
+}
+
+#[test]
+fn comment_definition_between_paragraphs_is_dropped() {
+    let tokens = parse("Before\n\n[//]: # (hidden note)\n\nAfter");
+    let text = Token::collect_all_text(&tokens);
+    assert!(!text.contains("hidden"), "got {:?}", tokens);
+    assert!(text.contains("Before") && text.contains("After"));
+}
+
+#[test]
+fn comment_definition_inside_a_paragraph_is_dropped() {
+    let tokens = parse("First line\n[//]: # (hidden note)\nsecond line");
+    let text = Token::collect_all_text(&tokens);
+    assert!(!text.contains("hidden"), "got {:?}", tokens);
+    assert!(text.contains("First line") && text.contains("second line"));
+}
+
+#[test]
+fn comment_label_is_not_a_reference() {
+    let tokens = parse("[//]: # (note)\n\nSee [//] here.");
+    assert!(
+        !tokens.iter().any(|t| matches!(t, Token::Link { .. })),
+        "got {:?}",
+        tokens
+    );
+    assert!(Token::collect_all_text(&tokens).contains("[//]"));
+}
+
+#[test]
+fn other_uses_of_the_comment_label_stay_text() {
+    // Not at the start of a line.
+    let inline = parse("Write [//]: # (note) to leave a comment.");
+    assert!(Token::collect_all_text(&inline).contains("[//]: # (note)"));
+    // Not a valid definition: the text after the URL isn't a title.
+    let invalid = parse("[//]: not a comment");
+    assert!(Token::collect_all_text(&invalid).contains("not a comment"));
+}
+
+#[test]
+fn reference_links_around_a_comment_still_resolve() {
+    let tokens = parse("[//]: # (note)\n[docs]: https://example.com\n\nRead [docs].");
+    assert!(
+        tokens
+            .iter()
+            .any(|t| matches!(t, Token::Link { url, .. } if url == "https://example.com")),
+        "got {:?}",
+        tokens
+    );
+}
+
+#[test]
+fn comment_definition_in_fenced_code_is_kept() {
+    let tokens = parse("```\n[//]: # (shown)\n```");
+    assert!(
+        matches!(tokens.as_slice(), [Token::Code { content, .. }] if content.contains("[//]: # (shown)")),
+        "got {:?}",
+        tokens
+    );
+}
@@ -120,7 +120,7 @@ fn heading_term_is_not_a_definition_list() {
     // heading into a definition list.
     let toks = parse("# Heading\n: Should be paragraph.\n");
     assert!(first_definition_list(&toks).is_none());
-    assert!(toks.iter().any(|t| matches!(t, Token::Heading(_, 1))));
+    assert!(toks.iter().any(|t| matches!(t, Token::Heading(_, 1, _))));
 }
 
 #[test]
@@ -86,7 +86,7 @@ fn emphasis_does_not_cross_blank_line() {
     let input = "para with *unclosed opener\n\n## Heading after blank";
     let tokens = parse(input);
     // The `## Heading…` must parse as a real heading token.
-    let has_heading = tokens.iter().any(|t| matches!(t, Token::Heading(_, 2)));
+    let has_heading = tokens.iter().any(|t| matches!(t, Token::Heading(_, 2, _)));
     assert!(
         has_heading,
         "expected H2 after blank line, got {:?}",
@@ -103,7 +103,7 @@ fn star_with_inner_space_does_not_eat_following_paragraph() {
     // gobble the next heading.
     let input = "Closer preceded: a *foo * — text.\n\n## Next heading";
     let tokens = parse(input);
-    let has_heading = tokens.iter().any(|t| matches!(t, Token::Heading(_, 2)));
+    let has_heading = tokens.iter().any(|t| matches!(t, Token::Heading(_, 2, _)));
     assert!(
         has_heading,
         "expected H2 after the paragraph, got {:?}",
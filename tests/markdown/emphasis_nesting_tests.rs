@@ -0,0 +1,101 @@
+//! Nested emphasis against CommonMark 0.31.2's delimiter-run rules
+//! (§6.2): left/right flanking, the `_` intraword restriction, the
+//! "multiple of 3" rule and the strong-before-emphasis preference.
+//! Each case is a spec example (or a trivial variant of one) and the
+//! expected tree is written in the spec's own HTML shorthand.
+
+use markdown2pdf::markdown::*;
+
+use super::common::parse;
+
+/// Flatten an inline token tree into the spec's `<em>` / `<strong>`
+/// notation, so one assertion pins both the nesting and the literal
+/// delimiters that were left unmatched.
+fn tree(tokens: &[Token]) -> String {
+    let mut out = String::new();
+    for t in tokens {
+        match t {
+            Token::Text(s) => out.push_str(s),
+            Token::Emphasis { level: 1, content } => {
+                out.push_str(&format!("<em>{}</em>", tree(content)))
+            }
+            Token::Emphasis { level: 2, content } | Token::StrongEmphasis(content) => {
+                out.push_str(&format!("<strong>{}</strong>", tree(content)))
+            }
+            other => panic!("unexpected token in emphasis tree: {:?}", other),
+        }
+    }
+    out
+}
+
+fn assert_tree(input: &str, expected: &str) {
+    assert_eq!(tree(&parse(input)), expected, "input: {input:?}");
+}
+
+#[test]
+fn emphasis_nested_in_strong() {
+    assert_tree(
+        "**bold _and italic_**",
+        "<strong>bold <em>and italic</em></strong>",
+    );
+}
+
+#[test]
+fn strong_nested_in_emphasis_intraword() {
+    assert_tree("*a**b**c*", "<em>a<strong>b</strong>c</em>");
+    assert_tree("*foo**bar**baz*", "<em>foo<strong>bar</strong>baz</em>");
+}
+
+#[test]
+fn double_underscore_is_strong() {
+    assert_tree("__strong__", "<strong>strong</strong>");
+}
+
+#[test]
+fn underscore_cannot_open_or_close_intraword() {
+    assert_tree("foo__bar__", "foo__bar__");
+    assert_tree("_foo_bar", "_foo_bar");
+    assert_tree("foo_bar_", "foo_bar_");
+}
+
+#[test]
+fn star_run_of_three_is_em_around_strong() {
+    assert_tree("***foo***", "<em><strong>foo</strong></em>");
+    assert_tree("foo***bar***baz", "foo<em><strong>bar</strong></em>baz");
+}
+
+#[test]
+fn rule_of_three_blocks_mismatched_runs() {
+    // `**` can't close `*` here: both runs can open and close and
+    // 1 + 2 is a multiple of 3, so the inner run stays literal.
+    assert_tree("*foo**bar*", "<em>foo**bar</em>");
+}
+
+#[test]
+fn nested_emphasis_of_same_delimiter() {
+    assert_tree("*foo *bar**", "<em>foo <em>bar</em></em>");
+    assert_tree("_(_foo_)_", "<em>(<em>foo</em>)</em>");
+}
+
+#[test]
+fn strong_then_emphasis_from_one_opener() {
+    assert_tree("***foo** bar*", "<em><strong>foo</strong> bar</em>");
+}
+
+#[test]
+fn excess_delimiters_stay_literal() {
+    assert_tree("**foo*", "*<em>foo</em>");
+    assert_tree("*foo**", "<em>foo</em>*");
+}
+
+#[test]
+fn first_closer_wins_across_delimiter_kinds() {
+    // The `*` pair closes first; `_` is left without a partner on
+    // either side of the `</em>`.
+    assert_tree("*foo _bar* baz_", "<em>foo _bar</em> baz_");
+}
+
+#[test]
+fn punctuation_after_intraword_dash_allows_opening() {
+    assert_tree("foo-_(bar)_", "foo-<em>(bar)</em>");
+}
@@ -75,7 +75,8 @@ fn entity_not_decoded_inside_code_span() {
         vec![Token::Code {
             language: "".to_string(),
             content: "&amp;".to_string(),
-            block: false
+            block: false,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
@@ -86,7 +86,7 @@ fn assert_no_literal_newline_in_text(input: &str) {
                 !s.contains('\n'),
                 "Token::Text carries a literal newline for {input:?}: {s:?}"
             ),
-            Token::Heading(inner, _)
+            Token::Heading(inner, _, _)
             | Token::Emphasis { content: inner, .. }
             | Token::StrongEmphasis(inner)
             | Token::Strikethrough(inner)
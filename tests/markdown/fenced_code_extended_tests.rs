@@ -13,6 +13,7 @@ fn first_code_block(input: &str) -> (String, String) {
         language,
         content,
         block: true,
+        ..
     }) = tokens
         .iter()
         .find(|t| matches!(t, Token::Code { block: true, .. }))
@@ -99,3 +99,80 @@ fn fence_body_unchanged_by_info_string_split() {
     assert!(body.contains("let x = 1;"));
     assert!(body.contains("let y = 2;"));
 }
+
+fn fence_attrs(input: &str) -> (String, CodeAttrs) {
+    let tokens = parse(input);
+    for t in tokens {
+        if let Token::Code {
+            language, attrs, ..
+        } = t
+        {
+            return (language, attrs);
+        }
+    }
+    panic!("expected Code token");
+}
+
+#[test]
+fn attribute_block_gives_caption_and_highlight() {
+    let (lang, attrs) =
+        fence_attrs("```rust {caption=\"Example 1\" highlight=\"3-5\"}\nfn x() {}\n```");
+    assert_eq!(lang, "rust");
+    assert_eq!(attrs.caption.as_deref(), Some("Example 1"));
+    assert_eq!(attrs.highlight, vec![(3, 5)]);
+}
+
+#[test]
+fn attribute_block_may_follow_the_language_directly() {
+    let (lang, attrs) = fence_attrs("~~~python{caption='Setup'}\nx = 1\n~~~");
+    assert_eq!(lang, "python");
+    assert_eq!(attrs.caption.as_deref(), Some("Setup"));
+}
+
+#[test]
+fn attribute_block_alone_leaves_the_language_empty() {
+    let (lang, attrs) = fence_attrs("```{highlight=2}\na\nb\n```");
+    assert_eq!(lang, "");
+    assert_eq!(attrs.highlight, vec![(2, 2)]);
+}
+
+#[test]
+fn chunk_braces_without_attribute_syntax_stay_in_the_info_string() {
+    let (lang, attrs) = fence_attrs("```{r}\nsummary(cars)\n```");
+    assert_eq!(lang, "{r}");
+    assert_eq!(attrs, CodeAttrs::default());
+    let (lang, attrs) = fence_attrs("```{r echo=FALSE}\nplot(x)\n```");
+    assert_eq!(lang, "{r");
+    assert_eq!(attrs, CodeAttrs::default());
+}
+
+#[test]
+fn highlight_lists_ranges_and_drops_bad_entries() {
+    let (_, attrs) = fence_attrs("```text {highlight=\"1, 4-6, x, 0, 9-7\"}\na\n```");
+    assert_eq!(attrs.highlight, vec![(1, 1), (4, 6), (7, 9)]);
+    assert!(attrs.highlights(5) && !attrs.highlights(3));
+}
+
+#[test]
+fn unknown_keys_and_bare_words_are_ignored() {
+    let (lang, attrs) =
+        fence_attrs("```rust {.numberLines #main linenos title=\"x\" caption=\"Kept\"}\nx\n```");
+    assert_eq!(lang, "rust");
+    assert_eq!(attrs.caption.as_deref(), Some("Kept"));
+    assert!(attrs.highlight.is_empty());
+}
+
+#[test]
+fn unclosed_attribute_block_is_plain_metadata() {
+    let (lang, attrs) = fence_attrs("```rust {caption=\"open\n{}\n```");
+    assert_eq!(lang, "rust");
+    assert_eq!(attrs, CodeAttrs::default());
+}
+
+#[test]
+fn code_spans_and_indented_blocks_have_no_attributes() {
+    let (_, attrs) = fence_attrs("`{caption=x}`");
+    assert_eq!(attrs, CodeAttrs::default());
+    let (_, attrs) = fence_attrs("    {caption=x}\n");
+    assert_eq!(attrs, CodeAttrs::default());
+}
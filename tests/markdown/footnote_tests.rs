@@ -10,7 +10,7 @@ fn refs_of(tokens: &[Token]) -> Vec<String> {
     fn walk(t: &Token, out: &mut Vec<String>) {
         match t {
             Token::FootnoteReference(label) => out.push(label.clone()),
-            Token::Heading(inner, _)
+            Token::Heading(inner, _, _)
             | Token::Emphasis { content: inner, .. }
             | Token::StrongEmphasis(inner)
             | Token::Strikethrough(inner)
@@ -245,6 +245,32 @@ fn forward_reference_before_definition() {
     assert_eq!(defs.len(), 1);
 }
 
+#[test]
+fn definition_before_its_reference() {
+    let tokens = parse("[^1]: Note first.\n\nBody[^1] after.");
+    assert_eq!(refs_of(&tokens), vec!["1".to_string()]);
+    let defs = defs_of(&tokens);
+    assert_eq!(defs, vec![("1".to_string(), "Note first.".to_string())]);
+}
+
+#[test]
+fn multiline_definition_before_its_reference() {
+    let src = "[^n]: Opening line.\n    Indented continuation.\n\nBody[^n].";
+    let tokens = parse(src);
+    assert_eq!(refs_of(&tokens), vec!["n".to_string()]);
+    let defs = defs_of(&tokens);
+    assert_eq!(defs.len(), 1);
+    assert_eq!(defs[0].1, "Opening line. Indented continuation.");
+    // The body is lexed once, inside the definition — it isn't echoed
+    // into the paragraph that follows.
+    let body: String = tokens
+        .iter()
+        .filter(|t| !matches!(t, Token::FootnoteDefinition { .. }))
+        .map(|t| Token::collect_all_text(std::slice::from_ref(t)))
+        .collect();
+    assert!(!body.contains("continuation"), "body leaked: {body:?}");
+}
+
 #[test]
 fn definition_with_empty_body_lexes() {
     let tokens = parse("[^1]:");
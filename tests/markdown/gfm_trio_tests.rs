@@ -84,6 +84,7 @@ fn tilde_fenced_code_block_basic() {
             language: "".to_string(),
             content: "fn main() {}".to_string(),
             block: true,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
@@ -97,7 +98,8 @@ fn tilde_fenced_code_block_with_language() {
         vec![Token::Code {
             language: "rust".to_string(),
             content: "let x = 5;".to_string(),
-            block: true
+            block: true,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
@@ -171,7 +173,8 @@ fn tilde_in_inline_code_stays_literal() {
         vec![Token::Code {
             language: "".to_string(),
             content: "~~not strikethrough~~".to_string(),
-            block: false
+            block: false,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
@@ -10,7 +10,7 @@ fn count_hard_breaks(tokens: &[Token]) -> usize {
         for t in tokens {
             match t {
                 Token::HardBreak => *n += 1,
-                Token::Heading(body, _) => walk(body, n),
+                Token::Heading(body, _, _) => walk(body, n),
                 Token::Emphasis { content, .. } => walk(content, n),
                 Token::StrongEmphasis(body) => walk(body, n),
                 Token::BlockQuote(body) => walk(body, n),
@@ -87,7 +87,7 @@ fn no_hard_break_in_atx_heading() {
     // Headings are single-line; trailing spaces are not a hard break.
     let tokens = parse("# Heading  \nbody");
     // Heading content shouldn't contain HardBreak.
-    if let Token::Heading(content, _) = &tokens[0] {
+    if let Token::Heading(content, _, _) = &tokens[0] {
         assert!(!content.iter().any(|t| matches!(t, Token::HardBreak)));
     }
 }
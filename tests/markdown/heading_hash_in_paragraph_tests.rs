@@ -33,7 +33,8 @@ fn line_start_heading_still_works() {
         tokens,
         vec![Token::Heading(
             vec![Token::Text("Real heading".to_string())],
-            1
+            1,
+            None
         )]
     );
 }
@@ -45,7 +46,8 @@ fn heading_with_hash_in_content() {
         tokens,
         vec![Token::Heading(
             vec![Token::Text("Summary about C#".to_string())],
-            2
+            2,
+            None
         )]
     );
 }
@@ -58,7 +60,7 @@ fn paragraph_then_heading() {
         vec![
             Token::Text("first uses C#".to_string()),
             Token::Newline,
-            Token::Heading(vec![Token::Text("heading".to_string())], 1),
+            Token::Heading(vec![Token::Text("heading".to_string())], 1, None),
         ]
     );
 }
@@ -69,7 +71,7 @@ fn heading_then_paragraph_with_hash() {
     assert_eq!(
         tokens,
         vec![
-            Token::Heading(vec![Token::Text("Title".to_string())], 1),
+            Token::Heading(vec![Token::Text("Title".to_string())], 1, None),
             Token::Newline,
             Token::Newline,
             Token::Text("body mentions C# here".to_string()),
@@ -84,7 +86,7 @@ fn full_csharp_issue_repro() {
     let mut lexer = Lexer::new(input.to_string());
     let tokens = lexer.parse().expect("must not error on C# in paragraph");
 
-    assert!(matches!(tokens[0], Token::Heading(_, 2)));
+    assert!(matches!(tokens[0], Token::Heading(_, 2, _)));
     let body = Token::collect_all_text(&tokens);
     assert!(body.contains("C#"));
     assert!(body.contains("Rust"));
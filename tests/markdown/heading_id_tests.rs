@@ -0,0 +1,73 @@
+use markdown2pdf::markdown::*;
+
+use super::common::parse;
+
+fn heading(tokens: &[Token]) -> (&Vec<Token>, &Option<String>) {
+    match tokens.iter().find(|t| matches!(t, Token::Heading(..))) {
+        Some(Token::Heading(content, _, id)) => (content, id),
+        _ => panic!("expected Heading, got {:?}", tokens),
+    }
+}
+
+#[test]
+fn atx_explicit_id_is_split_off_the_text() {
+    let tokens = parse("# Getting Started {#start}");
+    let (content, id) = heading(&tokens);
+    assert_eq!(id.as_deref(), Some("start"));
+    assert_eq!(Token::collect_all_text(content), "Getting Started");
+    assert_eq!(tokens[0].heading_id().as_deref(), Some("start"));
+}
+
+#[test]
+fn atx_explicit_id_with_closing_hashes() {
+    let tokens = parse("## Setup {#install-guide} ##");
+    let (content, id) = heading(&tokens);
+    assert_eq!(id.as_deref(), Some("install-guide"));
+    assert_eq!(Token::collect_all_text(content), "Setup");
+}
+
+#[test]
+fn setext_explicit_id() {
+    let tokens = parse("Overview {#intro}\n===");
+    let (content, id) = heading(&tokens);
+    assert_eq!(id.as_deref(), Some("intro"));
+    assert_eq!(Token::collect_all_text(content), "Overview");
+}
+
+#[test]
+fn auto_id_slugifies_the_heading_text() {
+    let tokens = parse("# Hello, World!");
+    let (_, id) = heading(&tokens);
+    assert_eq!(id, &None);
+    assert_eq!(tokens[0].heading_id().as_deref(), Some("hello-world"));
+}
+
+#[test]
+fn empty_heading_auto_id_falls_back_to_section() {
+    let tokens = parse("# ***");
+    assert_eq!(tokens[0].heading_id().as_deref(), Some("section"));
+}
+
+#[test]
+fn attribute_glued_to_text_or_invalid_stays_literal() {
+    for src in [
+        "# Title{#id}",
+        "# Title {#}",
+        "# Title {#bad id}",
+        "# Title \\{#id}",
+    ] {
+        let tokens = parse(src);
+        let (content, id) = heading(&tokens);
+        assert_eq!(id, &None, "{src:?} should not carry an id");
+        assert!(
+            Token::collect_all_text(content).contains("{#"),
+            "{src:?} should keep the braces as text"
+        );
+    }
+}
+
+#[test]
+fn non_heading_tokens_have_no_id() {
+    let tokens = parse("plain {#x}");
+    assert!(tokens.iter().all(|t| t.heading_id().is_none()));
+}
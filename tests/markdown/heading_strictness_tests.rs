@@ -11,19 +11,19 @@ fn atx_without_space_is_text() {
 #[test]
 fn atx_with_space_is_heading() {
     let tokens = parse("# hello");
-    assert!(matches!(tokens[0], Token::Heading(_, 1)));
+    assert!(matches!(tokens[0], Token::Heading(_, 1, _)));
 }
 
 #[test]
 fn atx_with_tab_after_hash_is_heading() {
     let tokens = parse("#\thello");
-    assert!(matches!(tokens[0], Token::Heading(_, 1)));
+    assert!(matches!(tokens[0], Token::Heading(_, 1, _)));
 }
 
 #[test]
 fn atx_seven_hashes_falls_back_to_text() {
     let tokens = parse("####### too deep");
-    assert!(!matches!(tokens[0], Token::Heading(_, _)));
+    assert!(!matches!(tokens[0], Token::Heading(_, _, _)));
     let text = Token::collect_all_text(&tokens);
     assert!(text.contains("####### too deep"), "got {:?}", text);
 }
@@ -31,13 +31,13 @@ fn atx_seven_hashes_falls_back_to_text() {
 #[test]
 fn atx_six_hashes_is_h6() {
     let tokens = parse("###### six");
-    assert!(matches!(tokens[0], Token::Heading(_, 6)));
+    assert!(matches!(tokens[0], Token::Heading(_, 6, _)));
 }
 
 #[test]
 fn atx_trailing_hashes_stripped() {
     let tokens = parse("## Title ##");
-    if let Token::Heading(content, 2) = &tokens[0] {
+    if let Token::Heading(content, 2, _) = &tokens[0] {
         let text = Token::collect_all_text(content);
         assert_eq!(text, "Title");
     } else {
@@ -48,7 +48,7 @@ fn atx_trailing_hashes_stripped() {
 #[test]
 fn atx_trailing_hashes_with_trailing_space_stripped() {
     let tokens = parse("## Title ## ");
-    if let Token::Heading(content, 2) = &tokens[0] {
+    if let Token::Heading(content, 2, _) = &tokens[0] {
         let text = Token::collect_all_text(content);
         assert_eq!(text, "Title");
     } else {
@@ -60,7 +60,7 @@ fn atx_trailing_hashes_with_trailing_space_stripped() {
 fn atx_trailing_hash_without_preceding_space_kept() {
     // Regression — `## C#` must keep the `#` as content (no preceding space).
     let tokens = parse("## C#");
-    if let Token::Heading(content, 2) = &tokens[0] {
+    if let Token::Heading(content, 2, _) = &tokens[0] {
         let text = Token::collect_all_text(content);
         assert_eq!(text, "C#");
     } else {
@@ -71,8 +71,8 @@ fn atx_trailing_hash_without_preceding_space_kept() {
 #[test]
 fn empty_atx_just_hashes() {
     let tokens = parse("##");
-    assert!(matches!(tokens[0], Token::Heading(_, 2)));
-    if let Token::Heading(content, _) = &tokens[0] {
+    assert!(matches!(tokens[0], Token::Heading(_, 2, _)));
+    if let Token::Heading(content, _, _) = &tokens[0] {
         assert!(content.is_empty());
     }
 }
@@ -18,7 +18,7 @@ fn highlights(tokens: &[Token]) -> Vec<String> {
                     out.push(Token::collect_all_text(c));
                     walk(c, out);
                 }
-                Token::Heading(c, _)
+                Token::Heading(c, _, _)
                 | Token::StrongEmphasis(c)
                 | Token::BlockQuote(c)
                 | Token::Strikethrough(c) => walk(c, out),
@@ -109,7 +109,11 @@ fn setext_h1_underline_is_not_a_highlight() {
     assert!(!has_highlight(&tokens));
     assert_eq!(
         tokens,
-        vec![Token::Heading(vec![Token::Text("Foo".to_string())], 1)]
+        vec![Token::Heading(
+            vec![Token::Text("Foo".to_string())],
+            1,
+            None
+        )]
     );
 }
 
@@ -119,7 +123,11 @@ fn setext_h2_underline_still_works() {
     assert!(!has_highlight(&tokens));
     assert_eq!(
         tokens,
-        vec![Token::Heading(vec![Token::Text("Bar".to_string())], 2)]
+        vec![Token::Heading(
+            vec![Token::Text("Bar".to_string())],
+            2,
+            None
+        )]
     );
 }
 
@@ -161,3 +169,20 @@ fn back_to_back_highlights() {
         vec!["one".to_string(), "two".to_string()]
     );
 }
+
+#[test]
+fn spaced_comparison_operators_are_literal() {
+    let tokens = parse("if a == b == c then");
+    assert!(!has_highlight(&tokens));
+    assert_eq!(
+        Token::collect_all_text(&tokens),
+        "if a == b == c then".to_string()
+    );
+}
+
+#[test]
+fn empty_or_padded_span_is_not_a_highlight() {
+    assert!(!has_highlight(&parse("a ==== b")));
+    assert!(!has_highlight(&parse("a == == b")));
+    assert!(!has_highlight(&parse("a ==foo == b")));
+}
@@ -11,6 +11,7 @@ fn four_space_indented_line_is_code() {
             language: "".to_string(),
             content: "let x = 5;".to_string(),
             block: true,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
@@ -24,6 +25,7 @@ fn tab_indent_is_code() {
             language: "".to_string(),
             content: "let x = 5;".to_string(),
             block: true,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
@@ -69,6 +71,7 @@ fn fenced_code_block_unaffected() {
             language: "".to_string(),
             content: "fn main() {}".to_string(),
             block: true,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
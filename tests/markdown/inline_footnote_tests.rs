@@ -20,7 +20,7 @@ fn inline_notes(tokens: &[Token]) -> Vec<(String, String)> {
                     walk(c, out);
                 }
             }
-            Token::Heading(inner, _)
+            Token::Heading(inner, _, _)
             | Token::Emphasis { content: inner, .. }
             | Token::StrongEmphasis(inner)
             | Token::Strikethrough(inner)
@@ -181,7 +181,9 @@ fn regular_footnote_reference_unaffected() {
         fn any_ref(t: &Token) -> bool {
             match t {
                 Token::FootnoteReference(_) => true,
-                Token::Heading(i, _) | Token::ListItem { content: i, .. } => i.iter().any(any_ref),
+                Token::Heading(i, _, _) | Token::ListItem { content: i, .. } => {
+                    i.iter().any(any_ref)
+                }
                 _ => false,
             }
         }
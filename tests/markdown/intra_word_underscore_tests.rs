@@ -67,7 +67,8 @@ fn heading_with_intra_word_underscore() {
             vec![Token::Text(
                 "phpmyadmin/localized_docs (GitHub)".to_string()
             )],
-            2
+            2,
+            None
         )]
     );
 }
@@ -75,7 +76,7 @@ fn heading_with_intra_word_underscore() {
 #[test]
 fn heading_with_code_containing_underscore() {
     let tokens = parse("## `phpmyadmin/localized_docs` (GitHub)");
-    if let Token::Heading(content, 2) = &tokens[0] {
+    if let Token::Heading(content, 2, _) = &tokens[0] {
         assert!(matches!(content[0], Token::Code { .. }));
         if let Token::Code { content: code, .. } = &content[0] {
             assert_eq!(code, "phpmyadmin/localized_docs");
@@ -205,7 +206,8 @@ fn code_with_underscore() {
         vec![Token::Code {
             language: "".to_string(),
             content: "foo_bar".to_string(),
-            block: false
+            block: false,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
@@ -231,13 +233,13 @@ fn full_unmatching_issue_repro() {
     let tokens = lexer.parse().expect("must not error on intra-word _");
 
     // Two headings, separated by Newline
-    assert!(matches!(tokens[0], Token::Heading(_, 2)));
+    assert!(matches!(tokens[0], Token::Heading(_, 2, _)));
     let last_heading = tokens
         .iter()
         .rev()
-        .find(|t| matches!(t, Token::Heading(_, 2)))
+        .find(|t| matches!(t, Token::Heading(_, 2, _)))
         .unwrap();
-    if let Token::Heading(content, _) = last_heading {
+    if let Token::Heading(content, _, _) = last_heading {
         let text = Token::collect_all_text(content);
         assert!(text.contains("phpmyadmin/localized_docs"));
     }
@@ -58,7 +58,7 @@ fn blank_line_ends_list_item() {
 fn heading_line_terminates_item() {
     let input = "- item one\n# heading";
     let tokens = parse(input);
-    assert!(tokens.iter().any(|t| matches!(t, Token::Heading(_, 1))));
+    assert!(tokens.iter().any(|t| matches!(t, Token::Heading(_, 1, _))));
     if let Token::ListItem { content, .. } = &tokens[0] {
         let text = Token::collect_all_text(content);
         assert!(
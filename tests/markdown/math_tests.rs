@@ -19,7 +19,7 @@ fn maths(tokens: &[Token]) -> Vec<(bool, String)> {
         for t in ts {
             match t {
                 Token::Math { inline, content } => out.push((*inline, content.clone())),
-                Token::Heading(c, _)
+                Token::Heading(c, _, _)
                 | Token::StrongEmphasis(c)
                 | Token::BlockQuote(c)
                 | Token::Strikethrough(c)
@@ -10,7 +10,8 @@ fn double_backtick_inline_with_single_backtick_inside() {
         vec![Token::Code {
             language: "".to_string(),
             content: "code with ` inside".to_string(),
-            block: false
+            block: false,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
@@ -32,7 +33,8 @@ fn double_backtick_with_count_mismatch_inside() {
         vec![Token::Code {
             language: "".to_string(),
             content: "a`b".to_string(),
-            block: false
+            block: false,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
@@ -46,7 +48,8 @@ fn fenced_block_still_works() {
         vec![Token::Code {
             language: "rust".to_string(),
             content: "fn main() {}".to_string(),
-            block: true
+            block: true,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
@@ -136,7 +139,8 @@ fn single_backtick_unchanged() {
         vec![Token::Code {
             language: "".to_string(),
             content: "simple".to_string(),
-            block: false
+            block: false,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
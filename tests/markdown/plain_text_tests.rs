@@ -0,0 +1,46 @@
+use markdown2pdf::markdown::*;
+
+use super::common::parse;
+
+#[test]
+fn heading_with_mixed_inline_content_flattens_to_its_words() {
+    let tokens = parse("# **Bold** `code` [link](https://example.com)\n");
+    assert_eq!(tokens[0].plain_text(), "Bold code link");
+}
+
+#[test]
+fn nested_emphasis_strike_and_highlight_keep_only_text() {
+    let tokens = parse("# ***Very*** ~~old~~ ==new== _api_\n");
+    assert_eq!(tokens[0].plain_text(), "Very old new api");
+}
+
+#[test]
+fn raw_html_and_footnote_markers_are_left_out() {
+    let tokens = parse("# Press <kbd>Ctrl</kbd>[^1] now\n\n[^1]: Or Cmd.\n");
+    assert_eq!(tokens[0].plain_text(), "Press Ctrl now");
+}
+
+#[test]
+fn image_alt_text_stands_in_for_the_image() {
+    let tokens = parse("# ![Logo](logo.png) Home\n");
+    assert_eq!(tokens[0].plain_text(), "Logo Home");
+}
+
+#[test]
+fn blocks_and_line_breaks_are_separated_by_spaces() {
+    let tokens = parse("> first\n> second\n\n- one\n- two\n");
+    let text = Token::plain_text_of(&tokens);
+    assert_eq!(
+        text.split_whitespace().collect::<Vec<_>>(),
+        ["first", "second", "one", "two"]
+    );
+}
+
+#[test]
+fn heading_id_slugs_the_plain_text() {
+    let tokens = parse("## Using `Token::plain_text` <sup>new</sup>\n");
+    assert_eq!(
+        tokens[0].heading_id().as_deref(),
+        Some("using-tokenplain-text-new")
+    );
+}
@@ -29,6 +29,38 @@ fn triple_star_is_nested_emphasis() {
     assert!(matches!(&tokens[0], Token::Emphasis { .. }));
 }
 
+#[test]
+fn double_delims_are_level_two_not_strong_variant() {
+    // Strong emphasis is reported as `Emphasis { level: 2 }`; the
+    // `StrongEmphasis` variant is never produced by the lexer.
+    for src in ["**x**", "__x__"] {
+        let tokens = parse(src);
+        assert!(
+            matches!(tokens[0], Token::Emphasis { level: 2, .. }),
+            "{src}: {tokens:?}"
+        );
+        assert!(!tokens.iter().any(|t| matches!(t, Token::StrongEmphasis(_))));
+    }
+}
+
+#[test]
+fn triple_star_nests_level_one_around_level_two() {
+    let tokens = parse("***x***");
+    let Token::Emphasis { level: 1, content } = &tokens[0] else {
+        panic!("expected outer level-1 emphasis, got {:?}", tokens);
+    };
+    assert!(matches!(content[0], Token::Emphasis { level: 2, .. }));
+}
+
+#[test]
+fn mixed_delims_nest_strong_around_em() {
+    let tokens = parse("**_mixed_**");
+    let Token::Emphasis { level: 2, content } = &tokens[0] else {
+        panic!("expected outer level-2 emphasis, got {:?}", tokens);
+    };
+    assert!(matches!(content[0], Token::Emphasis { level: 1, .. }));
+}
+
 #[test]
 fn basic_underscore_emphasis() {
     let tokens = parse("_x_");
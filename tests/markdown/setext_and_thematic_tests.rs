@@ -6,11 +6,11 @@ use super::common::parse;
 fn setext_h1_basic() {
     let tokens = parse("Title\n===");
     assert!(
-        matches!(tokens[0], Token::Heading(_, 1)),
+        matches!(tokens[0], Token::Heading(_, 1, _)),
         "expected H1, got {:?}",
         tokens
     );
-    if let Token::Heading(content, 1) = &tokens[0] {
+    if let Token::Heading(content, 1, _) = &tokens[0] {
         assert_eq!(Token::collect_all_text(content), "Title");
     }
 }
@@ -18,14 +18,14 @@ fn setext_h1_basic() {
 #[test]
 fn setext_h1_long_underline() {
     let tokens = parse("Title\n=======");
-    assert!(matches!(tokens[0], Token::Heading(_, 1)));
+    assert!(matches!(tokens[0], Token::Heading(_, 1, _)));
 }
 
 #[test]
 fn setext_h1_with_inline_emphasis() {
     let tokens = parse("Title with *emphasis*\n===");
-    assert!(matches!(tokens[0], Token::Heading(_, 1)));
-    if let Token::Heading(content, 1) = &tokens[0] {
+    assert!(matches!(tokens[0], Token::Heading(_, 1, _)));
+    if let Token::Heading(content, 1, _) = &tokens[0] {
         assert!(content.iter().any(|t| matches!(t, Token::Emphasis { .. })));
     }
 }
@@ -34,11 +34,11 @@ fn setext_h1_with_inline_emphasis() {
 fn setext_h2_basic() {
     let tokens = parse("Title\n---");
     assert!(
-        matches!(tokens[0], Token::Heading(_, 2)),
+        matches!(tokens[0], Token::Heading(_, 2, _)),
         "expected H2 (NOT a HorizontalRule), got {:?}",
         tokens
     );
-    if let Token::Heading(content, 2) = &tokens[0] {
+    if let Token::Heading(content, 2, _) = &tokens[0] {
         assert_eq!(Token::collect_all_text(content), "Title");
     }
 }
@@ -46,7 +46,7 @@ fn setext_h2_basic() {
 #[test]
 fn setext_h2_long_underline() {
     let tokens = parse("Title\n----------");
-    assert!(matches!(tokens[0], Token::Heading(_, 2)));
+    assert!(matches!(tokens[0], Token::Heading(_, 2, _)));
 }
 
 #[test]
@@ -85,7 +85,7 @@ fn paragraph_followed_by_dashes_is_setext_h2_not_hr() {
     // Must be Heading, not Text + HorizontalRule
     let has_hr = tokens.iter().any(|t| matches!(t, Token::HorizontalRule));
     assert!(!has_hr, "should not have produced an HR, got {:?}", tokens);
-    assert!(matches!(tokens[0], Token::Heading(_, 2)));
+    assert!(matches!(tokens[0], Token::Heading(_, 2, _)));
 }
 
 #[test]
@@ -95,16 +95,49 @@ fn lone_dashes_after_blank_line_is_hr() {
     assert!(tokens.iter().any(|t| matches!(t, Token::HorizontalRule)));
 }
 
+#[test]
+fn setext_h2_and_thematic_break_in_one_document() {
+    // The same `---` line is an underline right after a text line and
+    // a rule after a blank line; neither reading may leak into the
+    // other or orphan the heading text.
+    let tokens = parse("Section\n---\n\nBody text.\n\n---\n\nMore.");
+    let headings: Vec<_> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Heading(content, level, _) => Some((*level, Token::collect_all_text(content))),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(headings, [(2, "Section".to_string())], "{tokens:?}");
+    let rules = tokens
+        .iter()
+        .filter(|t| matches!(t, Token::HorizontalRule))
+        .count();
+    assert_eq!(rules, 1, "{tokens:?}");
+    assert!(!Token::collect_all_text(&tokens).contains("---"));
+}
+
+#[test]
+fn equals_line_after_blank_line_is_plain_text() {
+    // `===` has no thematic-break reading, so without a text line
+    // directly above it there is no heading at all.
+    let tokens = parse("Intro\n\n===");
+    assert!(
+        !tokens.iter().any(|t| matches!(t, Token::Heading(_, _, _))),
+        "{tokens:?}"
+    );
+}
+
 #[test]
 fn regression_atx_h1_still_works() {
     let tokens = parse("# H1");
-    assert!(matches!(tokens[0], Token::Heading(_, 1)));
+    assert!(matches!(tokens[0], Token::Heading(_, 1, _)));
 }
 
 #[test]
 fn regression_atx_h2_still_works() {
     let tokens = parse("## H2");
-    assert!(matches!(tokens[0], Token::Heading(_, 2)));
+    assert!(matches!(tokens[0], Token::Heading(_, 2, _)));
 }
 
 #[test]
@@ -130,7 +163,7 @@ fn display_math_opener_skips_setext_walker() {
         "expected display math token, got {tokens:?}"
     );
     assert!(
-        !tokens.iter().any(|t| matches!(t, Token::Heading(_, _))),
+        !tokens.iter().any(|t| matches!(t, Token::Heading(_, _, _))),
         "must not produce a heading for `$$ … = … $$`"
     );
 }
@@ -0,0 +1,106 @@
+use markdown2pdf::markdown::*;
+
+fn spans(src: &str) -> Vec<(Token, std::ops::Range<usize>)> {
+    Lexer::new(src.to_string()).parse_with_spans().unwrap()
+}
+
+/// Source text of the first token `pick` accepts.
+fn source_of<'a>(src: &'a str, pick: impl Fn(&Token) -> bool) -> &'a str {
+    let tokens = spans(src);
+    let (_, span) = tokens
+        .iter()
+        .find(|(t, _)| pick(t))
+        .unwrap_or_else(|| panic!("no matching token in {:?}", tokens));
+    &src[span.clone()]
+}
+
+#[test]
+fn heading_span_matches_its_source_location() {
+    let src = "Intro.\n\n## Setup\n\nBody.\n";
+    let tokens = spans(src);
+    let (_, span) = tokens
+        .iter()
+        .find(|(t, _)| matches!(t, Token::Heading(_, 2, _)))
+        .unwrap();
+    assert_eq!(span.clone(), 8..16);
+    assert_eq!(&src[span.clone()], "## Setup");
+}
+
+#[test]
+fn tokens_match_plain_parse() {
+    let src = "# Title\n\nSome *emphasis* and a [link](/x).\n\n- one\n- two\n\n> quote\n";
+    let spanned: Vec<Token> = spans(src).into_iter().map(|(t, _)| t).collect();
+    let plain = Lexer::new(src.to_string()).parse().unwrap();
+    assert_eq!(spanned, plain);
+}
+
+#[test]
+fn spans_are_ordered_and_in_bounds() {
+    let src = "# A\n\nText **bold** more.\n\n```\ncode\n```\n\n| a |\n|---|\n| 1 |\n";
+    let mut last_start = 0;
+    for (token, span) in spans(src) {
+        assert!(
+            span.start <= span.end && span.end <= src.len(),
+            "{token:?} {span:?}"
+        );
+        assert!(span.start >= last_start, "{token:?} {span:?} out of order");
+        last_start = span.start;
+    }
+}
+
+#[test]
+fn emphasis_span_covers_its_delimiters() {
+    let src = "a *b* c";
+    assert_eq!(
+        source_of(src, |t| matches!(t, Token::Emphasis { .. })),
+        "*b*"
+    );
+}
+
+#[test]
+fn spans_point_into_the_original_source_past_bom_and_crlf() {
+    let src = "\u{FEFF}# A\r\n\r\n# B\r\n";
+    assert_eq!(
+        source_of(
+            src,
+            |t| matches!(t, Token::Heading(c, _, _) if Token::plain_text_of(c) == "B")
+        ),
+        "# B"
+    );
+}
+
+#[test]
+fn spans_skip_removed_reference_definitions() {
+    let src = "[x]: /u\n\n# After [x]\n";
+    assert_eq!(
+        source_of(src, |t| matches!(t, Token::Heading(..))),
+        "# After [x]"
+    );
+}
+
+#[test]
+fn www_autolink_gets_its_own_span() {
+    let src = "see www.example.com now";
+    assert_eq!(
+        source_of(src, |t| matches!(t, Token::Link { .. })),
+        "www.example.com"
+    );
+}
+
+#[test]
+fn www_autolink_spans_are_byte_ranges_next_to_non_ascii_text() {
+    let src = "café www.x.com naïve";
+    let tokens = spans(src);
+    let parts: Vec<_> = tokens
+        .iter()
+        .filter_map(|(t, span)| match t {
+            Token::Text(text) => Some((text.clone(), span.clone())),
+            Token::Link { content, .. } => Some((Token::plain_text_of(content), span.clone())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(parts.len(), 3, "{tokens:?}");
+    for (text, span) in parts {
+        assert_eq!(&src[span], text);
+    }
+}
@@ -6,8 +6,8 @@ use markdown2pdf::markdown::*;
 use super::common::parse;
 
 fn heading_level_and_text(tokens: &[Token]) -> (usize, String) {
-    let Some(Token::Heading(body, level)) =
-        tokens.iter().find(|t| matches!(t, Token::Heading(_, _)))
+    let Some(Token::Heading(body, level, _)) =
+        tokens.iter().find(|t| matches!(t, Token::Heading(_, _, _)))
     else {
         panic!("expected Heading, got {:?}", tokens);
     };
@@ -27,13 +27,13 @@ fn one_to_six_hashes() {
 #[test]
 fn seven_hashes_is_not_a_heading() {
     let tokens = parse("####### too many\n");
-    assert!(!tokens.iter().any(|t| matches!(t, Token::Heading(_, _))));
+    assert!(!tokens.iter().any(|t| matches!(t, Token::Heading(_, _, _))));
 }
 
 #[test]
 fn empty_heading() {
     let tokens = parse("#\n");
-    let Some(Token::Heading(body, level)) = tokens.first() else {
+    let Some(Token::Heading(body, level, _)) = tokens.first() else {
         panic!("expected Heading, got {:?}", tokens);
     };
     assert_eq!(*level, 1);
@@ -57,7 +57,7 @@ fn trailing_hashes_without_space_kept() {
 #[test]
 fn escaped_hash_does_not_start_heading() {
     let tokens = parse(r"\# not heading");
-    assert!(!tokens.iter().any(|t| matches!(t, Token::Heading(_, _))));
+    assert!(!tokens.iter().any(|t| matches!(t, Token::Heading(_, _, _))));
 }
 
 #[test]
@@ -67,7 +67,7 @@ fn one_to_three_space_indent_allowed() {
         assert!(
             parse(&input)
                 .iter()
-                .any(|t| matches!(t, Token::Heading(_, _)))
+                .any(|t| matches!(t, Token::Heading(_, _, _)))
         );
     }
 }
@@ -75,5 +75,5 @@ fn one_to_three_space_indent_allowed() {
 #[test]
 fn four_space_indent_is_code_block_not_heading() {
     let tokens = parse("    # not heading\n");
-    assert!(!tokens.iter().any(|t| matches!(t, Token::Heading(_, _))));
+    assert!(!tokens.iter().any(|t| matches!(t, Token::Heading(_, _, _))));
 }
@@ -9,7 +9,7 @@ use super::common::parse;
 #[test]
 fn atx_heading_interrupts_paragraph() {
     let tokens = parse("paragraph\n# heading\n");
-    assert!(tokens.iter().any(|t| matches!(t, Token::Heading(_, 1))));
+    assert!(tokens.iter().any(|t| matches!(t, Token::Heading(_, 1, _))));
 }
 
 #[test]
@@ -33,7 +33,7 @@ fn dashes_after_paragraph_make_setext_h2_not_hr() {
     // CommonMark precedence: `---` immediately after a paragraph forms a
     // setext heading (h2), not a thematic break. Pin that contract.
     let tokens = parse("paragraph\n---\n");
-    assert!(tokens.iter().any(|t| matches!(t, Token::Heading(_, 2))));
+    assert!(tokens.iter().any(|t| matches!(t, Token::Heading(_, 2, _))));
     assert!(!tokens.iter().any(|t| matches!(t, Token::HorizontalRule)));
 }
 
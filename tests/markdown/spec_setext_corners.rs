@@ -6,7 +6,8 @@ use markdown2pdf::markdown::*;
 use super::common::parse;
 
 fn first_heading_level(tokens: &[Token]) -> usize {
-    let Some(Token::Heading(_, level)) = tokens.iter().find(|t| matches!(t, Token::Heading(_, _)))
+    let Some(Token::Heading(_, level, _)) =
+        tokens.iter().find(|t| matches!(t, Token::Heading(_, _, _)))
     else {
         panic!("expected Heading, got {:?}", tokens);
     };
@@ -38,7 +39,7 @@ fn single_dash_works() {
 fn setext_with_multi_line_paragraph_above() {
     // All preceding paragraph lines become the heading content.
     let tokens = parse("first\nsecond\n===\n");
-    let Some(Token::Heading(body, _)) = tokens.first() else {
+    let Some(Token::Heading(body, _, _)) = tokens.first() else {
         panic!("expected Heading, got {:?}", tokens);
     };
     let text = Token::collect_all_text(body);
@@ -50,7 +51,7 @@ fn setext_with_multi_line_paragraph_above() {
 fn blank_line_breaks_setext() {
     // Blank between text and underline disqualifies the underline.
     let tokens = parse("Title\n\n===\n");
-    assert!(!tokens.iter().any(|t| matches!(t, Token::Heading(_, _))));
+    assert!(!tokens.iter().any(|t| matches!(t, Token::Heading(_, _, _))));
 }
 
 #[test]
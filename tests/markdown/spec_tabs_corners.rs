@@ -47,7 +47,7 @@ fn tab_in_table_cell_works() {
 #[test]
 fn tab_between_atx_hashes_and_content_treated_as_space() {
     let tokens = parse("#\tHeading\n");
-    let Some(Token::Heading(body, _)) = tokens.first() else {
+    let Some(Token::Heading(body, _, _)) = tokens.first() else {
         panic!("expected Heading, got {:?}", tokens);
     };
     assert_eq!(Token::collect_all_text(body).trim(), "Heading");
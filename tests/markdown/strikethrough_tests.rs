@@ -66,6 +66,7 @@ fn tilde_inside_inline_code_is_literal() {
             language: "".to_string(),
             content: "~~not~~".to_string(),
             block: false,
+            attrs: CodeAttrs::default(),
         }]
     );
 }
@@ -100,7 +101,7 @@ fn strikethrough_inside_list_item() {
 #[test]
 fn strikethrough_inside_heading() {
     let tokens = parse("# ~~gone~~\n");
-    let Token::Heading(body, _) = &tokens[0] else {
+    let Token::Heading(body, _, _) = &tokens[0] else {
         panic!("expected Heading, got {:?}", tokens);
     };
     assert!(body.iter().any(|t| matches!(t, Token::Strikethrough(_))));
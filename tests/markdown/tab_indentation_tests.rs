@@ -47,7 +47,7 @@ fn three_leading_spaces_no_tab_keeps_heading() {
     // 3 spaces of indent before `#` is still a heading.
     let tokens = parse("   # heading");
     assert!(
-        tokens.iter().any(|t| matches!(t, Token::Heading(_, 1))),
+        tokens.iter().any(|t| matches!(t, Token::Heading(_, 1, _))),
         "expected Heading, got {}",
         Token::slice_to_compact(&tokens)
     );
@@ -58,7 +58,7 @@ fn one_space_plus_tab_before_hash_is_indented_code() {
     // 1 space + tab → 4 columns → indented code, NOT heading.
     let tokens = parse(" \t# not a heading");
     assert!(
-        !tokens.iter().any(|t| matches!(t, Token::Heading(_, _))),
+        !tokens.iter().any(|t| matches!(t, Token::Heading(_, _, _))),
         "unexpected Heading, got {}",
         Token::slice_to_compact(&tokens)
     );
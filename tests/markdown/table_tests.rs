@@ -35,11 +35,64 @@ fn basic_table() {
 }
 
 #[test]
-fn table_requires_outer_pipes() {
-    // This lexer requires outer pipes to detect tables; without them the
-    // input is paragraph text. Pin that contract.
+fn table_without_outer_pipes() {
+    // GFM makes the outer pipes optional on every row.
     let tokens = parse("a | b\n--- | ---\n1 | 2\n");
+    let (headers, _, rows) = first_table(&tokens);
+    assert_eq!(headers.len(), 2);
+    assert_eq!(Token::collect_all_text(&headers[1].content), "b");
+    assert_eq!(rows.len(), 1);
+    assert_eq!(Token::collect_all_text(&rows[0][0].content), "1");
+}
+
+#[test]
+fn pipeless_table_alignments() {
+    let tokens = parse("a | b | c | d\n:-- | :-: | --: | ---\n1 | 2 | 3 | 4\n");
+    let (_, aligns, _) = first_table(&tokens);
+    use markdown2pdf::markdown::TableAlignment::*;
+    assert!(
+        matches!(aligns[..], [Left, Center, Right, Left]),
+        "{aligns:?}"
+    );
+}
+
+#[test]
+fn compact_delimiter_row_with_outer_pipes() {
+    let tokens = parse("|a|b|c|\n|:-|:-:|-:|\n|1|2|3|\n");
+    let (_, aligns, _) = first_table(&tokens);
+    use markdown2pdf::markdown::TableAlignment::*;
+    assert!(matches!(aligns[..], [Left, Center, Right]), "{aligns:?}");
+}
+
+#[test]
+fn mixed_pipe_styles_across_rows() {
+    let tokens = parse("| a | b |\n--- | ---\n1 | 2 |\n");
+    let (headers, _, rows) = first_table(&tokens);
+    assert_eq!(headers.len(), 2);
+    assert_eq!(rows[0].len(), 2);
+}
+
+#[test]
+fn stray_pipe_in_prose_is_not_a_table() {
+    for src in [
+        "Use a | b to pipe\nthe output - then read it.\n",
+        "| not a table\nsome text - with a dash\n",
+        "a | b\n---\n",
+        "a | b | c\n--- | ---\n",
+    ] {
+        let tokens = parse(src);
+        assert!(
+            !tokens.iter().any(|t| matches!(t, Token::Table { .. })),
+            "{src:?} parsed as a table: {tokens:?}"
+        );
+    }
+}
+
+#[test]
+fn list_item_with_pipe_stays_a_list() {
+    let tokens = parse("- a | b\n- c | d\n");
     assert!(!tokens.iter().any(|t| matches!(t, Token::Table { .. })));
+    assert!(tokens.iter().any(|t| matches!(t, Token::ListItem { .. })));
 }
 
 #[test]
@@ -388,3 +441,21 @@ fn table_inside_unordered_list_item_body() {
         "table inside unordered list item must tokenize as Table"
     );
 }
+
+/// The delimiter row only ever feeds `Token::Table::aligns`: no
+/// standalone `TableAlignment` token and no `:---:` text anywhere in
+/// the stream, including the cells.
+#[test]
+fn delimiter_row_leaves_no_alignment_artifact() {
+    let tokens = parse("Intro.\n\n| a | b |\n|:---:|---:|\n| 1 | 2 |\n\nOutro.\n");
+    assert!(
+        !tokens.iter().any(|t| matches!(t, Token::TableAlignment(_))),
+        "{tokens:?}"
+    );
+    let text = Token::collect_all_text(&tokens);
+    assert!(
+        !text.contains("---"),
+        "delimiter row leaked as text: {text:?}"
+    );
+    assert!(text.contains("Outro."));
+}
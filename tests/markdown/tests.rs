@@ -16,15 +16,15 @@ fn test_headings() {
     let tests = vec![
         (
             "# H1",
-            vec![Token::Heading(vec![Token::Text("H1".to_string())], 1)],
+            vec![Token::Heading(vec![Token::Text("H1".to_string())], 1, None)],
         ),
         (
             "## H2",
-            vec![Token::Heading(vec![Token::Text("H2".to_string())], 2)],
+            vec![Token::Heading(vec![Token::Text("H2".to_string())], 2, None)],
         ),
         (
             "### H3",
-            vec![Token::Heading(vec![Token::Text("H3".to_string())], 3)],
+            vec![Token::Heading(vec![Token::Text("H3".to_string())], 3, None)],
         ),
     ];
 
@@ -75,6 +75,7 @@ fn test_code_blocks() {
                 language: "".to_string(),
                 content: "inline code".to_string(),
                 block: false,
+                attrs: CodeAttrs::default(),
             }],
         ),
         (
@@ -83,6 +84,7 @@ fn test_code_blocks() {
                 language: "rust".to_string(),
                 content: "fn main() {}".to_string(),
                 block: true,
+                attrs: CodeAttrs::default(),
             }],
         ),
     ];
@@ -251,7 +253,7 @@ This is a paragraph with *italic* and **bold** text.
 
     let tokens = parse(input);
     assert!(!tokens.is_empty());
-    assert!(matches!(tokens[0], Token::Heading(_, 1)));
+    assert!(matches!(tokens[0], Token::Heading(_, 1, _)));
     // Add more specific assertions as needed
 }
 
@@ -282,6 +284,7 @@ fn test_code_block_edge_cases() {
                 language: "".to_string(),
                 content: "empty language".to_string(),
                 block: true,
+                attrs: CodeAttrs::default(),
             }],
         ),
         (
@@ -290,6 +293,7 @@ fn test_code_block_edge_cases() {
                 language: "".to_string(),
                 content: "code with *asterisk*".to_string(),
                 block: false,
+                attrs: CodeAttrs::default(),
             }],
         ),
         (
@@ -298,6 +302,7 @@ fn test_code_block_edge_cases() {
                 language: "rust".to_string(),
                 content: "fn main() {\n    println!(\"Hello\");\n}".to_string(),
                 block: true,
+                attrs: CodeAttrs::default(),
             }],
         ),
     ];
@@ -442,7 +447,7 @@ A paragraph with `code` and [link](url).
     assert!(!tokens.is_empty());
 
     // Verify first token is a heading with emphasis
-    if let Token::Heading(content, 1) = &tokens[0] {
+    if let Token::Heading(content, 1, _) = &tokens[0] {
         assert!(
             content
                 .iter()
@@ -530,3 +535,37 @@ fn test_tables() {
         }]
     );
 }
+
+#[test]
+fn test_token_trees_compare_structurally() {
+    // Every nested type (table cells, definition-list entries) takes
+    // part in `==`, so whole trees compare and a one-character change
+    // anywhere inside shows up as a difference.
+    let table = "| a | b |\n|:--|--:|\n| 1 | 2 |\n";
+    assert_eq!(parse(table), parse(table));
+    assert_ne!(parse(table), parse("| a | b |\n|:--|--:|\n| 1 | 3 |\n"));
+    assert_ne!(parse(table), parse("| a | b |\n|---|--:|\n| 1 | 2 |\n"));
+
+    let deflist = "Term\n: Definition\n";
+    assert_eq!(parse(deflist), parse(deflist));
+    assert_ne!(parse(deflist), parse("Term\n: Definitions\n"));
+
+    assert_eq!(
+        parse("[*docs*](https://example.com \"Docs\")"),
+        vec![Token::Link {
+            content: vec![Token::Emphasis {
+                level: 1,
+                content: vec![Token::Text("docs".to_string())],
+            }],
+            url: "https://example.com".to_string(),
+            title: Some("Docs".to_string()),
+        }]
+    );
+}
+
+#[test]
+fn test_repeated_tokens_dedup() {
+    let mut tokens = [parse("# Same"), parse("# Same"), parse("# Other")].concat();
+    tokens.dedup();
+    assert_eq!(tokens, [parse("# Same"), parse("# Other")].concat());
+}
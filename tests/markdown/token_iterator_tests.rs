@@ -0,0 +1,70 @@
+use markdown2pdf::markdown::*;
+
+const MIXED: &str = "\
+# Title
+
+Intro with *emphasis* and **strong
+across a line** and a [link][ref].
+
+- tight one
+- tight two
+
+1. loose one
+
+2. loose two
+
+> quote with `code`
+
+| a | b |
+|---|---|
+| 1 | 2 |
+
+```rust
+fn main() {}
+```
+
+Closing *paragraph* with a footnote[^n].
+
+[ref]: https://example.com
+[^n]: The note.
+";
+
+#[test]
+fn iterator_yields_the_same_sequence_as_parse() {
+    let parsed = Lexer::new(MIXED.to_string()).parse().unwrap();
+    let streamed: Vec<Token> = Lexer::new(MIXED.to_string())
+        .tokens()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(streamed, parsed);
+}
+
+#[test]
+fn list_looseness_survives_chunking() {
+    let streamed: Vec<Token> = Lexer::new("- a\n\n- b\n\ntext\n".to_string())
+        .tokens()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let loose: Vec<bool> = streamed
+        .iter()
+        .filter_map(|t| match t {
+            Token::ListItem { loose, .. } => Some(*loose),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(loose, [true, true]);
+}
+
+#[test]
+fn tokens_before_an_error_are_yielded_first() {
+    // Nesting far past the lexer's depth cap is a typed error; the
+    // heading ahead of it still comes out before the error does.
+    let input = format!("# Title\n\nIntro.\n\n{} deep\n", ">".repeat(100));
+    assert!(Lexer::new(input.clone()).parse().is_err());
+
+    let mut lexer = Lexer::new(input);
+    let mut tokens = lexer.tokens();
+    assert!(matches!(tokens.next(), Some(Ok(Token::Heading(_, 1, _)))));
+    assert!(tokens.any(|t| t.is_err()));
+    assert!(tokens.next().is_none());
+}
@@ -65,7 +65,7 @@ fn valid_then_stray_emphasis() {
 #[test]
 fn stray_in_heading() {
     let tokens = parse("# heading with * stray");
-    assert!(matches!(tokens[0], Token::Heading(_, 1)));
+    assert!(matches!(tokens[0], Token::Heading(_, 1, _)));
     let text = Token::collect_all_text(&tokens);
     assert!(text.contains("*"), "got {:?}", text);
 }
@@ -121,7 +121,7 @@ fn regression_intra_word_underscore_still_text() {
 fn document_with_stray_does_not_lose_other_tokens() {
     let input = "# Title\n\nBody has * stray and `code` and [link](url).";
     let tokens = parse(input);
-    assert!(matches!(tokens[0], Token::Heading(_, 1)));
+    assert!(matches!(tokens[0], Token::Heading(_, 1, _)));
     // Code span and link must still parse despite the stray *.
     assert!(tokens.iter().any(|t| matches!(t, Token::Code { .. })));
     assert!(tokens.iter().any(|t| matches!(t, Token::Link { .. })));
@@ -21,7 +21,7 @@ fn links(tokens: &[Token]) -> Vec<(String, String)> {
                     out.push((url.clone(), Token::collect_all_text(content)));
                     walk(content, out);
                 }
-                Token::Heading(c, _)
+                Token::Heading(c, _, _)
                 | Token::StrongEmphasis(c)
                 | Token::BlockQuote(c)
                 | Token::Strikethrough(c) => walk(c, out),
@@ -0,0 +1,58 @@
+//! The `minimal` build: `--no-default-features --features minimal`.
+//! Without `system-fonts` no font name reaches the OS font
+//! directories, so conversion runs on the built-in fonts alone.
+#![cfg(all(feature = "minimal", not(feature = "system-fonts")))]
+
+use markdown2pdf::config::ConfigSource;
+use markdown2pdf::fonts::{FontConfig, find_system_font, list_available_fonts};
+
+/// `/BaseFont` of every font in the PDF, and whether any font file is
+/// embedded.
+fn fonts_of(pdf: &[u8]) -> (Vec<String>, bool) {
+    let doc = lopdf::Document::load_mem(pdf).expect("parse");
+    let mut names = Vec::new();
+    let mut embedded = false;
+    for obj in doc.objects.values() {
+        let Ok(dict) = obj.as_dict() else { continue };
+        if let Ok(name) = dict.get(b"BaseFont").and_then(|o| o.as_name()) {
+            names.push(String::from_utf8_lossy(name).into_owned());
+        }
+        embedded |= [b"FontFile".as_slice(), b"FontFile2", b"FontFile3"]
+            .iter()
+            .any(|k| dict.has(k));
+    }
+    (names, embedded)
+}
+
+#[test]
+fn font_lookup_sees_only_the_builtins() {
+    assert_eq!(list_available_fonts(), ["Courier", "Helvetica", "Times"]);
+    assert!(find_system_font("DejaVu Sans").is_none());
+}
+
+#[test]
+fn string_converts_to_bytes_with_builtin_fonts() {
+    let pdf = markdown2pdf::parse_into_bytes(
+        "# Title\n\nBody text with `code`.\n".to_string(),
+        ConfigSource::Embedded("[paragraph]\nfont_size_pt = 11.0\n"),
+        None,
+    )
+    .expect("minimal build converts");
+    assert!(pdf.starts_with(b"%PDF-"));
+    let (names, embedded) = fonts_of(&pdf);
+    assert!(
+        names.iter().any(|n| n.starts_with("Helvetica")),
+        "expected the built-in body font, got {:?}",
+        names
+    );
+    assert!(!embedded, "no font should be embedded");
+}
+
+#[test]
+fn system_font_name_falls_back_to_builtin() {
+    let fonts = FontConfig::new().with_default_font("DejaVu Sans");
+    let pdf =
+        markdown2pdf::parse_into_bytes("Hello.\n".to_string(), ConfigSource::Default, Some(&fonts))
+            .expect("unknown font name still converts");
+    assert!(!fonts_of(&pdf).1, "a system font was embedded");
+}
@@ -28,34 +28,6 @@ voluptate velit esse cillum dolore eu fugiat nulla pariatur.\n\n"
     out
 }
 
-/// All `x` values from `<x> <y> Td` ops anywhere in the decompressed
-/// PDF byte stream. `Td` writes the absolute text-line origin in PDF
-/// user space — the first column emits one cluster of x values around
-/// the left margin, each subsequent column emits its own cluster.
-fn td_xs(bytes: &[u8]) -> Vec<f32> {
-    let decoded = scan(bytes);
-    let s = String::from_utf8_lossy(&decoded);
-    let mut xs = Vec::new();
-    for line in s.lines() {
-        // `Td` lines look like `<x> <y> Td`; skip `TD`, `Tf`, `Tj`, etc.
-        let trimmed = line.trim_end();
-        if !trimmed.ends_with(" Td") {
-            continue;
-        }
-        let mut it = trimmed.split_whitespace();
-        let x = it.next();
-        let y = it.next();
-        let op = it.next();
-        if op != Some("Td") {
-            continue;
-        }
-        if let (Some(xs_), Some(_)) = (x.and_then(|t| t.parse::<f32>().ok()), y) {
-            xs.push(xs_);
-        }
-    }
-    xs
-}
-
 /// Cluster Td x values into bins ~10pt wide and return the bin
 /// centers, sorted. Two columns separated by a few-mm gap end up in
 /// well-separated bins; nested blocks (blockquote padding, code-block
@@ -614,3 +586,122 @@ Trailing text.
          col 0 right ≈ 168pt)"
     );
 }
+
+/// All `(x, y)` pairs from `<x> <y> Td` ops, in emission order.
+fn td_points(bytes: &[u8]) -> Vec<(f32, f32)> {
+    td_xs(bytes).into_iter().zip(td_ys(bytes)).collect()
+}
+
+/// A heading followed by roughly a column and a half of paragraphs:
+/// fills column 0 and spills into column 1 without leaving page 1.
+fn heading_then_body() -> String {
+    let sentence = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. Sed do \
+eiusmod tempor incididunt ut labore et dolore magna aliqua. Ut enim ad minim \
+veniam, quis nostrud exercitation ullamco laboris nisi ut aliquip. ";
+    let mut md = String::from("# Spanning Title\n\n");
+    for _ in 0..6 {
+        md.push_str(&sentence.repeat(3));
+        md.push_str("\n\n");
+    }
+    md
+}
+
+/// How far the top line of column 1 sits below the top line of
+/// column 0, in points. Column 1 is anything right of the page middle.
+fn column_one_drop(bytes: &[u8]) -> f32 {
+    let pts = td_points(bytes);
+    let top = |pred: &dyn Fn(f32) -> bool| {
+        pts.iter()
+            .filter(|(x, _)| pred(*x))
+            .map(|(_, y)| *y)
+            .fold(f32::NEG_INFINITY, f32::max)
+    };
+    let col0 = top(&|x| x < 250.0);
+    let col1 = top(&|x| x >= 250.0);
+    assert!(col1.is_finite(), "body should spill into column 1");
+    col0 - col1
+}
+
+#[test]
+fn two_column_document_renders_and_spans_a_page() {
+    let bytes = render(
+        &long_body(12),
+        r##"
+        [page]
+        columns = 2
+        column_gap_mm = 6
+        "##,
+    );
+    assert!(page_count(&bytes) >= 1);
+    assert!(distinct_column_edges(&bytes) >= 2);
+}
+
+#[test]
+fn span_columns_heading_pushes_both_columns_below_it() {
+    let columns = r##"
+        [page]
+        columns = 2
+        column_gap_mm = 8
+    "##;
+    let flowing = render(&heading_then_body(), columns);
+    let spanning = render(
+        &heading_then_body(),
+        &format!("{columns}\n[headings]\nspan_columns = true\n"),
+    );
+    assert_eq!(page_count(&flowing), 1);
+    assert_eq!(page_count(&spanning), 1);
+    // Without spanning, column 1 starts level with the heading at the
+    // top of column 0. With it, column 1 starts under the heading.
+    let flowing_drop = column_one_drop(&flowing);
+    let spanning_drop = column_one_drop(&spanning);
+    assert!(
+        spanning_drop > flowing_drop + 10.0,
+        "column 1 should start below a spanning heading \
+         (flowing drop={flowing_drop:.1}, spanning drop={spanning_drop:.1})"
+    );
+}
+
+#[test]
+fn span_columns_is_a_no_op_for_single_column_pages() {
+    let plain = render(&heading_then_body(), "");
+    let spanning = render(&heading_then_body(), "[headings]\nspan_columns = true\n");
+    assert_eq!(td_points(&plain), td_points(&spanning));
+}
+
+#[test]
+fn max_content_width_centers_a_narrow_column_on_a_landscape_page() {
+    let page = r##"
+        [page]
+        size = "A4"
+        orientation = "landscape"
+        margins = { top = 20, right = 20, bottom = 20, left = 20 }
+    "##;
+    let full = render(&long_body(3), page);
+    let narrow = render(&long_body(3), &format!("{page}\nmax_content_width = 400\n"));
+
+    // Landscape A4 is ~842pt wide; 20mm margins leave ~729pt, so a
+    // 400pt measure sits ~164pt in from each margin.
+    let margin = 20.0 * 72.0 / 25.4;
+    let inset = (841.89 - 2.0 * margin - 400.0) / 2.0;
+    let full_left = td_xs(&full).into_iter().fold(f32::INFINITY, f32::min);
+    let narrow_xs = td_xs(&narrow);
+    let narrow_left = narrow_xs.iter().copied().fold(f32::INFINITY, f32::min);
+    assert!(
+        (full_left - margin).abs() < 1.0,
+        "full left edge at {full_left}"
+    );
+    assert!(
+        (narrow_left - (margin + inset)).abs() < 1.0,
+        "narrow left edge at {narrow_left}, expected {}",
+        margin + inset
+    );
+    // Wrapping to the narrower measure takes more lines.
+    assert!(narrow_xs.len() > td_xs(&full).len());
+}
+
+#[test]
+fn max_content_width_wider_than_the_margins_changes_nothing() {
+    let plain = render(&long_body(2), "");
+    let capped = render(&long_body(2), "[page]\nmax_content_width = 5000\n");
+    assert_eq!(td_points(&plain), td_points(&capped));
+}
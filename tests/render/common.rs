@@ -65,6 +65,34 @@ pub fn scan(bytes: &[u8]) -> Vec<u8> {
     bytes.to_vec()
 }
 
+/// All `x` values from `<x> <y> Td` ops anywhere in the decompressed
+/// PDF byte stream. `Td` writes the absolute text-line origin in PDF
+/// user space — the first column emits one cluster of x values around
+/// the left margin, each subsequent column emits its own cluster.
+pub fn td_xs(bytes: &[u8]) -> Vec<f32> {
+    let decoded = scan(bytes);
+    let s = String::from_utf8_lossy(&decoded);
+    let mut xs = Vec::new();
+    for line in s.lines() {
+        // `Td` lines look like `<x> <y> Td`; skip `TD`, `Tf`, `Tj`, etc.
+        let trimmed = line.trim_end();
+        if !trimmed.ends_with(" Td") {
+            continue;
+        }
+        let mut it = trimmed.split_whitespace();
+        let x = it.next();
+        let y = it.next();
+        let op = it.next();
+        if op != Some("Td") {
+            continue;
+        }
+        if let (Some(xs_), Some(_)) = (x.and_then(|t| t.parse::<f32>().ok()), y) {
+            xs.push(xs_);
+        }
+    }
+    xs
+}
+
 /// `true` if `needle` appears anywhere in the PDF (raw structure or
 /// decompressed content).
 pub fn contains(bytes: &[u8], needle: &[u8]) -> bool {
@@ -200,25 +228,46 @@ pub fn contains_text(bytes: &[u8], needle: &str) -> bool {
 /// they run on Linux CI too and skip cleanly only when truly no
 /// system font is available.
 pub fn any_system_font() -> Option<String> {
-    const CANDIDATES: &[&str] = &[
-        "Georgia",
-        "DejaVu Sans",
-        "DejaVuSans",
-        "Liberation Sans",
-        "LiberationSans",
-        "Noto Sans",
-        "NotoSans",
-        "Arial",
-        "Helvetica",
-        "Verdana",
-        "FreeSans",
-    ];
-    CANDIDATES
+    SYSTEM_FONT_CANDIDATES
         .iter()
         .find(|name| markdown2pdf::fonts::find_system_font(name).is_some())
         .map(|s| s.to_string())
 }
 
+/// Two installed system fonts backed by different files, for tests
+/// that need to tell one face from another (body vs heading), or
+/// `None` when the host has fewer than two.
+pub fn two_system_fonts() -> Option<(String, String)> {
+    let mut found: Vec<(&str, std::path::PathBuf)> = Vec::new();
+    for name in SYSTEM_FONT_CANDIDATES {
+        if let Some(path) = markdown2pdf::fonts::find_system_font(name)
+            && found.iter().all(|(_, seen)| *seen != path)
+        {
+            found.push((name, path));
+        }
+    }
+    match found.as_slice() {
+        [(first, _), (second, _), ..] => Some((first.to_string(), second.to_string())),
+        _ => None,
+    }
+}
+
+/// System font names the helpers above probe, most portable first.
+const SYSTEM_FONT_CANDIDATES: &[&str] = &[
+    "Georgia",
+    "DejaVu Sans",
+    "DejaVuSans",
+    "Liberation Sans",
+    "LiberationSans",
+    "Liberation Serif",
+    "Noto Sans",
+    "NotoSans",
+    "Arial",
+    "Helvetica",
+    "Verdana",
+    "FreeSans",
+];
+
 /// Path to a small real JPEG generated on demand in the system temp
 /// dir. Image tests use this instead of an `examples/` fixture so
 /// they have no dependency on an uncommitted file — CI checkouts
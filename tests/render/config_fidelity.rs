@@ -197,6 +197,18 @@ fn code_block_no_weight_override_is_normalize_identical() {
     );
 }
 
+#[test]
+fn extra_blank_lines_collapse_to_one_block_break() {
+    // CommonMark: any number of blank lines between blocks is a
+    // single break. The gap comes from the block margins (and
+    // `paragraph_spacing_pt`), never from the count of blank lines.
+    for cfg in ["", "[paragraph]\nparagraph_spacing_pt = 12.0\n"] {
+        let one = render("First.\n\nSecond.\n\n# Head\n\nThird.\n", cfg);
+        let three = render("First.\n\n\n\nSecond.\n\n\n\n# Head\n\n\n\nThird.\n", cfg);
+        assert_eq!(normalize_pdf(&one), normalize_pdf(&three), "config {cfg:?}");
+    }
+}
+
 #[test]
 fn code_block_bold_with_builtin_courier_uses_courier_bold_handle() {
     // Without an external code font, [code_block].font_weight=bold
@@ -211,6 +223,45 @@ fn code_block_bold_with_builtin_courier_uses_courier_bold_handle() {
     );
 }
 
+#[test]
+fn inline_code_span_switches_to_the_monospace_font() {
+    // A backticked span inside a Helvetica paragraph must be set in
+    // Courier, not just recolored body text.
+    let with_code = render("Call `compute()` now.", "");
+    let without = render("Call compute() now.", "");
+    assert!(
+        contains_text(&with_code, "Courier"),
+        "inline code did not select the builtin Courier face"
+    );
+    assert!(
+        !contains_text(&without, "Courier"),
+        "a paragraph with no code must not reference Courier"
+    );
+}
+
+#[test]
+fn unknown_and_bare_fences_render_as_monospace_preformatted() {
+    // No highlighter knows `text` or `output`, and a bare fence has no
+    // language at all: each is still a Courier code block. Only the
+    // info string's first word is the language, so the trailing words
+    // never reach the page.
+    for fence in ["```text", "```output from the run", "```"] {
+        let bytes = render(&format!("{fence}\nexit code 0\n```\n"), "");
+        assert!(pdf_well_formed(&bytes), "{fence}");
+        assert!(contains_text(&bytes, "Courier"), "{fence}: not monospace");
+        assert!(
+            contains_text(&bytes, "exit code 0"),
+            "{fence}: body missing"
+        );
+        for word in ["text", "output", "from the run"] {
+            assert!(
+                !contains_text(&bytes, &format!("({word}")),
+                "{fence}: info string leaked {word:?}"
+            );
+        }
+    }
+}
+
 #[test]
 fn list_inside_blockquote_inherits_blockquote_text_color() {
     let md = "> Quote line.\n>\n> - first item\n> - second item\n";
@@ -232,6 +283,47 @@ fn list_inside_blockquote_inherits_blockquote_text_color() {
     );
 }
 
+#[test]
+fn list_marker_color_differs_from_item_text() {
+    let md = "1. one\n2. two\n";
+    let cfg = r##"
+        [list.ordered]
+        text_color = "#112233"
+        marker_color = "#CC2200"
+    "##;
+    let bytes = scan(&render(md, cfg));
+    let marker = rg_op(0xCC, 0x22, 0x00);
+    let text = rg_op(0x11, 0x22, 0x33);
+    assert!(
+        count_substr(&bytes, marker.as_bytes()) >= 2,
+        "expected both numbers filled with the marker color {marker:?}"
+    );
+    assert!(
+        count_substr(&bytes, text.as_bytes()) >= 2,
+        "item text should keep the list text color {text:?}"
+    );
+
+    let plain = scan(&render(md, "[list.ordered]\ntext_color = \"#112233\"\n"));
+    assert_eq!(count_substr(&plain, marker.as_bytes()), 0);
+}
+
+#[test]
+fn align_numbers_starts_every_item_text_at_one_x() {
+    let md: String = (1..=12).map(|n| format!("{n}. item\n")).collect();
+    // Item text sits right of its number, so the text origins are the
+    // rightmost `Td` x values; count how many items start there.
+    let at_rightmost = |bytes: &[u8]| {
+        let xs = td_xs(bytes);
+        let max = xs.iter().copied().fold(f32::MIN, f32::max);
+        xs.iter().filter(|x| (max - **x).abs() < 0.01).count()
+    };
+    let aligned = render(&md, "[list]\nalign_numbers = true\n");
+    assert_eq!(at_rightmost(&aligned), 12, "xs: {:?}", td_xs(&aligned));
+    // Without it, only the two-digit items (10–12) start that far in.
+    let plain = render(&md, "");
+    assert_eq!(at_rightmost(&plain), 3, "xs: {:?}", td_xs(&plain));
+}
+
 #[test]
 fn ordered_list_inside_blockquote_inherits_text_color() {
     let md = "> A quote.\n>\n> 1. one\n> 2. two\n";
@@ -971,3 +1063,42 @@ fn code_inline_padding_increases_pdf_size_meaningfully() {
         baseline.len()
     );
 }
+
+#[test]
+fn auto_contrast_lightens_dark_code_on_a_dark_background() {
+    let md = "```\nx = 1\n```\n";
+    let dark = r##"
+        [code_block]
+        background_color = "#1E1E1E"
+    "##;
+    let white = rg_op(0xFF, 0xFF, 0xFF);
+    let off = scan(&render(md, dark));
+    assert_eq!(count_substr(&off, white.as_bytes()), 0);
+    let on = scan(&render(
+        md,
+        &format!("[document]\nauto_contrast = true\n{dark}"),
+    ));
+    assert!(
+        count_substr(&on, white.as_bytes()) >= 1,
+        "dark code text on #1E1E1E should switch to white"
+    );
+}
+
+#[test]
+fn auto_contrast_keeps_a_text_color_the_config_sets() {
+    let md = "```\nx = 1\n```\n";
+    let cfg = r##"
+        [document]
+        auto_contrast = true
+
+        [code_block]
+        background_color = "#1E1E1E"
+        text_color = "#333333"
+    "##;
+    let scanned = scan(&render(md, cfg));
+    assert!(count_substr(&scanned, rg_op(0x33, 0x33, 0x33).as_bytes()) >= 1);
+    assert_eq!(
+        count_substr(&scanned, rg_op(0xFF, 0xFF, 0xFF).as_bytes()),
+        0
+    );
+}
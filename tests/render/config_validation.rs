@@ -674,3 +674,55 @@ mod config_hardening_matrix {
         );
     }
 }
+
+/// `[limits]` is a hard refusal, unlike the advisory `LargeDocument`
+/// validation warning.
+mod limits {
+    use super::*;
+    use markdown2pdf::{MdpError, parse_into_bytes};
+
+    fn try_render(md: &str, cfg: &str) -> Result<Vec<u8>, MdpError> {
+        parse_into_bytes(md.to_string(), ConfigSource::Embedded(cfg), None)
+    }
+
+    #[test]
+    fn oversized_input_is_rejected_before_lexing() {
+        let md = "word ".repeat(1_000);
+        let err = try_render(&md, "[limits]\nmax_input_bytes = 1024\n");
+        assert!(
+            matches!(err, Err(MdpError::ConfigError { .. })),
+            "expected ConfigError, got {err:?}"
+        );
+    }
+
+    #[test]
+    fn input_at_the_limit_renders() {
+        let md = "a".repeat(64);
+        let bytes = try_render(&md, "[limits]\nmax_input_bytes = 64\n").unwrap();
+        assert!(pdf_well_formed(&bytes));
+    }
+
+    #[test]
+    fn page_limit_is_enforced() {
+        let md = multi_page_markdown(200);
+        let err = try_render(&md, "[limits]\nmax_pages = 2\n");
+        assert!(
+            matches!(err, Err(MdpError::PdfError { .. })),
+            "expected PdfError, got {err:?}"
+        );
+    }
+
+    #[test]
+    fn document_within_page_limit_renders() {
+        let md = multi_page_markdown(200);
+        let bytes = render(&md, "[limits]\nmax_pages = 1000\n");
+        assert!(page_count(&bytes) > 2);
+    }
+
+    #[test]
+    fn limits_default_to_unlimited() {
+        let style = load_config_strict(ConfigSource::Default, None).unwrap();
+        assert_eq!(style.limits.max_input_bytes, None);
+        assert_eq!(style.limits.max_pages, None);
+    }
+}
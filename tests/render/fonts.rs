@@ -9,7 +9,7 @@ use markdown2pdf::config::ConfigSource;
 use markdown2pdf::fonts::{FontConfig, FontSource};
 use markdown2pdf::parse_into_bytes;
 
-use super::common::{any_system_font, scan};
+use super::common::{any_system_font, scan, two_system_fonts};
 
 /// Read every `/Ascent <number>` value emitted in the PDF.
 ///
@@ -294,3 +294,115 @@ fn fallback_font_loads_when_system_font_available() {
         "expected at least one embedded font (the fallback) with an `/Ascent` entry, got none"
     );
 }
+
+#[test]
+fn embed_fonts_false_renders_builtin_and_smaller() {
+    let Some(font) = any_system_font() else {
+        eprintln!("skip: no system font available to compare against");
+        return;
+    };
+    let md = "# Title\n\nBody text with `code` in it.".to_string();
+    let cfg = FontConfig::new().with_default_font(&font);
+    let embedded = parse_into_bytes(md.clone(), ConfigSource::Default, Some(&cfg)).unwrap();
+    let builtin = parse_into_bytes(
+        md,
+        ConfigSource::Embedded("[output]\nembed_fonts = false"),
+        Some(&cfg),
+    )
+    .unwrap();
+    assert!(
+        builtin.len() < embedded.len(),
+        "forced built-in PDF ({} bytes) must be smaller than the embedded one ({} bytes)",
+        builtin.len(),
+        embedded.len()
+    );
+    assert!(
+        !scan(&builtin).windows(8).any(|w| w == b"FontFile"),
+        "embed_fonts = false must not embed a font stream"
+    );
+}
+
+#[test]
+fn embed_fonts_true_embeds_even_for_builtin_names() {
+    if markdown2pdf::fonts::default_body_source().is_none() {
+        eprintln!("skip: no system font available to embed");
+        return;
+    }
+    // The built-in source is an explicit opt-out of auto-detection;
+    // `embed_fonts = true` overrides it.
+    let cfg = FontConfig::new().with_default_font_source(FontSource::Builtin("Helvetica"));
+    let md = "Body text.".to_string();
+    let plain = parse_into_bytes(md.clone(), ConfigSource::Default, Some(&cfg)).unwrap();
+    let forced = parse_into_bytes(
+        md,
+        ConfigSource::Embedded("[output]\nembed_fonts = true"),
+        Some(&cfg),
+    )
+    .unwrap();
+    assert!(!scan(&plain).windows(8).any(|w| w == b"FontFile"));
+    assert!(
+        scan(&forced).windows(8).any(|w| w == b"FontFile"),
+        "embed_fonts = true must embed a font stream"
+    );
+}
+
+#[test]
+fn fonts_map_embeds_a_separate_heading_font() {
+    let Some((body, heading)) = two_system_fonts() else {
+        eprintln!("skip: need two system fonts to tell heading and body apart");
+        return;
+    };
+    let md = "# Heading\n\nBody text.".to_string();
+    let font_files = |cfg: FontConfig| {
+        let bytes = parse_into_bytes(md.clone(), ConfigSource::Default, Some(&cfg)).unwrap();
+        scan(&bytes)
+            .windows(9)
+            .filter(|w| *w == b"/FontFile")
+            .count()
+    };
+    let body_only = font_files(FontConfig::new().with_default_font(&body));
+    let mapped = font_files(
+        FontConfig::new()
+            .with_default_font(&body)
+            .with_heading_font(&heading),
+    );
+    assert!(
+        mapped > body_only,
+        "a distinct heading font must embed its own face ({} vs {})",
+        mapped,
+        body_only
+    );
+    // Naming the body font for headings loads nothing extra.
+    let same = font_files(
+        FontConfig::new()
+            .with_default_font(&body)
+            .with_heading_font(&body),
+    );
+    assert_eq!(same, body_only);
+}
+
+#[test]
+fn config_fonts_section_applies_without_a_font_config() {
+    let Some((body, heading)) = two_system_fonts() else {
+        eprintln!("skip: need two system fonts to tell heading and body apart");
+        return;
+    };
+    let md = "# Heading\n\nBody text.".to_string();
+    let font_files = |config: &str| {
+        let bytes = parse_into_bytes(md.clone(), ConfigSource::Embedded(config), None).unwrap();
+        scan(&bytes)
+            .windows(9)
+            .filter(|w| *w == b"/FontFile")
+            .count()
+    };
+    let body_only = font_files(&format!("[fonts]\ndefault_font = \"{body}\"\n"));
+    let mapped = font_files(&format!(
+        "[fonts]\ndefault_font = \"{body}\"\n[fonts.map]\nheading = \"{heading}\"\n"
+    ));
+    assert!(
+        mapped > body_only,
+        "the config's [fonts.map] must reach the library path ({} vs {})",
+        mapped,
+        body_only
+    );
+}
@@ -18,6 +18,32 @@ fn highlight_paints_a_background_rect() {
     );
 }
 
+#[test]
+fn html_mark_paints_the_same_background_as_equals() {
+    let plain = render("Some important text.", "");
+    let marked = render("Some <mark>important</mark> text.", "");
+    assert!(count_rect_ops(&marked) > count_rect_ops(&plain));
+    assert!(contains_text(&marked, "(important)"));
+    assert!(!contains_text(&marked, "mark>"), "the tags must not render");
+    assert_eq!(
+        count_rect_ops(&marked),
+        count_rect_ops(&render("Some ==important== text.", "")),
+    );
+}
+
+#[test]
+fn html_kbd_renders_boxed_through_code_inline() {
+    let cfg = "[code_inline]\nbackground_color = \"#EEEEEE\"\n";
+    let plain = render("Press Ctrl now.", cfg);
+    let boxed = render("Press <kbd>Ctrl</kbd> now.", cfg);
+    assert!(
+        count_rect_ops(&boxed) > count_rect_ops(&plain),
+        "<kbd> must draw the [code_inline] box"
+    );
+    assert!(contains_text(&boxed, "(Ctrl)"));
+    assert!(!contains_text(&boxed, "kbd>"));
+}
+
 #[test]
 fn unterminated_highlight_adds_no_rect() {
     let plain = render("Some important text.", "");
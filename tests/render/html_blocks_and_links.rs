@@ -3,8 +3,8 @@
 //! (with an optional `title` tooltip), and `<div>` / `<section>` /
 //! `<figure>` / `<figcaption>` block wrappers drop out so their
 //! children render normally. Everything outside that subset still
-//! falls through as literal text — the renderer never executes or
-//! understands arbitrary HTML.
+//! falls through as literal text by default (`[html] mode = "raw"`) —
+//! the renderer never executes or understands arbitrary HTML.
 
 use super::common::*;
 use lopdf::{Document, Object};
@@ -537,3 +537,64 @@ fn empty_anchor_body_still_emits_annotation() {
     // the renderer is free to skip degenerate cases later.
     assert!(link_annotation_count(&bytes) <= 1);
 }
+
+/// A `<div>` wrapping a block tag the renderer has no meaning for:
+/// the wrapper unwraps and the inner `<aside>` reaches `[html] mode`.
+const UNINTERPRETED_DIV: &str = "<div>\n<aside>Boxed <b>aside</b> text</aside>\n</div>\n\nAfter.\n";
+
+fn html_mode(mode: &str) -> String {
+    format!("[html]\nmode = \"{mode}\"\n")
+}
+
+#[test]
+fn html_mode_raw_keeps_the_block_source() {
+    for cfg in ["".to_string(), html_mode("raw")] {
+        let bytes = render(UNINTERPRETED_DIV, &cfg);
+        assert!(contains_text(&bytes, "<aside>"), "config {cfg:?}");
+        assert!(contains_text(&bytes, "After."));
+    }
+}
+
+#[test]
+fn html_mode_ignore_drops_the_block() {
+    let bytes = render(UNINTERPRETED_DIV, &html_mode("ignore"));
+    assert!(pdf_well_formed(&bytes));
+    assert!(!contains_text(&bytes, "aside"));
+    assert!(!contains_text(&bytes, "Boxed"));
+    assert!(contains_text(&bytes, "After."));
+}
+
+#[test]
+fn html_mode_comment_drops_the_block() {
+    let bytes = render(UNINTERPRETED_DIV, &html_mode("comment"));
+    assert!(pdf_well_formed(&bytes));
+    assert!(!contains_text(&bytes, "Boxed"));
+    assert!(contains_text(&bytes, "After."));
+}
+
+#[test]
+fn html_mode_strip_tags_keeps_only_the_text() {
+    let bytes = render(UNINTERPRETED_DIV, &html_mode("strip_tags"));
+    assert!(pdf_well_formed(&bytes));
+    assert!(contains_text(&bytes, "Boxed aside text"));
+    assert!(!contains_text(&bytes, "<aside>"));
+    assert!(!contains_text(&bytes, "<b>"));
+    assert!(contains_text(&bytes, "After."));
+}
+
+#[test]
+fn html_mode_drops_uninterpreted_inline_tags_but_not_their_text() {
+    for mode in ["ignore", "comment", "strip_tags"] {
+        let bytes = render("text <weird>inside</weird> more\n", &html_mode(mode));
+        assert!(!contains_text(&bytes, "<weird>"), "mode {mode}");
+        assert!(!contains_text(&bytes, "</weird>"), "mode {mode}");
+        assert!(contains_text(&bytes, "inside"), "mode {mode}");
+    }
+    // Tags with a meaning are untouched by the mode.
+    let bytes = render(
+        "H<sub>2</sub>O <a href=\"https://example.com\">x</a>\n",
+        &html_mode("ignore"),
+    );
+    assert!(!contains_text(&bytes, "<sub>"));
+    assert_eq!(link_annotation_count(&bytes), 1);
+}
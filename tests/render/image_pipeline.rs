@@ -87,6 +87,62 @@ mod valid_images {
     }
 }
 
+mod inline_images {
+    use super::*;
+
+    #[test]
+    fn image_inside_a_line_is_drawn_not_replaced_by_alt_text() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(16, 16, image::Rgb([30, 30, 30])));
+        let p = write_temp(&img, ImageFormat::Png, "inline_icon");
+        let bytes = render_md(&format!(
+            "Click the ![gear]({}) icon to open settings.\n",
+            p
+        ));
+        assert!(pdf_well_formed(&bytes));
+        assert!(contains(&bytes, b"/Subtype /Image") || contains(&bytes, b"/Subtype/Image"));
+        assert!(!contains_text(&bytes, "[image: gear]"));
+        assert!(contains_text(&bytes, "Click"));
+        assert!(contains_text(&bytes, "settings."));
+        let _ = std::fs::remove_file(&p);
+    }
+
+    #[test]
+    fn repeated_inline_image_is_embedded_once() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(16, 16, image::Rgb([200, 30, 30])));
+        let p = write_temp(&img, ImageFormat::Png, "inline_repeat");
+        let bytes = render_md(&format!(
+            "A ![dot]({p}) and ![dot]({p}) twice.\n\n# Big ![dot]({p})\n"
+        ));
+        assert!(pdf_well_formed(&bytes));
+        let images =
+            count_substr(&bytes, b"/Subtype /Image") + count_substr(&bytes, b"/Subtype/Image");
+        assert_eq!(images, 1);
+        let _ = std::fs::remove_file(&p);
+    }
+
+    #[test]
+    fn linked_inline_image_keeps_its_link() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(40, 12, image::Rgb([40, 160, 60])));
+        let p = write_temp(&img, ImageFormat::Png, "inline_badge");
+        let bytes = render_md(&format!(
+            "Status: [![build]({})](https://ci.example.com/job) today.\n",
+            p
+        ));
+        assert!(pdf_well_formed(&bytes));
+        assert!(contains(&bytes, b"/Subtype /Image") || contains(&bytes, b"/Subtype/Image"));
+        assert!(contains(&bytes, b"/URI"));
+        assert!(contains(&bytes, b"https://ci.example.com/job"));
+        let _ = std::fs::remove_file(&p);
+    }
+
+    #[test]
+    fn missing_inline_image_keeps_its_alt_text() {
+        let bytes = render_md("See ![missing icon](does-not-exist-inline.png) here.\n");
+        assert!(pdf_well_formed(&bytes));
+        assert!(contains_text(&bytes, "[image: missing icon]"));
+    }
+}
+
 mod degenerate_and_hostile {
     use super::*;
 
@@ -207,6 +263,210 @@ mod html_img_paths {
     }
 }
 
+/// `[image] max_dpi` and the `parse_into_bytes_budget` size budget.
+/// The fixture is noise so image compression can't hide the pixel
+/// count: its size tracks the embedded resolution.
+mod size_budget {
+    use super::*;
+    use markdown2pdf::config::ConfigSource;
+    use markdown2pdf::fonts::{FontConfig, FontSource};
+
+    fn noisy_photo(name: &str) -> String {
+        let mut seed = 0x2545_f491_u32;
+        let img = RgbImage::from_fn(1200, 900, |_, _| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let [r, g, b, _] = seed.to_le_bytes();
+            image::Rgb([r, g, b])
+        });
+        write_temp(&DynamicImage::ImageRgb8(img), ImageFormat::Png, name)
+    }
+
+    #[test]
+    fn max_dpi_downsamples_dense_images() {
+        let p = noisy_photo("max_dpi");
+        let md = format!("![photo]({})\n", p);
+        let full = render(&md, "");
+        let capped = render(&md, "[image]\nmax_dpi = 72\n");
+        assert!(pdf_well_formed(&capped));
+        assert!(!contains(&capped, b"[image: photo]"));
+        assert!(
+            capped.len() * 4 < full.len(),
+            "72 dpi cap should shrink the image stream: {} vs {}",
+            capped.len(),
+            full.len()
+        );
+        let _ = std::fs::remove_file(&p);
+    }
+
+    #[test]
+    fn budget_rerenders_until_the_pdf_fits() {
+        let p = noisy_photo("budget");
+        let md = format!("# Site visit\n\n![photo]({})\n", p);
+        let cfg = FontConfig::new().with_default_font_source(FontSource::Builtin("Helvetica"));
+        let full =
+            markdown2pdf::parse_into_bytes(md.clone(), ConfigSource::Default, Some(&cfg)).unwrap();
+        let budget = full.len() / 2;
+        let fitted =
+            markdown2pdf::parse_into_bytes_budget(md, ConfigSource::Default, Some(&cfg), budget)
+                .unwrap();
+        assert!(pdf_well_formed(&fitted));
+        assert!(
+            fitted.len() <= budget,
+            "budget {} not met: {} bytes",
+            budget,
+            fitted.len()
+        );
+        let _ = std::fs::remove_file(&p);
+    }
+
+    #[test]
+    fn budget_already_met_renders_once_unchanged() {
+        let cfg = FontConfig::new().with_default_font_source(FontSource::Builtin("Helvetica"));
+        let md = "# Short\n\nNo images here.\n".to_string();
+        let plain =
+            markdown2pdf::parse_into_bytes(md.clone(), ConfigSource::Default, Some(&cfg)).unwrap();
+        let fitted = markdown2pdf::parse_into_bytes_budget(
+            md,
+            ConfigSource::Default,
+            Some(&cfg),
+            usize::MAX,
+        )
+        .unwrap();
+        assert_eq!(plain.len(), fitted.len());
+    }
+}
+
+/// `[image] gallery`: a line of images laid out as a grid.
+mod gallery {
+    use super::*;
+    use lopdf::content::Content;
+
+    /// Page-space origin of every image draw on page one, in order.
+    /// Tracks the full transformation matrix through `q` / `Q` / `cm`.
+    fn image_origins(bytes: &[u8]) -> Vec<(f32, f32)> {
+        let doc = lopdf::Document::load_mem(bytes).expect("rendered PDF must parse");
+        let page = doc.page_iter().next().expect("one page");
+        let content = Content::decode(&doc.get_page_content(page)).expect("content decodes");
+        let num = |o: &lopdf::Object| match o {
+            lopdf::Object::Integer(i) => *i as f32,
+            lopdf::Object::Real(r) => *r,
+            _ => 0.0,
+        };
+        let mut ctm = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0f32];
+        let mut stack = Vec::new();
+        let mut origins = Vec::new();
+        for op in content.operations {
+            match op.operator.as_str() {
+                "q" => stack.push(ctm),
+                "Q" => ctm = stack.pop().unwrap_or(ctm),
+                "cm" if op.operands.len() == 6 => {
+                    let m: Vec<f32> = op.operands.iter().map(num).collect();
+                    let c = ctm;
+                    ctm = [
+                        m[0] * c[0] + m[1] * c[2],
+                        m[0] * c[1] + m[1] * c[3],
+                        m[2] * c[0] + m[3] * c[2],
+                        m[2] * c[1] + m[3] * c[3],
+                        m[4] * c[0] + m[5] * c[2] + c[4],
+                        m[4] * c[1] + m[5] * c[3] + c[5],
+                    ];
+                }
+                "Do" => origins.push((ctm[4], ctm[5])),
+                _ => {}
+            }
+        }
+        origins
+    }
+
+    fn three_images() -> (String, Vec<String>) {
+        let paths: Vec<String> = (0..3)
+            .map(|i| {
+                let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(
+                    300,
+                    200,
+                    image::Rgb([i * 80, 60, 90]),
+                ));
+                write_temp(&img, ImageFormat::Png, &format!("gallery_{i}"))
+            })
+            .collect();
+        let md = format!(
+            "![one]({}) ![two]({}) ![three]({})\n",
+            paths[0], paths[1], paths[2]
+        );
+        (md, paths)
+    }
+
+    #[test]
+    fn three_images_fill_a_two_per_row_grid() {
+        let (md, paths) = three_images();
+        let bytes = render(&md, "[image]\ngallery = true\ngallery_columns = 2\n");
+        assert!(pdf_well_formed(&bytes));
+        let origins = image_origins(&bytes);
+        assert_eq!(origins.len(), 3, "every image is drawn: {origins:?}");
+        let [(x0, y0), (x1, y1), (x2, y2)] = [origins[0], origins[1], origins[2]];
+        assert!(
+            (y0 - y1).abs() < 0.5,
+            "first row shares a baseline: {origins:?}"
+        );
+        assert!(x1 > x0, "second image sits right of the first: {origins:?}");
+        assert!(y2 < y0, "third image starts the second row: {origins:?}");
+        assert!(
+            (x2 - x0).abs() < 0.5,
+            "second row starts in the first cell: {origins:?}"
+        );
+        for p in paths {
+            let _ = std::fs::remove_file(p);
+        }
+    }
+
+    #[test]
+    fn gallery_off_keeps_images_inline() {
+        let (md, paths) = three_images();
+        let bytes = render(&md, "");
+        assert!(image_origins(&bytes).is_empty());
+        assert!(contains_text(&bytes, "[image: one]"));
+        for p in paths {
+            let _ = std::fs::remove_file(p);
+        }
+    }
+}
+
+mod svg_images {
+    use super::*;
+
+    fn write_svg(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("m2p_w7e_{}.svg", name));
+        std::fs::write(
+            &path,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"120\" height=\"40\">\
+             <rect width=\"120\" height=\"40\" fill=\"#D9381E\"/></svg>",
+        )
+        .expect("write test svg");
+        path.to_string_lossy().to_string()
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn svg_rasterizes_and_embeds() {
+        let p = write_svg("embed");
+        let bytes = render_md(&format!("![logo]({})\n", p));
+        assert!(pdf_well_formed(&bytes));
+        assert!(contains(&bytes, b"/Subtype /Image") || contains(&bytes, b"/Subtype/Image"));
+        assert!(!contains(&bytes, b"[image: logo]"));
+        let _ = std::fs::remove_file(&p);
+    }
+
+    #[cfg(not(feature = "svg"))]
+    #[test]
+    fn svg_without_feature_falls_back_to_alt() {
+        let p = write_svg("no_feature");
+        let bytes = render_md(&format!("![logo]({})\n", p));
+        assert!(pdf_well_formed(&bytes));
+        assert!(contains_text(&bytes, "[image: logo]"));
+        let _ = std::fs::remove_file(&p);
+    }
+}
+
 /// `[security]` image-confinement policy (Plan 005). The defaults
 /// (`image_root` unset, `allow_absolute_image_paths = true`,
 /// `allow_remote_images = true`) preserve the historical, unconfined
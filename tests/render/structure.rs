@@ -240,6 +240,160 @@ mod multi_page_documents {
         let bytes = render(&md, "");
         validate(&bytes);
     }
+
+    #[test]
+    fn separately_lexed_fragments_render_into_one_document() {
+        use markdown2pdf::fonts::{FontConfig, FontSource};
+        use markdown2pdf::markdown::Lexer;
+        use markdown2pdf::render::DocumentBuilder;
+        use markdown2pdf::styling::ResolvedStyle;
+
+        let lex = |md: &str| Lexer::new(md.to_string()).parse().unwrap();
+        let mut doc = DocumentBuilder::new();
+        doc.push_tokens(lex("# Part one\n\nFirst fragment body"))
+            .push_page_break()
+            .push_tokens(lex("Second fragment body"))
+            .push_tokens(lex("Third fragment body"));
+        let fonts = FontConfig::new().with_default_font_source(FontSource::Builtin("Helvetica"));
+        let bytes = scan(
+            &doc.render_to_bytes(ResolvedStyle::default(), Some(&fonts))
+                .unwrap(),
+        );
+
+        assert_eq!(validate(&bytes), 2, "the page break splits the fragments");
+        assert!(contains_text(&bytes, "First fragment body"));
+        // Consecutive fragments stay separate paragraphs instead of
+        // running on into one line.
+        assert!(contains_text(&bytes, "(Second fragment body)"));
+        assert!(contains_text(&bytes, "(Third fragment body)"));
+    }
+}
+
+mod single_tokens {
+    use super::*;
+
+    #[test]
+    fn single_tokens_render_on_their_own() {
+        use markdown2pdf::fonts::{FontConfig, FontSource};
+        use markdown2pdf::markdown::{TableAlignment, TableCell, Token};
+        use markdown2pdf::render::render_token_to_bytes;
+        use markdown2pdf::styling::ResolvedStyle;
+
+        let fonts = FontConfig::new().with_default_font_source(FontSource::Builtin("Helvetica"));
+        let render_one = |token: Token| {
+            scan(&render_token_to_bytes(token, ResolvedStyle::default(), Some(&fonts)).unwrap())
+        };
+
+        let heading = render_one(Token::Heading(
+            vec![Token::Text("Lone heading".into())],
+            2,
+            None,
+        ));
+        assert_eq!(validate(&heading), 1);
+        assert!(contains_text(&heading, "(Lone heading)"));
+
+        let cell = |text: &str| TableCell::new(vec![Token::Text(text.into())]);
+        let table = render_one(Token::Table {
+            headers: vec![cell("Name"), cell("Score")],
+            aligns: vec![TableAlignment::Left, TableAlignment::Right],
+            rows: vec![vec![cell("Alice"), cell("91")]],
+        });
+        assert_eq!(validate(&table), 1);
+        for text in ["(Name)", "(Score)", "(Alice)", "(91)"] {
+            assert!(contains_text(&table, text), "missing {text}");
+        }
+    }
+}
+
+mod pdf_include {
+    use super::*;
+
+    /// A one-page PDF on disk to splice in, rendered by this crate.
+    fn one_page_fixture(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "m2p_pdf_include_{}_{}.pdf",
+            name,
+            std::process::id()
+        ));
+        std::fs::write(&path, render("Fixture page.\n", "")).unwrap();
+        path
+    }
+
+    fn page_texts(bytes: &[u8]) -> Vec<String> {
+        let doc = parse(bytes);
+        doc.get_pages()
+            .values()
+            .map(|&id| String::from_utf8_lossy(&doc.get_page_content(id).unwrap()).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn fenced_include_splices_the_pages_in_place() {
+        let fixture = one_page_fixture("fence");
+        let md = format!(
+            "Intro.\n\n```pdf-include\n{}\n```\n\nOutro.\n",
+            fixture.display()
+        );
+        let without = render("Intro.\n\nOutro.\n", "");
+        let bytes = render(&md, "");
+        std::fs::remove_file(&fixture).ok();
+
+        assert_eq!(validate(&without), 1);
+        assert_eq!(validate(&bytes), 3);
+        let pages = page_texts(&bytes);
+        assert!(pages[0].contains("Intro."));
+        assert!(pages[1].contains("Fixture page."));
+        assert!(pages[2].contains("Outro."));
+    }
+
+    #[test]
+    fn standalone_pdf_image_reference_is_included() {
+        let fixture = one_page_fixture("image");
+        let md = format!("Intro.\n\n![appendix]({})\n", fixture.display());
+        let bytes = render(&md, "");
+        std::fs::remove_file(&fixture).ok();
+
+        assert_eq!(validate(&bytes), 2);
+        assert!(page_texts(&bytes)[1].contains("Fixture page."));
+    }
+
+    #[test]
+    fn unreadable_include_falls_back_to_a_link() {
+        let md = "Intro.\n\n```pdf-include\nno/such/appendix.pdf\n```\n";
+        let bytes = render(md, "");
+        assert_eq!(validate(&bytes), 1);
+        assert!(contains_text(&bytes, "no/such/appendix.pdf"));
+        assert!(contains(&bytes, b"/URI"));
+    }
+
+    #[test]
+    fn included_pages_count_towards_toc_and_footer_numbers() {
+        let fixture = one_page_fixture("numbering");
+        let md = format!(
+            "Intro.\n\n```pdf-include\n{}\n```\n\n# Appendix\n\nOutro.\n",
+            fixture.display()
+        );
+        let bytes = render(
+            &md,
+            r##"
+            [toc]
+            enabled = true
+
+            [footer]
+            right = "{page}/{total_pages}"
+            "##,
+        );
+        std::fs::remove_file(&fixture).ok();
+
+        // TOC, intro, the included page, then the appendix.
+        assert_eq!(validate(&bytes), 4);
+        let pages = page_texts(&bytes);
+        assert!(pages[0].contains("(Appendix)"));
+        assert!(pages[0].contains("(4)"), "TOC should point at page 4");
+        assert!(pages[0].contains("(1/4)"));
+        assert!(pages[2].contains("Fixture page."));
+        assert!(pages[3].contains("(4/4)"));
+    }
 }
 
 mod feature_combinations {
@@ -257,6 +411,109 @@ mod feature_combinations {
         validate(&bytes);
     }
 
+    /// `x` of the last `Td` before the first line that shows `needle`.
+    fn text_x(bytes: &[u8], needle: &str) -> Option<f32> {
+        text_pos(bytes, needle).map(|(x, _)| x)
+    }
+
+    /// `(x, y)` of the last `Td` before the first line that shows
+    /// `needle`.
+    fn text_pos(bytes: &[u8], needle: &str) -> Option<(f32, f32)> {
+        let text = String::from_utf8_lossy(&scan(bytes)).into_owned();
+        let mut pos = None;
+        for line in text.lines() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if let [px, py, "Td"] = parts[..] {
+                pos = px.parse().ok().zip(py.parse().ok());
+            }
+            if line.contains(&format!("({needle})")) {
+                return pos;
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn table_inside_list_item_and_blockquote() {
+        let top = render("| Qcell | Rcell |\n| --- | --- |\n| Scell | Tcell |\n", "");
+        let in_list = render(
+            "- item with a table:\n\n  | Qcell | Rcell |\n  | --- | --- |\n  | Scell | Tcell |\n",
+            "",
+        );
+        let in_quote = render(
+            "> quoted table:\n>\n> | Qcell | Rcell |\n> | --- | --- |\n> | Scell | Tcell |\n",
+            "",
+        );
+        for bytes in [&in_list, &in_quote] {
+            validate(bytes);
+            for cell in ["Qcell", "Rcell", "Scell", "Tcell"] {
+                assert!(contains_text(bytes, cell), "missing cell {cell}");
+            }
+            assert!(
+                !contains_text(bytes, "(---"),
+                "table fell back to literal pipes"
+            );
+        }
+        // The nested tables sit at the container's indent, not the margin.
+        let base = text_x(&top, "Qcell").expect("top-level cell");
+        for bytes in [&in_list, &in_quote] {
+            let x = text_x(bytes, "Qcell").expect("nested cell");
+            assert!(x > base + 5.0, "nested table not indented: {x} vs {base}");
+        }
+    }
+
+    #[test]
+    fn code_block_in_a_list_inside_a_blockquote() {
+        let top = render("```\nlet_answer\n```\n", "");
+        let nested = render(
+            "> - Alpha\n>\n>   ```\n>   let_answer\n>   ```\n> - Beta\n",
+            "",
+        );
+        validate(&nested);
+        for needle in ["Alpha", "Beta", "let_answer"] {
+            assert!(contains_text(&nested, needle), "missing {needle}");
+        }
+        assert!(!contains_text(&nested, "```"), "fence leaked as text");
+        // The code sits under the item text: quote indent plus the
+        // bullet column, well right of a top-level code block.
+        let base = text_x(&top, "let_answer").expect("top-level code");
+        let item = text_x(&nested, "Alpha").expect("item text");
+        let code = text_x(&nested, "let_answer").expect("nested code");
+        assert!(code > base + 10.0, "code not indented: {code} vs {base}");
+        assert!(code >= item, "code left of its item: {code} vs {item}");
+    }
+
+    #[test]
+    fn loose_item_paragraph_after_a_blank_line_starts_its_own_line() {
+        let bytes = render("- Firstpara\n\n  Secondpara\n- Thirdpara\n", "");
+        validate(&bytes);
+        let (x1, y1) = text_pos(&bytes, "Firstpara").expect("first paragraph");
+        let (x2, y2) = text_pos(&bytes, "Secondpara").expect("second paragraph");
+        let (_, y3) = text_pos(&bytes, "Thirdpara").expect("next item");
+        assert!(
+            y2 < y1 - 1.0,
+            "second paragraph on the item's line: {y2} vs {y1}"
+        );
+        assert!(y3 < y2 - 1.0, "next item above the paragraph: {y3} vs {y2}");
+        assert!(
+            (x2 - x1).abs() < 1.0,
+            "second paragraph not under the item text: {x2} vs {x1}"
+        );
+    }
+
+    #[test]
+    fn html_block_in_a_list_item_is_kept_and_indented() {
+        let top = render("<div>\nboxed_html\n</div>\n", "");
+        let nested = render("- Item\n\n  <div>\n  boxed_html\n  </div>\n- Next\n", "");
+        validate(&nested);
+        for needle in ["Item", "boxed_html", "Next"] {
+            assert!(contains_text(&nested, needle), "missing {needle}");
+        }
+        let base = text_x(&top, "boxed_html").expect("top-level html");
+        let html = text_x(&nested, "boxed_html").expect("nested html");
+        assert!(html > base + 10.0, "html not indented: {html} vs {base}");
+    }
+
     #[test]
     fn nested_lists() {
         let md = "\
@@ -658,6 +915,44 @@ mod document_info {
         assert_eq!(author_text, "Test Author");
     }
 
+    #[test]
+    fn frontmatter_renders_as_a_metadata_table_when_enabled() {
+        let md = "---\ntitle: Quarterly Report\nauthor: Jane Doe\ndate: 2024-05-01\n---\nBody.\n";
+        assert!(!contains_text(&render(md, ""), "Quarterly Report"));
+
+        let bytes = render(md, "[metadata]\nrender_table = true\n");
+        let text = String::from_utf8_lossy(&scan(&bytes)).to_string();
+        // Search from the header cell on, so `/CreationDate` and the
+        // like elsewhere in the file don't count.
+        let start = text.find("Field").expect("metadata table header");
+        let at = |needle: &str| {
+            start
+                + text[start..]
+                    .find(needle)
+                    .unwrap_or_else(|| panic!("{needle:?} missing from the metadata table"))
+        };
+        // One row per key in the default order, then the body.
+        assert!(at("Title") < at("Quarterly Report"));
+        assert!(at("Title") < at("Quarterly Report"));
+        assert!(at("Quarterly Report") < at("Author"));
+        assert!(at("Author") < at("Jane Doe"));
+        assert!(at("Jane Doe") < at("Date"));
+        assert!(at("Date") < at("2024-05-01"));
+        assert!(at("2024-05-01") < at("Body."));
+    }
+
+    #[test]
+    fn metadata_table_keys_pick_and_order_the_rows() {
+        let md = "---\ntitle: Quarterly Report\nauthor: Jane Doe\ndate: 2024-05-01\n---\nBody.\n";
+        let bytes = render(
+            md,
+            "[metadata]\nrender_table = true\ntable_keys = [\"date\", \"title\"]\n",
+        );
+        let text = String::from_utf8_lossy(&scan(&bytes)).to_string();
+        assert!(text.find("2024-05-01").unwrap() < text.find("Quarterly Report").unwrap());
+        assert!(!text.contains("Jane Doe"));
+    }
+
     fn decode_pdf_text(obj: &Object) -> Option<String> {
         match obj {
             Object::String(bytes, _) => {
@@ -752,3 +1047,39 @@ mod document_language {
         assert_eq!(catalog_lang(&bytes).as_deref(), Some("en-GB"));
     }
 }
+
+mod encryption {
+    use markdown2pdf::config::ConfigSource;
+    use markdown2pdf::fonts::{FontConfig, FontSource};
+
+    /// Raw renderer output: `render` re-saves through lopdf, which
+    /// can't open a file locked with a user password.
+    fn render_raw(md: &str, cfg: &str) -> Vec<u8> {
+        let fonts = FontConfig::new().with_default_font_source(FontSource::Builtin("Helvetica"));
+        markdown2pdf::parse_into_bytes(md.to_string(), ConfigSource::Embedded(cfg), Some(&fonts))
+            .expect("render must succeed")
+    }
+
+    fn has(bytes: &[u8], needle: &[u8]) -> bool {
+        bytes.windows(needle.len()).any(|w| w == needle)
+    }
+
+    #[test]
+    fn user_password_writes_an_encrypted_pdf() {
+        let bytes = render_raw(
+            "# Quarterly numbers\n\nConfidential.\n",
+            "[output]\npassword = \"secret\"\npermissions = [\"print\"]\n",
+        );
+        assert!(bytes.starts_with(b"%PDF-"));
+        assert!(has(&bytes, b"/Encrypt"), "trailer has no /Encrypt");
+        assert!(has(&bytes, b"/Standard"), "no standard security handler");
+        assert!(has(&bytes, b"/AESV2"), "streams are not AES-encrypted");
+        assert!(!has(&bytes, b"secret"), "password leaked into the file");
+    }
+
+    #[test]
+    fn no_password_leaves_the_pdf_unencrypted() {
+        let bytes = render_raw("# Open\n", "[output]\npermissions = [\"print\"]\n");
+        assert!(!has(&bytes, b"/Encrypt"));
+    }
+}
@@ -47,6 +47,21 @@ fn heading_with_border_bottom_emits_a_stroke() {
     assert!(has_stroke, "expected a stroke op for the heading border");
 }
 
+#[test]
+fn heading_transform_recases_heading_text_only() {
+    let md = "# the guide to pdf output\n\nbody text stays as written.\n";
+    let upper = render(md, "[headings]\ntransform = \"upper\"\n");
+    assert!(contains_text(&upper, "THE GUIDE TO PDF OUTPUT"));
+    assert!(contains_text(&upper, "body text stays as written."));
+
+    let title = render(md, "[headings]\ntransform = \"title\"\n");
+    assert!(contains_text(&title, "The Guide to Pdf Output"));
+    assert!(contains_text(&title, "body text stays as written."));
+
+    let plain = render(md, "");
+    assert!(contains_text(&plain, "the guide to pdf output"));
+}
+
 #[test]
 fn code_block_padding_shifts_text_inward() {
     let no_pad = render(
@@ -124,6 +139,143 @@ fn table_alignment_does_not_crash() {
     assert!(contains(&bytes, b"%%EOF"));
 }
 
+#[test]
+fn table_delimiter_row_is_not_drawn() {
+    let md = "\
+| Name | Score | Grade |
+|:-----|:-----:|------:|
+| Alice | 91 | A |
+";
+    let bytes = render(md, "");
+    // The delimiter row sets alignment only; none of it is drawn.
+    assert!(!contains(&bytes, b":---"), "delimiter row rendered as text");
+    assert!(!contains(&bytes, b"---:"), "delimiter row rendered as text");
+    assert!(contains(&bytes, b"(Alice)"));
+}
+
+#[test]
+fn centered_table_column_centers_every_wrapped_line() {
+    let long = "centered cell text ".repeat(30);
+    let distinct_xs = |delim: &str| {
+        let bytes = render(&format!("| Head |\n|{delim}|\n| {long}end |\n"), "");
+        let mut xs: Vec<i32> = td_xs(&bytes).iter().map(|x| x.round() as i32).collect();
+        xs.sort();
+        xs.dedup();
+        xs.len()
+    };
+    // Flush left, every line starts at the cell edge; centered, the
+    // short last line starts further in than the full ones.
+    assert_eq!(distinct_xs(":--"), 1);
+    assert!(distinct_xs(":-:") >= 3);
+}
+
+#[test]
+fn wide_table_columns_stay_on_the_page() {
+    let cell = "Incomprehensibilities";
+    let row = |c: &str| format!("|{}\n", format!(" {} |", c).repeat(8));
+    let md = format!("{}{}{}", row("Head"), row("---"), row(cell));
+    // Letter with 16mm margins: the body ends near x = 566.6pt.
+    for cfg in [
+        "",
+        "[table]\nmin_column_width_pt = 40.0\n",
+        // Eight 200pt minimums can't fit; they give way to the page.
+        "[table]\nmin_column_width_pt = 200.0\n",
+    ] {
+        let bytes = render(&md, cfg);
+        let max_x = td_xs(&bytes).into_iter().fold(f32::NEG_INFINITY, f32::max);
+        assert!(
+            max_x < 567.0,
+            "cell text past the body edge ({max_x:.1}) with {cfg:?}"
+        );
+    }
+}
+
+#[test]
+fn max_column_width_keeps_a_small_table_narrow() {
+    let md = "| a | b | c |\n|---|---|---|\n| 1 | 2 | 3 |\n";
+    let xs = |cfg: &str| td_xs(&render(md, cfg));
+    let max_x = |cfg: &str| xs(cfg).into_iter().fold(f32::NEG_INFINITY, f32::max);
+    // Unbounded, the three columns share the full body width.
+    assert!(max_x("") > 300.0);
+    // Capped at 50pt each, the last column starts 100pt in.
+    let capped = max_x("[table]\nmax_column_width_pt = 50.0\n");
+    assert!(capped < 46.0 + 100.0 + 10.0, "last cell at {capped:.1}");
+}
+
+/// A ten-column table whose cells, unwrapped, are wider than the body.
+fn ten_column_table() -> String {
+    let row =
+        |f: &dyn Fn(usize) -> String| (0..10).map(|i| format!(" {} |", f(i))).collect::<String>();
+    format!(
+        "|{}\n|{}\n|{}\n",
+        row(&|i| format!("Heading{i}")),
+        row(&|_| "---".to_string()),
+        row(&|i| format!("Measurement{i}")),
+    )
+}
+
+#[test]
+fn scale_overflow_fits_a_ten_column_table_on_one_line_per_row() {
+    let md = ten_column_table();
+    let wrapped = render(&md, "");
+    let scaled = render(&md, "[table]\noverflow = \"scale\"\n");
+    // Wrapped to fit, cells break onto a second line; shrunk instead,
+    // every cell keeps to one, so fewer lines are set.
+    assert!(
+        td_xs(&scaled).len() < td_xs(&wrapped).len(),
+        "scaled {} lines, wrapped {}",
+        td_xs(&scaled).len(),
+        td_xs(&wrapped).len()
+    );
+    let max_x = td_xs(&scaled).into_iter().fold(f32::NEG_INFINITY, f32::max);
+    assert!(max_x < 567.0, "cell text past the body edge ({max_x:.1})");
+    assert!(contains_text(&scaled, "Measurement9"));
+}
+
+#[test]
+fn scale_overflow_leaves_a_table_that_fits_alone() {
+    let md = "| a | b |\n|---|---|\n| 1 | 2 |\n";
+    assert_eq!(
+        td_xs(&render(md, "")),
+        td_xs(&render(md, "[table]\noverflow = \"scale\"\n"))
+    );
+}
+
+#[test]
+fn rotate_overflow_gives_the_table_a_landscape_page() {
+    let md = format!("Before.\n\n{}\nAfter.\n", ten_column_table());
+    let bytes = render(&md, "[table]\noverflow = \"rotate\"\n");
+    let doc = lopdf::Document::load_mem(&bytes).expect("rendered PDF must parse");
+    let sizes: Vec<(f32, f32)> = doc
+        .get_pages()
+        .values()
+        .map(|&id| {
+            let page = doc.get_dictionary(id).unwrap();
+            let b = page.get(b"MediaBox").unwrap().as_array().unwrap();
+            let num = |o: &lopdf::Object| match o {
+                lopdf::Object::Integer(i) => *i as f32,
+                lopdf::Object::Real(r) => *r,
+                _ => 0.0,
+            };
+            (num(&b[2]), num(&b[3]))
+        })
+        .collect();
+    assert_eq!(sizes.len(), 3, "page sizes: {sizes:?}");
+    assert!(sizes[0].0 < sizes[0].1, "first page should stay portrait");
+    assert!(sizes[1].0 > sizes[1].1, "table page should be landscape");
+    assert!(sizes[2].0 < sizes[2].1, "page after the table is portrait");
+}
+
+#[test]
+fn scroll_overflow_drops_the_columns_past_the_edge_with_a_note() {
+    let bytes = render(&ten_column_table(), "[table]\noverflow = \"scroll\"\n");
+    assert!(contains_text(&bytes, "Heading0"));
+    assert!(!contains_text(&bytes, "Heading9"));
+    assert!(contains_text(&bytes, "not shown"));
+    let max_x = td_xs(&bytes).into_iter().fold(f32::NEG_INFINITY, f32::max);
+    assert!(max_x < 567.0, "cell text past the body edge ({max_x:.1})");
+}
+
 #[test]
 fn list_with_custom_bullet_uses_the_configured_glyph() {
     let bytes = render(
@@ -297,6 +449,40 @@ fn show_on_first_page_false_skips_first() {
     assert_eq!(without_count - with_count, 1, "should skip exactly 1 page");
 }
 
+#[test]
+fn numbering_marker_switches_from_roman_to_arabic() {
+    let md = "Preface.\n\n<!-- pagebreak -->\n\nMore preface.\n\n<!-- pagebreak -->\n\n\
+              <!-- numbering: arabic reset -->\n\n# Chapter\n\n<!-- pagebreak -->\n\nMore body.\n";
+    let bytes = render(
+        md,
+        r##"
+        [footer]
+        center = "page {page}"
+        page_number_style = "roman-lower"
+        "##,
+    );
+    let s = String::from_utf8_lossy(&bytes);
+    assert!(s.contains("(page i)"), "front matter page 1 not roman");
+    assert!(s.contains("(page ii)"), "front matter page 2 not roman");
+    assert!(s.contains("(page 1)"), "body did not restart at 1");
+    assert!(s.contains("(page 2)"), "body did not continue in arabic");
+    assert!(!s.contains("(page iii)") && !s.contains("(page 3)"));
+}
+
+#[test]
+fn start_at_and_upper_roman_style_the_first_page() {
+    let bytes = render(
+        "Body.",
+        r##"
+        [footer]
+        center = "page {page}"
+        page_number_style = "roman-upper"
+        start_at = 4
+        "##,
+    );
+    assert!(String::from_utf8_lossy(&bytes).contains("(page IV)"));
+}
+
 #[test]
 fn title_var_pulls_from_metadata() {
     let md = "Just one paragraph.";
@@ -344,6 +530,95 @@ Even more body.
     );
 }
 
+#[test]
+fn bookmark_titles_use_the_heading_plain_text() {
+    let md = "# Intro[^1]\n\nBody.\n\n[^1]: A note.\n";
+    let bytes = render(md, "");
+    let s = String::from_utf8_lossy(&bytes).to_uppercase();
+    // "Intro" in UTF-16BE; the footnote marker `1` (0031) must not
+    // follow it.
+    assert!(
+        s.contains("FEFF0049006E00740072006F"),
+        "expected the h1 bookmark"
+    );
+    assert!(
+        !s.contains("FEFF0049006E00740072006F0031"),
+        "footnote marker leaked into the bookmark title"
+    );
+}
+
+fn outline_title(doc: &lopdf::Document, id: lopdf::ObjectId) -> String {
+    let item = doc.get_dictionary(id).unwrap();
+    let raw = item.get(b"Title").unwrap().as_str().unwrap();
+    let units: Vec<u16> = raw[2..]
+        .chunks(2)
+        .map(|c| u16::from_be_bytes([c[0], c[1]]))
+        .collect();
+    String::from_utf16(&units).unwrap()
+}
+
+fn outline_ref(dict: &lopdf::Dictionary, key: &[u8]) -> Option<lopdf::ObjectId> {
+    dict.get(key).ok().and_then(|o| o.as_reference().ok())
+}
+
+#[test]
+fn bookmarks_nest_by_heading_level() {
+    let md = "# Guide\n\nIntro.\n\n## Install\n\nSteps.\n\n## Usage\n\nMore.\n";
+    let bytes = render(md, "");
+    let doc = lopdf::Document::load_mem(&bytes).expect("parse");
+    let outlines = doc
+        .catalog()
+        .unwrap()
+        .get(b"Outlines")
+        .unwrap()
+        .as_reference()
+        .unwrap();
+    let root = doc.get_dictionary(outlines).unwrap();
+    let h1 = outline_ref(root, b"First").unwrap();
+    assert_eq!(outline_ref(root, b"Last"), Some(h1), "one top-level item");
+    assert_eq!(outline_title(&doc, h1), "Guide");
+
+    let h1_dict = doc.get_dictionary(h1).unwrap();
+    assert_eq!(h1_dict.get(b"Count").unwrap().as_i64().unwrap(), 2);
+    let first = outline_ref(h1_dict, b"First").unwrap();
+    let last = outline_ref(h1_dict, b"Last").unwrap();
+    assert_eq!(outline_title(&doc, first), "Install");
+    assert_eq!(outline_title(&doc, last), "Usage");
+
+    let first_dict = doc.get_dictionary(first).unwrap();
+    assert_eq!(outline_ref(first_dict, b"Next"), Some(last));
+    assert_eq!(outline_ref(first_dict, b"Parent"), Some(h1));
+    let last_dict = doc.get_dictionary(last).unwrap();
+    assert_eq!(outline_ref(last_dict, b"Prev"), Some(first));
+    assert_eq!(outline_ref(last_dict, b"Parent"), Some(h1));
+}
+
+#[test]
+fn bookmarks_can_be_turned_off() {
+    let bytes = render("# Guide\n\n## Install\n", "[document]\nbookmarks = false\n");
+    let s = String::from_utf8_lossy(&bytes);
+    assert!(
+        !s.contains("/Outlines"),
+        "outline written despite bookmarks = false"
+    );
+}
+
+#[test]
+fn conditional_region_follows_document_flags() {
+    let md = "Public text.\n\n<!-- if: draft -->\nDraftOnlyNote\n<!-- endif -->\n";
+    let without = render(md, "");
+    assert!(contains_text(&without, "Public text."));
+    assert!(
+        !contains_text(&without, "DraftOnlyNote"),
+        "draft region rendered without the flag"
+    );
+    let with = render(md, "[document]\nflags = [\"draft\"]\n");
+    assert!(
+        contains_text(&with, "DraftOnlyNote"),
+        "draft region missing with the flag set"
+    );
+}
+
 #[test]
 fn internal_link_emits_goto_action() {
     let md = "\
@@ -524,6 +799,53 @@ fn subtitle_and_author_render_when_present() {
     assert!(s.contains("(2026-01-02)"), "date missing");
 }
 
+#[test]
+fn title_page_takes_its_text_from_front_matter() {
+    let md = "---\ntitle: FmTitle\nsubtitle: FmSub\nauthor: FmAuthor\ndate: 2026-03-04\n---\n\nBodyWords here.";
+    let bytes = render(
+        md,
+        r##"
+        [title_page]
+        enabled = true
+        "##,
+    );
+    let s = String::from_utf8_lossy(&bytes);
+    for text in ["(FmTitle)", "(FmSub)", "(FmAuthor)", "(2026-03-04)"] {
+        assert!(s.contains(text), "{text} missing from title page");
+    }
+    // One title page, then the body on page 2.
+    assert_eq!(page_count(&bytes), 2);
+    let title_at = s.find("(FmTitle)").unwrap();
+    let body_at = s.find("(BodyWords").expect("body text missing");
+    assert!(title_at < body_at, "title page must come before the body");
+}
+
+#[test]
+fn enabled_title_page_without_any_title_is_skipped() {
+    let bytes = render(
+        "Body only.",
+        r##"
+        [title_page]
+        enabled = true
+        "##,
+    );
+    assert_eq!(page_count(&bytes), 1);
+}
+
+#[test]
+fn disabled_title_page_ignores_a_configured_title() {
+    let bytes = render(
+        "---\ntitle: FmTitle\n---\nBody only.",
+        r##"
+        [title_page]
+        enabled = false
+        title = "Configured"
+        "##,
+    );
+    assert_eq!(page_count(&bytes), 1);
+    assert!(!String::from_utf8_lossy(&bytes).contains("(Configured)"));
+}
+
 #[test]
 fn footnote_reference_renders_as_superscript_number() {
     let bytes = render("Text with note[^a].\n\n[^a]: Defined.", "");
@@ -535,6 +857,115 @@ fn footnote_reference_renders_as_superscript_number() {
     );
 }
 
+#[test]
+fn superscript_scale_config_sizes_footnote_marker() {
+    // Default body is 8pt; `scale = 0.55` puts the marker at 4.4pt.
+    let md = "A word[^a] with a note.\n\n[^a]: Defined.";
+    assert!(!contains(&render(md, ""), b"4.4 Tf"));
+    let bytes = render(md, "[superscript]\nscale = 0.55\n");
+    assert!(
+        contains(&bytes, b"4.4 Tf"),
+        "footnote marker must use the configured superscript scale"
+    );
+}
+
+#[test]
+fn emphasis_levels_take_their_own_color_and_size() {
+    let md = "Plain *soft* and **loud** and ***both*** words.";
+    let plain = render(md, "");
+    assert!(!contains(&plain, b"13.5 Tf"));
+    assert!(!contains_text(&plain, "0 1 0 rg"));
+
+    let cfg = r##"
+[emphasis]
+text_color = "#00FF00"
+
+[strong_emphasis]
+text_color = "#0000FF"
+
+[emphasis_strong]
+font_size_pt = 13.5
+"##;
+    let bytes = render(md, cfg);
+    assert!(pdf_well_formed(&bytes));
+    assert!(
+        contains_text(&bytes, "0 1 0 rg"),
+        "[emphasis] colour must reach the italic run"
+    );
+    assert!(
+        contains_text(&bytes, "0 0 1 rg"),
+        "[strong_emphasis] colour must reach the bold run"
+    );
+    assert!(
+        contains(&bytes, b"13.5 Tf"),
+        "[emphasis_strong] size must reach the bold-italic run"
+    );
+}
+
+#[test]
+fn strong_emphasis_color_applies_to_the_run_and_then_restores() {
+    let bytes = render(
+        "Before **alarm** after.",
+        "[paragraph]\ntext_color = \"#000000\"\n\n[strong_emphasis]\ntext_color = \"#FF0000\"\n",
+    );
+    let text = String::from_utf8_lossy(&scan(&bytes)).to_string();
+    let red = text.find("1 0 0 rg").expect("red fill for the strong run");
+    let word = text.find("alarm").expect("strong run text");
+    let after = text.find(" after.").expect("trailing text");
+    assert!(red < word && word < after);
+    // Unset properties inherit: the trailing plain run goes back to
+    // the paragraph colour rather than staying red.
+    assert!(
+        text[word..after].contains("0 0 0 rg"),
+        "paragraph colour must be restored after the strong run"
+    );
+}
+
+#[test]
+fn superscript_rise_moves_marker_not_line_spacing() {
+    let md = "A word[^a] here.\n\nNext paragraph.\n\n[^a]: Defined.";
+    let low = render(md, "[superscript]\nrise = 0.1\n");
+    let high = render(md, "[superscript]\nrise = 0.5\n");
+    assert_ne!(low, high, "rise must move the raised glyphs");
+    // Line placement is untouched: every cursor move outside the
+    // superscript sections is identical, so only the markers' own
+    // `Td`s (in the text and in the footnotes list) differ.
+    let tds = |b: &[u8]| -> Vec<String> {
+        String::from_utf8_lossy(&scan(b))
+            .lines()
+            .filter(|l| l.trim_end().ends_with(" Td"))
+            .map(str::to_string)
+            .collect()
+    };
+    let (a, b) = (tds(&low), tds(&high));
+    assert_eq!(a.len(), b.len());
+    let differing = a.iter().zip(&b).filter(|(x, y)| x != y).count();
+    assert!(
+        (1..=2).contains(&differing),
+        "only the marker cursors should move, {differing} did"
+    );
+}
+
+#[test]
+fn ordinals_option_raises_suffixes() {
+    let md = "The 21st century.";
+    let plain = render(md, "");
+    assert!(contains(&plain, b"(The 21st century.)"));
+    let raised = render(md, "[superscript]\nordinals = true\n");
+    assert!(
+        contains(&raised, b"(The 21)"),
+        "digits stay on the baseline"
+    );
+    assert!(
+        contains(&raised, b"(st)"),
+        "suffix is split into its own run"
+    );
+    assert!(
+        contains(&raised, b"5.6 Tf"),
+        "suffix renders at superscript size"
+    );
+}
+
 #[test]
 fn footnotes_section_heading_appears() {
     let bytes = render("Note[^a].\n\n[^a]: First definition.", "");
@@ -623,6 +1054,95 @@ fn text_align_left_does_not_emit_word_spacing() {
     assert!(bytes_left.starts_with(b"%PDF-"));
 }
 
+#[test]
+fn code_block_max_lines_truncates_with_a_count_of_the_rest() {
+    let body: Vec<String> = (1..=100).map(|n| format!("row{n}")).collect();
+    let md = format!("```\n{}\n```\n", body.join("\n"));
+
+    let bytes = render(&md, "[code_block]\nmax_lines = 20\n");
+    assert!(contains_text(&bytes, "row1"));
+    assert!(contains_text(&bytes, "row20"));
+    assert!(!contains_text(&bytes, "row21"));
+    assert!(!contains_text(&bytes, "row100"));
+    assert!(contains_text(&bytes, "80 more lines"));
+
+    let full = render(&md, "");
+    assert!(contains_text(&full, "row100"));
+    assert!(!contains_text(&full, "more lines"));
+}
+
+#[test]
+fn code_fence_caption_and_highlighted_lines() {
+    let md =
+        "```rust {caption=\"Listing 1\" highlight=\"2-3\"}\nalpha\nbravo\ncharlie\ndelta\n```\n";
+    let cfg = "[code_block]\nhighlight_color = \"#00FF00\"\n";
+    let bytes = render(md, cfg);
+    let text = String::from_utf8_lossy(&scan(&bytes)).into_owned();
+    let caption = text.find("Listing 1").expect("caption drawn");
+    let first = text.find("alpha").expect("code drawn");
+    assert!(caption < first, "caption must come before the block");
+    assert!(contains_text(&bytes, "0 1 0 rg"), "highlight tint missing");
+
+    let plain = render("```rust\nalpha\nbravo\ncharlie\ndelta\n```\n", cfg);
+    assert_eq!(
+        count_rect_ops(&bytes),
+        count_rect_ops(&plain) + 2,
+        "one tint band per highlighted line"
+    );
+    assert!(!contains_text(&plain, "0 1 0 rg"));
+}
+
+/// Operands of every `Tw` (word spacing) operator in the content.
+fn word_spacings(bytes: &[u8]) -> Vec<f32> {
+    let text = String::from_utf8_lossy(&scan(bytes)).into_owned();
+    let words: Vec<&str> = text.split_ascii_whitespace().collect();
+    words
+        .windows(2)
+        .filter(|w| w[1] == "Tw")
+        .filter_map(|w| w[0].parse().ok())
+        .collect()
+}
+
+#[test]
+fn max_space_stretch_sets_loose_justified_lines_ragged() {
+    // Three columns leave room for one long word and a short one per
+    // line, so justifying would open very wide gaps.
+    let md = "an incomprehensibilities of counterrevolutionaries to \
+              electroencephalographic in institutionalization at \
+              uncharacteristically by telecommunications on \
+              intercontinental and disproportionately the end.\n";
+    let base = "[page]\ncolumns = 3\n[paragraph]\ntext_align = \"justify\"\n";
+    let max = |v: &[f32]| v.iter().cloned().fold(0.0f32, f32::max);
+
+    let loose = word_spacings(&render(
+        md,
+        &format!("{base}[text]\nmax_space_stretch = 100.0\n"),
+    ));
+    let default = word_spacings(&render(md, base));
+    let doubling = word_spacings(&render(
+        md,
+        &format!("{base}[text]\nmax_space_stretch = 1.0\n"),
+    ));
+    let strict = word_spacings(&render(
+        md,
+        &format!("{base}[text]\nmax_space_stretch = 0.0\n"),
+    ));
+
+    assert!(
+        max(&loose) > 0.0,
+        "a generous tolerance justifies: {loose:?}"
+    );
+    assert_eq!(default, loose, "unset must not limit the stretch");
+    assert!(
+        max(&doubling) < max(&loose),
+        "a tolerance of 1.0 must refuse the widest stretches: {doubling:?} vs {loose:?}"
+    );
+    assert!(
+        strict.iter().all(|&tw| tw == 0.0),
+        "zero tolerance leaves every line ragged: {strict:?}"
+    );
+}
+
 #[test]
 fn small_caps_uppercases_lowercase_letters_in_paragraph() {
     let cfg = "[paragraph]\nsmall_caps = true\n";
@@ -720,6 +1240,33 @@ fn image_with_no_title_renders_without_caption() {
     );
 }
 
+#[test]
+fn image_show_caption_renders_alt_text() {
+    let img = temp_jpeg_path();
+    let md = format!("![A blue square]({})\n", img);
+    let bytes = render(&md, "[image]\nshow_caption = true\n");
+    let s = String::from_utf8_lossy(&bytes);
+    assert!(
+        s.contains("(A blue square)"),
+        "alt text should render as caption"
+    );
+}
+
+#[test]
+fn image_caption_prefix_numbers_figures() {
+    let a = temp_jpeg_path();
+    let b = temp_jpeg_path();
+    let md = format!("![First]({})\n\n![ignored]({} \"Second\")\n", a, b);
+    let cfg = "[image]\nshow_caption = true\ncaption_prefix = \"Figure {n}: \"\n";
+    let bytes = render(&md, cfg);
+    let s = String::from_utf8_lossy(&bytes);
+    assert!(s.contains("(Figure 1: First)"), "first figure numbered 1");
+    assert!(
+        s.contains("(Figure 2: Second)"),
+        "title wins over alt and numbering increments"
+    );
+}
+
 #[test]
 fn image_right_align_changes_xobject_translation() {
     let img = temp_jpeg_path();
@@ -748,6 +1295,57 @@ fn image_max_width_pct_shrinks_image() {
     );
 }
 
+#[test]
+fn image_float_wraps_following_paragraph_beside_it() {
+    let img = temp_jpeg_path();
+    let body = "Wrapping prose flows beside the floated figure. ".repeat(30);
+    let md = format!("![alt]({img})\n\n{body}\n");
+    // Every `Td` operand pair, plus the one that opens the paragraph.
+    let tds = |float: &str| -> (Vec<(f32, f32)>, (f32, f32)) {
+        let cfg = format!(
+            "[page]\nsize = \"A4\"\nmargins = 20.0\n\
+             [image]\nmax_width_pct = 30.0\nfloat = \"{float}\"\n"
+        );
+        let text = String::from_utf8_lossy(&scan(&render(&md, &cfg))).into_owned();
+        let (mut all, mut first) = (Vec::new(), None);
+        for line in text.lines() {
+            if let Some(p) = line.trim().strip_suffix(" Td") {
+                let v: Vec<f32> = p
+                    .split_whitespace()
+                    .filter_map(|n| n.parse().ok())
+                    .collect();
+                all.push((v[0], v[1]));
+            }
+            if first.is_none() && line.contains("(Wrapping") {
+                first = all.last().copied();
+            }
+        }
+        (all, first.expect("paragraph text"))
+    };
+    let margin = 20.0 * 72.0 / 25.4;
+    // 30% of the 170mm column, plus the gap to the text.
+    let strip = 170.0 * 72.0 / 25.4 * 0.3 + 10.0;
+
+    let (_, none) = tds("none");
+    let (left_all, left) = tds("left");
+    let (_, right) = tds("right");
+    assert!((none.0 - margin).abs() < 0.5, "{none:?}");
+    assert!(
+        (left.0 - margin - strip).abs() < 0.5,
+        "left float pushes the text past the image: {left:?}"
+    );
+    assert!((right.0 - margin).abs() < 0.5, "{right:?}");
+    // The image is ~93pt tall; floated, the text starts level with
+    // its top instead of below it.
+    assert!(left.1 > none.1 + 80.0, "{left:?} vs {none:?}");
+    assert!(right.1 > none.1 + 80.0, "{right:?} vs {none:?}");
+    // Past the image's bottom the lines step back to the margin.
+    assert!(
+        left_all.iter().any(|&(dx, _)| (dx + strip).abs() < 0.5),
+        "no line returns to the margin: {left_all:?}"
+    );
+}
+
 #[test]
 fn very_long_word_does_not_overflow_horizontally() {
     let long = "x".repeat(200);
@@ -1658,3 +2256,153 @@ mod inline_style_application {
         );
     }
 }
+
+#[test]
+fn link_display_parenthetical_resolves_fragment_against_base() {
+    let md = "See [setup](#setup) and [docs](https://docs.test/start).\n";
+    let cfg = "[link]\ndisplay = \"parenthetical\"\nrelative_urls = \"resolve_base\"\nbase_url = \"https://example.com/guide.html\"\n";
+    let bytes = render(md, cfg);
+    assert!(contains_text(
+        &bytes,
+        "https://example.com/guide.html#setup"
+    ));
+
+    let cfg = "[link]\ndisplay = \"parenthetical\"\nrelative_urls = \"strip\"\n";
+    let bytes = render(md, cfg);
+    assert!(!contains_text(&bytes, "#setup"), "fragment must not print");
+}
+
+#[test]
+fn explicit_heading_id_resolves_internal_links() {
+    let goto_count = |md: &str| {
+        let bytes = render(md, "");
+        let s = String::from_utf8_lossy(&bytes);
+        count_substr(s.as_bytes(), b"/S/GoTo") + count_substr(s.as_bytes(), b"/S /GoTo")
+    };
+    let by_id = goto_count("# Setup {#install}\n\nSee [install](#install).\n");
+    let by_old_slug = goto_count("# Setup {#install}\n\nSee [setup](#setup).\n");
+    assert!(
+        by_id > by_old_slug,
+        "`#install` should resolve to the heading ({by_id} vs {by_old_slug} GoTo actions)"
+    );
+    let bytes = render("# Setup {#install}\n", "");
+    assert!(
+        !contains_text(&bytes, "{#install}"),
+        "the id attribute must not render as heading text"
+    );
+}
+
+#[test]
+fn only_resolved_anchor_links_get_goto_annotations() {
+    let goto_count = |md: &str| {
+        let bytes = render(md, "");
+        let s = String::from_utf8_lossy(&bytes);
+        count_substr(s.as_bytes(), b"/S/GoTo") + count_substr(s.as_bytes(), b"/S /GoTo")
+    };
+    let resolved = goto_count("# Heading\n\n[jump](#heading)\n");
+    let unresolved = goto_count("# Heading\n\n[jump](#nowhere)\n");
+    assert_eq!(resolved, unresolved + 1);
+}
+
+#[test]
+fn paragraph_spacing_tightens_gap_under_heading() {
+    // `y` operands of every `… Td`, top of the page first.
+    let td_ys = |b: &[u8]| -> Vec<f32> {
+        String::from_utf8_lossy(&scan(b))
+            .lines()
+            .filter_map(|l| l.trim().strip_suffix(" Td"))
+            .filter_map(|p| p.split_whitespace().nth(1)?.parse::<f32>().ok())
+            .collect()
+    };
+    let md = "# Title\n\nFirst paragraph.\n\nSecond paragraph.\n";
+    let base = "[headings.h1]\nmargin_after_pt = 0.0\n\
+                [paragraph]\nmargin_before_pt = 12.0\nmargin_after_pt = 2.0\n";
+    let plain = td_ys(&render(md, base));
+    let spaced = td_ys(&render(md, &format!("{base}paragraph_spacing_pt = 4.0\n")));
+    assert_eq!(plain.len(), 3, "one cursor per line: {plain:?}");
+    assert_eq!(spaced.len(), 3, "one cursor per line: {spaced:?}");
+
+    let under_heading = |ys: &[f32]| ys[0] - ys[1];
+    let between = |ys: &[f32]| ys[1] - ys[2];
+    assert!(
+        (under_heading(&plain) - under_heading(&spaced) - 12.0).abs() < 0.1,
+        "the first paragraph under a heading drops its before-margin: {plain:?} vs {spaced:?}"
+    );
+    // Margins collapse: plain paragraphs sit max(2, 12) apart, spaced
+    // ones max(2, 4).
+    assert!(
+        (between(&plain) - between(&spaced) - 8.0).abs() < 0.1,
+        "successive paragraphs sit `paragraph_spacing_pt` apart: {plain:?} vs {spaced:?}"
+    );
+}
+
+#[test]
+fn list_and_following_paragraph_margins_collapse() {
+    // Distance from the list item's line down to the paragraph's.
+    let gap = |list_after: f32, para_before: f32| -> f32 {
+        let cfg = format!(
+            "[list.common]\nmargin_after_pt = {list_after}\n\
+             [paragraph]\nmargin_before_pt = {para_before}\nmargin_after_pt = 0.0\n"
+        );
+        let ys: Vec<f32> = String::from_utf8_lossy(&scan(&render("- item\n\nAfter.\n", &cfg)))
+            .lines()
+            .filter_map(|l| l.trim().strip_suffix(" Td"))
+            .filter_map(|p| p.split_whitespace().nth(1)?.parse::<f32>().ok())
+            .collect();
+        ys[0] - ys[ys.len() - 1]
+    };
+    let none = gap(0.0, 0.0);
+    assert!(
+        (gap(10.0, 6.0) - none - 10.0).abs() < 0.1,
+        "list after-margin 10 + paragraph before-margin 6 must give 10, not 16"
+    );
+    assert!(
+        (gap(6.0, 10.0) - none - 10.0).abs() < 0.1,
+        "the larger margin wins whichever side it is on"
+    );
+}
+
+#[test]
+fn image_full_width_mode_clamps_to_content_width() {
+    use image::{DynamicImage, ImageFormat, RgbImage};
+    // 4000px at the renderer's 300 dpi is 960pt — wider than an A4
+    // page — so `full_width` has to shrink it to the column.
+    let path =
+        std::env::temp_dir().join(format!("m2p_test_wide_banner_{}.jpg", std::process::id()));
+    let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(4000, 400, image::Rgb([40, 80, 120])));
+    let mut buf = Vec::new();
+    img.write_to(&mut std::io::Cursor::new(&mut buf), ImageFormat::Jpeg)
+        .expect("encode banner jpeg");
+    std::fs::write(&path, buf).expect("write banner jpeg");
+    let md = format!("![banner]({})\n", path.display());
+
+    // First operand of every `… cm` — the image's drawn width in points.
+    let cm_widths = |b: &[u8]| -> Vec<f32> {
+        String::from_utf8_lossy(&scan(b))
+            .lines()
+            .filter_map(|l| l.trim().strip_suffix(" cm"))
+            .filter_map(|p| p.split_whitespace().next()?.parse::<f32>().ok())
+            .collect()
+    };
+    // A4 is 210mm wide; 20mm margins leave a 170mm (481.9pt) column.
+    let content_w_pt = 170.0 * 72.0 / 25.4;
+    let page = "[page]\nsize = \"A4\"\nmargins = 20.0\n";
+    let full = render(
+        &md,
+        &format!("{page}[image]\nmode = \"full_width\"\nmax_width_pct = 30.0\n"),
+    );
+    let inline = render(&md, &format!("{page}[image]\nmax_width_pct = 30.0\n"));
+    std::fs::remove_file(&path).ok();
+
+    let near = |ws: &[f32], w: f32| ws.iter().any(|v| (v - w).abs() < 0.5);
+    assert!(
+        near(&cm_widths(&full), content_w_pt),
+        "full_width ignores max_width_pct and fills the column: {:?}",
+        cm_widths(&full)
+    );
+    assert!(
+        near(&cm_widths(&inline), content_w_pt * 0.3),
+        "inline keeps the max_width_pct cap: {:?}",
+        cm_widths(&inline)
+    );
+}
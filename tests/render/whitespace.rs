@@ -147,12 +147,19 @@ mod space_collapsing {
     use super::*;
 
     #[test]
-    fn interior_multiple_spaces_are_preserved_in_one_run() {
-        // Documents current behavior: the renderer keeps the author's
-        // interior spaces in a single show-text run (it does not
-        // HTML-collapse them). Regression guard — if we ever choose
-        // to collapse, this test is the deliberate breaking point.
+    fn interior_multiple_spaces_collapse_by_default() {
         let lines = show_text_lines("a      b", "");
+        assert!(
+            lines.iter().any(|l| l.contains("a b")),
+            "interior multi-space run not collapsed: {:?}",
+            lines
+        );
+        assert!(!lines.iter().any(|l| l.contains("a  b")), "{:?}", lines);
+    }
+
+    #[test]
+    fn interior_multiple_spaces_are_preserved_when_collapse_is_off() {
+        let lines = show_text_lines("a      b", "[text]\ncollapse_whitespace = false\n");
         assert!(
             lines.iter().any(|l| l.contains("a      b")),
             "interior multi-space run not preserved as-is: {:?}",
@@ -160,6 +167,22 @@ mod space_collapsing {
         );
     }
 
+    #[test]
+    fn double_space_after_a_sentence_is_kept_when_collapse_is_off() {
+        let md = "End.  Next sentence.";
+        let kept = show_text_lines(md, "[text]\ncollapse_whitespace = false\n").join("");
+        assert!(kept.contains("End.  Next"), "{kept:?}");
+        let collapsed = show_text_lines(md, "").join("");
+        assert!(collapsed.contains("End. Next"), "{collapsed:?}");
+    }
+
+    #[test]
+    fn inline_code_keeps_its_spaces_while_text_collapses() {
+        let lines = show_text_lines("x  `a   b`  y", "").join("");
+        assert!(lines.contains("a   b"), "{lines:?}");
+        assert!(!lines.contains("x  "), "{lines:?}");
+    }
+
     #[test]
     fn trailing_whitespace_does_not_crash_or_corrupt() {
         // Trailing spaces at EOF are invisible in the PDF anyway;
@@ -205,6 +228,103 @@ mod control_whitespace {
     }
 }
 
+mod break_hints {
+    use super::*;
+
+    // Narrow column + big font: a line holds about fifteen letters.
+    const NARROW: &str = "[page]\nsize = { width_mm = 70.0, height_mm = 200.0 }\nmargins = { top = 5.0, right = 5.0, bottom = 5.0, left = 5.0 }\n[paragraph]\nfont_size_pt = 20.0\n";
+
+    #[test]
+    fn soft_hyphen_breaks_with_a_visible_hyphen() {
+        let md = "abcdefgh\u{00AD}".repeat(5) + "abcdefgh";
+        let lines = show_text_lines(&md, NARROW);
+        assert!(lines.len() > 1, "the word must wrap: {lines:?}");
+        let (last, broken) = lines.split_last().unwrap();
+        for line in broken {
+            assert!(
+                line.ends_with("abcdefgh-"),
+                "break not at a hint: {lines:?}"
+            );
+        }
+        assert!(!last.ends_with('-'), "{lines:?}");
+        let joined: String = lines.iter().map(|l| l.trim_end_matches('-')).collect();
+        assert_eq!(joined, "abcdefgh".repeat(6), "soft hyphens must not render");
+    }
+
+    #[test]
+    fn soft_hyphen_inside_a_line_is_invisible() {
+        let lines = show_text_lines("co\u{00AD}operate", "");
+        assert_eq!(lines, vec!["cooperate".to_string()]);
+    }
+
+    #[test]
+    fn zero_width_space_breaks_without_a_hyphen() {
+        let md = "abcdefgh\u{200B}".repeat(5) + "abcdefgh";
+        let lines = show_text_lines(&md, NARROW);
+        assert!(lines.len() > 1, "the word must wrap: {lines:?}");
+        assert!(lines.iter().all(|l| !l.ends_with('-')), "{lines:?}");
+        assert_eq!(lines.concat(), "abcdefgh".repeat(6));
+        assert_eq!(
+            show_text_lines("wide\u{200B}word", ""),
+            vec!["wideword".to_string()]
+        );
+    }
+}
+
+mod code_blocks {
+    use super::*;
+
+    const ASCII_TABLE: &str = "```text\n\
++-------+--------+\n\
+| name  |  count |\n\
++-------+--------+\n\
+| a     |      1 |\n\
+| bb    |     22 |\n\
++-------+--------+\n\
+```\n";
+
+    #[test]
+    fn ascii_table_rows_keep_every_space() {
+        let lines = show_text_lines(ASCII_TABLE, "");
+        for row in [
+            "+-------+--------+",
+            "| name  |  count |",
+            "| a     |      1 |",
+            "| bb    |     22 |",
+        ] {
+            assert!(
+                lines.iter().any(|l| l == row),
+                "{row:?} not intact: {lines:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn tabs_expand_to_aligned_columns() {
+        let lines = show_text_lines("```\nab\tc\nabcde\tf\n```\n", "");
+        assert!(lines.iter().any(|l| l == "ab  c"), "{lines:?}");
+        assert!(lines.iter().any(|l| l == "abcde   f"), "{lines:?}");
+    }
+
+    #[test]
+    fn justified_code_block_does_not_stretch_spaces() {
+        let cfg = "[page]\nsize = { width_mm = 60.0, height_mm = 200.0 }\n\
+                   [code_block]\ntext_align = \"justify\"\n";
+        let md = "```\nalpha  bravo  charlie  delta  echo  foxtrot  golf\n```\n";
+        let bytes = render(md, cfg);
+        let s = String::from_utf8_lossy(&bytes);
+        let stretched: Vec<&str> = s
+            .lines()
+            .map(str::trim)
+            .filter(|l| l.ends_with(" Tw") && l.trim_end_matches(" Tw").parse::<f32>() != Ok(0.0))
+            .collect();
+        assert!(
+            stretched.is_empty(),
+            "code spaces were stretched: {stretched:?}"
+        );
+    }
+}
+
 mod regression_guards {
     use super::*;
 
@@ -382,3 +382,69 @@ body on page {}",
 silently pass on a broken renderer"
     );
 }
+
+/// A 600px-square PNG: two inches across at 300 DPI. Written once per
+/// test under a unique name.
+fn square_png(name: &str) -> String {
+    let img = image::RgbImage::from_pixel(600, 600, image::Rgb([40, 90, 160]));
+    let path = std::env::temp_dir().join(format!("m2p_keep_{name}.png"));
+    img.save(&path).expect("write test image");
+    path.to_string_lossy().to_string()
+}
+
+/// One-line filler paragraphs, so each step of a sweep moves the probe
+/// down by a single line and some step lands it right at the bottom.
+fn short_lines_then(n: usize, probe: &str) -> String {
+    let mut out = String::new();
+    for i in 0..n {
+        out.push_str(&format!("Short filler line {i}.\n\n"));
+    }
+    out.push_str(probe);
+    out
+}
+
+/// Sweeps the probe across the page boundary and asserts `a` and `b`
+/// always land on the same page. Fails if the sweep never pushed the
+/// probe onto page two, which would leave the check vacuous.
+fn assert_kept_together(probe: &str, cfg: &str, a: &str, b: &str) {
+    let mut crossed = false;
+    for n in 20..=60 {
+        let bytes = render(&short_lines_then(n, probe), cfg);
+        let streams = page_streams(&bytes);
+        let a_page = streams.iter().position(|s| page_contains(s, a));
+        let b_page = streams.iter().position(|s| page_contains(s, b));
+        let (Some(a_page), Some(b_page)) = (a_page, b_page) else {
+            panic!("filler={n}: `{a}` or `{b}` missing from the PDF");
+        };
+        crossed |= a_page > 0;
+        assert_eq!(
+            a_page,
+            b_page,
+            "filler={n}: `{a}` on page {} but `{b}` on page {}",
+            a_page + 1,
+            b_page + 1
+        );
+    }
+    assert!(crossed, "the sweep never reached the page boundary");
+}
+
+#[test]
+fn image_and_caption_stay_on_one_page() {
+    let p = square_png("caption");
+    let probe = format!("![diagram]({p} \"CAPMARK the caption under the image\")\n");
+    assert_kept_together(&probe, "", " Do", "CAPMARK");
+    let _ = std::fs::remove_file(&p);
+}
+
+#[test]
+fn keep_with_previous_binds_image_to_its_intro_line() {
+    let p = square_png("previous");
+    let probe = format!("INTROMARK The diagram below shows the flow.\n\n![diagram]({p})\n");
+    assert_kept_together(
+        &probe,
+        "[image]\nkeep_with_previous = true\n",
+        "INTROMARK",
+        " Do",
+    );
+    let _ = std::fs::remove_file(&p);
+}
@@ -21,7 +21,7 @@ fn render_blocks(tokens: &[Token], out: &mut String, in_loose_list_item: bool) {
             Token::Newline => {
                 i += 1;
             }
-            Token::Heading(content, level) => {
+            Token::Heading(content, level, _) => {
                 out.push_str(&format!("<h{}>", level));
                 render_inlines(content, out);
                 out.push_str(&format!("</h{}>\n", level));
@@ -50,6 +50,7 @@ fn render_blocks(tokens: &[Token], out: &mut String, in_loose_list_item: bool) {
                 language,
                 content,
                 block: true,
+                ..
             } => {
                 out.push_str("<pre><code");
                 let lang_first = language.split_whitespace().next().unwrap_or("");
@@ -247,7 +248,7 @@ fn render_tight_blocks(tokens: &[Token], out: &mut String) {
             Token::Newline => {
                 i += 1;
             }
-            Token::Heading(content, level) => {
+            Token::Heading(content, level, _) => {
                 out.push_str(&format!("<h{}>", level));
                 render_inlines(content, out);
                 out.push_str(&format!("</h{}>\n", level));
@@ -276,6 +277,7 @@ fn render_tight_blocks(tokens: &[Token], out: &mut String) {
                 language,
                 content,
                 block: true,
+                ..
             } => {
                 out.push_str("<pre><code");
                 let lang_first = language.split_whitespace().next().unwrap_or("");
@@ -359,7 +361,7 @@ fn split_item_content(content: &[Token]) -> (Vec<Token>, Vec<Token>) {
 fn is_block_level(tok: &Token) -> bool {
     matches!(
         tok,
-        Token::Heading(_, _)
+        Token::Heading(_, _, _)
             | Token::BlockQuote(_)
             | Token::ListItem { .. }
             | Token::HorizontalRule
@@ -492,7 +494,7 @@ fn render_inline_token(t: &Token, out: &mut String) {
         Token::HardBreak => out.push_str("<br />\n"),
         Token::Newline => out.push('\n'),
         Token::HorizontalRule => out.push_str("<hr />\n"),
-        Token::Heading(_, _)
+        Token::Heading(_, _, _)
         | Token::BlockQuote(_)
         | Token::ListItem { .. }
         | Token::Table { .. }
@@ -4,8 +4,9 @@
 
 use markdown2pdf::config::{ConfigSource, load_config_strict};
 use markdown2pdf::styling::{
-    Color, DocumentConfig, FontStyleVariant, FontWeight, PageSize, ResolveError, ResolvedStyle,
-    Sides, TextAlignment, available_theme_names, load_theme_preset, merge_documents, resolve,
+    Color, Digits, DocumentConfig, FontStyleVariant, FontWeight, HtmlMode, ImageMode, LinkDisplay,
+    PageSize, PdfPermission, RelativeUrlPolicy, ResolveError, ResolvedStyle, Sides, TableOverflow,
+    TextAlignment, available_theme_names, load_theme_preset, merge_documents, resolve,
 };
 
 #[test]
@@ -244,6 +245,136 @@ fn text_align_and_font_style_round_trip() {
     assert!(s.paragraph.is_italic());
 }
 
+#[test]
+fn default_theme_left_aligns_every_heading_level() {
+    let s = ResolvedStyle::default();
+    for (level, h) in s.headings.iter().enumerate() {
+        assert_eq!(h.text_align, TextAlignment::Left, "h{}", level + 1);
+    }
+}
+
+#[test]
+fn headings_text_align_applies_to_levels_without_their_own() {
+    let cfg = r#"
+        [headings]
+        text_align = "center"
+        [headings.h2]
+        text_align = "right"
+    "#;
+    let s = load_config_strict(ConfigSource::Embedded(cfg), None).unwrap();
+    assert_eq!(s.headings[0].text_align, TextAlignment::Center);
+    assert_eq!(s.headings[1].text_align, TextAlignment::Right);
+    assert_eq!(s.headings[5].text_align, TextAlignment::Center);
+    // Only headings pick it up.
+    assert_eq!(s.paragraph.text_align, TextAlignment::Left);
+}
+
+#[test]
+fn headings_text_align_overrides_a_themes_per_level_alignment() {
+    // academic centers h1 in its own `[headings.h1]`.
+    let centered = load_config_strict(ConfigSource::Theme("academic"), None).unwrap();
+    assert_eq!(centered.headings[0].text_align, TextAlignment::Center);
+
+    let cfg = r#"
+        theme = "academic"
+        [headings]
+        text_align = "left"
+    "#;
+    let s = load_config_strict(ConfigSource::Embedded(cfg), None).unwrap();
+    for level in &s.headings {
+        assert_eq!(level.text_align, TextAlignment::Left);
+    }
+}
+
+#[test]
+fn text_alignment_converts_to_and_from_table_alignment() {
+    use markdown2pdf::markdown::TableAlignment;
+    let cases = [
+        (TextAlignment::Left, TableAlignment::Left),
+        (TextAlignment::Center, TableAlignment::Center),
+        (TextAlignment::Right, TableAlignment::Right),
+        // No justified table column: falls back to flush left.
+        (TextAlignment::Justify, TableAlignment::Left),
+    ];
+    for (text, table) in cases {
+        assert_eq!(TableAlignment::from(text), table, "{text:?}");
+    }
+    for (text, table) in &cases[..3] {
+        assert_eq!(TextAlignment::from(*table), *text, "{table:?}");
+    }
+}
+
+#[test]
+fn contrast_ratio_matches_wcag_reference_values() {
+    let black = Color::rgb(0, 0, 0);
+    let white = Color::rgb(255, 255, 255);
+    assert!(black.luminance().abs() < 1e-6);
+    assert!((white.luminance() - 1.0).abs() < 1e-6);
+    assert!((black.contrast_ratio(white) - 21.0).abs() < 1e-3);
+    assert!((white.contrast_ratio(black) - 21.0).abs() < 1e-3);
+    assert!((white.contrast_ratio(white) - 1.0).abs() < 1e-6);
+    // #767676 on white is the classic just-passing AA grey (4.54:1).
+    let grey = Color::rgb(0x76, 0x76, 0x76);
+    assert!((grey.contrast_ratio(white) - 4.54).abs() < 0.01);
+}
+
+#[test]
+fn readable_on_keeps_good_colors_and_flips_poor_ones() {
+    let navy = Color::rgb(0x1F, 0x23, 0x28);
+    let pale = Color::rgb(0xF6, 0xF8, 0xFA);
+    let dark = Color::rgb(0x1E, 0x1E, 0x1E);
+    assert_eq!(navy.readable_on(pale, 4.5), navy);
+    assert_eq!(navy.readable_on(dark, 4.5), Color::rgb(255, 255, 255));
+    assert_eq!(
+        pale.readable_on(Color::rgb(0xFF, 0xFF, 0xE0), 4.5),
+        Color::rgb(0, 0, 0)
+    );
+}
+
+#[test]
+fn auto_contrast_only_recolors_text_the_config_leaves_unset() {
+    let cfg: DocumentConfig = toml::from_str(
+        r##"
+        [document]
+        auto_contrast = true
+
+        [code_block]
+        background_color = "#1E1E1E"
+
+        [blockquote]
+        background_color = "#1E1E1E"
+        text_color = "#333333"
+        "##,
+    )
+    .unwrap();
+    let resolved = resolve(cfg, None).unwrap();
+    assert_eq!(resolved.code_block.text_color, Color::rgb(255, 255, 255));
+    assert_eq!(resolved.blockquote.text_color, Color::rgb(0x33, 0x33, 0x33));
+    // No background of its own: left as the theme has it.
+    let plain = resolve(DocumentConfig::default(), None).unwrap();
+    assert_eq!(resolved.paragraph.text_color, plain.paragraph.text_color);
+}
+
+#[test]
+fn page_max_content_width_parses_points_and_ems() {
+    let pt: DocumentConfig = toml::from_str("[page]\nmax_content_width = 400").unwrap();
+    let resolved = resolve(pt, None).unwrap();
+    assert_eq!(resolved.page.max_content_width_pt, Some(400.0));
+    assert!(!resolved.page.full_width_spans_margins);
+
+    // An em is the paragraph font size (8pt in the default theme).
+    let em: DocumentConfig =
+        toml::from_str("[page]\nmax_content_width = \"30em\"\nfull_width_spans_margins = true")
+            .unwrap();
+    let resolved = resolve(em, None).unwrap();
+    assert_eq!(resolved.page.max_content_width_pt, Some(240.0));
+    assert!(resolved.page.full_width_spans_margins);
+
+    let zero: DocumentConfig = toml::from_str("[page]\nmax_content_width = 0").unwrap();
+    assert_eq!(resolve(zero, None).unwrap().page.max_content_width_pt, None);
+    assert_eq!(ResolvedStyle::default().page.max_content_width_pt, None);
+}
+
 #[test]
 fn print_effective_config_round_trip() {
     // Take the academic preset's resolved style, serialize to TOML,
@@ -260,6 +391,30 @@ fn print_effective_config_round_trip() {
     assert!(toml_text.contains("[paragraph]"));
 }
 
+#[test]
+fn output_passwords_resolve_but_never_serialize() {
+    let cfg = r#"
+        [output]
+        password = "hunter2"
+        permissions = ["print", "copy"]"#;
+    let s = load_config_strict(ConfigSource::Embedded(cfg), None).unwrap();
+    let enc = s
+        .output
+        .encryption
+        .as_ref()
+        .expect("password enables encryption");
+    assert_eq!(enc.user_password, "hunter2");
+    // No owner password given: a random one is made when writing, so
+    // the user password doesn't lift the permissions.
+    assert_eq!(enc.owner_password, None);
+    assert_eq!(enc.permissions, [PdfPermission::Print, PdfPermission::Copy]);
+    let toml_text = toml::to_string(&s).expect("serialize resolved style");
+    assert!(!toml_text.contains("hunter2"), "{toml_text}");
+
+    let plain = load_config_strict(ConfigSource::Default, None).unwrap();
+    assert!(plain.output.encryption.is_none());
+}
+
 #[test]
 fn maintained_reference_config_parses() {
     // `docs/config.toml` is the maintained, human-facing reference config
@@ -397,3 +552,345 @@ fn security_merge_overlay_wins_on_some() {
     // Overlay's allow_remote_images wins.
     assert_eq!(security.allow_remote_images, Some(true));
 }
+
+#[test]
+fn image_caption_options_round_trip_and_default_off() {
+    let cfg = r#"[image]
+        show_caption = true
+        caption_prefix = "Figure {n}: ""#;
+    let s = load_config_strict(ConfigSource::Embedded(cfg), None).unwrap();
+    assert!(s.image.show_caption);
+    assert_eq!(s.image.caption_prefix.as_deref(), Some("Figure {n}: "));
+
+    let d = load_config_strict(ConfigSource::Embedded(""), None).unwrap();
+    assert!(!d.image.show_caption);
+    assert_eq!(d.image.caption_prefix, None);
+}
+
+#[test]
+fn resolved_style_clone_compares_equal() {
+    let cfg = r##"[paragraph]
+        font_size_pt = 11.0
+        text_color = "#333333"
+
+        [footer]
+        center = "{page} / {total_pages}""##;
+    let custom = load_config_strict(ConfigSource::Embedded(cfg), None).unwrap();
+    let copy = custom.clone();
+    assert_eq!(copy, custom);
+    assert_ne!(custom, ResolvedStyle::default());
+
+    let mut tweaked = custom.clone();
+    tweaked.headings[2].font_size_pt += 1.0;
+    assert_ne!(tweaked, custom);
+}
+
+#[test]
+fn link_display_options_sit_beside_link_styling() {
+    let cfg = r##"[link]
+        text_color = "#112233"
+        display = "footnote"
+        relative_urls = "resolve_base"
+        base_url = "https://example.com/book/""##;
+    let s = load_config_strict(ConfigSource::Embedded(cfg), None).unwrap();
+    assert_eq!(s.link.text_color_rgb(), (0x11, 0x22, 0x33));
+    assert_eq!(s.link_display.display, LinkDisplay::Footnote);
+    assert_eq!(s.link_display.relative_urls, RelativeUrlPolicy::ResolveBase);
+    assert_eq!(
+        s.link_display.base_url.as_deref(),
+        Some("https://example.com/book/")
+    );
+
+    let d = load_config_strict(ConfigSource::Embedded(""), None).unwrap();
+    assert_eq!(d.link_display.display, LinkDisplay::Inline);
+    assert_eq!(d.link_display.relative_urls, RelativeUrlPolicy::Strip);
+}
+
+#[test]
+fn table_column_width_bounds_parse() {
+    let s = load_config_strict(
+        ConfigSource::Embedded(
+            "[table]\n\
+             cell_padding = { top = 2.0, right = 6.0, bottom = 2.0, left = 6.0 }\n\
+             min_column_width_pt = 36.0\n\
+             max_column_width_pt = 180.0\n",
+        ),
+        None,
+    )
+    .unwrap();
+    assert_eq!(s.table.cell_padding.left, 6.0);
+    assert_eq!(s.table.min_column_width_pt, 36.0);
+    assert_eq!(s.table.max_column_width_pt, Some(180.0));
+
+    let d = load_config_strict(ConfigSource::Embedded(""), None).unwrap();
+    assert_eq!(d.table.min_column_width_pt, 0.0);
+    assert_eq!(d.table.max_column_width_pt, None);
+}
+
+#[test]
+fn table_overflow_parses_each_policy() {
+    for (name, policy) in [
+        ("wrap", TableOverflow::Wrap),
+        ("scale", TableOverflow::Scale),
+        ("rotate", TableOverflow::Rotate),
+        ("scroll", TableOverflow::Scroll),
+    ] {
+        let cfg = format!("[table]\noverflow = \"{name}\"\n");
+        let s = load_config_strict(ConfigSource::Embedded(&cfg), None).unwrap();
+        assert_eq!(s.table.overflow, policy);
+    }
+    let d = load_config_strict(ConfigSource::Embedded(""), None).unwrap();
+    assert_eq!(d.table.overflow, TableOverflow::Wrap);
+    assert!(
+        load_config_strict(
+            ConfigSource::Embedded("[table]\noverflow = \"shrink\"\n"),
+            None
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn paragraph_spacing_is_read_from_paragraph_table() {
+    let s = load_config_strict(
+        ConfigSource::Embedded("[paragraph]\nparagraph_spacing_pt = 3.5\n"),
+        None,
+    )
+    .unwrap();
+    assert_eq!(s.paragraph_spacing_pt, Some(3.5));
+
+    let d = load_config_strict(ConfigSource::Embedded(""), None).unwrap();
+    assert_eq!(d.paragraph_spacing_pt, None);
+
+    // Negative spacing is dropped rather than overlapping paragraphs.
+    let neg = load_config_strict(
+        ConfigSource::Embedded("[paragraph]\nparagraph_spacing_pt = -4.0\n"),
+        None,
+    )
+    .unwrap();
+    assert_eq!(neg.paragraph_spacing_pt, None);
+}
+
+#[test]
+fn image_mode_parses_and_defaults_to_inline() {
+    for (raw, mode) in [
+        ("inline", ImageMode::Inline),
+        ("full_width", ImageMode::FullWidth),
+        ("cover", ImageMode::Cover),
+    ] {
+        let cfg = format!("[image]\nmode = \"{raw}\"\n");
+        let s = load_config_strict(ConfigSource::Embedded(&cfg), None).unwrap();
+        assert_eq!(s.image.mode, mode);
+    }
+    let d = load_config_strict(ConfigSource::Embedded(""), None).unwrap();
+    assert_eq!(d.image.mode, ImageMode::Inline);
+    assert!(
+        load_config_strict(ConfigSource::Embedded("[image]\nmode = \"banner\"\n"), None).is_err()
+    );
+}
+
+#[test]
+fn code_block_max_lines_parses_beside_block_styling() {
+    let d = load_config_strict(ConfigSource::Embedded(""), None).unwrap();
+    assert_eq!(d.code_block_max_lines, None);
+    let s = load_config_strict(
+        ConfigSource::Embedded("[code_block]\nmax_lines = 20\nfont_size_pt = 7.0\n"),
+        None,
+    )
+    .unwrap();
+    assert_eq!(s.code_block_max_lines, Some(20));
+    assert_eq!(s.code_block.font_size_pt, 7.0);
+    let zero = load_config_strict(
+        ConfigSource::Embedded("[code_block]\nmax_lines = 0\n"),
+        None,
+    )
+    .unwrap();
+    assert_eq!(zero.code_block_max_lines, None);
+}
+
+#[test]
+fn html_mode_parses_and_defaults_to_raw() {
+    for (raw, mode) in [
+        ("ignore", HtmlMode::Ignore),
+        ("comment", HtmlMode::Comment),
+        ("raw", HtmlMode::Raw),
+        ("strip_tags", HtmlMode::StripTags),
+    ] {
+        let cfg = format!("[html]\nmode = \"{raw}\"\n");
+        let s = load_config_strict(ConfigSource::Embedded(&cfg), None).unwrap();
+        assert_eq!(s.html.mode, mode);
+    }
+    let d = load_config_strict(ConfigSource::Embedded(""), None).unwrap();
+    assert_eq!(d.html.mode, HtmlMode::Raw);
+    assert!(
+        load_config_strict(ConfigSource::Embedded("[html]\nmode = \"escape\"\n"), None).is_err()
+    );
+}
+
+#[test]
+fn palette_names_resolve_in_color_fields() {
+    let cfg = r##"[palette]
+        accent = "#0A64C8"
+        muted = { r = 120, g = 120, b = 120 }
+
+        [paragraph]
+        text_color = "muted"
+
+        [link]
+        text_color = "accent"
+
+        [blockquote]
+        background_color = [250, 250, 250]"##;
+    let s = load_config_strict(ConfigSource::Embedded(cfg), None).unwrap();
+    assert_eq!(s.paragraph.text_color, Color::rgb(120, 120, 120));
+    assert_eq!(s.link.text_color_rgb(), (0x0A, 0x64, 0xC8));
+    assert_eq!(
+        s.blockquote.background_color,
+        Some(Color::rgb(250, 250, 250))
+    );
+}
+
+#[test]
+fn list_marker_color_cascades_from_common() {
+    let cfg = r##"[palette]
+        accent = "#C83200"
+
+        [list.common]
+        marker_color = "accent"
+
+        [list.task]
+        marker_color = "#008000""##;
+    let s = load_config_strict(ConfigSource::Embedded(cfg), None).unwrap();
+    assert_eq!(
+        s.list_unordered.marker_color,
+        Some(Color::rgb(0xC8, 0x32, 0))
+    );
+    assert_eq!(s.list_ordered.marker_color, Some(Color::rgb(0xC8, 0x32, 0)));
+    assert_eq!(s.list_task.marker_color, Some(Color::rgb(0, 0x80, 0)));
+
+    let d = load_config_strict(ConfigSource::Default, None).unwrap();
+    assert_eq!(d.list_unordered.marker_color, None);
+}
+
+#[test]
+fn undefined_palette_name_is_an_error() {
+    let cfg = r##"[palette]
+        accent = "#0A64C8"
+
+        [headings.h1]
+        text_color = "acent""##;
+    match load_config_strict(ConfigSource::Embedded(cfg), None) {
+        Err(ResolveError::UnknownPaletteColor { name, suggestion }) => {
+            assert_eq!(name, "acent");
+            assert_eq!(suggestion.as_deref(), Some("accent"));
+        }
+        other => panic!("expected UnknownPaletteColor, got {other:?}"),
+    }
+}
+
+#[test]
+fn document_scale_multiplies_sizes_and_spacing() {
+    let base = load_config_strict(ConfigSource::Embedded(""), None).unwrap();
+    let s = load_config_strict(
+        ConfigSource::Embedded("[document]\nscale = 1.5\n[paragraph]\nmargin_before_pt = 2.0"),
+        None,
+    )
+    .unwrap();
+    assert_eq!(s.paragraph.font_size_pt, base.paragraph.font_size_pt * 1.5);
+    assert_eq!(s.paragraph.margin_before_pt, 3.0);
+    assert_eq!(
+        s.headings[0].font_size_pt,
+        base.headings[0].font_size_pt * 1.5
+    );
+    assert_eq!(
+        s.list_unordered.indent_per_level_pt,
+        base.list_unordered.indent_per_level_pt * 1.5
+    );
+    assert_eq!(
+        s.code_inline.font_size_pt,
+        base.code_inline.font_size_pt * 1.5
+    );
+    // Ratios and page geometry are left alone.
+    assert_eq!(s.paragraph.line_height, base.paragraph.line_height);
+    assert_eq!(s.page.margins_mm, base.page.margins_mm);
+}
+
+#[test]
+fn document_digits_parse_and_map() {
+    let d = load_config_strict(ConfigSource::Embedded(""), None).unwrap();
+    assert_eq!(d.digits, Digits::Latin);
+    for raw in ["arabic_indic", "arabic-indic"] {
+        let cfg = format!("[document]\ndigits = \"{raw}\"\n");
+        let s = load_config_strict(ConfigSource::Embedded(&cfg), None).unwrap();
+        assert_eq!(s.digits, Digits::ArabicIndic);
+    }
+    assert_eq!(Digits::ArabicIndic.localize("3."), "\u{0663}.");
+    assert_eq!(Digits::Devanagari.localize("[10]"), "[\u{0967}\u{0966}]");
+    assert_eq!(Digits::Latin.localize("42"), "42");
+}
+
+#[test]
+fn document_scale_rejects_non_positive_values() {
+    let base = load_config_strict(ConfigSource::Embedded(""), None).unwrap();
+    let s = load_config_strict(ConfigSource::Embedded("[document]\nscale = 0.0"), None).unwrap();
+    assert_eq!(s.paragraph.font_size_pt, base.paragraph.font_size_pt);
+}
+
+#[test]
+fn emphasis_sections_resolve_and_default_to_the_surrounding_text() {
+    let d = load_config_strict(ConfigSource::Embedded(""), None).unwrap();
+    assert_eq!(d.emphasis.text_color, None);
+    assert_eq!(d.strong_emphasis.font_size_pt, None);
+    assert_eq!(d.emphasis_strong.text_color, None);
+
+    let cfg = r##"
+[palette]
+accent = "#112233"
+
+[emphasis]
+text_color = "accent"
+
+[strong_emphasis]
+font_size_pt = 11.0
+
+[emphasis_strong]
+text_color = "#FF0000"
+font_size_pt = 12.0
+"##;
+    let s = load_config_strict(ConfigSource::Embedded(cfg), None).unwrap();
+    assert_eq!(s.emphasis.text_color, Some(Color::rgb(0x11, 0x22, 0x33)));
+    assert_eq!(s.emphasis.font_size_pt, None);
+    assert_eq!(s.strong_emphasis.font_size_pt, Some(11.0));
+    assert_eq!(s.emphasis_strong.text_color, Some(Color::rgb(255, 0, 0)));
+    assert_eq!(s.emphasis_strong.font_size_pt, Some(12.0));
+
+    let err = load_config_strict(
+        ConfigSource::Embedded("[emphasis_strong]\nfont_weight = \"bold\"\n"),
+        None,
+    );
+    assert!(err.is_err(), "emphasis sections only take colour and size");
+}
+
+#[test]
+fn em_margins_resolve_against_the_block_font_size() {
+    let s = load_config_strict(
+        ConfigSource::Embedded(
+            "[paragraph]\nfont_size_pt = 12.0\nmargin_before_pt = \"1em\"\nmargin_after_pt = \"0.5em\"\n\
+             [headings.h1]\nfont_size_pt = 24.0\nmargin_after_pt = \"6pt\"\n",
+        ),
+        None,
+    )
+    .unwrap();
+    assert_eq!(s.paragraph.margin_before_pt, 12.0);
+    assert_eq!(s.paragraph.margin_after_pt, 6.0);
+    assert_eq!(s.headings[0].margin_after_pt, 6.0);
+}
+
+#[test]
+fn bad_spacing_unit_is_a_config_error() {
+    let r = load_config_strict(
+        ConfigSource::Embedded("[paragraph]\nmargin_before_pt = \"1cm\"\n"),
+        None,
+    );
+    assert!(r.is_err());
+}